@@ -85,6 +85,9 @@ fn criterion_benchmark(c: &mut Criterion) {
         ext_qos: ext::QoSType::DEFAULT,
         ext_tstamp: None,
         ext_nodeid: ext::NodeIdType::DEFAULT,
+        ext_hop_limit: None,
+        ext_lifespan: None,
+        ext_redundant: None,
         payload: PushBody::Put(Put {
             timestamp: None,
             encoding: Encoding::empty(),
@@ -131,6 +134,9 @@ fn criterion_benchmark(c: &mut Criterion) {
         ext_qos: ext::QoSType::DEFAULT,
         ext_tstamp: None,
         ext_nodeid: ext::NodeIdType::DEFAULT,
+        ext_hop_limit: None,
+        ext_lifespan: None,
+        ext_redundant: None,
         payload: PushBody::Put(Put {
             timestamp: None,
             encoding: Encoding::empty(),
@@ -172,6 +178,9 @@ fn criterion_benchmark(c: &mut Criterion) {
         ext_qos: ext::QoSType::DEFAULT,
         ext_tstamp: None,
         ext_nodeid: ext::NodeIdType::DEFAULT,
+        ext_hop_limit: None,
+        ext_lifespan: None,
+        ext_redundant: None,
         payload: PushBody::Put(Put {
             timestamp: None,
             encoding: Encoding::empty(),
@@ -213,6 +222,9 @@ fn criterion_benchmark(c: &mut Criterion) {
         ext_qos: ext::QoSType::DEFAULT,
         ext_tstamp: None,
         ext_nodeid: ext::NodeIdType::DEFAULT,
+        ext_hop_limit: None,
+        ext_lifespan: None,
+        ext_redundant: None,
         payload: PushBody::Put(Put {
             timestamp: None,
             encoding: Encoding::empty(),
@@ -241,6 +253,9 @@ fn criterion_benchmark(c: &mut Criterion) {
         ext_qos: ext::QoSType::DEFAULT,
         ext_tstamp: None,
         ext_nodeid: ext::NodeIdType::DEFAULT,
+        ext_hop_limit: None,
+        ext_lifespan: None,
+        ext_redundant: None,
         payload: PushBody::Put(Put {
             timestamp: None,
             encoding: Encoding::empty(),
@@ -280,6 +295,9 @@ fn criterion_benchmark(c: &mut Criterion) {
         ext_qos: ext::QoSType::DEFAULT,
         ext_tstamp: None,
         ext_nodeid: ext::NodeIdType::DEFAULT,
+        ext_hop_limit: None,
+        ext_lifespan: None,
+        ext_redundant: None,
         payload: PushBody::Put(Put {
             timestamp: None,
             encoding: Encoding::empty(),
@@ -223,6 +223,45 @@ where
     }
 }
 
+// Extensions: HopLimit
+impl<W, const ID: u8> WCodec<(ext::HopLimitType<{ ID }>, bool), &mut W> for Zenoh080
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: (ext::HopLimitType<{ ID }>, bool)) -> Self::Output {
+        let (x, more) = x;
+        let ext: ZExtZ64<{ ID }> = x.into();
+        self.write(&mut *writer, (&ext, more))
+    }
+}
+
+impl<R, const ID: u8> RCodec<(ext::HopLimitType<{ ID }>, bool), &mut R> for Zenoh080
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<(ext::HopLimitType<{ ID }>, bool), Self::Error> {
+        let header: u8 = self.read(&mut *reader)?;
+        let codec = Zenoh080Header::new(header);
+        codec.read(reader)
+    }
+}
+
+impl<R, const ID: u8> RCodec<(ext::HopLimitType<{ ID }>, bool), &mut R> for Zenoh080Header
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<(ext::HopLimitType<{ ID }>, bool), Self::Error> {
+        let (ext, more): (ZExtZ64<{ ID }>, bool) = self.read(&mut *reader)?;
+        Ok((ext.into(), more))
+    }
+}
+
 // Extension: EntityId
 impl<const ID: u8> LCodec<&ext::EntityGlobalIdType<{ ID }>> for Zenoh080 {
     fn w_len(self, x: &ext::EntityGlobalIdType<{ ID }>) -> usize {
@@ -274,3 +313,56 @@ where
         Ok((ext::EntityGlobalIdType { zid, eid }, more))
     }
 }
+
+// Extension: Redundant
+impl<const ID: u8> LCodec<&ext::RedundantType<{ ID }>> for Zenoh080 {
+    fn w_len(self, x: &ext::RedundantType<{ ID }>) -> usize {
+        let ext::RedundantType { zid, sn, .. } = x;
+
+        1 + self.w_len(zid) + self.w_len(*sn)
+    }
+}
+
+impl<W, const ID: u8> WCodec<(&ext::RedundantType<{ ID }>, bool), &mut W> for Zenoh080
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: (&ext::RedundantType<{ ID }>, bool)) -> Self::Output {
+        let (x, more) = x;
+        let header: ZExtZBufHeader<{ ID }> = ZExtZBufHeader::new(self.w_len(x));
+        self.write(&mut *writer, (&header, more))?;
+
+        let flags: u8 = ((x.zid.size() as u8 - 1) << 4) | x.tree as u8;
+        self.write(&mut *writer, flags)?;
+
+        let lodec = Zenoh080Length::new(x.zid.size());
+        lodec.write(&mut *writer, &x.zid)?;
+
+        self.write(&mut *writer, x.sn)?;
+        Ok(())
+    }
+}
+
+impl<R, const ID: u8> RCodec<(ext::RedundantType<{ ID }>, bool), &mut R> for Zenoh080Header
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<(ext::RedundantType<{ ID }>, bool), Self::Error> {
+        let (_, more): (ZExtZBufHeader<{ ID }>, bool) = self.read(&mut *reader)?;
+
+        let flags: u8 = self.codec.read(&mut *reader)?;
+        let length = 1 + ((flags >> 4) as usize);
+        let tree = flags & 1 != 0;
+
+        let lodec = Zenoh080Length::new(length);
+        let zid: ZenohIdProto = lodec.read(&mut *reader)?;
+
+        let sn: u32 = self.codec.read(&mut *reader)?;
+
+        Ok((ext::RedundantType { zid, sn, tree }, more))
+    }
+}
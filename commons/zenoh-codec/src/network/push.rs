@@ -40,6 +40,9 @@ where
             ext_qos,
             ext_tstamp,
             ext_nodeid,
+            ext_hop_limit,
+            ext_lifespan,
+            ext_redundant,
             payload,
         } = x;
 
@@ -47,7 +50,10 @@ where
         let mut header = id::PUSH;
         let mut n_exts = ((ext_qos != &ext::QoSType::DEFAULT) as u8)
             + (ext_tstamp.is_some() as u8)
-            + ((ext_nodeid != &ext::NodeIdType::DEFAULT) as u8);
+            + ((ext_nodeid != &ext::NodeIdType::DEFAULT) as u8)
+            + (ext_hop_limit.is_some() as u8)
+            + (ext_lifespan.is_some() as u8)
+            + (ext_redundant.is_some() as u8);
         if n_exts != 0 {
             header |= flag::Z;
         }
@@ -75,6 +81,19 @@ where
             n_exts -= 1;
             self.write(&mut *writer, (*ext_nodeid, n_exts != 0))?;
         }
+        if let Some(hl) = ext_hop_limit {
+            n_exts -= 1;
+            self.write(&mut *writer, (*hl, n_exts != 0))?;
+        }
+        if let Some(ls) = ext_lifespan.as_ref() {
+            n_exts -= 1;
+            let e = ext::Lifespan::new(ls.as_millis() as u64);
+            self.write(&mut *writer, (&e, n_exts != 0))?;
+        }
+        if let Some(redundant) = ext_redundant {
+            n_exts -= 1;
+            self.write(&mut *writer, (redundant, n_exts != 0))?;
+        }
 
         // Payload
         self.write(&mut *writer, payload)?;
@@ -120,6 +139,9 @@ where
         let mut ext_qos = ext::QoSType::DEFAULT;
         let mut ext_tstamp = None;
         let mut ext_nodeid = ext::NodeIdType::DEFAULT;
+        let mut ext_hop_limit = None;
+        let mut ext_lifespan = None;
+        let mut ext_redundant = None;
 
         let mut has_ext = imsg::has_flag(self.header, flag::Z);
         while has_ext {
@@ -141,6 +163,21 @@ where
                     ext_nodeid = nid;
                     has_ext = ext;
                 }
+                ext::HopLimit::ID => {
+                    let (hl, ext): (ext::HopLimitType, bool) = eodec.read(&mut *reader)?;
+                    ext_hop_limit = Some(hl);
+                    has_ext = ext;
+                }
+                ext::Lifespan::ID => {
+                    let (ls, ext): (ext::Lifespan, bool) = eodec.read(&mut *reader)?;
+                    ext_lifespan = Some(ext::LifespanType::from_millis(ls.value));
+                    has_ext = ext;
+                }
+                ext::Redundant::ID => {
+                    let (redundant, ext): (ext::RedundantType, bool) = eodec.read(&mut *reader)?;
+                    ext_redundant = Some(redundant);
+                    has_ext = ext;
+                }
                 _ => {
                     has_ext = extension::skip(reader, "Push", ext)?;
                 }
@@ -156,6 +193,9 @@ where
             ext_qos,
             ext_tstamp,
             ext_nodeid,
+            ext_hop_limit,
+            ext_lifespan,
+            ext_redundant,
         })
     }
 }
@@ -84,6 +84,7 @@ where
             ext_target,
             ext_budget,
             ext_timeout,
+            ext_hop_limit,
             payload,
         } = x;
 
@@ -94,7 +95,8 @@ where
             + ((ext_target != &ext::QueryTarget::DEFAULT) as u8)
             + (ext_budget.is_some() as u8)
             + (ext_timeout.is_some() as u8)
-            + ((ext_nodeid != &ext::NodeIdType::DEFAULT) as u8);
+            + ((ext_nodeid != &ext::NodeIdType::DEFAULT) as u8)
+            + (ext_hop_limit.is_some() as u8);
         if n_exts != 0 {
             header |= flag::Z;
         }
@@ -137,6 +139,10 @@ where
             n_exts -= 1;
             self.write(&mut *writer, (*ext_nodeid, n_exts != 0))?;
         }
+        if let Some(hl) = ext_hop_limit {
+            n_exts -= 1;
+            self.write(&mut *writer, (*hl, n_exts != 0))?;
+        }
 
         // Payload
         self.write(&mut *writer, payload)?;
@@ -187,6 +193,7 @@ where
         let mut ext_target = ext::QueryTarget::DEFAULT;
         let mut ext_limit = None;
         let mut ext_timeout = None;
+        let mut ext_hop_limit = None;
 
         let mut has_ext = imsg::has_flag(self.header, flag::Z);
         while has_ext {
@@ -223,6 +230,11 @@ where
                     ext_timeout = Some(ext::TimeoutType::from_millis(to.value));
                     has_ext = ext;
                 }
+                ext::HopLimit::ID => {
+                    let (hl, ext): (ext::HopLimitType, bool) = eodec.read(&mut *reader)?;
+                    ext_hop_limit = Some(hl);
+                    has_ext = ext;
+                }
                 _ => {
                     has_ext = extension::skip(reader, "Request", ext)?;
                 }
@@ -242,6 +254,7 @@ where
             ext_target,
             ext_budget: ext_limit,
             ext_timeout,
+            ext_hop_limit,
         })
     }
 }
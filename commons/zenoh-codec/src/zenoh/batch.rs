@@ -0,0 +1,157 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use alloc::{string::String, vec::Vec};
+
+use zenoh_buffers::{
+    reader::{DidntRead, Reader},
+    writer::{DidntWrite, Writer},
+};
+use zenoh_protocol::{
+    common::{iext, imsg},
+    zenoh::{
+        batch::{ext, flag, Batch, BatchEntry},
+        id, PushBody,
+    },
+};
+
+use crate::{common::extension, RCodec, WCodec, Zenoh080, Zenoh080Bounded, Zenoh080Header};
+
+impl<W> WCodec<&Batch, &mut W> for Zenoh080
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: &Batch) -> Self::Output {
+        let Batch {
+            timestamp,
+            ext_sinfo,
+            ext_unknown,
+            entries,
+        } = x;
+
+        // Header
+        let mut header = id::BATCH;
+        if timestamp.is_some() {
+            header |= flag::T;
+        }
+        let mut n_exts = (ext_sinfo.is_some()) as u8 + (ext_unknown.len() as u8);
+        if n_exts != 0 {
+            header |= flag::Z;
+        }
+        self.write(&mut *writer, header)?;
+
+        // Body
+        if let Some(ts) = timestamp.as_ref() {
+            self.write(&mut *writer, ts)?;
+        }
+
+        // Extensions
+        if let Some(sinfo) = ext_sinfo.as_ref() {
+            n_exts -= 1;
+            self.write(&mut *writer, (sinfo, n_exts != 0))?;
+        }
+        for u in ext_unknown.iter() {
+            n_exts -= 1;
+            self.write(&mut *writer, (u, n_exts != 0))?;
+        }
+
+        // Entries
+        let zodec = Zenoh080Bounded::<u32>::new();
+        zodec.write(&mut *writer, entries.len())?;
+        for BatchEntry { key_expr, payload } in entries.iter() {
+            if let PushBody::Batch(_) = payload {
+                return Err(DidntWrite);
+            }
+            self.write(&mut *writer, key_expr)?;
+            self.write(&mut *writer, payload)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<R> RCodec<Batch, &mut R> for Zenoh080
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<Batch, Self::Error> {
+        let header: u8 = self.read(&mut *reader)?;
+        let codec = Zenoh080Header::new(header);
+        codec.read(reader)
+    }
+}
+
+impl<R> RCodec<Batch, &mut R> for Zenoh080Header
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<Batch, Self::Error> {
+        if imsg::mid(self.header) != id::BATCH {
+            return Err(DidntRead);
+        }
+
+        // Body
+        let mut timestamp: Option<uhlc::Timestamp> = None;
+        if imsg::has_flag(self.header, flag::T) {
+            timestamp = Some(self.codec.read(&mut *reader)?);
+        }
+
+        // Extensions
+        let mut ext_sinfo: Option<ext::SourceInfoType> = None;
+        let mut ext_unknown = Vec::new();
+
+        let mut has_ext = imsg::has_flag(self.header, flag::Z);
+        while has_ext {
+            let ext: u8 = self.codec.read(&mut *reader)?;
+            let eodec = Zenoh080Header::new(ext);
+            match iext::eid(ext) {
+                ext::SourceInfo::ID => {
+                    let (s, ext): (ext::SourceInfoType, bool) = eodec.read(&mut *reader)?;
+                    ext_sinfo = Some(s);
+                    has_ext = ext;
+                }
+                _ => {
+                    let (u, ext) = extension::read(reader, "Batch", ext)?;
+                    ext_unknown.push(u);
+                    has_ext = ext;
+                }
+            }
+        }
+
+        // Entries
+        let zodec = Zenoh080Bounded::<u32>::new();
+        let len: usize = zodec.read(&mut *reader)?;
+        let mut entries = Vec::with_capacity(len.min(reader.remaining()));
+        for _ in 0..len {
+            let key_expr: String = self.codec.read(&mut *reader)?;
+            let payload: PushBody = self.codec.read(&mut *reader)?;
+            if let PushBody::Batch(_) = payload {
+                return Err(DidntRead);
+            }
+            entries.push(BatchEntry { key_expr, payload });
+        }
+
+        Ok(Batch {
+            timestamp,
+            ext_sinfo,
+            ext_unknown,
+            entries,
+        })
+    }
+}
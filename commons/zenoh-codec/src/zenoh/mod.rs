@@ -11,6 +11,7 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
+pub mod batch;
 pub mod del;
 pub mod err;
 pub mod put;
@@ -47,6 +48,7 @@ where
         match x {
             PushBody::Put(b) => self.write(&mut *writer, b),
             PushBody::Del(b) => self.write(&mut *writer, b),
+            PushBody::Batch(b) => self.write(&mut *writer, b),
         }
     }
 }
@@ -64,6 +66,7 @@ where
         let body = match imsg::mid(codec.header) {
             id::PUT => PushBody::Put(codec.read(&mut *reader)?),
             id::DEL => PushBody::Del(codec.read(&mut *reader)?),
+            id::BATCH => PushBody::Batch(codec.read(&mut *reader)?),
             _ => return Err(DidntRead),
         };
 
@@ -64,6 +64,9 @@ where
         }
 
         // Payload
+        if let ReplyBody::Batch(_) = payload {
+            return Err(DidntWrite);
+        }
         self.write(&mut *writer, payload)?;
 
         Ok(())
@@ -113,6 +116,9 @@ where
 
         // Payload
         let payload: ReplyBody = self.codec.read(&mut *reader)?;
+        if let ReplyBody::Batch(_) = payload {
+            return Err(DidntRead);
+        }
 
         Ok(Reply {
             consolidation,
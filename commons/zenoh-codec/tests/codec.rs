@@ -609,6 +609,11 @@ fn codec_del() {
     run!(zenoh::Del, zenoh::Del::rand());
 }
 
+#[test]
+fn codec_batch() {
+    run!(zenoh::Batch, zenoh::Batch::rand());
+}
+
 #[test]
 fn codec_query() {
     run!(zenoh::Query, zenoh::Query::rand());
@@ -162,6 +162,11 @@ pub mod routing {
     pub mod interests {
         pub const timeout: u64 = 10000;
     }
+    pub mod queries {
+        use crate::QueryLoadBalancing;
+
+        pub const load_balancing: QueryLoadBalancing = QueryLoadBalancing::First;
+    }
 }
 
 impl Default for ListenConfig {
@@ -337,6 +342,7 @@ impl Default for AclConfig {
         Self {
             enabled: false,
             default_permission: Permission::Deny,
+            enforcement: AclEnforcement::Enforce,
             rules: None,
             subjects: None,
             policies: None,
@@ -344,6 +350,20 @@ impl Default for AclConfig {
     }
 }
 
+impl Default for AuditConf {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: None,
+            format: AuditFormat::Json,
+            key_exprs: None,
+            flows: None,
+            max_file_size: None,
+            max_files: None,
+        }
+    }
+}
+
 impl Default for ConnectionRetryModeDependentConf {
     fn default() -> Self {
         Self {
@@ -28,14 +28,23 @@ pub mod wrappers;
 use std::convert::TryFrom;
 // This is a false positive from the rust analyser
 use std::{
-    any::Any, collections::HashSet, fmt, io::Read, net::SocketAddr, ops, path::Path, sync::Weak,
+    any::Any,
+    collections::HashSet,
+    fmt,
+    io::Read,
+    net::{IpAddr, SocketAddr},
+    num::{NonZeroU16, NonZeroU8},
+    ops,
+    path::Path,
+    sync::Weak,
 };
 
 use include::recursive_include;
-use qos::PublisherQoSConfList;
+use qos::{PublisherQoSConfList, QosOverwriteItemConf};
 use secrecy::{CloneableSecret, DebugSecret, Secret, SerializableSecret, Zeroize};
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
+use serde_with::{DeserializeFromStr, SerializeDisplay};
 use validated_struct::ValidatedMapAssociatedTypes;
 pub use validated_struct::{GetError, ValidatedMap};
 pub use wrappers::ZenohId;
@@ -78,13 +87,23 @@ impl Zeroize for SecretString {
 
 pub type SecretValue = Secret<SecretString>;
 
-#[derive(Debug, Deserialize, Serialize, Clone, Copy)]
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum InterceptorFlow {
     Egress,
     Ingress,
 }
 
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DownsamplingMode {
+    /// Drop the samples exceeding the frequency
+    #[default]
+    Drop,
+    /// Hold the most recent sample exceeding the frequency and send it at the end of the period
+    Latest,
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct DownsamplingRuleConf {
     /// A list of key-expressions to which the downsampling will be applied.
@@ -92,6 +111,12 @@ pub struct DownsamplingRuleConf {
     pub key_expr: OwnedKeyExpr,
     /// The maximum frequency in Hertz;
     pub freq: f64,
+    /// Downsampling mode: drop, latest (default: drop)
+    #[serde(default)]
+    pub mode: DownsamplingMode,
+    /// Downsample each source (publisher entity) independently instead of each rule (default: false)
+    #[serde(default)]
+    pub per_source: bool,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -107,6 +132,143 @@ pub struct DownsamplingItemConf {
     pub flow: InterceptorFlow,
 }
 
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum QuotaAction {
+    /// Drop the messages exceeding the quota
+    #[default]
+    Drop,
+    /// Delay the messages exceeding the quota until enough tokens are available
+    Delay,
+}
+
+/// How queries targeting the best matching queryable are distributed among the
+/// complete queryables matching their key expression.
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryLoadBalancing {
+    /// Route queries to the nearest complete queryable
+    #[default]
+    First,
+    /// Route queries to each complete queryable in turn
+    RoundRobin,
+    /// Route queries to a randomly chosen complete queryable
+    Random,
+    /// Route queries to the complete queryable with the fewest pending queries it got from this
+    /// load balancing
+    LeastOutstanding,
+}
+
+/// The weight of the link (transport) to a node in linkstate routing.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct TransportWeight {
+    /// The zenoh id of the node at the other end of the link
+    pub dst_zid: ZenohId,
+    /// The weight of the link, the default weight being 100
+    pub weight: NonZeroU16,
+}
+
+/// A gateway router of another region.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RegionGatewayConf {
+    /// The zenoh id of the gateway router
+    pub zid: ZenohId,
+    /// The name of the region of the gateway router
+    pub region: String,
+    /// The key expressions on which the gateway router aggregates the subscribers and queryables of
+    /// its region, only the declarations included in them being exchanged with the gateway.
+    /// If empty, all the declarations of its region are exchanged individually.
+    #[serde(default)]
+    pub aggregation: Vec<OwnedKeyExpr>,
+}
+
+/// A subject of an interceptor, matching a transport if all its specified properties match,
+/// a property matching if any of its values does. The access control subjects share these properties.
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
+pub struct InterceptorSubjectConf {
+    /// Interfaces of the remote transport links
+    pub interfaces: Option<Vec<Interface>>,
+    /// Common names of the remote TLS certificates
+    pub cert_common_names: Option<Vec<CertCommonName>>,
+    /// Usernames of the remote authenticated users
+    pub usernames: Option<Vec<Username>>,
+    /// Zenoh ids of the remote nodes
+    pub zids: Option<Vec<ZenohId>>,
+    /// Protocols of the remote transport links
+    pub link_protocols: Option<Vec<LinkProtocol>>,
+    /// IP networks of the remote transport links
+    pub remote_networks: Option<Vec<IpNetwork>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct QuotaItemConf {
+    /// Optional identifier for the quota configuration item
+    pub id: Option<String>,
+    /// A list of subjects to which the quota will be applied, the transports matching the same subject
+    /// share its quota. Quota will be applied to all transports, each remote getting its own quota,
+    /// if the parameter is None
+    pub subjects: Option<Vec<InterceptorSubjectConf>>,
+    /// A list of key-expressions, the messages on key expressions intersecting one of them are
+    /// accounted in the quota. All key expressions are accounted if the parameter is None
+    pub key_exprs: Option<Vec<OwnedKeyExpr>>,
+    /// The maximum bandwidth in bytes per second
+    pub bytes_per_sec: Option<f64>,
+    /// The maximum rate in messages per second
+    pub messages_per_sec: Option<f64>,
+    /// The burst size expressed in seconds of traffic at the maximum rate (default: 1)
+    pub burst: Option<f64>,
+    /// Action on messages exceeding the quota: drop, delay (default: drop)
+    #[serde(default)]
+    pub action: QuotaAction,
+    /// The maximum delay in milliseconds of a message when action is delay,
+    /// messages that would be delayed longer are dropped (default: 1000)
+    pub max_delay_ms: Option<u64>,
+    /// Quota flow direction: egress, ingress
+    pub flow: InterceptorFlow,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RewriteRuleConf {
+    /// The key-expression to rewrite, either a wildcard-free key or a wildcard-free prefix followed by `/**`
+    pub from: OwnedKeyExpr,
+    /// The replacement key-expression, of the same form as `from`
+    pub to: OwnedKeyExpr,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RewriteItemConf {
+    /// Optional identifier for the rewrite configuration item
+    pub id: Option<String>,
+    /// A list of subjects to which the rewriting will be applied.
+    /// Rewriting will be applied to all transports if the parameter is None
+    pub subjects: Option<Vec<InterceptorSubjectConf>>,
+    /// A list of rewrite rules, the most specific matching rule applies.
+    pub rules: Vec<RewriteRuleConf>,
+    /// Rewrite flow direction: egress, ingress
+    pub flow: InterceptorFlow,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct NamespaceConf {
+    /// Optional identifier for the namespace
+    pub id: Option<String>,
+    /// The wildcard-free key-expression prefix the key space of the subjects is mapped under
+    pub prefix: OwnedKeyExpr,
+    /// A list of subjects confined to the namespace, a transport matching several namespaces
+    /// being confined to the first one
+    pub subjects: Vec<InterceptorSubjectConf>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditFormat {
+    /// One JSON object per line
+    #[default]
+    Json,
+    /// Length-prefixed binary records
+    Binary,
+}
+
 #[derive(Serialize, Debug, Deserialize, Clone)]
 pub struct AclConfigRule {
     pub id: String,
@@ -119,9 +281,8 @@ pub struct AclConfigRule {
 #[derive(Serialize, Debug, Deserialize, Clone)]
 pub struct AclConfigSubjects {
     pub id: String,
-    pub interfaces: Option<Vec<Interface>>,
-    pub cert_common_names: Option<Vec<CertCommonName>>,
-    pub usernames: Option<Vec<Username>>,
+    #[serde(flatten)]
+    pub subject: InterceptorSubjectConf,
 }
 
 #[derive(Serialize, Debug, Deserialize, Clone, PartialEq, Eq, Hash)]
@@ -151,6 +312,120 @@ impl std::fmt::Display for Username {
     }
 }
 
+/// The protocol of the links of a transport.
+#[derive(Serialize, Debug, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum LinkProtocol {
+    Tcp,
+    Udp,
+    Tls,
+    Quic,
+    Ws,
+    Serial,
+    UnixsockStream,
+    Unixpipe,
+    Vsock,
+    /// Matches the transports with shared memory enabled, whatever their link protocol
+    Shm,
+}
+
+impl LinkProtocol {
+    /// The link protocol of a locator protocol name.
+    pub fn from_locator_protocol(protocol: &str) -> Option<Self> {
+        match protocol {
+            "tcp" => Some(Self::Tcp),
+            "udp" => Some(Self::Udp),
+            "tls" => Some(Self::Tls),
+            "quic" => Some(Self::Quic),
+            "ws" => Some(Self::Ws),
+            "serial" => Some(Self::Serial),
+            "unixsock-stream" => Some(Self::UnixsockStream),
+            "unixpipe" => Some(Self::Unixpipe),
+            "vsock" => Some(Self::Vsock),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for LinkProtocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "LinkProtocol({self:?})")
+    }
+}
+
+/// An IP network in CIDR notation, e.g. `192.168.1.0/24` or `fd00::/8`.
+/// An address without prefix length designates a single host.
+#[derive(SerializeDisplay, DeserializeFromStr, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IpNetwork {
+    addr: IpAddr,
+    prefix_len: u8,
+}
+
+impl IpNetwork {
+    pub fn new(addr: IpAddr, prefix_len: u8) -> ZResult<Self> {
+        let max_len = match addr {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        if prefix_len > max_len {
+            bail!("Invalid prefix length {} for {}", prefix_len, addr);
+        }
+        Ok(Self { addr, prefix_len })
+    }
+
+    /// Checks if the network contains the given address, IPv4-mapped IPv6 addresses
+    /// are considered as IPv4 addresses.
+    pub fn contains(&self, addr: &IpAddr) -> bool {
+        match (self.addr, addr.to_canonical()) {
+            (IpAddr::V4(net), IpAddr::V4(addr)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - self.prefix_len as u32)
+                    .unwrap_or(0);
+                u32::from(net) & mask == u32::from(addr) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(addr)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - self.prefix_len as u32)
+                    .unwrap_or(0);
+                u128::from(net) & mask == u128::from(addr) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl std::str::FromStr for IpNetwork {
+    type Err = zenoh_result::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix_len) = match s.split_once('/') {
+            Some((addr, prefix_len)) => (
+                addr,
+                Some(
+                    prefix_len
+                        .parse::<u8>()
+                        .map_err(|e| zerror!("Invalid IP network '{}': {}", s, e))?,
+                ),
+            ),
+            None => (s, None),
+        };
+        let addr = addr
+            .parse::<IpAddr>()
+            .map_err(|e| zerror!("Invalid IP network '{}': {}", s, e))?;
+        let prefix_len = prefix_len.unwrap_or(match addr {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        });
+        Self::new(addr, prefix_len)
+    }
+}
+
+impl std::fmt::Display for IpNetwork {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix_len)
+    }
+}
+
 #[derive(Serialize, Debug, Deserialize, Clone, PartialEq, Eq, Hash)]
 pub struct AclConfigPolicyEntry {
     pub rules: Vec<String>,
@@ -178,6 +453,12 @@ pub enum AclMessage {
     LivelinessToken,
     DeclareLivelinessSubscriber,
     LivelinessQuery,
+    /// Interest in the subscribers matching a publisher (matching status)
+    DeclarePublisher,
+    /// Interest in the queryables matching a querier (matching status)
+    DeclareQuerier,
+    /// Any other interest, e.g. in key expression declarations only
+    Interest,
 }
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, Eq, Hash, PartialEq)]
@@ -187,6 +468,17 @@ pub enum Permission {
     Deny,
 }
 
+/// How the access control decisions are applied.
+#[derive(Default, Clone, Copy, Debug, Serialize, Deserialize, Eq, Hash, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AclEnforcement {
+    /// Denied messages are dropped
+    #[default]
+    Enforce,
+    /// Denied messages are logged and counted, but not dropped
+    Audit,
+}
+
 /// Strategy for autoconnection, mainly to avoid nodes connecting to each other redundantly.
 #[derive(Default, Clone, Copy, Debug, Serialize, Deserialize, Eq, Hash, PartialEq)]
 #[serde(rename_all = "kebab-case")]
@@ -369,12 +661,46 @@ validated_struct::validator! {
                 /// connected to each other.
                 /// The failover brokering only works if gossip discovery is enabled.
                 peers_failover_brokering: Option<bool>,
+                /// The weights of the links of the routers network.
+                pub linkstate: #[derive(Default)]
+                LinkstateConf {
+                    /// The weights of the links (transports) to the given nodes.
+                    /// Trees are computed over the lowest total weight paths.
+                    transport_weights: Vec<TransportWeight>,
+                    /// Interval in milliseconds between round-trip time measurements of the links.
+                    /// If set, the links without configured weight get a weight derived from their measured round-trip time,
+                    /// otherwise they get the default weight.
+                    rtt_probe_interval_ms: Option<u64>,
+                },
+                /// The redundant delivery of critical data along two paths of the routers network.
+                pub redundancy: #[derive(Default)]
+                RedundancyConf {
+                    /// The key-expressions whose puts and deletes are forwarded along two trees of the routers
+                    /// network, as disjoint as its links allow, the duplicates being dropped by the receiving routers.
+                    key_exprs: Vec<OwnedKeyExpr>,
+                },
+                /// The region of the router and the gateway routers of other regions it is connected to.
+                pub region: #[derive(Default)]
+                RegionConf {
+                    /// The name of the region of the router, a single key expression chunk without wildcards.
+                    name: Option<String>,
+                    /// The gateway routers of other regions this router is connected to.
+                    gateways: Vec<RegionGatewayConf>,
+                },
+                /// The aggregation of the declarations advertised by the router on behalf of its clients and peers.
+                pub aggregation: #[derive(Default)]
+                RouterAggregationConf {
+                    /// The key-expressions advertised to the routers and gateways in place of the subscriptions of clients and peers they include.
+                    subscribers: Vec<OwnedKeyExpr>,
+                },
             },
             /// The routing strategy to use in peers and it's configuration.
             pub peer: #[derive(Default)]
             PeerRoutingConf {
                 /// The routing strategy to use in peers. ("peer_to_peer" or "linkstate").
                 mode: Option<String>,
+                /// The weights of the links of the peers network in "linkstate" mode.
+                pub linkstate: LinkstateConf,
             },
             /// The interests-based routing configuration.
             /// This configuration applies regardless of the mode (router, peer or client).
@@ -383,6 +709,16 @@ validated_struct::validator! {
                 /// The timeout to wait for incoming interests declarations.
                 timeout: Option<u64>,
             },
+            /// The maximum number of links (transports) the data and query messages originated by this node
+            /// may traverse. Messages exceeding it are dropped. No limit if not set.
+            hop_limit: Option<NonZeroU8>,
+            /// The queries routing configuration.
+            pub queries: #[derive(Default)]
+            QueriesRoutingConf {
+                /// How queries targeting the best matching queryable are distributed among
+                /// the complete queryables. ("first", "round_robin", "random" or "least_outstanding")
+                load_balancing: Option<QueryLoadBalancing>,
+            },
         },
 
         /// The declarations aggregation strategy.
@@ -399,6 +735,8 @@ validated_struct::validator! {
         QoSConfig {
             /// A list of QoS configurations for PUT and DELETE messages by key expressions
             publication: PublisherQoSConfList,
+            /// A list of QoS overwrites for the messages routed through this node
+            network: Vec<QosOverwriteItemConf>,
         },
 
         pub transport: #[derive(Default)]
@@ -634,15 +972,43 @@ validated_struct::validator! {
         /// Configuration of the downsampling.
         downsampling: Vec<DownsamplingItemConf>,
 
+        /// Configuration of the bandwidth and message rate quotas.
+        quota: Vec<QuotaItemConf>,
+
+        /// Configuration of the key-expressions rewriting.
+        rewrite: Vec<RewriteItemConf>,
+
+        /// Configuration of the namespaces confining subjects to a part of the key space.
+        namespaces: Vec<NamespaceConf>,
+
         ///Configuration of the access control (ACL)
         pub access_control: AclConfig {
             pub enabled: bool,
             pub default_permission: Permission,
+            /// Whether denied messages are dropped or only reported
+            pub enforcement: AclEnforcement,
             pub rules: Option<Vec<AclConfigRule>>,
             pub subjects: Option<Vec<AclConfigSubjects>>,
             pub policies: Option<Vec<AclConfigPolicyEntry>>,
         },
 
+        /// Configuration of the audit log of the data messages (put, delete, query, reply).
+        pub audit: AuditConf {
+            pub enabled: bool,
+            /// Path of the audit log file, rotated files are suffixed with `.1`, `.2`, ...
+            pub path: Option<String>,
+            pub format: AuditFormat,
+            /// A list of key-expressions, the messages on key expressions intersecting one of them
+            /// are audited. All key expressions are audited if None
+            pub key_exprs: Option<Vec<OwnedKeyExpr>>,
+            /// The audited flows, both flows are audited if None
+            pub flows: Option<Vec<InterceptorFlow>>,
+            /// The size in bytes above which the audit log file is rotated, no rotation if None
+            pub max_file_size: Option<u64>,
+            /// The number of rotated files to keep
+            pub max_files: Option<usize>,
+        },
+
         /// A list of directories where plugins may be searched for if no `__path__` was specified for them.
         /// The executable's current directory will be added to the search paths.
         pub plugins_loading: #[derive(Default)]
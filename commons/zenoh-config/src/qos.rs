@@ -15,6 +15,8 @@ use serde::{Deserialize, Serialize};
 use zenoh_keyexpr::keyexpr_tree::{IKeyExprTreeMut, KeBoxTree};
 use zenoh_protocol::core::{key_expr::OwnedKeyExpr, CongestionControl, Reliability};
 
+use crate::{InterceptorFlow, InterceptorSubjectConf};
+
 #[derive(Debug, Deserialize, Default, Serialize, Clone)]
 pub struct PublisherQoSConfList(pub(crate) Vec<PublisherQoSConf>);
 
@@ -117,3 +119,38 @@ pub enum PublisherLocalityConf {
     Remote,
     Any,
 }
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct QosOverwriteItemConf {
+    /// Optional identifier for the QoS overwrite configuration item
+    pub id: Option<String>,
+    /// A list of subjects whose messages will be overwritten.
+    /// Messages of all transports are overwritten if the parameter is None
+    pub subjects: Option<Vec<InterceptorSubjectConf>>,
+    /// A list of message types to overwrite: put, delete, query, reply.
+    /// All of them are overwritten if the parameter is None
+    pub messages: Option<Vec<QosOverwriteMessage>>,
+    /// A list of key-expressions, the messages on key expressions intersecting one of them are overwritten.
+    /// Messages on all key expressions are overwritten if the parameter is None
+    pub key_exprs: Option<Vec<OwnedKeyExpr>>,
+    /// The QoS options to overwrite, the options that are not supplied are left untouched
+    pub overwrite: QosOverwrites,
+    /// QoS overwrite flow direction: egress, ingress
+    pub flow: InterceptorFlow,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
+pub struct QosOverwrites {
+    pub congestion_control: Option<PublisherCongestionControlConf>,
+    pub priority: Option<PublisherPriorityConf>,
+    pub express: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QosOverwriteMessage {
+    Put,
+    Delete,
+    Query,
+    Reply,
+}
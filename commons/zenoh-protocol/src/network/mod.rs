@@ -69,6 +69,9 @@ impl Mapping {
 }
 
 // Zenoh messages at zenoh-network level
+// Clippy cannot size the pushes and responses, whose push bodies may carry batches of push bodies,
+// and reports the requests as the largest variant while they are smaller than both
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum NetworkBody {
     Push(Push),
@@ -373,6 +376,45 @@ pub mod ext {
         }
     }
 
+    /// ```text
+    ///  7 6 5 4 3 2 1 0
+    /// +-+-+-+-+-+-+-+-+
+    /// |Z|0_1|    ID   |
+    /// +-+-+-+---------+
+    /// %      hops     %
+    /// +---------------+
+    /// ```
+    /// The number of links (transports) a message may still traverse, set by the originator
+    /// and decremented by each node forwarding the message to another node.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct HopLimitType<const ID: u8> {
+        pub hops: u8,
+    }
+
+    impl<const ID: u8> HopLimitType<{ ID }> {
+        #[cfg(feature = "test")]
+        pub fn rand() -> Self {
+            use rand::Rng;
+            let mut rng = rand::thread_rng();
+            let hops = rng.gen();
+            Self { hops }
+        }
+    }
+
+    impl<const ID: u8> From<ZExtZ64<{ ID }>> for HopLimitType<{ ID }> {
+        fn from(ext: ZExtZ64<{ ID }>) -> Self {
+            Self {
+                hops: ext.value.min(u8::MAX as u64) as u8,
+            }
+        }
+    }
+
+    impl<const ID: u8> From<HopLimitType<{ ID }>> for ZExtZ64<{ ID }> {
+        fn from(ext: HopLimitType<{ ID }>) -> Self {
+            ZExtZ64::new(ext.hops as u64)
+        }
+    }
+
     /// ```text
     ///  7 6 5 4 3 2 1 0
     /// +-+-+-+-+-+-+-+-+
@@ -446,4 +488,39 @@ pub mod ext {
             Self { zid, eid }
         }
     }
+
+    /// ```text
+    ///  7 6 5 4 3 2 1 0
+    /// +-+-+-+-+-+-+-+-+
+    /// |zid_len|X|X|X|T|
+    /// +-------+-+-+---+
+    /// ~      zid      ~
+    /// +---------------+
+    /// %      sn       %
+    /// +---------------+
+    ///
+    /// - T: Tree. The message is routed along the redundant tree of the node designated by
+    ///      the node id, else along its regular tree.
+    /// ```
+    /// Identifies a message delivered redundantly by the routers to detect its copies:
+    /// the router it was first received by, and the sequence number this router gave it.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct RedundantType<const ID: u8> {
+        pub zid: ZenohIdProto,
+        pub sn: u32,
+        pub tree: bool,
+    }
+
+    impl<const ID: u8> RedundantType<{ ID }> {
+        #[cfg(feature = "test")]
+        pub fn rand() -> Self {
+            use rand::Rng;
+            let mut rng = rand::thread_rng();
+
+            let zid = ZenohIdProto::rand();
+            let sn: u32 = rng.gen();
+            let tree = rng.gen_bool(0.5);
+            Self { zid, sn, tree }
+        }
+    }
 }
@@ -25,6 +25,13 @@ pub mod id {
     use super::OamId;
 
     pub const OAM_LINKSTATE: OamId = 0x0001;
+    pub const OAM_LINK_RTT: OamId = 0x0002;
+    // Sent in the direction of a request by a requester no longer interested in its responses,
+    // the body being the request id as a Z64. Ignored by the nodes not supporting it.
+    pub const OAM_QUERY_CANCEL: OamId = 0x0003;
+    // Sent in the direction of a request by a requester granting more responses to one of its
+    // responders, the body being a ZBuf. Ignored by the nodes not supporting it.
+    pub const OAM_QUERY_CREDIT: OamId = 0x0004;
 }
 
 /// ```text
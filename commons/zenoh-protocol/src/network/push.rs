@@ -44,6 +44,9 @@ pub struct Push {
     pub ext_qos: ext::QoSType,
     pub ext_tstamp: Option<ext::TimestampType>,
     pub ext_nodeid: ext::NodeIdType,
+    pub ext_hop_limit: Option<ext::HopLimitType>,
+    pub ext_lifespan: Option<ext::LifespanType>,
+    pub ext_redundant: Option<ext::RedundantType>,
     pub payload: PushBody,
 }
 
@@ -61,6 +64,19 @@ pub mod ext {
 
     pub type NodeId = zextz64!(0x3, true);
     pub type NodeIdType = crate::network::ext::NodeIdType<{ NodeId::ID }>;
+
+    pub type HopLimit = zextz64!(0x4, false);
+    pub type HopLimitType = crate::network::ext::HopLimitType<{ HopLimit::ID }>;
+
+    // The duration after the timestamp of the data beyond which it is stale and discarded
+    pub type Lifespan = zextz64!(0x5, false);
+    pub type LifespanType = core::time::Duration;
+
+    // The copies of a message delivered redundantly by the routers, routed along the regular
+    // and the redundant trees, the nodes not supporting this extension only route it along
+    // the regular tree
+    pub type Redundant = zextzbuf!(0x6, false);
+    pub type RedundantType = crate::network::ext::RedundantType<{ Redundant::ID }>;
 }
 
 impl Push {
@@ -74,6 +90,13 @@ impl Push {
         let ext_qos = ext::QoSType::rand();
         let ext_tstamp = rng.gen_bool(0.5).then(ext::TimestampType::rand);
         let ext_nodeid = ext::NodeIdType::rand();
+        let ext_hop_limit = rng.gen_bool(0.5).then(ext::HopLimitType::rand);
+        let ext_lifespan = if rng.gen_bool(0.5) {
+            Some(ext::LifespanType::from_millis(rng.gen()))
+        } else {
+            None
+        };
+        let ext_redundant = rng.gen_bool(0.5).then(ext::RedundantType::rand);
 
         Self {
             wire_expr,
@@ -81,6 +104,9 @@ impl Push {
             ext_tstamp,
             ext_qos,
             ext_nodeid,
+            ext_hop_limit,
+            ext_lifespan,
+            ext_redundant,
         }
     }
 }
@@ -61,6 +61,7 @@ pub struct Request {
     pub ext_target: ext::QueryTarget,
     pub ext_budget: Option<ext::BudgetType>,
     pub ext_timeout: Option<ext::TimeoutType>,
+    pub ext_hop_limit: Option<ext::HopLimitType>,
     pub payload: RequestBody,
 }
 
@@ -129,6 +130,10 @@ pub mod ext {
     // The timeout of the request
     pub type Timeout = zextz64!(0x6, false);
     pub type TimeoutType = Duration;
+
+    // The number of links the request may still traverse
+    pub type HopLimit = zextz64!(0x7, false);
+    pub type HopLimitType = crate::network::ext::HopLimitType<{ HopLimit::ID }>;
 }
 
 impl Request {
@@ -156,6 +161,7 @@ impl Request {
         } else {
             None
         };
+        let ext_hop_limit = rng.gen_bool(0.5).then(ext::HopLimitType::rand);
 
         Self {
             wire_expr,
@@ -167,6 +173,7 @@ impl Request {
             ext_target,
             ext_budget,
             ext_timeout,
+            ext_hop_limit,
         }
     }
 }
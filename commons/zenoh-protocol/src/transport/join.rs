@@ -134,6 +134,7 @@ pub mod ext {
     /// Used to negotiate the patch version of the protocol
     /// if not present (or 0), then protocol as released with 1.0.0
     /// if >= 1, then fragmentation first/drop markers
+    /// if >= 2, then batch push bodies
     pub type Patch = zextz64!(0x7, false); // use the same id as Init
     pub type PatchType = crate::transport::ext::PatchType<{ Patch::ID }>;
 }
@@ -0,0 +1,125 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use alloc::{string::String, vec::Vec};
+
+use uhlc::Timestamp;
+
+use crate::{common::ZExtUnknown, zenoh::PushBody};
+
+/// # Batch message
+///
+/// A batch atomically carries several puts and deletes on different key expressions,
+/// sharing the same timestamp. The key expression of the [`Push`](crate::network::Push)
+/// carrying the batch includes the key expressions of all its entries: it is their common
+/// prefix followed by `**`, or `**` if they share none. Batches are only sent on the transports that negotiated a
+/// [patch](crate::transport::init::ext::Patch) of at least 2.
+///
+/// ```text
+/// Flags:
+/// - T: Timestamp      If T==1 then the timestamp is present
+/// - X: Reserved
+/// - Z: Extension      If Z==1 then at least one extension is present
+///
+///   7 6 5 4 3 2 1 0
+///  +-+-+-+-+-+-+-+-+
+///  |Z|X|T|  BATCH  |
+///  +-+-+-+---------+
+///  ~ ts: <u8;z16>  ~  if T==1
+///  +---------------+
+///  ~ [batch_exts]  ~  if Z==1
+///  +---------------+
+///  %   len:z32     %  -- The number of entries
+///  +---------------+
+///  ~ key:<u8;z16>  ~  -- The key expression of the entry \
+///  +---------------+                                       | len times
+///  ~   PushBody    ~  -- The Put or Del of the entry     /
+///  +---------------+
+/// ```
+///
+/// The entries are not timestamped: they share the timestamp of the batch.
+pub mod flag {
+    pub const T: u8 = 1 << 5; // 0x20 Timestamp     if T==1 then the timestamp is present
+                              // pub const X: u8 = 1 << 6; // 0x40 Reserved
+    pub const Z: u8 = 1 << 7; // 0x80 Extensions    if Z==1 then an extension will follow
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Batch {
+    pub timestamp: Option<Timestamp>,
+    pub ext_sinfo: Option<ext::SourceInfoType>,
+    pub ext_unknown: Vec<ZExtUnknown>,
+    pub entries: Vec<BatchEntry>,
+}
+
+/// An entry of a [`Batch`]: a [`Put`](crate::zenoh::Put) or a [`Del`](crate::zenoh::Del)
+/// on a key expression. Entries cannot be batches themselves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchEntry {
+    pub key_expr: String,
+    pub payload: PushBody,
+}
+
+pub mod ext {
+    use crate::{common::ZExtZBuf, zextzbuf};
+
+    /// # SourceInfo extension
+    /// Used to carry additional information about the source of data
+    pub type SourceInfo = zextzbuf!(0x1, false);
+    pub type SourceInfoType = crate::zenoh::ext::SourceInfoType<{ SourceInfo::ID }>;
+}
+
+impl Batch {
+    #[cfg(feature = "test")]
+    pub fn rand() -> Self {
+        use rand::Rng;
+
+        use crate::{
+            common::iext,
+            core::ZenohIdProto,
+            zenoh::{Del, Put},
+        };
+        let mut rng = rand::thread_rng();
+
+        let timestamp = rng.gen_bool(0.5).then_some({
+            let time = uhlc::NTP64(rng.gen());
+            let id = uhlc::ID::try_from(ZenohIdProto::rand().to_le_bytes()).unwrap();
+            Timestamp::new(time, id)
+        });
+        let ext_sinfo = rng.gen_bool(0.5).then_some(ext::SourceInfoType::rand());
+        let mut ext_unknown = Vec::new();
+        for _ in 0..rng.gen_range(0..4) {
+            ext_unknown.push(ZExtUnknown::rand2(
+                iext::mid(ext::SourceInfo::ID) + 1,
+                false,
+            ));
+        }
+        let entries = (0..rng.gen_range(0..4))
+            .map(|i| BatchEntry {
+                key_expr: alloc::format!("batch/{i}"),
+                payload: if rng.gen_bool(0.5) {
+                    PushBody::Put(Put::rand())
+                } else {
+                    PushBody::Del(Del::rand())
+                },
+            })
+            .collect();
+
+        Self {
+            timestamp,
+            ext_sinfo,
+            ext_unknown,
+            entries,
+        }
+    }
+}
@@ -11,18 +11,22 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
+pub mod batch;
 pub mod del;
 pub mod err;
 pub mod put;
 pub mod query;
 pub mod reply;
 
+pub use batch::{Batch, BatchEntry};
 pub use del::Del;
 pub use err::Err;
 pub use put::Put;
 pub use query::{ConsolidationMode, Query};
 pub use reply::Reply;
 
+use uhlc::Timestamp;
+
 use crate::core::Encoding;
 
 pub mod id {
@@ -32,6 +36,7 @@ pub mod id {
     pub const QUERY: u8 = 0x03;
     pub const REPLY: u8 = 0x04;
     pub const ERR: u8 = 0x05;
+    pub const BATCH: u8 = 0x06;
 }
 
 // DataInfo
@@ -45,18 +50,29 @@ pub struct DataInfo {
 pub enum PushBody {
     Put(Put),
     Del(Del),
+    Batch(Batch),
 }
 
 impl PushBody {
+    /// The timestamp of the pushed data, if any.
+    pub fn timestamp(&self) -> Option<&Timestamp> {
+        match self {
+            PushBody::Put(p) => p.timestamp.as_ref(),
+            PushBody::Del(d) => d.timestamp.as_ref(),
+            PushBody::Batch(b) => b.timestamp.as_ref(),
+        }
+    }
+
     #[cfg(feature = "test")]
     pub fn rand() -> Self {
         use rand::Rng;
 
         let mut rng = rand::thread_rng();
 
-        match rng.gen_range(0..2) {
+        match rng.gen_range(0..3) {
             0 => PushBody::Put(Put::rand()),
             1 => PushBody::Del(Del::rand()),
+            2 => PushBody::Batch(Batch::rand()),
             _ => unreachable!(),
         }
     }
@@ -74,6 +90,12 @@ impl From<Del> for PushBody {
     }
 }
 
+impl From<Batch> for PushBody {
+    fn from(b: Batch) -> PushBody {
+        PushBody::Batch(b)
+    }
+}
+
 // Request
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum RequestBody {
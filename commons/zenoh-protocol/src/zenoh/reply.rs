@@ -37,6 +37,8 @@ use crate::{
 ///  ~   ReplyBody   ~  -- Payload
 ///  +---------------+
 /// ```
+///
+/// The payload of a reply is a Put or a Del, batches are not valid replies.
 pub mod flag {
     pub const C: u8 = 1 << 5; // 0x20 Consolidation if C==1 then consolidation is present
                               // pub const X: u8 = 1 << 6; // 0x40 Reserved
@@ -56,9 +58,15 @@ impl Reply {
     #[cfg(feature = "test")]
     pub fn rand() -> Self {
         use rand::Rng;
+
+        use crate::zenoh::{Del, Put};
         let mut rng = rand::thread_rng();
 
-        let payload = ReplyBody::rand();
+        let payload = if rng.gen_bool(0.5) {
+            ReplyBody::Put(Put::rand())
+        } else {
+            ReplyBody::Del(Del::rand())
+        };
         let consolidation = ConsolidationMode::rand();
         let mut ext_unknown = Vec::new();
         for _ in 0..rng.gen_range(0..4) {
@@ -611,6 +611,9 @@ mod tests {
             ext_qos: ext::QoSType::new(Priority::DEFAULT, CongestionControl::Block, false),
             ext_tstamp: None,
             ext_nodeid: ext::NodeIdType::DEFAULT,
+            ext_hop_limit: None,
+            ext_lifespan: None,
+            ext_redundant: None,
             payload: PushBody::Put(Put {
                 timestamp: None,
                 encoding: Encoding::empty(),
@@ -1042,6 +1042,9 @@ mod tests {
                 ext_qos: ext::QoSType::new(Priority::Control, CongestionControl::Block, false),
                 ext_tstamp: None,
                 ext_nodeid: ext::NodeIdType::DEFAULT,
+                ext_hop_limit: None,
+                ext_lifespan: None,
+                ext_redundant: None,
                 payload: PushBody::Put(Put {
                     timestamp: None,
                     encoding: Encoding::empty(),
@@ -1170,6 +1173,9 @@ mod tests {
                 ext_qos: ext::QoSType::new(Priority::Control, CongestionControl::Block, false),
                 ext_tstamp: None,
                 ext_nodeid: ext::NodeIdType::DEFAULT,
+                ext_hop_limit: None,
+                ext_lifespan: None,
+                ext_redundant: None,
                 payload: PushBody::Put(Put {
                     timestamp: None,
                     encoding: Encoding::empty(),
@@ -1288,6 +1294,9 @@ mod tests {
                         ),
                         ext_tstamp: None,
                         ext_nodeid: ext::NodeIdType::DEFAULT,
+                        ext_hop_limit: None,
+                        ext_lifespan: None,
+                        ext_redundant: None,
                         payload: PushBody::Put(Put {
                             timestamp: None,
                             encoding: Encoding::empty(),
@@ -1347,6 +1356,9 @@ mod tests {
             ext_qos: ext::QoSType::new(Priority::Control, CongestionControl::Block, true),
             ext_tstamp: None,
             ext_nodeid: ext::NodeIdType::DEFAULT,
+            ext_hop_limit: None,
+            ext_lifespan: None,
+            ext_redundant: None,
             payload: PushBody::Put(Put {
                 timestamp: None,
                 encoding: Encoding::empty(),
@@ -245,6 +245,10 @@ stats_struct! {
         # TYPE "counter"
         pub rx_n_dropped,
 
+        # HELP "Counter of received network messages dropped because their hop limit expired."
+        # TYPE "counter"
+        pub rx_n_expired,
+
         # HELP "Counter of received zenoh put messages."
         # TYPE "counter"
         pub rx_z_put_msgs DiscriminatedStats,
@@ -22,7 +22,7 @@ use zenoh_protocol::{
         err::Err,
         ext::ShmType,
         query::{ext::QueryBodyType, Query},
-        PushBody, Put, Reply, RequestBody, ResponseBody,
+        Batch, PushBody, Put, Reply, RequestBody, ResponseBody,
     },
 };
 use zenoh_result::ZResult;
@@ -66,6 +66,7 @@ pub fn map_zmsg_to_partner<ShmCfg: PartnerShmConfig>(
         NetworkBody::Push(Push { payload, .. }) => match payload {
             PushBody::Put(b) => b.map_to_partner(partner_shm_cfg),
             PushBody::Del(_) => Ok(()),
+            PushBody::Batch(b) => b.map_to_partner(partner_shm_cfg),
         },
         NetworkBody::Request(Request { payload, .. }) => match payload {
             RequestBody::Query(b) => b.map_to_partner(partner_shm_cfg),
@@ -86,6 +87,7 @@ pub fn map_zmsg_to_shmbuf(msg: &mut NetworkMessage, shmr: &ShmReader) -> ZResult
         NetworkBody::Push(Push { payload, .. }) => match payload {
             PushBody::Put(b) => b.map_to_shmbuf(shmr),
             PushBody::Del(_) => Ok(()),
+            PushBody::Batch(b) => b.map_to_shmbuf(shmr),
         },
         NetworkBody::Request(Request { payload, .. }) => match payload {
             RequestBody::Query(b) => b.map_to_shmbuf(shmr),
@@ -209,6 +211,30 @@ impl MapShm for Query {
     }
 }
 
+// Impl - Batch
+impl MapShm for Batch {
+    fn map_to_partner<ShmCfg: PartnerShmConfig>(
+        &mut self,
+        partner_shm_cfg: &Option<ShmCfg>,
+    ) -> ZResult<()> {
+        for entry in self.entries.iter_mut() {
+            if let PushBody::Put(put) = &mut entry.payload {
+                put.map_to_partner(partner_shm_cfg)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn map_to_shmbuf(&mut self, shmr: &ShmReader) -> ZResult<()> {
+        for entry in self.entries.iter_mut() {
+            if let PushBody::Put(put) = &mut entry.payload {
+                put.map_to_shmbuf(shmr)?;
+            }
+        }
+        Ok(())
+    }
+}
+
 // Impl - Reply
 impl MapShm for Reply {
     fn map_to_partner<ShmCfg: PartnerShmConfig>(
@@ -222,7 +248,7 @@ impl MapShm for Reply {
                 } = put;
                 map_to_partner!(payload, ext_shm, partner_shm_cfg)
             }
-            PushBody::Del(_) => Ok(()),
+            PushBody::Del(_) | PushBody::Batch(_) => Ok(()),
         }
     }
 
@@ -234,7 +260,7 @@ impl MapShm for Reply {
                 } = put;
                 map_zbuf_to_shmbuf!(payload, ext_shm, shmr)
             }
-            PushBody::Del(_) => Ok(()),
+            PushBody::Del(_) | PushBody::Batch(_) => Ok(()),
         }
     }
 }
@@ -91,6 +91,12 @@ impl TransportUnicast {
         Ok(transport.get_whatami())
     }
 
+    #[inline(always)]
+    pub fn get_patch(&self) -> ZResult<PatchType> {
+        let transport = self.get_inner()?;
+        Ok(transport.get_config().patch)
+    }
+
     #[cfg(feature = "shared-memory")]
     #[inline(always)]
     pub fn is_shm(&self) -> ZResult<bool> {
@@ -54,6 +54,9 @@ lazy_static! {
         ext_qos: QoSType::new(Priority::DEFAULT, CongestionControl::Drop, false),
         ext_tstamp: None,
         ext_nodeid: NodeIdType::DEFAULT,
+        ext_hop_limit: None,
+        ext_lifespan: None,
+        ext_redundant: None,
         payload: Put {
             // 10 MB payload to stress fragmentation
             payload: (0..10_000_000).map(|b| b as u8).collect::<Vec<u8>>().into(),
@@ -252,6 +252,9 @@ mod tests {
                 ext_qos: QoSType::new(Priority::DEFAULT, CongestionControl::Block, false),
                 ext_tstamp: None,
                 ext_nodeid: NodeIdType::DEFAULT,
+                ext_hop_limit: None,
+                ext_lifespan: None,
+                ext_redundant: None,
                 payload: Put {
                     payload: sbuf.into(),
                     timestamp: None,
@@ -293,6 +296,9 @@ mod tests {
                 ext_qos: QoSType::new(Priority::DEFAULT, CongestionControl::Block, false),
                 ext_tstamp: None,
                 ext_nodeid: NodeIdType::DEFAULT,
+                ext_hop_limit: None,
+                ext_lifespan: None,
+                ext_redundant: None,
                 payload: Put {
                     payload: sbuf.into(),
                     timestamp: None,
@@ -76,6 +76,9 @@ mod tests {
                 ext_qos: QoSType::new(Priority::Control, CongestionControl::Block, false),
                 ext_tstamp: None,
                 ext_nodeid: NodeIdType::DEFAULT,
+                ext_hop_limit: None,
+                ext_lifespan: None,
+                ext_redundant: None,
                 payload: Put {
                     payload: vec![0u8; MSG_SIZE].into(),
                     timestamp: None,
@@ -179,6 +179,10 @@ impl StorageService {
                                 continue;
                             }
                         };
+                        if sample.is_expired() {
+                            tracing::debug!("Discarding expired sample on {}", sample.key_expr());
+                            continue;
+                        }
                         let timestamp = sample.timestamp().cloned().unwrap_or(self.session.new_timestamp());
                         let sample = SampleBuilder::from(sample).timestamp(timestamp).into();
                         if let Err(e) = self.process_sample(sample).await {
@@ -260,6 +260,9 @@ impl AdvancedCache {
                                         .source_sn()
                                         .is_some_and(|sn| range.contains(&sn))
                                 {
+                                    if sample.is_expired() {
+                                        continue;
+                                    }
                                     if let (Some(Ok(time_range)), Some(timestamp)) =
                                         (query.parameters().time_range(), sample.timestamp())
                                     {
@@ -297,6 +300,9 @@ impl AdvancedCache {
                                         .source_sn()
                                         .is_some_and(|sn| range.contains(&sn))
                                 {
+                                    if sample.is_expired() {
+                                        continue;
+                                    }
                                     if let (Some(Ok(time_range)), Some(timestamp)) =
                                         (query.parameters().time_range(), sample.timestamp())
                                     {
@@ -352,6 +358,10 @@ impl AdvancedCache {
     #[zenoh_macros::unstable]
     pub(crate) fn cache_sample(&self, sample: Sample) {
         if let Ok(mut queue) = self.cache.write() {
+            // Samples older than their lifespan are no longer part of the history
+            while queue.front().is_some_and(|sample| sample.is_expired()) {
+                queue.pop_front();
+            }
             if queue.len() >= self.max_samples {
                 queue.pop_front();
             }
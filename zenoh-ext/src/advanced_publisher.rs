@@ -82,6 +82,7 @@ pub struct AdvancedPublisherBuilder<'a, 'b, 'c> {
     encoding: Encoding,
     destination: Locality,
     reliability: Reliability,
+    lifespan: Option<Duration>,
     congestion_control: CongestionControl,
     priority: Priority,
     is_express: bool,
@@ -103,6 +104,7 @@ impl<'a, 'b, 'c> AdvancedPublisherBuilder<'a, 'b, 'c> {
             encoding: builder.encoding,
             destination: builder.destination,
             reliability: builder.reliability,
+            lifespan: builder.lifespan,
             congestion_control: builder.congestion_control,
             priority: builder.priority,
             is_express: builder.is_express,
@@ -140,6 +142,19 @@ impl<'a, 'b, 'c> AdvancedPublisherBuilder<'a, 'b, 'c> {
         }
     }
 
+    /// Changes the lifespan of the published data.
+    ///
+    /// The data is timestamped and, once older than its lifespan, it is discarded by the routers,
+    /// the subscribers and the cache instead of being delivered.
+    #[zenoh_macros::unstable]
+    #[inline]
+    pub fn lifespan(self, lifespan: Duration) -> Self {
+        Self {
+            lifespan: Some(lifespan),
+            ..self
+        }
+    }
+
     /// Allow matching [`AdvancedSubscribers`](crate::AdvancedSubscriber) to detect lost samples and optionally ask for retransimission.
     ///
     /// Retransmission can only be achieved if [`cache`](crate::AdvancedPublisherBuilder::cache) is enabled.
@@ -273,7 +288,7 @@ impl<'a> AdvancedPublisher<'a> {
             None => None,
         };
 
-        let publisher = conf
+        let mut publisher = conf
             .session
             .declare_publisher(key_expr.clone())
             .encoding(conf.encoding)
@@ -281,8 +296,11 @@ impl<'a> AdvancedPublisher<'a> {
             .reliability(conf.reliability)
             .congestion_control(conf.congestion_control)
             .priority(conf.priority)
-            .express(conf.is_express)
-            .wait()?;
+            .express(conf.is_express);
+        if let Some(lifespan) = conf.lifespan {
+            publisher = publisher.lifespan(lifespan);
+        }
+        let publisher = publisher.wait()?;
         let id = publisher.id();
         let prefix = KE_ADV_PREFIX / KE_PUB / &id.zid().into_keyexpr();
         let prefix = match conf.sequencing {
@@ -430,6 +448,8 @@ impl<'a> AdvancedPublisher<'a> {
         }
         if let Some(hlc) = self.publisher.session().hlc() {
             builder = builder.timestamp(hlc.new_timestamp());
+        } else if self.publisher.lifespan().is_some() {
+            builder = builder.timestamp(self.publisher.session().new_timestamp());
         }
         AdvancedPublisherPutBuilder {
             builder,
@@ -461,6 +481,8 @@ impl<'a> AdvancedPublisher<'a> {
         }
         if let Some(hlc) = self.publisher.session().hlc() {
             builder = builder.timestamp(hlc.new_timestamp());
+        } else if self.publisher.lifespan().is_some() {
+            builder = builder.timestamp(self.publisher.session().new_timestamp());
         }
         AdvancedPublisherDeleteBuilder {
             builder,
@@ -836,3 +836,83 @@ async fn test_advanced_retransmission_heartbeat() {
 
     router.close().await.unwrap();
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_advanced_history_lifespan() {
+    use std::time::Duration;
+
+    use zenoh::internal::ztimeout;
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+    const SLEEP: Duration = Duration::from_secs(1);
+    const LIFESPAN: Duration = Duration::from_secs(2);
+    const PEER1_ENDPOINT: &str = "tcp/localhost:27057";
+
+    const ADVANCED_HISTORY_LIFESPAN_KEYEXPR: &str = "test/advanced/history/lifespan";
+
+    zenoh_util::init_log_from_env_or("error");
+
+    let peer1 = {
+        let mut c = zenoh::Config::default();
+        c.listen
+            .endpoints
+            .set(vec![PEER1_ENDPOINT.parse::<EndPoint>().unwrap()])
+            .unwrap();
+        c.scouting.multicast.set_enabled(Some(false)).unwrap();
+        c.timestamping
+            .set_enabled(Some(ModeDependentValue::Unique(true)))
+            .unwrap();
+        let _ = c.set_mode(Some(WhatAmI::Peer));
+        let s = ztimeout!(zenoh::open(c)).unwrap();
+        tracing::info!("Peer (1) ZID: {}", s.zid());
+        s
+    };
+
+    let publ = ztimeout!(peer1
+        .declare_publisher(ADVANCED_HISTORY_LIFESPAN_KEYEXPR)
+        .cache(CacheConfig::default().max_samples(3))
+        .lifespan(LIFESPAN))
+    .unwrap();
+    ztimeout!(publ.put("1")).unwrap();
+    ztimeout!(publ.put("2")).unwrap();
+
+    tokio::time::sleep(LIFESPAN + SLEEP).await;
+    ztimeout!(publ.put("3")).unwrap();
+
+    let peer2 = {
+        let mut c = zenoh::Config::default();
+        c.connect
+            .endpoints
+            .set(vec![PEER1_ENDPOINT.parse::<EndPoint>().unwrap()])
+            .unwrap();
+        c.scouting.multicast.set_enabled(Some(false)).unwrap();
+        let _ = c.set_mode(Some(WhatAmI::Peer));
+        let s = ztimeout!(zenoh::open(c)).unwrap();
+        tracing::info!("Peer (2) ZID: {}", s.zid());
+        s
+    };
+
+    let sub = ztimeout!(peer2
+        .declare_subscriber(ADVANCED_HISTORY_LIFESPAN_KEYEXPR)
+        .history(HistoryConfig::default()))
+    .unwrap();
+    tokio::time::sleep(SLEEP).await;
+
+    ztimeout!(publ.put("4")).unwrap();
+    tokio::time::sleep(SLEEP).await;
+
+    // The samples older than their lifespan are not part of the history
+    let sample = ztimeout!(sub.recv_async()).unwrap();
+    assert_eq!(sample.payload().try_to_string().unwrap().as_ref(), "3");
+
+    let sample = ztimeout!(sub.recv_async()).unwrap();
+    assert_eq!(sample.payload().try_to_string().unwrap().as_ref(), "4");
+    assert_eq!(sample.lifespan(), Some(LIFESPAN));
+
+    assert!(sub.try_recv().unwrap().is_none());
+
+    publ.undeclare().await.unwrap();
+
+    peer1.close().await.unwrap();
+    peer2.close().await.unwrap();
+}
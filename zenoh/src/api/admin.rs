@@ -79,6 +79,7 @@ pub(crate) fn init(session: WeakSession) {
                 let session = session.clone();
                 move |q| on_admin_query(&session, KE_AT, q)
             })),
+            None,
         );
 
         let adv_prefix = KE_ADV_PREFIX / KE_PUB / own_zid / KE_EMPTY / KE_EMPTY / KE_AT / KE_AT;
@@ -91,6 +92,7 @@ pub(crate) fn init(session: WeakSession) {
                 let session = session.clone();
                 move |q| on_admin_query(&session, &adv_prefix, q)
             })),
+            None,
         );
     }
 }
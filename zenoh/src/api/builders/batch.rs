@@ -0,0 +1,233 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::{
+    future::{IntoFuture, Ready},
+    time::Duration,
+};
+
+use uhlc::Timestamp;
+use zenoh_core::{Resolvable, Wait};
+use zenoh_protocol::{
+    core::{CongestionControl, Reliability},
+    network::push,
+};
+use zenoh_result::ZResult;
+
+use crate::{
+    api::{
+        builders::sample::{QoSBuilderTrait, SampleBuilder, TimestampBuilderTrait},
+        bytes::ZBytes,
+        key_expr::KeyExpr,
+        publisher::Priority,
+        sample::{Locality, QoSBuilder, Sample},
+    },
+    Session,
+};
+
+/// A builder for publishing several puts and deletes atomically, returned by [`Session::batch`].
+///
+/// The samples of the batch are sent in a single message and share the same timestamp.
+/// Subscribers declared with a [`batch_callback`](crate::pubsub::SubscriberBuilder::batch_callback)
+/// matching all the key expressions of the batch receive it as a unit, the other subscribers
+/// receive the samples matching their key expression one by one.
+///
+/// The batch is routed on the common prefix of the key expressions of its samples (e.g.
+/// `robot/**` for `robot/pose` and `robot/velocity`, `**` if they share none). The nodes not
+/// supporting batches receive the samples as individual puts and deletes.
+///
+/// # Examples
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+///
+/// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+/// session
+///     .batch()
+///     .put("robot/pose", "1.0,2.0")
+///     .put("robot/velocity", "0.5")
+///     .delete("robot/covariance")
+///     .await
+///     .unwrap();
+/// # }
+/// ```
+#[must_use = "Resolvables do nothing unless you resolve them using `.await` or `zenoh::Wait::wait`"]
+#[derive(Debug)]
+pub struct BatchBuilder<'a> {
+    pub(crate) session: &'a Session,
+    pub(crate) samples: ZResult<Vec<Sample>>,
+    pub(crate) timestamp: Option<Timestamp>,
+    pub(crate) qos: QoSBuilder,
+    pub(crate) destination: Locality,
+    pub(crate) reliability: Reliability,
+    pub(crate) lifespan: Option<Duration>,
+}
+
+impl<'a> BatchBuilder<'a> {
+    pub(crate) fn new(session: &'a Session) -> Self {
+        Self {
+            session,
+            samples: Ok(Vec::new()),
+            timestamp: None,
+            qos: push::ext::QoSType::DEFAULT.into(),
+            destination: Locality::default(),
+            reliability: Reliability::DEFAULT,
+            lifespan: None,
+        }
+    }
+
+    /// Adds a put of `payload` on `key_expr` to the batch.
+    pub fn put<'b, TryIntoKeyExpr, IntoZBytes>(
+        self,
+        key_expr: TryIntoKeyExpr,
+        payload: IntoZBytes,
+    ) -> Self
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+        IntoZBytes: Into<ZBytes>,
+    {
+        let payload = payload.into();
+        self.push(key_expr, |key_expr| {
+            SampleBuilder::put(key_expr, payload).into()
+        })
+    }
+
+    /// Adds a delete of `key_expr` to the batch.
+    pub fn delete<'b, TryIntoKeyExpr>(self, key_expr: TryIntoKeyExpr) -> Self
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+    {
+        self.push(key_expr, |key_expr| SampleBuilder::delete(key_expr).into())
+    }
+
+    /// Adds a sample to the batch, allowing to set its encoding and attachment
+    /// with a [`SampleBuilder`].
+    ///
+    /// The timestamp and quality of service of the sample are the ones of the batch.
+    pub fn sample<IntoSample>(mut self, sample: IntoSample) -> Self
+    where
+        IntoSample: Into<Sample>,
+    {
+        if let Ok(samples) = &mut self.samples {
+            samples.push(sample.into());
+        }
+        self
+    }
+
+    /// Restricts the matching subscribers that will receive the batch to the ones
+    /// that have the given [`Locality`](Locality).
+    #[inline]
+    pub fn allowed_destination(mut self, destination: Locality) -> Self {
+        self.destination = destination;
+        self
+    }
+
+    /// Changes the [`crate::qos::Reliability`] to apply when routing the batch.
+    ///
+    /// **NOTE**: Currently `reliability` does not trigger any data retransmission on the wire. It
+    ///   is rather used as a marker on the wire and it may be used to select the best link
+    ///   available (e.g. TCP for reliable data and UDP for best effort data).
+    #[inline]
+    pub fn reliability(self, reliability: Reliability) -> Self {
+        Self {
+            reliability,
+            ..self
+        }
+    }
+
+    /// Changes the lifespan of the samples of the batch.
+    ///
+    /// The batch is timestamped and, once older than its lifespan, it is discarded by the
+    /// routers and the subscribers instead of being delivered.
+    #[inline]
+    pub fn lifespan(self, lifespan: Duration) -> Self {
+        Self {
+            lifespan: Some(lifespan),
+            ..self
+        }
+    }
+
+    fn push<'b, TryIntoKeyExpr>(
+        mut self,
+        key_expr: TryIntoKeyExpr,
+        sample: impl FnOnce(KeyExpr<'static>) -> Sample,
+    ) -> Self
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+    {
+        if let Ok(samples) = &mut self.samples {
+            match key_expr.try_into() {
+                Ok(key_expr) => samples.push(sample(key_expr.into_owned())),
+                Err(e) => self.samples = Err(e.into()),
+            }
+        }
+        self
+    }
+}
+
+#[zenoh_macros::internal_trait]
+impl TimestampBuilderTrait for BatchBuilder<'_> {
+    fn timestamp<T: Into<Option<Timestamp>>>(self, timestamp: T) -> Self {
+        Self {
+            timestamp: timestamp.into(),
+            ..self
+        }
+    }
+}
+
+#[zenoh_macros::internal_trait]
+impl QoSBuilderTrait for BatchBuilder<'_> {
+    fn congestion_control(self, congestion_control: CongestionControl) -> Self {
+        let qos = self.qos.congestion_control(congestion_control);
+        Self { qos, ..self }
+    }
+
+    fn priority(self, priority: Priority) -> Self {
+        let qos = self.qos.priority(priority);
+        Self { qos, ..self }
+    }
+
+    fn express(self, is_express: bool) -> Self {
+        let qos = self.qos.express(is_express);
+        Self { qos, ..self }
+    }
+}
+
+impl Resolvable for BatchBuilder<'_> {
+    type To = ZResult<()>;
+}
+
+impl Wait for BatchBuilder<'_> {
+    fn wait(self) -> <Self as Resolvable>::To {
+        self.session.0.resolve_batch(
+            self.samples?,
+            self.timestamp,
+            self.qos.into(),
+            self.destination,
+            self.reliability,
+            self.lifespan,
+        )
+    }
+}
+
+impl IntoFuture for BatchBuilder<'_> {
+    type Output = <Self as Resolvable>::To;
+    type IntoFuture = Ready<<Self as Resolvable>::To>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        std::future::ready(self.wait())
+    }
+}
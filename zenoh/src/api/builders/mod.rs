@@ -12,6 +12,8 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
+#[cfg(feature = "unstable")]
+pub(crate) mod batch;
 pub(crate) mod close;
 pub(crate) mod info;
 pub(crate) mod matching_listener;
@@ -25,3 +27,5 @@ pub(crate) mod sample;
 pub(crate) mod scouting;
 pub(crate) mod session;
 pub(crate) mod subscriber;
+#[cfg(feature = "unstable")]
+pub(crate) mod topology_listener;
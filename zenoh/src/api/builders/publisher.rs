@@ -12,6 +12,8 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use std::future::{IntoFuture, Ready};
+#[cfg(feature = "unstable")]
+use std::time::Duration;
 
 use itertools::Itertools;
 use zenoh_config::qos::PublisherQoSConfig;
@@ -148,6 +150,19 @@ impl<T> PublicationBuilder<PublisherBuilder<'_, '_>, T> {
             ..self
         }
     }
+
+    /// Changes the lifespan of the published data.
+    ///
+    /// The data is timestamped and, once older than its lifespan, it is discarded by the routers,
+    /// the subscribers and the caches instead of being delivered.
+    #[zenoh_macros::unstable]
+    #[inline]
+    pub fn lifespan(self, lifespan: Duration) -> Self {
+        Self {
+            publisher: self.publisher.lifespan(lifespan),
+            ..self
+        }
+    }
 }
 
 #[zenoh_macros::internal_trait]
@@ -220,6 +235,8 @@ impl Wait for PublicationBuilder<PublisherBuilder<'_, '_>, PublicationBuilderPut
             self.publisher.destination,
             #[cfg(feature = "unstable")]
             self.publisher.reliability,
+            #[cfg(feature = "unstable")]
+            self.publisher.lifespan,
             self.timestamp,
             #[cfg(feature = "unstable")]
             self.source_info,
@@ -243,6 +260,8 @@ impl Wait for PublicationBuilder<PublisherBuilder<'_, '_>, PublicationBuilderDel
             self.publisher.destination,
             #[cfg(feature = "unstable")]
             self.publisher.reliability,
+            #[cfg(feature = "unstable")]
+            self.publisher.lifespan,
             self.timestamp,
             #[cfg(feature = "unstable")]
             self.source_info,
@@ -321,6 +340,18 @@ pub struct PublisherBuilder<'a, 'b> {
     #[cfg(feature = "unstable")]
     pub(crate) reliability: Reliability,
     #[cfg(feature = "internal")]
+    #[cfg(feature = "unstable")]
+    pub lifespan: Option<Duration>,
+    #[cfg(not(feature = "internal"))]
+    #[cfg(feature = "unstable")]
+    pub(crate) lifespan: Option<Duration>,
+    #[cfg(feature = "internal")]
+    #[cfg(feature = "unstable")]
+    pub deadline: Option<Duration>,
+    #[cfg(not(feature = "internal"))]
+    #[cfg(feature = "unstable")]
+    pub(crate) deadline: Option<Duration>,
+    #[cfg(feature = "internal")]
     pub destination: Locality,
     #[cfg(not(feature = "internal"))]
     pub(crate) destination: Locality,
@@ -340,6 +371,10 @@ impl Clone for PublisherBuilder<'_, '_> {
             is_express: self.is_express,
             #[cfg(feature = "unstable")]
             reliability: self.reliability,
+            #[cfg(feature = "unstable")]
+            lifespan: self.lifespan,
+            #[cfg(feature = "unstable")]
+            deadline: self.deadline,
             destination: self.destination,
         }
     }
@@ -449,6 +484,38 @@ impl PublisherBuilder<'_, '_> {
             ..self
         }
     }
+
+    /// Changes the lifespan of the published data.
+    ///
+    /// The data is timestamped and, once older than its lifespan, it is discarded by the routers,
+    /// the subscribers and the caches instead of being delivered.
+    #[zenoh_macros::unstable]
+    #[inline]
+    pub fn lifespan(self, lifespan: Duration) -> Self {
+        Self {
+            lifespan: Some(lifespan),
+            ..self
+        }
+    }
+
+    /// Commits the publisher to publish at least once per `deadline`.
+    ///
+    /// The samples of the publisher carry its [`id`](crate::pubsub::Publisher::id) as source id,
+    /// for the subscribers declared with a [`deadline`](crate::pubsub::SubscriberBuilder::deadline)
+    /// to be notified when it is missed, and the publisher declares a liveliness token for them
+    /// to stop monitoring it once undeclared.
+    ///
+    /// The `deadline` value itself is not sent: each subscriber monitors the publishers with its
+    /// own deadline, the value being only returned by [`Publisher::deadline`](crate::pubsub::Publisher::deadline).
+    /// A zero `deadline` is rejected when declaring the publisher.
+    #[zenoh_macros::unstable]
+    #[inline]
+    pub fn deadline(self, deadline: Duration) -> Self {
+        Self {
+            deadline: Some(deadline),
+            ..self
+        }
+    }
 }
 
 impl<'b> Resolvable for PublisherBuilder<'_, 'b> {
@@ -458,6 +525,10 @@ impl<'b> Resolvable for PublisherBuilder<'_, 'b> {
 impl Wait for PublisherBuilder<'_, '_> {
     fn wait(mut self) -> <Self as Resolvable>::To {
         self = self.apply_qos_overwrites();
+        #[cfg(feature = "unstable")]
+        if self.deadline.is_some_and(|deadline| deadline.is_zero()) {
+            zenoh_core::bail!("Invalid deadline: it should be greater than zero");
+        }
         let mut key_expr = self.key_expr?;
         if !key_expr.is_fully_optimized(&self.session.0) {
             key_expr = self.session.declare_keyexpr(key_expr).wait()?;
@@ -466,6 +537,27 @@ impl Wait for PublisherBuilder<'_, '_> {
             .session
             .0
             .declare_publisher_inner(key_expr.clone(), self.destination)?;
+        #[cfg(feature = "unstable")]
+        let deadline_token = match self.deadline {
+            Some(_) => {
+                let token = crate::api::deadline::deadline_token_key_expr(
+                    &zenoh_protocol::core::EntityGlobalIdProto {
+                        zid: self.session.zid().into(),
+                        eid: id,
+                    }
+                    .into(),
+                )
+                .and_then(|key_expr| self.session.liveliness().declare_token(key_expr).wait());
+                match token {
+                    Ok(token) => Some(token),
+                    Err(e) => {
+                        self.session.0.undeclare_publisher_inner(id)?;
+                        return Err(e);
+                    }
+                }
+            }
+            None => None,
+        };
         Ok(Publisher {
             session: self.session.downgrade(),
             id,
@@ -478,6 +570,14 @@ impl Wait for PublisherBuilder<'_, '_> {
             #[cfg(feature = "unstable")]
             reliability: self.reliability,
             #[cfg(feature = "unstable")]
+            lifespan: self.lifespan,
+            #[cfg(feature = "unstable")]
+            deadline: self.deadline,
+            #[cfg(feature = "unstable")]
+            deadline_token,
+            #[cfg(feature = "unstable")]
+            next_sn: Default::default(),
+            #[cfg(feature = "unstable")]
             matching_listeners: Default::default(),
             undeclare_on_drop: true,
         })
@@ -506,6 +606,8 @@ impl Wait for PublicationBuilder<&Publisher<'_>, PublicationBuilderPut> {
             self.publisher.destination,
             #[cfg(feature = "unstable")]
             self.publisher.reliability,
+            #[cfg(feature = "unstable")]
+            self.publisher.lifespan,
             self.timestamp,
             #[cfg(feature = "unstable")]
             self.source_info,
@@ -527,6 +629,8 @@ impl Wait for PublicationBuilder<&Publisher<'_>, PublicationBuilderDelete> {
             self.publisher.destination,
             #[cfg(feature = "unstable")]
             self.publisher.reliability,
+            #[cfg(feature = "unstable")]
+            self.publisher.lifespan,
             self.timestamp,
             #[cfg(feature = "unstable")]
             self.source_info,
@@ -28,7 +28,7 @@ use super::sample::QoSBuilderTrait;
 #[cfg(feature = "unstable")]
 use crate::api::query::ReplyKeyExpr;
 #[cfg(feature = "unstable")]
-use crate::api::sample::SourceInfo;
+use crate::api::{cancellation::CancellationToken, sample::SourceInfo};
 #[cfg(feature = "unstable")]
 use crate::query::ZenohParameters;
 use crate::{
@@ -226,6 +226,10 @@ pub struct QuerierGetBuilder<'a, 'b, Handler> {
     pub(crate) attachment: Option<ZBytes>,
     #[cfg(feature = "unstable")]
     pub(crate) source_info: SourceInfo,
+    #[cfg(feature = "unstable")]
+    pub(crate) cancellation_token: Option<CancellationToken>,
+    #[cfg(feature = "unstable")]
+    pub(crate) reply_credits: Option<u32>,
 }
 
 #[zenoh_macros::internal_trait]
@@ -361,6 +365,10 @@ impl<'a, 'b> QuerierGetBuilder<'a, 'b, DefaultHandler> {
             attachment,
             #[cfg(feature = "unstable")]
             source_info,
+            #[cfg(feature = "unstable")]
+            cancellation_token,
+            #[cfg(feature = "unstable")]
+            reply_credits,
             handler: _,
         } = self;
         QuerierGetBuilder {
@@ -370,11 +378,37 @@ impl<'a, 'b> QuerierGetBuilder<'a, 'b, DefaultHandler> {
             attachment,
             #[cfg(feature = "unstable")]
             source_info,
+            #[cfg(feature = "unstable")]
+            cancellation_token,
+            #[cfg(feature = "unstable")]
+            reply_credits,
             handler,
         }
     }
 }
 impl<'b, Handler> QuerierGetBuilder<'_, 'b, Handler> {
+    /// Attach a [`CancellationToken`] to the query, to be able to cancel it before it completes.
+    #[inline]
+    #[zenoh_macros::unstable]
+    pub fn cancellation_token(mut self, cancellation_token: CancellationToken) -> Self {
+        self.cancellation_token = Some(cancellation_token);
+        self
+    }
+
+    /// Request streaming replies: the responding sessions share a window of `credits` replies.
+    /// Each of them starts with a single credit and is granted its share of the window back as
+    /// its replies are received. Asynchronous replies wait for the credits, while synchronous
+    /// replies fail when no credit is left.
+    ///
+    /// Replying to the query then waits while the queryable has no credit left, so that a slow
+    /// querier is not overwhelmed by large query results.
+    #[inline]
+    #[zenoh_macros::unstable]
+    pub fn reply_credits(mut self, credits: u32) -> Self {
+        self.reply_credits = Some(credits);
+        self
+    }
+
     /// Set the query payload.
     #[inline]
     #[zenoh_macros::unstable]
@@ -423,6 +457,10 @@ where
         if self.querier.accept_replies() == ReplyKeyExpr::Any {
             parameters.set_reply_key_expr_any();
         }
+        #[cfg(feature = "unstable")]
+        if self.reply_credits.is_some() {
+            parameters.set_reply_credits(self.reply_credits);
+        }
         self.querier
             .session
             .query(
@@ -437,6 +475,8 @@ where
                 self.attachment,
                 #[cfg(feature = "unstable")]
                 self.source_info,
+                #[cfg(feature = "unstable")]
+                self.cancellation_token,
                 callback,
             )
             .map(|_| receiver)
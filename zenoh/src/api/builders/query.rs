@@ -24,7 +24,7 @@ use zenoh_result::ZResult;
 #[cfg(feature = "unstable")]
 use crate::api::query::ReplyKeyExpr;
 #[cfg(feature = "unstable")]
-use crate::api::{sample::SourceInfo, selector::ZenohParameters};
+use crate::api::{cancellation::CancellationToken, sample::SourceInfo, selector::ZenohParameters};
 use crate::{
     api::{
         builders::sample::{EncodingBuilderTrait, QoSBuilderTrait, SampleBuilderTrait},
@@ -75,6 +75,8 @@ pub struct SessionGetBuilder<'a, 'b, Handler> {
     pub(crate) attachment: Option<ZBytes>,
     #[cfg(feature = "unstable")]
     pub(crate) source_info: SourceInfo,
+    #[cfg(feature = "unstable")]
+    pub(crate) cancellation_token: Option<CancellationToken>,
 }
 
 #[zenoh_macros::internal_trait]
@@ -212,6 +214,8 @@ impl<'a, 'b> SessionGetBuilder<'a, 'b, DefaultHandler> {
             attachment,
             #[cfg(feature = "unstable")]
             source_info,
+            #[cfg(feature = "unstable")]
+            cancellation_token,
             handler: _,
         } = self;
         SessionGetBuilder {
@@ -226,6 +230,8 @@ impl<'a, 'b> SessionGetBuilder<'a, 'b, DefaultHandler> {
             attachment,
             #[cfg(feature = "unstable")]
             source_info,
+            #[cfg(feature = "unstable")]
+            cancellation_token,
             handler,
         }
     }
@@ -276,6 +282,35 @@ impl<Handler> SessionGetBuilder<'_, '_, Handler> {
         Self { timeout, ..self }
     }
 
+    /// Attach a [`CancellationToken`] to the query, to be able to cancel it before it completes.
+    #[zenoh_macros::unstable]
+    #[inline]
+    pub fn cancellation_token(self, cancellation_token: CancellationToken) -> Self {
+        Self {
+            cancellation_token: Some(cancellation_token),
+            ..self
+        }
+    }
+
+    /// Request streaming replies: the responding sessions share a window of `credits` replies.
+    /// Each of them starts with a single credit and is granted its share of the window back as
+    /// its replies are received. Asynchronous replies wait for the credits, while synchronous
+    /// replies fail when no credit is left.
+    ///
+    /// Replying to the query then waits while the queryable has no credit left, so that a slow
+    /// querier is not overwhelmed by large query results.
+    #[zenoh_macros::unstable]
+    pub fn reply_credits(self, credits: u32) -> Self {
+        let selector = self.selector.map(|mut selector| {
+            selector
+                .parameters
+                .to_mut()
+                .set_reply_credits(Some(credits));
+            selector
+        });
+        Self { selector, ..self }
+    }
+
     ///
     ///
     /// By default, `get` guarantees that it will only receive replies whose key expressions intersect
@@ -336,6 +371,8 @@ where
                 self.attachment,
                 #[cfg(feature = "unstable")]
                 self.source_info,
+                #[cfg(feature = "unstable")]
+                self.cancellation_token,
                 callback,
             )
             .map(|_| receiver)
@@ -19,11 +19,13 @@ use std::{
 use zenoh_core::{Resolvable, Wait};
 use zenoh_result::ZResult;
 
+#[cfg(feature = "unstable")]
+use crate::api::queryable::OverloadPolicy;
 use crate::{
     api::{
         handlers::{locked, DefaultHandler, IntoHandler},
         key_expr::KeyExpr,
-        queryable::{Query, Queryable, QueryableInner},
+        queryable::{Query, Queryable, QueryableAdmission, QueryableInner},
         sample::Locality,
     },
     handlers::Callback,
@@ -48,6 +50,10 @@ pub struct QueryableBuilder<'a, 'b, Handler, const BACKGROUND: bool = false> {
     pub(crate) key_expr: ZResult<KeyExpr<'b>>,
     pub(crate) complete: bool,
     pub(crate) origin: Locality,
+    #[cfg(feature = "unstable")]
+    pub(crate) max_concurrent_queries: Option<usize>,
+    #[cfg(feature = "unstable")]
+    pub(crate) overload_policy: OverloadPolicy,
     pub(crate) handler: Handler,
 }
 
@@ -130,6 +136,10 @@ impl<'a, 'b> QueryableBuilder<'a, 'b, DefaultHandler> {
             key_expr,
             complete,
             origin,
+            #[cfg(feature = "unstable")]
+            max_concurrent_queries,
+            #[cfg(feature = "unstable")]
+            overload_policy,
             handler: _,
         } = self;
         QueryableBuilder {
@@ -137,6 +147,10 @@ impl<'a, 'b> QueryableBuilder<'a, 'b, DefaultHandler> {
             key_expr,
             complete,
             origin,
+            #[cfg(feature = "unstable")]
+            max_concurrent_queries,
+            #[cfg(feature = "unstable")]
+            overload_policy,
             handler,
         }
     }
@@ -168,6 +182,10 @@ impl<'a, 'b> QueryableBuilder<'a, 'b, Callback<Query>> {
             key_expr: self.key_expr,
             complete: self.complete,
             origin: self.origin,
+            #[cfg(feature = "unstable")]
+            max_concurrent_queries: self.max_concurrent_queries,
+            #[cfg(feature = "unstable")]
+            overload_policy: self.overload_policy,
             handler: self.handler,
         }
     }
@@ -191,6 +209,53 @@ impl<Handler, const BACKGROUND: bool> QueryableBuilder<'_, '_, Handler, BACKGROU
         self.origin = origin;
         self
     }
+
+    /// Limit the number of queries concurrently handled by this [`Queryable`].
+    ///
+    /// A query is handled until the [`Query`] and all its clones are dropped. The queries
+    /// received beyond this limit are handled according to the [`OverloadPolicy`].
+    ///
+    /// # Examples
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use zenoh::query::OverloadPolicy;
+    ///
+    /// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+    /// let queryable = session
+    ///     .declare_queryable("key/expression")
+    ///     .max_concurrent_queries(16)
+    ///     .overload_policy(OverloadPolicy::Redirect)
+    ///     .await
+    ///     .unwrap();
+    /// # }
+    /// ```
+    #[inline]
+    #[zenoh_macros::unstable]
+    pub fn max_concurrent_queries(mut self, max: usize) -> Self {
+        self.max_concurrent_queries = Some(max);
+        self
+    }
+
+    /// Change the policy applied to the queries received beyond the
+    /// [maximum number of concurrent queries](Self::max_concurrent_queries).
+    #[inline]
+    #[zenoh_macros::unstable]
+    pub fn overload_policy(mut self, policy: OverloadPolicy) -> Self {
+        self.overload_policy = policy;
+        self
+    }
+
+    #[cfg(feature = "unstable")]
+    fn admission(&self) -> Option<Arc<QueryableAdmission>> {
+        self.max_concurrent_queries
+            .map(|max| Arc::new(QueryableAdmission::new(max, self.overload_policy)))
+    }
+
+    #[cfg(not(feature = "unstable"))]
+    fn admission(&self) -> Option<Arc<QueryableAdmission>> {
+        None
+    }
 }
 
 impl<Handler> Resolvable for QueryableBuilder<'_, '_, Handler>
@@ -208,10 +273,17 @@ where
 {
     fn wait(self) -> <Self as Resolvable>::To {
         let session = self.session;
+        let admission = self.admission();
         let (callback, receiver) = self.handler.into_handler();
         session
             .0
-            .declare_queryable_inner(&self.key_expr?, self.complete, self.origin, callback)
+            .declare_queryable_inner(
+                &self.key_expr?,
+                self.complete,
+                self.origin,
+                callback,
+                admission,
+            )
             .map(|qable_state| Queryable {
                 inner: QueryableInner {
                     session: self.session.downgrade(),
@@ -242,11 +314,13 @@ impl Resolvable for QueryableBuilder<'_, '_, Callback<Query>, true> {
 
 impl Wait for QueryableBuilder<'_, '_, Callback<Query>, true> {
     fn wait(self) -> <Self as Resolvable>::To {
+        let admission = self.admission();
         self.session.0.declare_queryable_inner(
             &self.key_expr?,
             self.complete,
             self.origin,
             self.handler,
+            admission,
         )?;
         Ok(())
     }
@@ -13,6 +13,8 @@
 //
 use std::future::{IntoFuture, Ready};
 
+use futures::future::{BoxFuture, Either};
+
 use uhlc::Timestamp;
 use zenoh_core::{Resolvable, Wait};
 use zenoh_protocol::{
@@ -34,9 +36,13 @@ use crate::api::{
     key_expr::KeyExpr,
     publisher::Priority,
     queryable::Query,
-    sample::QoSBuilder,
+    sample::{QoSBuilder, Sample},
 };
 
+/// The future of a reply: ready unless the querier requested streaming replies, in which case
+/// it waits for a response credit.
+pub(crate) type ReplyFuture<'a> = Either<Ready<ZResult<()>>, BoxFuture<'a, ZResult<()>>>;
+
 #[derive(Debug)]
 pub struct ReplyBuilderPut {
     payload: ZBytes,
@@ -169,48 +175,84 @@ impl<T> Resolvable for ReplyBuilder<'_, '_, T> {
     type To = ZResult<()>;
 }
 
+impl<'a> ReplyBuilder<'a, '_, ReplyBuilderPut> {
+    fn into_sample(self) -> (&'a Query, ZResult<Sample>) {
+        let sample = self.key_expr.map(|key_expr| {
+            let sample = SampleBuilder::put(key_expr.into_owned(), self.kind.payload)
+                .encoding(self.kind.encoding)
+                .timestamp(self.timestamp)
+                .qos(self.qos.into());
+            #[cfg(feature = "unstable")]
+            let sample = sample.source_info(self.source_info);
+            sample.attachment(self.attachment).into()
+        });
+        (self.query, sample)
+    }
+}
+
+impl<'a> ReplyBuilder<'a, '_, ReplyBuilderDelete> {
+    fn into_sample(self) -> (&'a Query, ZResult<Sample>) {
+        let sample = self.key_expr.map(|key_expr| {
+            let sample = SampleBuilder::delete(key_expr.into_owned())
+                .timestamp(self.timestamp)
+                .qos(self.qos.into());
+            #[cfg(feature = "unstable")]
+            let sample = sample.source_info(self.source_info);
+            sample.attachment(self.attachment).into()
+        });
+        (self.query, sample)
+    }
+}
+
 impl Wait for ReplyBuilder<'_, '_, ReplyBuilderPut> {
     fn wait(self) -> <Self as Resolvable>::To {
-        let key_expr = self.key_expr?.into_owned();
-        let sample = SampleBuilder::put(key_expr, self.kind.payload)
-            .encoding(self.kind.encoding)
-            .timestamp(self.timestamp)
-            .qos(self.qos.into());
-        #[cfg(feature = "unstable")]
-        let sample = sample.source_info(self.source_info);
-        let sample = sample.attachment(self.attachment);
-        self.query._reply_sample(sample.into())
+        let (query, sample) = self.into_sample();
+        let sample = sample?;
+        query.inner.try_acquire_credit()?;
+        query._reply_sample(sample)
     }
 }
 
 impl Wait for ReplyBuilder<'_, '_, ReplyBuilderDelete> {
     fn wait(self) -> <Self as Resolvable>::To {
-        let key_expr = self.key_expr?.into_owned();
-        let sample = SampleBuilder::delete(key_expr)
-            .timestamp(self.timestamp)
-            .qos(self.qos.into());
-        #[cfg(feature = "unstable")]
-        let sample = sample.source_info(self.source_info);
-        let sample = sample.attachment(self.attachment);
-        self.query._reply_sample(sample.into())
+        let (query, sample) = self.into_sample();
+        let sample = sample?;
+        query.inner.try_acquire_credit()?;
+        query._reply_sample(sample)
     }
 }
 
-impl IntoFuture for ReplyBuilder<'_, '_, ReplyBuilderPut> {
+impl<'a> IntoFuture for ReplyBuilder<'a, '_, ReplyBuilderPut> {
     type Output = <Self as Resolvable>::To;
-    type IntoFuture = Ready<<Self as Resolvable>::To>;
+    type IntoFuture = ReplyFuture<'a>;
 
     fn into_future(self) -> Self::IntoFuture {
-        std::future::ready(self.wait())
+        if !self.query.inner.has_credits() {
+            return Either::Left(std::future::ready(self.wait()));
+        }
+        let (query, sample) = self.into_sample();
+        Either::Right(Box::pin(async move {
+            let sample = sample?;
+            query.inner.acquire_credit().await;
+            query._reply_sample(sample)
+        }))
     }
 }
 
-impl IntoFuture for ReplyBuilder<'_, '_, ReplyBuilderDelete> {
+impl<'a> IntoFuture for ReplyBuilder<'a, '_, ReplyBuilderDelete> {
     type Output = <Self as Resolvable>::To;
-    type IntoFuture = Ready<<Self as Resolvable>::To>;
+    type IntoFuture = ReplyFuture<'a>;
 
     fn into_future(self) -> Self::IntoFuture {
-        std::future::ready(self.wait())
+        if !self.query.inner.has_credits() {
+            return Either::Left(std::future::ready(self.wait()));
+        }
+        let (query, sample) = self.into_sample();
+        Either::Right(Box::pin(async move {
+            let sample = sample?;
+            query.inner.acquire_credit().await;
+            query._reply_sample(sample)
+        }))
     }
 }
 
@@ -250,8 +292,12 @@ impl Resolvable for ReplyErrBuilder<'_> {
     type To = ZResult<()>;
 }
 
-impl Wait for ReplyErrBuilder<'_> {
-    fn wait(self) -> <Self as Resolvable>::To {
+impl ReplyErrBuilder<'_> {
+    fn send(self) -> ZResult<()> {
+        if self.query.inner.is_cancelled() {
+            tracing::trace!("Discard reply to cancelled query {}", self.query.inner.qid);
+            return Ok(());
+        }
         self.query.inner.primitives.send_response(Response {
             rid: self.query.inner.qid,
             wire_expr: WireExpr {
@@ -278,11 +324,24 @@ impl Wait for ReplyErrBuilder<'_> {
     }
 }
 
-impl IntoFuture for ReplyErrBuilder<'_> {
+impl Wait for ReplyErrBuilder<'_> {
+    fn wait(self) -> <Self as Resolvable>::To {
+        self.query.inner.try_acquire_credit()?;
+        self.send()
+    }
+}
+
+impl<'a> IntoFuture for ReplyErrBuilder<'a> {
     type Output = <Self as Resolvable>::To;
-    type IntoFuture = Ready<<Self as Resolvable>::To>;
+    type IntoFuture = ReplyFuture<'a>;
 
     fn into_future(self) -> Self::IntoFuture {
-        std::future::ready(self.wait())
+        if !self.query.inner.has_credits() {
+            return Either::Left(std::future::ready(self.wait()));
+        }
+        Either::Right(Box::pin(async move {
+            self.query.inner.acquire_credit().await;
+            self.send()
+        }))
     }
 }
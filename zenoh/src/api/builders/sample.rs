@@ -12,6 +12,8 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use std::marker::PhantomData;
+#[cfg(feature = "unstable")]
+use std::time::Duration;
 
 use uhlc::Timestamp;
 use zenoh_core::zresult;
@@ -95,6 +97,8 @@ impl SampleBuilder<SampleBuilderPut> {
                 reliability: Reliability::DEFAULT,
                 #[cfg(feature = "unstable")]
                 source_info: SourceInfo::empty(),
+                #[cfg(feature = "unstable")]
+                lifespan: None,
                 attachment: None,
             },
             _t: PhantomData::<SampleBuilderPut>,
@@ -127,6 +131,8 @@ impl SampleBuilder<SampleBuilderDelete> {
                 reliability: Reliability::DEFAULT,
                 #[cfg(feature = "unstable")]
                 source_info: SourceInfo::empty(),
+                #[cfg(feature = "unstable")]
+                lifespan: None,
                 attachment: None,
             },
             _t: PhantomData::<SampleBuilderDelete>,
@@ -167,6 +173,17 @@ impl<T> SampleBuilder<T> {
             _t: PhantomData::<T>,
         }
     }
+
+    #[zenoh_macros::unstable]
+    pub fn lifespan<TL: Into<Option<Duration>>>(self, lifespan: TL) -> Self {
+        Self {
+            sample: Sample {
+                lifespan: lifespan.into(),
+                ..self.sample
+            },
+            _t: PhantomData::<T>,
+        }
+    }
 }
 
 #[zenoh_macros::internal_trait]
@@ -307,6 +324,8 @@ impl From<&PublicationBuilder<&Publisher<'_>, PublicationBuilderPut>> for Sample
             reliability: builder.publisher.reliability,
             #[cfg(feature = "unstable")]
             source_info: builder.source_info.clone(),
+            #[cfg(feature = "unstable")]
+            lifespan: builder.publisher.lifespan,
             attachment: builder.attachment.clone(),
         }
     }
@@ -330,6 +349,8 @@ impl From<&PublicationBuilder<&Publisher<'_>, PublicationBuilderDelete>> for Sam
             reliability: builder.publisher.reliability,
             #[cfg(feature = "unstable")]
             source_info: builder.source_info.clone(),
+            #[cfg(feature = "unstable")]
+            lifespan: builder.publisher.lifespan,
             attachment: builder.attachment.clone(),
         }
     }
@@ -24,6 +24,8 @@ use zenoh_result::ZResult;
 use zenoh_shm::api::client_storage::ShmClientStorage;
 
 use crate::api::session::Session;
+#[cfg(feature = "unstable")]
+use crate::net::routing::interceptor::InterceptorFactory;
 #[cfg(feature = "internal")]
 use crate::net::runtime::Runtime;
 
@@ -46,6 +48,8 @@ where
     config: TryIntoConfig,
     #[cfg(feature = "shared-memory")]
     shm_clients: Option<Arc<ShmClientStorage>>,
+    #[cfg(feature = "unstable")]
+    interceptor_factories: Vec<InterceptorFactory>,
 }
 
 impl<TryIntoConfig> OpenBuilder<TryIntoConfig>
@@ -58,10 +62,26 @@ where
             config,
             #[cfg(feature = "shared-memory")]
             shm_clients: None,
+            #[cfg(feature = "unstable")]
+            interceptor_factories: vec![],
         }
     }
 }
 
+#[zenoh_macros::unstable]
+impl<TryIntoConfig> OpenBuilder<TryIntoConfig>
+where
+    TryIntoConfig: std::convert::TryInto<crate::config::Config> + Send + 'static,
+    <TryIntoConfig as std::convert::TryInto<crate::config::Config>>::Error: std::fmt::Debug,
+{
+    /// Attach the interceptors built by `factory` to every transport of the session,
+    /// after the ones configured in the config (e.g. downsampling and access control).
+    pub fn with_interceptor_factory(mut self, factory: InterceptorFactory) -> Self {
+        self.interceptor_factories.push(factory);
+        self
+    }
+}
+
 #[cfg(feature = "shared-memory")]
 impl<TryIntoConfig> OpenBuilder<TryIntoConfig>
 where
@@ -96,6 +116,8 @@ where
             config,
             #[cfg(feature = "shared-memory")]
             self.shm_clients,
+            #[cfg(feature = "unstable")]
+            self.interceptor_factories,
         )
         .wait()
     }
@@ -19,6 +19,14 @@ use std::{
 use zenoh_core::{Resolvable, Wait};
 use zenoh_result::ZResult;
 
+#[cfg(feature = "unstable")]
+use std::time::Duration;
+
+#[cfg(feature = "unstable")]
+use crate::api::{
+    deadline::{DeadlineMissed, DeadlineMonitor},
+    sample::SampleBatch,
+};
 use crate::{
     api::{
         handlers::{locked, Callback, DefaultHandler, IntoHandler},
@@ -65,6 +73,12 @@ pub struct SubscriberBuilder<'a, 'b, Handler, const BACKGROUND: bool = false> {
     pub handler: Handler,
     #[cfg(not(feature = "internal"))]
     pub(crate) handler: Handler,
+
+    #[cfg(feature = "unstable")]
+    pub(crate) batch_callback: Option<Callback<SampleBatch>>,
+
+    #[cfg(feature = "unstable")]
+    pub(crate) deadline: Option<(Duration, Callback<DeadlineMissed>)>,
 }
 
 impl<'a, 'b> SubscriberBuilder<'a, 'b, DefaultHandler> {
@@ -146,12 +160,20 @@ impl<'a, 'b> SubscriberBuilder<'a, 'b, DefaultHandler> {
             key_expr,
             origin,
             handler: _,
+            #[cfg(feature = "unstable")]
+            batch_callback,
+            #[cfg(feature = "unstable")]
+            deadline,
         } = self;
         SubscriberBuilder {
             session,
             key_expr,
             origin,
             handler,
+            #[cfg(feature = "unstable")]
+            batch_callback,
+            #[cfg(feature = "unstable")]
+            deadline,
         }
     }
 }
@@ -182,6 +204,10 @@ impl<'a, 'b> SubscriberBuilder<'a, 'b, Callback<Sample>> {
             key_expr: self.key_expr,
             origin: self.origin,
             handler: self.handler,
+            #[cfg(feature = "unstable")]
+            batch_callback: self.batch_callback,
+            #[cfg(feature = "unstable")]
+            deadline: self.deadline,
         }
     }
 }
@@ -197,6 +223,73 @@ impl<Handler, const BACKGROUND: bool> SubscriberBuilder<'_, '_, Handler, BACKGRO
         self.origin = origin;
         self
     }
+
+    /// Receive the batches published with [`Session::batch`](crate::Session::batch) as a unit.
+    ///
+    /// The batches whose samples all match the key expression of this subscriber are passed to
+    /// `callback` instead of the subscriber handler. The samples of the other batches matching this
+    /// subscriber are still received one by one by the subscriber handler.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+    /// let subscriber = session
+    ///     .declare_subscriber("robot/**")
+    ///     .batch_callback(|batch| {
+    ///         for sample in batch.samples() {
+    ///             println!("Received: {} {:?}", sample.key_expr(), sample.payload());
+    ///         }
+    ///     })
+    ///     .await
+    ///     .unwrap();
+    /// # }
+    /// ```
+    #[zenoh_macros::unstable]
+    #[inline]
+    pub fn batch_callback<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(SampleBatch) + Send + Sync + 'static,
+    {
+        self.batch_callback = Some(Callback::new(Arc::new(callback)));
+        self
+    }
+
+    /// Monitor the deadline of the publishers of this subscriber.
+    ///
+    /// `callback` is notified with a [`DeadlineMissed`] event each time `deadline` elapses without
+    /// receiving a sample from a publisher, until a new sample is received from it. Only the
+    /// publishers declared with a [`deadline`](crate::pubsub::PublisherBuilder::deadline) are
+    /// monitored, from their first received sample until they are undeclared, being identified by
+    /// the source id of their samples.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use std::time::Duration;
+    ///
+    /// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+    /// let subscriber = session
+    ///     .declare_subscriber("key/expression")
+    ///     .deadline(Duration::from_millis(100), |missed| {
+    ///         println!("Deadline missed by {:?}", missed.publisher());
+    ///     })
+    ///     .await
+    ///     .unwrap();
+    /// # }
+    /// ```
+    #[zenoh_macros::unstable]
+    #[inline]
+    pub fn deadline<F>(mut self, deadline: Duration, callback: F) -> Self
+    where
+        F: Fn(DeadlineMissed) + Send + Sync + 'static,
+    {
+        self.deadline = Some((deadline, Callback::new(Arc::new(callback))));
+        self
+    }
 }
 
 impl<Handler> Resolvable for SubscriberBuilder<'_, '_, Handler>
@@ -216,9 +309,22 @@ where
         let key_expr = self.key_expr?;
         let session = self.session;
         let (callback, receiver) = self.handler.into_handler();
+        #[cfg(feature = "unstable")]
+        let callback = match self.deadline {
+            Some((deadline, missed)) => {
+                DeadlineMonitor::spawn(session, deadline, callback, missed)?
+            }
+            None => callback,
+        };
         session
             .0
-            .declare_subscriber_inner(&key_expr, self.origin, callback)
+            .declare_subscriber_inner(
+                &key_expr,
+                self.origin,
+                callback,
+                #[cfg(feature = "unstable")]
+                self.batch_callback,
+            )
             .map(|sub_state| Subscriber {
                 inner: SubscriberInner {
                     session: session.downgrade(),
@@ -251,9 +357,22 @@ impl Resolvable for SubscriberBuilder<'_, '_, Callback<Sample>, true> {
 
 impl Wait for SubscriberBuilder<'_, '_, Callback<Sample>, true> {
     fn wait(self) -> <Self as Resolvable>::To {
-        self.session
-            .0
-            .declare_subscriber_inner(&self.key_expr?, self.origin, self.handler)?;
+        let key_expr = self.key_expr?;
+        let callback = self.handler;
+        #[cfg(feature = "unstable")]
+        let callback = match self.deadline {
+            Some((deadline, missed)) => {
+                DeadlineMonitor::spawn(self.session, deadline, callback, missed)?
+            }
+            None => callback,
+        };
+        self.session.0.declare_subscriber_inner(
+            &key_expr,
+            self.origin,
+            callback,
+            #[cfg(feature = "unstable")]
+            self.batch_callback,
+        )?;
         Ok(())
     }
 }
@@ -0,0 +1,225 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::{
+    future::{IntoFuture, Ready},
+    sync::Arc,
+};
+
+use zenoh_core::{Resolvable, Wait};
+use zenoh_result::ZResult;
+
+use crate::{
+    api::{
+        handlers::{Callback, DefaultHandler, IntoHandler},
+        topology::{TopologyEvent, TopologyListener},
+    },
+    net::runtime::Runtime,
+};
+
+/// A builder returned by [`SessionInfo::topology_listener()`](crate::session::SessionInfo::topology_listener)
+/// for initializing a [`TopologyListener`].
+#[zenoh_macros::unstable]
+pub struct TopologyListenerBuilder<'a, Handler, const BACKGROUND: bool = false> {
+    pub(crate) runtime: &'a Runtime,
+    pub handler: Handler,
+}
+
+#[zenoh_macros::unstable]
+impl<'a> TopologyListenerBuilder<'a, DefaultHandler> {
+    pub(crate) fn new(runtime: &'a Runtime) -> Self {
+        Self {
+            runtime,
+            handler: DefaultHandler::default(),
+        }
+    }
+
+    /// Receive the topology events with a callback.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+    /// let listener = session
+    ///     .info()
+    ///     .topology_listener()
+    ///     .callback(|event| println!("Topology changed: {event:?}"))
+    ///     .await
+    ///     .unwrap();
+    /// # }
+    /// ```
+    #[inline]
+    pub fn callback<F>(self, callback: F) -> TopologyListenerBuilder<'a, Callback<TopologyEvent>>
+    where
+        F: Fn(TopologyEvent) + Send + Sync + 'static,
+    {
+        self.with(Callback::new(Arc::new(callback)))
+    }
+
+    /// Receive the topology events with a mutable callback.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let mut n = 0;
+    /// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+    /// let listener = session
+    ///     .info()
+    ///     .topology_listener()
+    ///     .callback_mut(move |_event| { n += 1; })
+    ///     .await
+    ///     .unwrap();
+    /// # }
+    /// ```
+    #[inline]
+    pub fn callback_mut<F>(
+        self,
+        callback: F,
+    ) -> TopologyListenerBuilder<'a, Callback<TopologyEvent>>
+    where
+        F: FnMut(TopologyEvent) + Send + Sync + 'static,
+    {
+        self.callback(crate::api::handlers::locked(callback))
+    }
+
+    /// Receive the topology events with a [`Handler`](IntoHandler).
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+    /// let listener = session
+    ///     .info()
+    ///     .topology_listener()
+    ///     .with(flume::bounded(32))
+    ///     .await
+    ///     .unwrap();
+    /// while let Ok(event) = listener.recv_async().await {
+    ///     println!("Topology changed: {event:?}");
+    /// }
+    /// # }
+    /// ```
+    #[inline]
+    pub fn with<Handler>(self, handler: Handler) -> TopologyListenerBuilder<'a, Handler>
+    where
+        Handler: IntoHandler<TopologyEvent>,
+    {
+        TopologyListenerBuilder {
+            runtime: self.runtime,
+            handler,
+        }
+    }
+}
+
+#[zenoh_macros::unstable]
+impl<'a> TopologyListenerBuilder<'a, Callback<TopologyEvent>> {
+    /// Register the listener callback to be run in background until the session is closed.
+    ///
+    /// Background builder doesn't return a `TopologyListener` object anymore.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+    /// // no need to assign and keep a variable with a background listener
+    /// session
+    ///     .info()
+    ///     .topology_listener()
+    ///     .callback(|event| println!("Topology changed: {event:?}"))
+    ///     .background()
+    ///     .await
+    ///     .unwrap();
+    /// # }
+    /// ```
+    pub fn background(self) -> TopologyListenerBuilder<'a, Callback<TopologyEvent>, true> {
+        TopologyListenerBuilder {
+            runtime: self.runtime,
+            handler: self.handler,
+        }
+    }
+}
+
+#[zenoh_macros::unstable]
+impl<Handler> Resolvable for TopologyListenerBuilder<'_, Handler>
+where
+    Handler: IntoHandler<TopologyEvent> + Send,
+    Handler::Handler: Send,
+{
+    type To = ZResult<TopologyListener<Handler::Handler>>;
+}
+
+#[zenoh_macros::unstable]
+impl<Handler> Wait for TopologyListenerBuilder<'_, Handler>
+where
+    Handler: IntoHandler<TopologyEvent> + Send,
+    Handler::Handler: Send,
+{
+    fn wait(self) -> <Self as Resolvable>::To {
+        let (callback, handler) = self.handler.into_handler();
+        let notifier = self.runtime.topology().clone();
+        let (id, task) = notifier.declare_listener(callback);
+        self.runtime.spawn_abortable(task);
+        Ok(TopologyListener {
+            notifier,
+            id,
+            undeclare_on_drop: true,
+            handler,
+        })
+    }
+}
+
+#[zenoh_macros::unstable]
+impl<Handler> IntoFuture for TopologyListenerBuilder<'_, Handler>
+where
+    Handler: IntoHandler<TopologyEvent> + Send,
+    Handler::Handler: Send,
+{
+    type Output = <Self as Resolvable>::To;
+    type IntoFuture = Ready<<Self as Resolvable>::To>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        std::future::ready(self.wait())
+    }
+}
+
+#[zenoh_macros::unstable]
+impl Resolvable for TopologyListenerBuilder<'_, Callback<TopologyEvent>, true> {
+    type To = ZResult<()>;
+}
+
+#[zenoh_macros::unstable]
+impl Wait for TopologyListenerBuilder<'_, Callback<TopologyEvent>, true> {
+    fn wait(self) -> <Self as Resolvable>::To {
+        let (_, task) = self.runtime.topology().declare_listener(self.handler);
+        self.runtime.spawn_abortable(task);
+        Ok(())
+    }
+}
+
+#[zenoh_macros::unstable]
+impl IntoFuture for TopologyListenerBuilder<'_, Callback<TopologyEvent>, true> {
+    type Output = <Self as Resolvable>::To;
+    type IntoFuture = Ready<<Self as Resolvable>::To>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        std::future::ready(self.wait())
+    }
+}
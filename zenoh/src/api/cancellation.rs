@@ -0,0 +1,130 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::{
+    fmt,
+    sync::{Arc, Mutex, Weak},
+};
+
+use zenoh_protocol::network::RequestId;
+
+use super::session::SessionInner;
+
+/// A token to cancel the queries issued with it
+/// (see [`SessionGetBuilder::cancellation_token`](crate::session::SessionGetBuilder::cancellation_token)).
+///
+/// Cancelling a query closes its reply handler, and notifies the queryables still handling it,
+/// which can observe it with [`Query::is_cancelled`](crate::query::Query::is_cancelled).
+/// A token can be shared by several queries, all of them being cancelled together.
+///
+/// # Examples
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() {
+/// use zenoh::query::CancellationToken;
+///
+/// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+/// let token = CancellationToken::default();
+/// let replies = session
+///     .get("key/expression")
+///     .cancellation_token(token.clone())
+///     .await
+///     .unwrap();
+/// if let Ok(reply) = replies.recv_async().await {
+///     println!(">> Received {:?}", reply.result());
+///     // Not interested in the other replies
+///     token.cancel();
+/// }
+/// # }
+/// ```
+#[zenoh_macros::unstable]
+#[derive(Clone, Default)]
+pub struct CancellationToken(Arc<Mutex<CancellationState>>);
+
+#[derive(Default)]
+struct CancellationState {
+    cancelled: bool,
+    queries: Vec<(Weak<SessionInner>, RequestId)>,
+}
+
+#[zenoh_macros::unstable]
+impl CancellationToken {
+    /// Cancel the queries issued with this token, and the ones issued with it afterwards.
+    pub fn cancel(&self) {
+        let queries = {
+            let mut state = zlock!(self.0);
+            state.cancelled = true;
+            std::mem::take(&mut state.queries)
+        };
+        for (session, qid) in queries {
+            if let Some(session) = session.upgrade() {
+                session.cancel_query(qid);
+            }
+        }
+    }
+
+    /// Whether this token was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        zlock!(self.0).cancelled
+    }
+
+    /// Registers the query `qid` of `session` to be cancelled with this token,
+    /// cancelling it immediately if the token was already cancelled.
+    /// The query is unregistered when the returned registration is dropped.
+    pub(crate) fn register(
+        &self,
+        session: &Arc<SessionInner>,
+        qid: RequestId,
+    ) -> Option<CancellationRegistration> {
+        let mut state = zlock!(self.0);
+        if state.cancelled {
+            drop(state);
+            session.cancel_query(qid);
+            None
+        } else {
+            state.queries.push((Arc::downgrade(session), qid));
+            Some(CancellationRegistration {
+                token: Arc::downgrade(&self.0),
+                session: Arc::downgrade(session),
+                qid,
+            })
+        }
+    }
+}
+
+/// The registration of a query in a [`CancellationToken`], kept in the state of the query so that
+/// the finished queries are removed from the tokens outliving them.
+pub(crate) struct CancellationRegistration {
+    token: Weak<Mutex<CancellationState>>,
+    session: Weak<SessionInner>,
+    qid: RequestId,
+}
+
+impl Drop for CancellationRegistration {
+    fn drop(&mut self) {
+        if let Some(token) = self.token.upgrade() {
+            zlock!(token)
+                .queries
+                .retain(|(session, qid)| *qid != self.qid || !session.ptr_eq(&self.session));
+        }
+    }
+}
+
+#[zenoh_macros::unstable]
+impl fmt::Debug for CancellationToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CancellationToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
@@ -0,0 +1,238 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use zenoh_config::wrappers::EntityGlobalId;
+use zenoh_core::{bail, zlock, Wait};
+use zenoh_protocol::core::{EntityGlobalIdProto, ZenohIdProto};
+use zenoh_result::ZResult;
+
+use super::{
+    handlers::Callback,
+    key_expr::KeyExpr,
+    sample::{Sample, SampleKind},
+    session::Session,
+};
+
+/// The prefix of the liveliness tokens declared by the publishers with a deadline,
+/// followed by their zid and eid.
+const DEADLINE_TOKEN_PREFIX: &str = "@deadline";
+
+/// The key expression of the liveliness token declared by the publisher `id` with a deadline,
+/// its undeclaration telling the subscribers to stop monitoring it.
+pub(crate) fn deadline_token_key_expr(id: &EntityGlobalId) -> ZResult<KeyExpr<'static>> {
+    KeyExpr::try_from(format!("{DEADLINE_TOKEN_PREFIX}/{}/{}", id.zid(), id.eid()))
+}
+
+/// Parses the publisher id out of the key expression of a deadline liveliness token.
+fn deadline_token_publisher(key_expr: &KeyExpr) -> Option<EntityGlobalId> {
+    let mut chunks = key_expr
+        .as_str()
+        .strip_prefix(DEADLINE_TOKEN_PREFIX)?
+        .strip_prefix('/')?
+        .split('/');
+    let zid: ZenohIdProto = chunks.next()?.parse().ok()?;
+    let eid = chunks.next()?.parse().ok()?;
+    if chunks.next().is_some() {
+        return None;
+    }
+    Some(EntityGlobalIdProto { zid, eid }.into())
+}
+
+/// The event notified to a [`Subscriber`](crate::pubsub::Subscriber) declared with a
+/// [`deadline`](crate::pubsub::SubscriberBuilder::deadline) when a publisher missed it.
+///
+/// The event is notified once per deadline period elapsed without receiving a sample from the
+/// publisher, until a new sample is received from it.
+#[zenoh_macros::unstable]
+#[derive(Debug, Clone)]
+pub struct DeadlineMissed {
+    publisher: EntityGlobalId,
+    key_expr: KeyExpr<'static>,
+    elapsed: Duration,
+    missed_count: u64,
+}
+
+#[zenoh_macros::unstable]
+impl DeadlineMissed {
+    /// The [`EntityGlobalId`] of the publisher that missed the deadline.
+    #[inline]
+    pub fn publisher(&self) -> EntityGlobalId {
+        self.publisher
+    }
+
+    /// The key expression of the last sample received from the publisher.
+    #[inline]
+    pub fn key_expr(&self) -> &KeyExpr<'static> {
+        &self.key_expr
+    }
+
+    /// The time elapsed since the last sample received from the publisher.
+    #[inline]
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// The number of consecutive deadlines missed by the publisher.
+    #[inline]
+    pub fn missed_count(&self) -> u64 {
+        self.missed_count
+    }
+}
+
+struct PublisherDeadline {
+    key_expr: KeyExpr<'static>,
+    last: Instant,
+    next: Instant,
+    missed_count: u64,
+}
+
+/// Tracks the arrival of the samples of a subscriber per publisher,
+/// the publishers being identified by the source id of their samples.
+///
+/// Only the publishers with a live deadline liveliness token are monitored, from their first
+/// sample on, until their token is undeclared.
+pub(crate) struct DeadlineMonitor {
+    deadline: Duration,
+    publishers: Mutex<HashMap<EntityGlobalId, Option<PublisherDeadline>>>,
+    callback: Callback<DeadlineMissed>,
+}
+
+impl DeadlineMonitor {
+    /// Wraps the sample `callback` of a subscriber to monitor the samples it receives,
+    /// and spawns the task checking the deadline until the subscriber or the session is closed.
+    pub(crate) fn spawn(
+        session: &Session,
+        deadline: Duration,
+        callback: Callback<Sample>,
+        missed_callback: Callback<DeadlineMissed>,
+    ) -> ZResult<Callback<Sample>> {
+        if deadline.is_zero() {
+            bail!("Invalid deadline: it should be greater than zero");
+        }
+        let monitor = Arc::new(DeadlineMonitor {
+            deadline,
+            publishers: Mutex::new(HashMap::new()),
+            callback: missed_callback,
+        });
+        let tokens = session
+            .liveliness()
+            .declare_subscriber(format!("{DEADLINE_TOKEN_PREFIX}/**"))
+            .history(true)
+            .callback({
+                let monitor = Arc::downgrade(&monitor);
+                move |sample: Sample| {
+                    if let (Some(monitor), Some(publisher)) = (
+                        monitor.upgrade(),
+                        deadline_token_publisher(sample.key_expr()),
+                    ) {
+                        match sample.kind() {
+                            SampleKind::Put => monitor.watch(publisher),
+                            SampleKind::Delete => monitor.forget(&publisher),
+                        }
+                    }
+                }
+            })
+            .wait()?;
+        let token = session.0.task_controller.get_cancellation_token();
+        session
+            .0
+            .task_controller
+            .spawn_with_rt(zenoh_runtime::ZRuntime::Application, {
+                let monitor = Arc::downgrade(&monitor);
+                async move {
+                    loop {
+                        // The monitor is dropped with the subscriber callback when undeclared
+                        let Some(monitor) = monitor.upgrade() else {
+                            break;
+                        };
+                        let next_check = monitor.check(Instant::now());
+                        drop(monitor);
+                        tokio::select! {
+                            _ = tokio::time::sleep(next_check) => {}
+                            _ = token.cancelled() => break,
+                        }
+                    }
+                    // The liveliness subscriber is undeclared with the monitor
+                    drop(tokens);
+                }
+            });
+        Ok(Callback::new(Arc::new(move |sample: Sample| {
+            monitor.received(&sample);
+            callback.call(sample);
+        })))
+    }
+
+    fn received(&self, sample: &Sample) {
+        let Some(publisher) = sample.source_info().source_id() else {
+            return;
+        };
+        if let Some(state) = zlock!(self.publishers).get_mut(publisher) {
+            let now = Instant::now();
+            *state = Some(PublisherDeadline {
+                key_expr: sample.key_expr().clone().into_owned(),
+                last: now,
+                next: now + self.deadline,
+                missed_count: 0,
+            });
+        }
+    }
+
+    /// Starts monitoring the `publisher` which declared a deadline.
+    fn watch(&self, publisher: EntityGlobalId) {
+        zlock!(self.publishers).entry(publisher).or_insert(None);
+    }
+
+    /// Stops monitoring the undeclared `publisher`.
+    fn forget(&self, publisher: &EntityGlobalId) {
+        zlock!(self.publishers).remove(publisher);
+    }
+
+    /// Notifies the publishers which missed the deadline at `now`,
+    /// and returns the time to wait before the next check.
+    fn check(&self, now: Instant) -> Duration {
+        let mut missed = Vec::new();
+        let mut next_check = now + self.deadline;
+        {
+            let mut publishers = zlock!(self.publishers);
+            for (publisher, state) in publishers.iter_mut() {
+                let Some(state) = state else {
+                    continue;
+                };
+                if state.next <= now {
+                    state.missed_count += 1;
+                    state.next += self.deadline;
+                    if state.next <= now {
+                        state.next = now + self.deadline;
+                    }
+                    missed.push(DeadlineMissed {
+                        publisher: *publisher,
+                        key_expr: state.key_expr.clone(),
+                        elapsed: now - state.last,
+                        missed_count: state.missed_count,
+                    });
+                }
+                next_check = next_check.min(state.next);
+            }
+        }
+        for event in missed {
+            self.callback.call(event);
+        }
+        next_check - now
+    }
+}
@@ -14,7 +14,7 @@
 
 //! Callback handler trait.
 
-use std::sync::Arc;
+use std::{fmt, sync::Arc};
 
 use crate::api::handlers::IntoHandler;
 
@@ -34,6 +34,12 @@ impl<T> Clone for Callback<T> {
     }
 }
 
+impl<T> fmt::Debug for Callback<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Callback").finish_non_exhaustive()
+    }
+}
+
 impl<T> Callback<T> {
     /// Instantiate a `Callback` from a callback function.
     pub fn new(cb: Arc<dyn Fn(T) + Send + Sync>) -> Self {
@@ -13,6 +13,8 @@
 //
 
 //! Tools to access information about the current zenoh [`Session`](crate::Session).
+#[cfg(feature = "unstable")]
+use crate::api::{builders::topology_listener::TopologyListenerBuilder, handlers::DefaultHandler};
 use crate::{
     api::builders::info::{PeersZenohIdBuilder, RoutersZenohIdBuilder, ZenohIdBuilder},
     net::runtime::Runtime,
@@ -82,4 +84,25 @@ impl SessionInfo {
     pub fn peers_zid(&self) -> PeersZenohIdBuilder<'_> {
         PeersZenohIdBuilder::new(&self.runtime)
     }
+
+    /// Create a [`TopologyListener`](crate::session::TopologyListener) receiving the changes of
+    /// the network topology seen by the current zenoh [`Session`](crate::Session): the transports
+    /// opened and closed and, on routers, the changes of the routers' link-state graph.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+    /// let listener = session.info().topology_listener().await.unwrap();
+    /// while let Ok(event) = listener.recv_async().await {
+    ///     println!("Topology changed: {event:?}");
+    /// }
+    /// # }
+    /// ```
+    #[zenoh_macros::unstable]
+    pub fn topology_listener(&self) -> TopologyListenerBuilder<'_, DefaultHandler> {
+        TopologyListenerBuilder::new(&self.runtime)
+    }
 }
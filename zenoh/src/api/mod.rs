@@ -17,7 +17,11 @@ pub(crate) type Id = u32;
 pub(crate) mod admin;
 pub(crate) mod builders;
 pub(crate) mod bytes;
+#[cfg(feature = "unstable")]
+pub(crate) mod cancellation;
 pub(crate) mod config;
+#[cfg(feature = "unstable")]
+pub(crate) mod deadline;
 pub(crate) mod encoding;
 pub(crate) mod handlers;
 pub(crate) mod info;
@@ -39,3 +43,4 @@ pub(crate) mod scouting;
 pub(crate) mod selector;
 pub(crate) mod session;
 pub(crate) mod subscriber;
+pub(crate) mod topology;
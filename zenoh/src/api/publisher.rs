@@ -35,7 +35,14 @@ use {
         matching::{MatchingStatus, MatchingStatusType},
         sample::SourceInfo,
     },
-    std::{collections::HashSet, sync::Arc, sync::Mutex},
+    std::{
+        collections::HashSet,
+        sync::{
+            atomic::{AtomicU32, Ordering},
+            Arc, Mutex,
+        },
+        time::Duration,
+    },
     zenoh_config::wrappers::EntityGlobalId,
     zenoh_protocol::core::EntityGlobalIdProto,
     zenoh_protocol::core::Reliability,
@@ -112,6 +119,14 @@ pub struct Publisher<'a> {
     #[cfg(feature = "unstable")]
     pub(crate) reliability: Reliability,
     #[cfg(feature = "unstable")]
+    pub(crate) lifespan: Option<Duration>,
+    #[cfg(feature = "unstable")]
+    pub(crate) deadline: Option<Duration>,
+    #[cfg(feature = "unstable")]
+    pub(crate) deadline_token: Option<crate::api::liveliness::LivelinessToken>,
+    #[cfg(feature = "unstable")]
+    pub(crate) next_sn: AtomicU32,
+    #[cfg(feature = "unstable")]
     pub(crate) matching_listeners: Arc<Mutex<HashSet<Id>>>,
     pub(crate) undeclare_on_drop: bool,
 }
@@ -170,6 +185,33 @@ impl<'a> Publisher<'a> {
         self.reliability
     }
 
+    /// Get the lifespan of the published data
+    #[zenoh_macros::unstable]
+    #[inline]
+    pub fn lifespan(&self) -> Option<Duration> {
+        self.lifespan
+    }
+
+    /// Get the deadline this publisher committed to publish within
+    #[zenoh_macros::unstable]
+    #[inline]
+    pub fn deadline(&self) -> Option<Duration> {
+        self.deadline
+    }
+
+    /// The publishers with a deadline identify themselves in the source info of their samples,
+    /// numbered in sequence, for the subscribers to monitor the deadline per publisher.
+    #[cfg(feature = "unstable")]
+    fn default_source_info(&self) -> SourceInfo {
+        match self.deadline {
+            Some(_) => SourceInfo::new(
+                Some(self.id()),
+                Some(self.next_sn.fetch_add(1, Ordering::Relaxed)),
+            ),
+            None => SourceInfo::empty(),
+        }
+    }
+
     /// Put data.
     ///
     /// # Examples
@@ -195,7 +237,7 @@ impl<'a> Publisher<'a> {
             },
             timestamp: None,
             #[cfg(feature = "unstable")]
-            source_info: SourceInfo::empty(),
+            source_info: self.default_source_info(),
             attachment: None,
         }
     }
@@ -218,7 +260,7 @@ impl<'a> Publisher<'a> {
             kind: PublicationBuilderDelete,
             timestamp: None,
             #[cfg(feature = "unstable")]
-            source_info: SourceInfo::empty(),
+            source_info: self.default_source_info(),
             attachment: None,
         }
     }
@@ -312,6 +354,9 @@ impl<'a> Publisher<'a> {
             for id in ids {
                 self.session.undeclare_matches_listener_inner(id)?
             }
+            if let Some(token) = self.deadline_token.take() {
+                token.undeclare().wait()?;
+            }
         }
         self.session.undeclare_publisher_inner(self.id)
     }
@@ -402,9 +447,11 @@ impl Sink<Sample> for Publisher<'_> {
             self.destination,
             #[cfg(feature = "unstable")]
             self.reliability,
+            #[cfg(feature = "unstable")]
+            self.lifespan,
             None,
             #[cfg(feature = "unstable")]
-            SourceInfo::empty(),
+            self.default_source_info(),
             attachment,
         )
     }
@@ -165,6 +165,10 @@ impl<'a> Querier<'a> {
             querier: self,
             #[cfg(feature = "unstable")]
             source_info: SourceInfo::empty(),
+            #[cfg(feature = "unstable")]
+            cancellation_token: None,
+            #[cfg(feature = "unstable")]
+            reply_credits: None,
             value: None,
             attachment: None,
             parameters: Parameters::empty(),
@@ -27,6 +27,8 @@ pub use zenoh_protocol::network::request::ext::QueryTarget;
 #[doc(inline)]
 pub use zenoh_protocol::zenoh::query::ConsolidationMode;
 
+#[cfg(feature = "unstable")]
+use crate::api::sample::Locality;
 use crate::api::{
     bytes::ZBytes, encoding::Encoding, handlers::Callback, key_expr::KeyExpr, sample::Sample,
     selector::Selector,
@@ -99,6 +101,23 @@ impl ReplyError {
     pub fn encoding(&self) -> &Encoding {
         &self.encoding
     }
+
+    /// The error replied by a [`Queryable`](crate::query::Queryable) rejecting a query because it
+    /// reached its maximum number of concurrent queries.
+    pub(crate) fn overloaded() -> Self {
+        Self::new("queryable overloaded", Self::overloaded_encoding())
+    }
+
+    fn overloaded_encoding() -> Encoding {
+        Encoding::ZENOH_STRING.with_schema("zenoh/overloaded")
+    }
+
+    /// Whether the query was rejected by an overloaded [`Queryable`](crate::query::Queryable)
+    /// (see [`QueryableBuilder::max_concurrent_queries`](crate::query::QueryableBuilder::max_concurrent_queries)).
+    #[zenoh_macros::unstable]
+    pub fn is_overloaded(&self) -> bool {
+        self.encoding == Self::overloaded_encoding()
+    }
 }
 
 impl Display for ReplyError {
@@ -164,6 +183,12 @@ pub(crate) struct QueryState {
     pub(crate) parameters: Parameters<'static>,
     pub(crate) reception_mode: ConsolidationMode,
     pub(crate) replies: Option<HashMap<OwnedKeyExpr, Reply>>,
+    #[cfg(feature = "unstable")]
+    pub(crate) destination: Locality,
+    #[cfg(feature = "unstable")]
+    pub(crate) reply_window: Option<ReplyWindow>,
+    #[cfg(feature = "unstable")]
+    pub(crate) cancellation: Option<crate::api::cancellation::CancellationRegistration>,
     pub(crate) callback: Callback<Reply>,
 }
 
@@ -171,7 +196,61 @@ impl QueryState {
     pub(crate) fn selector(&self) -> Selector {
         Selector::borrowed(&self.key_expr, &self.parameters)
     }
+
+    /// Accounts for a reply received from `responder`, returning the credits to grant back to
+    /// its queryables and their locality if the query requested streaming replies.
+    #[cfg(feature = "unstable")]
+    pub(crate) fn consume_reply_credit(
+        &mut self,
+        responder: Option<ZenohIdProto>,
+    ) -> Option<(u32, Locality)> {
+        let destination = self.destination;
+        let window = self.reply_window.as_mut()?;
+        window
+            .consume(responder?)
+            .map(|credit| (credit, destination))
+    }
 }
+
+/// The reply credits of a query requesting streaming replies.
+///
+/// The window is shared among the responding sessions: each of them starts with a single
+/// credit and is granted back up to its share of the window once half of it has been received.
+#[cfg(feature = "unstable")]
+pub(crate) struct ReplyWindow {
+    window: u32,
+    in_flight: HashMap<ZenohIdProto, u32>,
+}
+
+#[cfg(feature = "unstable")]
+impl ReplyWindow {
+    pub(crate) fn new(window: u32) -> Self {
+        Self {
+            window,
+            in_flight: HashMap::new(),
+        }
+    }
+
+    fn consume(&mut self, responder: ZenohIdProto) -> Option<u32> {
+        let credits = self
+            .in_flight
+            .entry(responder)
+            .or_insert(INITIAL_REPLY_CREDITS);
+        *credits = credits.saturating_sub(1);
+        let credits = *credits;
+        let share = (self.window / self.in_flight.len() as u32).max(1);
+        if credits > share / 2 {
+            return None;
+        }
+        self.in_flight.insert(responder, share);
+        Some(share - credits)
+    }
+}
+
+/// The credits a queryable starts with when replying to a query requesting streaming replies.
+#[cfg(feature = "unstable")]
+pub(crate) const INITIAL_REPLY_CREDITS: u32 = 1;
+
 /// The kind of accepted query replies.
 #[zenoh_macros::unstable]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Deserialize)]
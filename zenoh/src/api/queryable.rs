@@ -15,9 +15,14 @@ use std::{
     fmt,
     future::{IntoFuture, Ready},
     ops::{Deref, DerefMut},
-    sync::Arc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
 };
 
+use tokio::sync::Semaphore;
+use tokio_util::sync::CancellationToken;
 use tracing::error;
 use zenoh_core::{Resolvable, Resolve, Wait};
 use zenoh_protocol::{
@@ -25,10 +30,12 @@ use zenoh_protocol::{
     network::{response, Mapping, RequestId, Response, ResponseFinal},
     zenoh::{self, reply::ReplyBody, Del, Put, ResponseBody},
 };
-use zenoh_result::ZResult;
+use zenoh_result::{bail, ZResult};
 #[zenoh_macros::unstable]
 use {
-    crate::api::query::ReplyKeyExpr, zenoh_config::wrappers::EntityGlobalId,
+    crate::api::{query::ReplyKeyExpr, session::SessionInner},
+    std::sync::Weak,
+    zenoh_config::wrappers::EntityGlobalId,
     zenoh_protocol::core::EntityGlobalIdProto,
 };
 
@@ -55,10 +62,85 @@ pub(crate) struct QueryInner {
     pub(crate) qid: RequestId,
     pub(crate) zid: ZenohIdProto,
     pub(crate) primitives: Arc<dyn Primitives>,
+    pub(crate) control: Option<QueryControl>,
+}
+
+/// The control of a query received by the queryables of a session, by its querier.
+#[derive(Clone)]
+pub(crate) struct QueryControl {
+    /// Cancelled when the querier cancels the query
+    pub(crate) token: CancellationToken,
+    /// The response credits granted by the querier, if it requested streaming replies
+    pub(crate) credits: Option<Arc<Semaphore>>,
+    #[cfg(feature = "unstable")]
+    pub(crate) session: Weak<SessionInner>,
+    #[cfg(feature = "unstable")]
+    pub(crate) local: bool,
+}
+
+impl QueryInner {
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.control
+            .as_ref()
+            .is_some_and(|control| control.token.is_cancelled())
+    }
+
+    /// Waits for a response credit if the querier requested streaming replies,
+    /// until the query is cancelled.
+    pub(crate) async fn acquire_credit(&self) {
+        let Some(control) = &self.control else {
+            return;
+        };
+        let Some(credits) = &control.credits else {
+            return;
+        };
+        tokio::select! {
+            permit = credits.acquire() => {
+                if let Ok(permit) = permit {
+                    permit.forget();
+                }
+            }
+            _ = control.token.cancelled() => {}
+        }
+    }
+
+    /// Returns `true` if the querier requested streaming replies.
+    pub(crate) fn has_credits(&self) -> bool {
+        self.control
+            .as_ref()
+            .is_some_and(|control| control.credits.is_some())
+    }
+
+    /// Non-blocking version of [`QueryInner::acquire_credit`], failing if no response credit
+    /// is left.
+    pub(crate) fn try_acquire_credit(&self) -> ZResult<()> {
+        let Some(credits) = self.control.as_ref().and_then(|c| c.credits.as_ref()) else {
+            return Ok(());
+        };
+        match credits.try_acquire() {
+            Ok(permit) => permit.forget(),
+            Err(_) if self.is_cancelled() => {}
+            Err(_) => bail!(
+                "No response credit left for query {}: reply asynchronously to wait for the credits granted by the querier",
+                self.qid
+            ),
+        }
+        Ok(())
+    }
 }
 
 impl Drop for QueryInner {
     fn drop(&mut self) {
+        #[cfg(feature = "unstable")]
+        if let Some(control) = &self.control {
+            if let Some(session) = control.session.upgrade() {
+                zlock!(session.received_queries).remove(&(control.local, self.qid));
+            }
+        }
+        // The querier no longer expects the final response of a cancelled query
+        if self.is_cancelled() {
+            return;
+        }
         self.primitives.send_response_final(ResponseFinal {
             rid: self.qid,
             ext_qos: response::ext::QoSType::RESPONSE_FINAL,
@@ -67,6 +149,90 @@ impl Drop for QueryInner {
     }
 }
 
+/// The policy applied by a [`Queryable`] to the queries received beyond its maximum number of
+/// concurrent queries (see [`QueryableBuilder::max_concurrent_queries`](crate::query::QueryableBuilder::max_concurrent_queries)).
+#[zenoh_macros::unstable_doc]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OverloadPolicy {
+    /// The queries are rejected with a [`ReplyError`](crate::query::ReplyError) for which
+    /// [`ReplyError::is_overloaded`](crate::query::ReplyError::is_overloaded) returns `true`.
+    #[default]
+    Reject,
+    /// A complete queryable is declared as incomplete while overloaded, so that the routers route
+    /// the queries to the other complete queryables on the same key expressions. It is declared complete
+    /// again once its concurrent queries dropped to half of the maximum.
+    /// The queries still received are rejected like with [`OverloadPolicy::Reject`].
+    Redirect,
+}
+
+/// The admission control of the queries received by a queryable.
+pub(crate) struct QueryableAdmission {
+    pub(crate) max: usize,
+    pub(crate) policy: OverloadPolicy,
+    in_flight: AtomicUsize,
+    /// Whether the queryable is currently declared as incomplete by the [`OverloadPolicy::Redirect`] policy
+    redirected: Mutex<bool>,
+}
+
+impl QueryableAdmission {
+    #[cfg(feature = "unstable")]
+    pub(crate) fn new(max: usize, policy: OverloadPolicy) -> Self {
+        Self {
+            max,
+            policy,
+            in_flight: AtomicUsize::new(0),
+            redirected: Mutex::new(false),
+        }
+    }
+
+    /// Admits a query if the queryable is not overloaded.
+    pub(crate) fn admit(&self) -> bool {
+        self.in_flight
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+                (n < self.max).then_some(n + 1)
+            })
+            .is_ok()
+    }
+
+    /// Updates the declared completeness of the queryable `id` after a change of its in-flight queries.
+    pub(crate) fn update_redirection(&self, session: &WeakSession, id: Id) {
+        if self.policy != OverloadPolicy::Redirect {
+            return;
+        }
+        let mut redirected = zlock!(self.redirected);
+        let in_flight = self.in_flight.load(Ordering::SeqCst);
+        // A redirected queryable is declared complete again only once half of its maximum number
+        // of queries completed, so that it is not redeclared on every query at the limit
+        let overloaded = if *redirected {
+            in_flight > self.max / 2
+        } else {
+            in_flight >= self.max
+        };
+        if *redirected != overloaded {
+            *redirected = overloaded;
+            // The declaration is sent while holding the lock, so that the declarations of
+            // concurrent updates are not reordered
+            if let Err(e) = session.redeclare_queryable(id, !overloaded) {
+                error!("Unable to redeclare queryable: {}", e);
+            }
+        }
+    }
+}
+
+/// An admitted query, counted in the in-flight queries of its queryable until all its clones are dropped.
+pub(crate) struct QueryPermit {
+    pub(crate) admission: Arc<QueryableAdmission>,
+    pub(crate) session: WeakSession,
+    pub(crate) id: Id,
+}
+
+impl Drop for QueryPermit {
+    fn drop(&mut self) {
+        self.admission.in_flight.fetch_sub(1, Ordering::SeqCst);
+        self.admission.update_redirection(&self.session, self.id);
+    }
+}
+
 /// Structs received by a [`Queryable`].
 #[derive(Clone)]
 pub struct Query {
@@ -74,6 +240,7 @@ pub struct Query {
     pub(crate) eid: EntityId,
     pub(crate) value: Option<(ZBytes, Encoding)>,
     pub(crate) attachment: Option<ZBytes>,
+    pub(crate) permit: Option<Arc<QueryPermit>>,
 }
 
 impl Query {
@@ -195,6 +362,40 @@ impl Query {
             }
         })
     }
+
+    /// Whether this Query was cancelled by the querier, in which case its replies are discarded.
+    #[zenoh_macros::unstable]
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.is_cancelled()
+    }
+
+    /// Wait until this Query is cancelled by the querier.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+    /// let queryable = session.declare_queryable("key/expression").await.unwrap();
+    /// while let Ok(query) = queryable.recv_async().await {
+    ///     tokio::select! {
+    ///         _ = query.cancelled() => println!(">> Query '{}' cancelled", query.selector()),
+    ///         _ = tokio::time::sleep(std::time::Duration::from_secs(1)) => {
+    ///             query.reply("key/expression", "value").await.unwrap();
+    ///         }
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    #[zenoh_macros::unstable]
+    pub async fn cancelled(&self) {
+        match &self.inner.control {
+            Some(control) => control.token.cancelled().await,
+            None => std::future::pending().await,
+        }
+    }
+
     #[cfg(feature = "unstable")]
     fn _accepts_any_replies(&self) -> ZResult<bool> {
         Ok(self.parameters().reply_key_expr_any())
@@ -235,17 +436,24 @@ impl Resolvable for ReplySample<'_> {
 #[zenoh_macros::internal]
 impl Wait for ReplySample<'_> {
     fn wait(self) -> <Self as Resolvable>::To {
+        self.query.inner.try_acquire_credit()?;
         self.query._reply_sample(self.sample)
     }
 }
 
 #[zenoh_macros::internal]
-impl IntoFuture for ReplySample<'_> {
+impl<'a> IntoFuture for ReplySample<'a> {
     type Output = <Self as Resolvable>::To;
-    type IntoFuture = Ready<<Self as Resolvable>::To>;
+    type IntoFuture = crate::api::builders::reply::ReplyFuture<'a>;
 
     fn into_future(self) -> Self::IntoFuture {
-        std::future::ready(self.wait())
+        if !self.query.inner.has_credits() {
+            return futures::future::Either::Left(std::future::ready(self.wait()));
+        }
+        futures::future::Either::Right(Box::pin(async move {
+            self.query.inner.acquire_credit().await;
+            self.query._reply_sample(self.sample)
+        }))
     }
 }
 
@@ -259,6 +467,10 @@ impl Query {
         if c && !self.key_expr().intersects(&sample.key_expr) {
             bail!("Attempted to reply on `{}`, which does not intersect with query `{}`, despite query only allowing replies on matching key expressions", sample.key_expr, self.key_expr())
         }
+        if self.inner.is_cancelled() {
+            tracing::trace!("Discard reply to cancelled query {}", self.inner.qid);
+            return Ok(());
+        }
         #[cfg(not(feature = "unstable"))]
         let ext_sinfo = None;
         #[cfg(feature = "unstable")]
@@ -308,6 +520,7 @@ pub(crate) struct QueryableState {
     pub(crate) complete: bool,
     pub(crate) origin: Locality,
     pub(crate) callback: Callback<Query>,
+    pub(crate) admission: Option<Arc<QueryableAdmission>>,
 }
 
 impl fmt::Debug for QueryableState {
@@ -13,7 +13,7 @@
 //
 
 //! Sample primitives
-use std::{convert::TryFrom, fmt};
+use std::{convert::TryFrom, fmt, time::Duration};
 
 use serde::{Deserialize, Serialize};
 use zenoh_config::{qos::PublisherLocalityConf, wrappers::EntityGlobalId};
@@ -78,6 +78,7 @@ pub(crate) struct DataInfo {
     pub source_id: Option<EntityGlobalId>,
     pub source_sn: Option<SourceSn>,
     pub qos: QoS,
+    pub lifespan: Option<Duration>,
 }
 
 pub(crate) trait DataInfoIntoSample {
@@ -124,6 +125,8 @@ impl DataInfoIntoSample for DataInfo {
                 source_id: self.source_id,
                 source_sn: self.source_sn,
             },
+            #[cfg(feature = "unstable")]
+            lifespan: self.lifespan,
             attachment,
         }
     }
@@ -162,6 +165,8 @@ impl DataInfoIntoSample for Option<DataInfo> {
                 reliability,
                 #[cfg(feature = "unstable")]
                 source_info: SourceInfo::empty(),
+                #[cfg(feature = "unstable")]
+                lifespan: None,
                 attachment,
             }
         }
@@ -313,6 +318,8 @@ pub struct SampleFields {
     pub reliability: Reliability,
     #[cfg(feature = "unstable")]
     pub source_info: SourceInfo,
+    #[cfg(feature = "unstable")]
+    pub lifespan: Option<Duration>,
     pub attachment: Option<ZBytes>,
 }
 
@@ -331,6 +338,8 @@ impl From<Sample> for SampleFields {
             reliability: sample.reliability,
             #[cfg(feature = "unstable")]
             source_info: sample.source_info,
+            #[cfg(feature = "unstable")]
+            lifespan: sample.lifespan,
             attachment: sample.attachment,
         }
     }
@@ -350,6 +359,8 @@ pub struct Sample {
     pub(crate) reliability: Reliability,
     #[cfg(feature = "unstable")]
     pub(crate) source_info: SourceInfo,
+    #[cfg(feature = "unstable")]
+    pub(crate) lifespan: Option<Duration>,
     pub(crate) attachment: Option<ZBytes>,
 }
 
@@ -418,6 +429,24 @@ impl Sample {
         &self.source_info
     }
 
+    /// Gets the lifespan of this Sample: the duration after its timestamp beyond which it is stale.
+    #[zenoh_macros::unstable]
+    #[inline]
+    pub fn lifespan(&self) -> Option<Duration> {
+        self.lifespan
+    }
+
+    /// Returns `true` if this Sample is older than its lifespan.
+    ///
+    /// A Sample without timestamp or without lifespan never expires. The routers timestamp the
+    /// data received with a lifespan but without a timestamp, its lifespan then being counted
+    /// from its reception by the first router.
+    #[zenoh_macros::unstable]
+    #[inline]
+    pub fn is_expired(&self) -> bool {
+        crate::net::protocol::lifespan::is_expired(self.timestamp.as_ref(), self.lifespan)
+    }
+
     /// Gets the sample attachment: a map of key-value pairs, where each key and value are byte-slices.
     #[inline]
     pub fn attachment(&self) -> Option<&ZBytes> {
@@ -431,6 +460,43 @@ impl Sample {
     }
 }
 
+/// A group of samples published atomically with [`Session::batch`](crate::Session::batch).
+///
+/// The samples of a batch share the same timestamp and are delivered as a unit to the subscribers
+/// declared with a [`batch_callback`](crate::pubsub::SubscriberBuilder::batch_callback) whose key
+/// expression matches all of them.
+#[zenoh_macros::unstable]
+#[derive(Clone, Debug)]
+pub struct SampleBatch {
+    pub(crate) timestamp: Option<Timestamp>,
+    pub(crate) samples: Vec<Sample>,
+}
+
+#[zenoh_macros::unstable]
+impl SampleBatch {
+    /// Gets the timestamp shared by the samples of the batch.
+    #[inline]
+    pub fn timestamp(&self) -> Option<&Timestamp> {
+        self.timestamp.as_ref()
+    }
+
+    /// Gets the samples of the batch, in publication order.
+    #[inline]
+    pub fn samples(&self) -> &[Sample] {
+        &self.samples
+    }
+}
+
+#[zenoh_macros::unstable]
+impl IntoIterator for SampleBatch {
+    type Item = Sample;
+    type IntoIter = std::vec::IntoIter<Sample>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.samples.into_iter()
+    }
+}
+
 /// Structure containing quality of service data
 #[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
 pub(crate) struct QoS {
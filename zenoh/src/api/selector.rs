@@ -57,6 +57,8 @@ use crate::api::{key_expr::KeyExpr, queryable::Query};
 ///   this parameter must be readable by the [Zenoh Time DSL](zenoh_util::time_range::TimeRange) for the value to be considered valid.
 /// - **`[unstable]`** `_anyke`: used in queries to express interest in replies coming from any key expression. By default, only replies
 ///   whose key expression match query's key expression are accepted. `_anyke` disables the query-reply key expression matching check.
+/// - **`[unstable]`** `_credits`: used in queries to request streaming replies. Its value is the window of replies shared by the
+///   responding sessions, each of them waiting for the querier to grant it more credits once its share is consumed.
 #[derive(Clone, PartialEq, Eq)]
 pub struct Selector<'a> {
     /// The part of this selector identifying which keys should be part of the selection.
@@ -143,6 +145,7 @@ pub trait ZenohParameters {
     /// which now are stored in the key-value pairs will be later passed in some other way, keeping the same get/set interface functions.
     const REPLY_KEY_EXPR_ANY_SEL_PARAM: &'static str = "_anyke";
     const TIME_RANGE_KEY: &'static str = "_time";
+    const REPLY_CREDITS_SEL_PARAM: &'static str = "_credits";
     /// Sets the time range targeted by the selector parameters.
     fn set_time_range<T: Into<Option<TimeRange>>>(&mut self, time_range: T);
     /// Sets the parameter allowing to receive replies from queryables not matching
@@ -161,6 +164,12 @@ pub trait ZenohParameters {
     fn time_range(&self) -> Option<ZResult<TimeRange>>;
     /// Returns true if `_anyke` parameter is present in the selector parameters
     fn reply_key_expr_any(&self) -> bool;
+    /// Sets the window of replies the queryables may send before waiting for more credits from the querier.
+    /// `None` disables streaming replies.
+    fn set_reply_credits(&mut self, credits: Option<u32>);
+    /// Extracts the standardized `_credits` argument from the selector parameters.
+    /// Returns `None` if the `_credits` argument is not present or is not a valid number.
+    fn reply_credits(&self) -> Option<u32>;
 }
 
 #[cfg(feature = "unstable")]
@@ -192,6 +201,20 @@ impl ZenohParameters for Parameters<'_> {
     fn reply_key_expr_any(&self) -> bool {
         self.contains_key(Self::REPLY_KEY_EXPR_ANY_SEL_PARAM)
     }
+
+    /// Sets the window of replies the queryables may send before waiting for more credits from the querier.
+    fn set_reply_credits(&mut self, credits: Option<u32>) {
+        match credits {
+            Some(credits) => self.insert(Self::REPLY_CREDITS_SEL_PARAM, credits.to_string()),
+            None => self.remove(Self::REPLY_CREDITS_SEL_PARAM),
+        };
+    }
+
+    /// Extracts the standardized `_credits` argument from the selector parameters.
+    fn reply_credits(&self) -> Option<u32> {
+        self.get(Self::REPLY_CREDITS_SEL_PARAM)
+            .and_then(|credits| credits.parse().ok())
+    }
 }
 
 impl std::fmt::Debug for Selector<'_> {
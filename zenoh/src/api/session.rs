@@ -27,6 +27,10 @@ use async_trait::async_trait;
 #[zenoh_macros::internal]
 use ref_cast::ref_cast_custom;
 use ref_cast::RefCastCustom;
+#[cfg(feature = "unstable")]
+use tokio::sync::Semaphore;
+#[cfg(feature = "unstable")]
+use tokio_util::sync::CancellationToken;
 use tracing::{error, info, trace, warn};
 use uhlc::Timestamp;
 #[cfg(feature = "internal")]
@@ -37,7 +41,13 @@ use zenoh_config::{qos::PublisherQoSConfig, unwrap_or_default, wrappers::ZenohId
 use zenoh_core::{zconfigurable, zread, Resolve, ResolveClosure, ResolveFuture, Wait};
 use zenoh_keyexpr::keyexpr_tree::KeBoxTree;
 #[cfg(feature = "unstable")]
-use zenoh_protocol::network::declare::SubscriberId;
+use zenoh_protocol::{
+    common::ZExtBody,
+    network::{
+        declare::SubscriberId,
+        oam::id::{OAM_QUERY_CANCEL, OAM_QUERY_CREDIT},
+    },
+};
 use zenoh_protocol::{
     core::{
         key_expr::{keyexpr, OwnedKeyExpr},
@@ -53,13 +63,13 @@ use zenoh_protocol::{
         },
         ext,
         interest::{InterestId, InterestMode, InterestOptions},
-        push, request, AtomicRequestId, DeclareFinal, Interest, Mapping, Push, Request, RequestId,
-        Response, ResponseFinal,
+        push, request, AtomicRequestId, DeclareFinal, Interest, Mapping, Oam, Push, Request,
+        RequestId, Response, ResponseFinal,
     },
     zenoh::{
         query::{self, ext::QueryBodyType},
         reply::ReplyBody,
-        Del, PushBody, Put, RequestBody, ResponseBody,
+        Batch, BatchEntry, Del, PushBody, Put, RequestBody, ResponseBody,
     },
 };
 use zenoh_result::ZResult;
@@ -72,11 +82,17 @@ use super::builders::close::{CloseBuilder, Closeable, Closee};
 use crate::api::selector::ZenohParameters;
 #[cfg(feature = "unstable")]
 use crate::api::{
-    builders::querier::QuerierBuilder,
+    builders::{batch::BatchBuilder, querier::QuerierBuilder},
     matching::{MatchingListenerState, MatchingStatus, MatchingStatusType},
     querier::QuerierState,
-    query::ReplyKeyExpr,
-    sample::SourceInfo,
+    query::{ReplyKeyExpr, ReplyWindow, INITIAL_REPLY_CREDITS},
+    queryable::{OverloadPolicy, QueryControl},
+    sample::{SampleBatch, SourceInfo},
+};
+#[cfg(feature = "unstable")]
+use crate::net::{
+    protocol::{batch::batch_key_expr, query_credit::QueryCredit},
+    routing::interceptor::InterceptorFactory,
 };
 use crate::{
     api::{
@@ -102,7 +118,7 @@ use crate::{
             ConsolidationMode, LivelinessQueryState, QueryConsolidation, QueryState, QueryTarget,
             Reply,
         },
-        queryable::{Query, QueryInner, QueryableState},
+        queryable::{Query, QueryInner, QueryPermit, QueryableAdmission, QueryableState},
         sample::{DataInfo, DataInfoIntoSample, Locality, QoS, Sample, SampleKind},
         selector::Selector,
         subscriber::{SubscriberKind, SubscriberState},
@@ -110,6 +126,7 @@ use crate::{
     },
     net::{
         primitives::Primitives,
+        protocol::lifespan::is_expired,
         routing::dispatcher::face::Face,
         runtime::{Runtime, RuntimeBuilder},
     },
@@ -344,6 +361,7 @@ impl SessionState {
         key_expr: &'a KeyExpr,
         origin: Locality,
         callback: Callback<Sample>,
+        #[cfg(feature = "unstable")] batch_callback: Option<Callback<SampleBatch>>,
     ) -> (Arc<SubscriberState>, Option<KeyExpr<'a>>) {
         let mut sub_state = SubscriberState {
             id,
@@ -351,6 +369,8 @@ impl SessionState {
             key_expr: key_expr.clone().into_owned(),
             origin,
             callback,
+            #[cfg(feature = "unstable")]
+            batch_callback,
         };
 
         let declared_sub = origin != Locality::SessionLocal;
@@ -527,7 +547,11 @@ pub(crate) struct SessionInner {
     pub(crate) state: RwLock<SessionState>,
     pub(crate) id: u16,
     owns_runtime: bool,
-    task_controller: TaskController,
+    pub(crate) task_controller: TaskController,
+    /// The control of the queries being handled by the queryables of the session,
+    /// by locality and request id
+    #[cfg(feature = "unstable")]
+    pub(crate) received_queries: Mutex<HashMap<(bool, RequestId), QueryControl>>,
 }
 
 impl fmt::Debug for SessionInner {
@@ -689,6 +713,8 @@ impl Session {
                 id: SESSION_ID_COUNTER.fetch_add(1, Ordering::SeqCst),
                 owns_runtime,
                 task_controller: TaskController::default(),
+                #[cfg(feature = "unstable")]
+                received_queries: Mutex::new(HashMap::new()),
             }));
 
             runtime.new_handler(Arc::new(admin::Handler::new(session.downgrade())));
@@ -823,15 +849,7 @@ impl Session {
     /// # }
     /// ```
     pub fn new_timestamp(&self) -> Timestamp {
-        match self.0.runtime.hlc() {
-            Some(hlc) => hlc.new_timestamp(),
-            None => {
-                // Called in the case that the runtime is not initialized with an hlc
-                // UNIX_EPOCH is Returns a Timespec::zero(), Unwrap Should be permissable here
-                let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().into();
-                Timestamp::new(now, self.0.zid().into())
-            }
-        }
+        self.0.new_timestamp()
     }
 }
 
@@ -887,6 +905,10 @@ impl Session {
             session: self,
             key_expr: TryIntoKeyExpr::try_into(key_expr).map_err(Into::into),
             origin: Locality::default(),
+            #[cfg(feature = "unstable")]
+            batch_callback: None,
+            #[cfg(feature = "unstable")]
+            deadline: None,
             handler: DefaultHandler::default(),
         }
     }
@@ -930,6 +952,10 @@ impl Session {
             key_expr: key_expr.try_into().map_err(Into::into),
             complete: false,
             origin: Locality::default(),
+            #[cfg(feature = "unstable")]
+            max_concurrent_queries: None,
+            #[cfg(feature = "unstable")]
+            overload_policy: OverloadPolicy::default(),
             handler: DefaultHandler::default(),
         }
     }
@@ -969,6 +995,10 @@ impl Session {
             is_express: false,
             #[cfg(feature = "unstable")]
             reliability: Reliability::DEFAULT,
+            #[cfg(feature = "unstable")]
+            lifespan: None,
+            #[cfg(feature = "unstable")]
+            deadline: None,
             destination: Locality::default(),
         }
     }
@@ -1169,6 +1199,30 @@ impl Session {
             source_info: SourceInfo::empty(),
         }
     }
+
+    /// Publish several puts and deletes on different key expressions atomically.
+    ///
+    /// The samples of the batch are sent in a single message and share the same timestamp.
+    /// See [`BatchBuilder`](crate::pubsub::BatchBuilder) for how they are delivered to subscribers.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+    /// session
+    ///     .batch()
+    ///     .put("robot/pose", "1.0,2.0")
+    ///     .put("robot/velocity", "0.5")
+    ///     .await
+    ///     .unwrap();
+    /// # }
+    /// ```
+    #[zenoh_macros::unstable]
+    pub fn batch(&self) -> BatchBuilder<'_> {
+        BatchBuilder::new(self)
+    }
     /// Query data from the matching queryables in the system.
     ///
     /// Unless explicitly requested via [`accept_replies`](crate::session::SessionGetBuilder::accept_replies), replies are guaranteed to have
@@ -1217,6 +1271,8 @@ impl Session {
             handler: DefaultHandler::default(),
             #[cfg(feature = "unstable")]
             source_info: SourceInfo::empty(),
+            #[cfg(feature = "unstable")]
+            cancellation_token: None,
         }
     }
 }
@@ -1226,6 +1282,7 @@ impl Session {
     pub(super) fn new(
         config: Config,
         #[cfg(feature = "shared-memory")] shm_clients: Option<Arc<ShmClientStorage>>,
+        #[cfg(feature = "unstable")] interceptor_factories: Vec<InterceptorFactory>,
     ) -> impl Resolve<ZResult<Session>> {
         ResolveFuture::new(async move {
             tracing::debug!("Config: {:?}", &config);
@@ -1237,6 +1294,10 @@ impl Session {
             {
                 runtime = runtime.shm_clients(shm_clients);
             }
+            #[cfg(feature = "unstable")]
+            {
+                runtime = runtime.interceptor_factories(interceptor_factories);
+            }
             let mut runtime = runtime.build().await?;
 
             let session = Self::init(
@@ -1257,6 +1318,18 @@ impl SessionInner {
         self.runtime.zid()
     }
 
+    pub(crate) fn new_timestamp(&self) -> Timestamp {
+        match self.runtime.hlc() {
+            Some(hlc) => hlc.new_timestamp(),
+            None => {
+                // Called in the case that the runtime is not initialized with an hlc
+                // UNIX_EPOCH is Returns a Timespec::zero(), Unwrap Should be permissable here
+                let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().into();
+                Timestamp::new(now, self.zid().into())
+            }
+        }
+    }
+
     pub(crate) fn declare_prefix<'a>(
         &'a self,
         prefix: &'a str,
@@ -1452,7 +1525,9 @@ impl SessionInner {
                     primitives.send_interest(Interest {
                         id: querier_state.remote_id,
                         mode: InterestMode::Final,
-                        options: InterestOptions::empty(),
+                        // Note: InterestMode::Final options are undefined in the current protocol specification,
+                        //       they are initialized here for internal use by local egress interceptors.
+                        options: InterestOptions::QUERYABLES,
                         wire_expr: None,
                         ext_qos: declare::ext::QoSType::DEFAULT,
                         ext_tstamp: None,
@@ -1471,11 +1546,19 @@ impl SessionInner {
         key_expr: &KeyExpr,
         origin: Locality,
         callback: Callback<Sample>,
+        #[cfg(feature = "unstable")] batch_callback: Option<Callback<SampleBatch>>,
     ) -> ZResult<Arc<SubscriberState>> {
         let mut state = zwrite!(self.state);
         tracing::trace!("declare_subscriber({:?})", key_expr);
         let id = self.runtime.next_id();
-        let (sub_state, declared_sub) = state.register_subscriber(id, key_expr, origin, callback);
+        let (sub_state, declared_sub) = state.register_subscriber(
+            id,
+            key_expr,
+            origin,
+            callback,
+            #[cfg(feature = "unstable")]
+            batch_callback,
+        );
         if let Some(key_expr) = declared_sub {
             let primitives = state.primitives()?;
             drop(state);
@@ -1638,6 +1721,7 @@ impl SessionInner {
         complete: bool,
         origin: Locality,
         callback: Callback<Query>,
+        admission: Option<Arc<QueryableAdmission>>,
     ) -> ZResult<Arc<QueryableState>> {
         let wire_expr = key_expr.to_wire(self);
         let mut state = zwrite!(self.state);
@@ -1649,6 +1733,7 @@ impl SessionInner {
             complete,
             origin,
             callback,
+            admission,
         });
 
         state.queryables.insert(id, qable_state.clone());
@@ -1688,6 +1773,54 @@ impl SessionInner {
         Ok(qable_state)
     }
 
+    /// Redeclares the queryable `qid` with the given completeness, only if it was declared complete.
+    pub(crate) fn redeclare_queryable(&self, qid: Id, complete: bool) -> ZResult<()> {
+        let state = zread!(self.state);
+        let Some(qable_state) = state.queryables.get(&qid) else {
+            return Ok(());
+        };
+        if !qable_state.complete || qable_state.origin == Locality::SessionLocal {
+            return Ok(());
+        }
+        trace!(
+            "redeclare_queryable({:?}, complete: {})",
+            qable_state,
+            complete
+        );
+        let wire_expr = qable_state.key_expr.clone();
+        let primitives = state.primitives()?;
+        drop(state);
+        primitives.send_declare(Declare {
+            interest_id: None,
+            ext_qos: declare::ext::QoSType::DECLARE,
+            ext_tstamp: None,
+            ext_nodeid: declare::ext::NodeIdType::DEFAULT,
+            body: DeclareBody::DeclareQueryable(DeclareQueryable {
+                id: qid,
+                wire_expr: wire_expr.clone(),
+                ext_info: QueryableInfoType {
+                    complete,
+                    distance: 0,
+                },
+            }),
+        });
+        // The queryable may have been closed concurrently, its undeclaration being possibly sent
+        // before this declaration, which is then undeclared again
+        if !zread!(self.state).queryables.contains_key(&qid) {
+            primitives.send_declare(Declare {
+                interest_id: None,
+                ext_qos: declare::ext::QoSType::DECLARE,
+                ext_tstamp: None,
+                ext_nodeid: declare::ext::NodeIdType::DEFAULT,
+                body: DeclareBody::UndeclareQueryable(UndeclareQueryable {
+                    id: qid,
+                    ext_wire_expr: WireExprType { wire_expr },
+                }),
+            });
+        }
+        Ok(())
+    }
+
     pub(crate) fn close_queryable(self: &Arc<Self>, qid: Id) -> ZResult<()> {
         let mut state = zwrite!(self.state);
         let Ok(primitives) = state.primitives() else {
@@ -1762,6 +1895,8 @@ impl SessionInner {
             key_expr: key_expr.clone().into_owned(),
             origin,
             callback: callback.clone(),
+            #[cfg(feature = "unstable")]
+            batch_callback: None,
         };
 
         let sub_state = Arc::new(sub_state);
@@ -1821,6 +1956,8 @@ impl SessionInner {
                             reliability: Reliability::Reliable,
                             #[cfg(feature = "unstable")]
                             source_info: SourceInfo::empty(),
+                            #[cfg(feature = "unstable")]
+                            lifespan: None,
                             attachment: None,
                         });
                     }
@@ -2142,13 +2279,20 @@ impl SessionInner {
         is_express: bool,
         destination: Locality,
         #[cfg(feature = "unstable")] reliability: Reliability,
+        #[cfg(feature = "unstable")] lifespan: Option<Duration>,
         timestamp: Option<uhlc::Timestamp>,
         #[cfg(feature = "unstable")] source_info: SourceInfo,
         attachment: Option<ZBytes>,
     ) -> ZResult<()> {
         trace!("write({:?}, [...])", key_expr);
         let primitives = zread!(self.state).primitives()?;
-        let timestamp = timestamp.or_else(|| self.runtime.new_timestamp());
+        #[cfg(not(feature = "unstable"))]
+        let lifespan = None;
+        let timestamp = match timestamp.or_else(|| self.runtime.new_timestamp()) {
+            // The lifespan of the data is counted from its timestamp
+            None if lifespan.is_some() => Some(self.new_timestamp()),
+            timestamp => timestamp,
+        };
         let wire_expr = key_expr.to_wire(self);
         if destination != Locality::SessionLocal {
             primitives.send_push_lazy(
@@ -2156,6 +2300,7 @@ impl SessionInner {
                 push::ext::QoSType::new(priority.into(), congestion_control, is_express),
                 None,
                 push::ext::NodeIdType::DEFAULT,
+                lifespan,
                 || match kind {
                     SampleKind::Put => PushBody::Put(Put {
                         timestamp,
@@ -2186,7 +2331,8 @@ impl SessionInner {
                 Reliability::DEFAULT,
             );
         }
-        if destination != Locality::Remote {
+        // Data published with an old timestamp may already be stale
+        if destination != Locality::Remote && !is_expired(timestamp.as_ref(), lifespan) {
             let data_info = DataInfo {
                 kind,
                 encoding: Some(encoding),
@@ -2204,6 +2350,7 @@ impl SessionInner {
                     congestion_control,
                     is_express,
                 )),
+                lifespan,
             };
 
             self.execute_subscriber_callbacks(
@@ -2220,6 +2367,184 @@ impl SessionInner {
         Ok(())
     }
 
+    #[cfg(feature = "unstable")]
+    pub(crate) fn resolve_batch(
+        &self,
+        samples: Vec<Sample>,
+        timestamp: Option<uhlc::Timestamp>,
+        qos: QoS,
+        destination: Locality,
+        reliability: Reliability,
+        lifespan: Option<Duration>,
+    ) -> ZResult<()> {
+        trace!("batch({} samples)", samples.len());
+        if samples.is_empty() {
+            return Ok(());
+        }
+        let primitives = zread!(self.state).primitives()?;
+        let timestamp = match timestamp.or_else(|| self.runtime.new_timestamp()) {
+            // The lifespan of the data is counted from its timestamp
+            None if lifespan.is_some() => Some(self.new_timestamp()),
+            timestamp => timestamp,
+        };
+        let batch = Batch {
+            timestamp,
+            ext_sinfo: None,
+            ext_unknown: vec![],
+            entries: samples
+                .into_iter()
+                .map(|sample| BatchEntry {
+                    key_expr: sample.key_expr.as_str().to_owned(),
+                    payload: match sample.kind {
+                        SampleKind::Put => PushBody::Put(Put {
+                            timestamp: None,
+                            encoding: sample.encoding.into(),
+                            ext_sinfo: None,
+                            #[cfg(feature = "shared-memory")]
+                            ext_shm: None,
+                            ext_attachment: sample.attachment.map(|a| a.into()),
+                            ext_unknown: vec![],
+                            payload: sample.payload.into(),
+                        }),
+                        SampleKind::Delete => PushBody::Del(Del {
+                            timestamp: None,
+                            ext_sinfo: None,
+                            ext_attachment: sample.attachment.map(|a| a.into()),
+                            ext_unknown: vec![],
+                        }),
+                    },
+                })
+                .collect(),
+        };
+        // The batch is routed as a unit on the common prefix of its samples
+        let key_expr = KeyExpr::from(batch_key_expr(
+            batch.entries.iter().map(|entry| entry.key_expr.as_str()),
+        ));
+        if destination != Locality::SessionLocal {
+            primitives.send_push_lazy(
+                key_expr.to_wire(self).to_owned(),
+                qos.into(),
+                None,
+                push::ext::NodeIdType::DEFAULT,
+                lifespan,
+                || PushBody::Batch(batch.clone()),
+                reliability,
+            );
+        }
+        // Data published with an old timestamp may already be stale
+        if destination != Locality::Remote && !is_expired(timestamp.as_ref(), lifespan) {
+            self.execute_batch_callbacks(true, batch, qos, reliability, lifespan);
+        }
+        Ok(())
+    }
+
+    /// Delivers the samples of a batch to the matching subscribers: as a unit to the subscribers
+    /// with a batch callback matching all of them, one by one to the others.
+    pub(crate) fn execute_batch_callbacks(
+        &self,
+        local: bool,
+        batch: Batch,
+        qos: QoS,
+        #[cfg(feature = "unstable")] reliability: Reliability,
+        lifespan: Option<Duration>,
+    ) {
+        enum Delivery {
+            #[cfg(feature = "unstable")]
+            Batch(Callback<SampleBatch>),
+            Samples(Callback<Sample>, Vec<usize>),
+        }
+
+        let Batch {
+            timestamp,
+            ext_sinfo,
+            entries,
+            ..
+        } = batch;
+        let samples: Vec<Sample> = entries
+            .into_iter()
+            .filter_map(|BatchEntry { key_expr, payload }| {
+                let key_expr = match KeyExpr::try_from(key_expr) {
+                    Ok(key_expr) => key_expr,
+                    Err(e) => {
+                        error!("Received batch entry for invalid key_expr: {}", e);
+                        return None;
+                    }
+                };
+                let (kind, encoding, payload, attachment) = match payload {
+                    PushBody::Put(put) => (
+                        SampleKind::Put,
+                        Some(put.encoding.into()),
+                        put.payload,
+                        put.ext_attachment.map(Into::into),
+                    ),
+                    PushBody::Del(del) => (
+                        SampleKind::Delete,
+                        None,
+                        ZBuf::empty(),
+                        del.ext_attachment.map(Into::into),
+                    ),
+                    PushBody::Batch(_) => return None,
+                };
+                let info = DataInfo {
+                    kind,
+                    encoding,
+                    timestamp,
+                    qos,
+                    source_id: ext_sinfo.as_ref().map(|i| i.id.into()),
+                    source_sn: ext_sinfo.as_ref().map(|i| i.sn),
+                    lifespan,
+                };
+                Some(info.into_sample(
+                    key_expr,
+                    payload,
+                    #[cfg(feature = "unstable")]
+                    reliability,
+                    attachment,
+                ))
+            })
+            .collect();
+
+        let mut deliveries = Vec::new();
+        let state = zread!(self.state);
+        if state.primitives.is_none() {
+            return; // Session closing or closed
+        }
+        for sub in state.subscribers(SubscriberKind::Subscriber).values() {
+            if !(sub.origin == Locality::Any || (local == (sub.origin == Locality::SessionLocal))) {
+                continue;
+            }
+            let matching: Vec<usize> = (0..samples.len())
+                .filter(|i| samples[*i].key_expr.intersects(&sub.key_expr))
+                .collect();
+            if matching.is_empty() {
+                continue;
+            }
+            #[cfg(feature = "unstable")]
+            if let Some(batch_callback) = &sub.batch_callback {
+                if matching.len() == samples.len() {
+                    deliveries.push(Delivery::Batch(batch_callback.clone()));
+                    continue;
+                }
+            }
+            deliveries.push(Delivery::Samples(sub.callback.clone(), matching));
+        }
+        drop(state);
+        for delivery in deliveries {
+            match delivery {
+                #[cfg(feature = "unstable")]
+                Delivery::Batch(callback) => callback.call(SampleBatch {
+                    timestamp,
+                    samples: samples.clone(),
+                }),
+                Delivery::Samples(callback, matching) => {
+                    for i in matching {
+                        callback.call(samples[i].clone());
+                    }
+                }
+            }
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn query(
         self: &Arc<Self>,
@@ -2233,6 +2558,9 @@ impl SessionInner {
         value: Option<(ZBytes, Encoding)>,
         attachment: Option<ZBytes>,
         #[cfg(feature = "unstable")] source: SourceInfo,
+        #[cfg(feature = "unstable")] cancellation_token: Option<
+            crate::api::cancellation::CancellationToken,
+        >,
         callback: Callback<Reply>,
     ) -> ZResult<()> {
         tracing::trace!(
@@ -2265,6 +2593,11 @@ impl SessionInner {
                             if let Some(query) = state.queries.remove(&qid) {
                                 std::mem::drop(state);
                                 tracing::debug!("Timeout on query {}! Send error and close.", qid);
+                                // Streaming queryables would otherwise wait for credits forever
+                                #[cfg(feature = "unstable")]
+                                if query.reply_window.is_some() {
+                                    session.send_query_cancel(query.destination, qid);
+                                }
                                 if query.reception_mode == ConsolidationMode::Latest {
                                     for (_, reply) in query.replies.unwrap().into_iter() {
                                         query.callback.call(reply);
@@ -2292,6 +2625,12 @@ impl SessionInner {
                 parameters: parameters.clone().into_owned(),
                 reception_mode: consolidation,
                 replies: (consolidation != ConsolidationMode::None).then(HashMap::new),
+                #[cfg(feature = "unstable")]
+                destination,
+                #[cfg(feature = "unstable")]
+                reply_window: parameters.reply_credits().map(ReplyWindow::new),
+                #[cfg(feature = "unstable")]
+                cancellation: None,
                 callback,
             },
         );
@@ -2310,6 +2649,7 @@ impl SessionInner {
                 ext_target: target,
                 ext_budget: None,
                 ext_timeout: Some(timeout),
+                ext_hop_limit: None,
                 payload: RequestBody::Query(zenoh_protocol::zenoh::Query {
                     consolidation,
                     parameters: parameters.to_string(),
@@ -2345,9 +2685,115 @@ impl SessionInner {
                 attachment,
             );
         }
+        #[cfg(feature = "unstable")]
+        if let Some(cancellation_token) = cancellation_token {
+            if let Some(registration) = cancellation_token.register(self, qid) {
+                // Dropped with the state of the query, or right away if it already finished
+                if let Some(query) = zwrite!(self.state).queries.get_mut(&qid) {
+                    query.cancellation = Some(registration);
+                }
+            }
+        }
         Ok(())
     }
 
+    /// Cancels the query `qid` issued by the session: its reply handler is closed and the
+    /// queryables handling it are notified.
+    #[cfg(feature = "unstable")]
+    pub(crate) fn cancel_query(self: &Arc<Self>, qid: RequestId) {
+        let mut state = zwrite!(self.state);
+        let Some(query) = state.queries.remove(&qid) else {
+            return;
+        };
+        drop(state);
+        trace!("Cancel query {}", qid);
+        self.send_query_cancel(query.destination, qid);
+    }
+
+    /// Notifies the queryables handling the query `qid` issued by the session that it is over,
+    /// releasing the ones waiting for response credits.
+    #[cfg(feature = "unstable")]
+    fn send_query_cancel(&self, destination: Locality, qid: RequestId) {
+        if destination != Locality::SessionLocal {
+            let primitives = zread!(self.state).primitives();
+            if let Ok(primitives) = primitives {
+                primitives.send_oam(Oam {
+                    id: OAM_QUERY_CANCEL,
+                    body: ZExtBody::Z64(qid as u64),
+                    ext_qos: zenoh_protocol::network::oam::ext::QoSType::OAM,
+                    ext_tstamp: None,
+                });
+            }
+        }
+        if destination != Locality::Remote {
+            self.cancel_received_query(true, qid);
+        }
+    }
+
+    /// Notifies the queryables of the session that the query `qid` they handle was cancelled.
+    #[cfg(feature = "unstable")]
+    fn cancel_received_query(&self, local: bool, qid: RequestId) {
+        let control = zlock!(self.received_queries).remove(&(local, qid));
+        match control {
+            Some(control) => {
+                trace!("Query {} cancelled", qid);
+                control.token.cancel();
+            }
+            None => trace!("Received cancellation for unknown Query: {}", qid),
+        }
+    }
+
+    /// Grants the queryables of the session `credit` more responses to the query `qid`.
+    #[cfg(feature = "unstable")]
+    fn grant_received_query_credit(&self, local: bool, qid: RequestId, credit: u32) {
+        let credits = zlock!(self.received_queries)
+            .get(&(local, qid))
+            .and_then(|control| control.credits.clone());
+        match credits {
+            Some(credits) => credits.add_permits(credit as usize),
+            None => trace!("Received credit for unknown Query: {}", qid),
+        }
+    }
+
+    /// Grants the queryables of the session `responder` handling the query `qid` issued by the
+    /// session `credit` more responses.
+    #[cfg(feature = "unstable")]
+    fn grant_query_credit(
+        &self,
+        qid: RequestId,
+        destination: Locality,
+        responder: zenoh_protocol::core::ZenohIdProto,
+        credit: u32,
+    ) {
+        trace!(
+            "Grant {} credits to {} for query {}",
+            credit,
+            responder,
+            qid
+        );
+        if responder == self.zid().into() {
+            if destination != Locality::Remote {
+                self.grant_received_query_credit(true, qid, credit);
+            }
+            return;
+        }
+        if destination == Locality::SessionLocal {
+            return;
+        }
+        let primitives = zread!(self.state).primitives.clone();
+        if let Some(primitives) = primitives {
+            let credit = QueryCredit {
+                rid: qid,
+                credit,
+                responder,
+            };
+            match credit.to_oam() {
+                Ok(oam) => primitives.send_oam(oam),
+                Err(_) => error!("Failed to encode credit for query {}", qid),
+            }
+        }
+    }
+
     pub(crate) fn liveliness_query(
         self: &Arc<Self>,
         key_expr: &KeyExpr<'_>,
@@ -2445,8 +2891,8 @@ impl SessionInner {
                                     }
                                 }
                         )
-                        .map(|(id, qable)| (*id, qable.callback.clone()))
-                        .collect::<Vec<(u32, Callback<Query>)>>();
+                        .map(|(id, qable)| (*id, qable.callback.clone(), qable.admission.clone()))
+                        .collect::<Vec<_>>();
                     (primitives, key_expr.into_owned(), queryables)
                 }
                 Err(err) => {
@@ -2458,6 +2904,23 @@ impl SessionInner {
 
         let zid = self.zid();
 
+        // Queries are only cancelled or streamed by the unstable queriers
+        #[cfg(feature = "unstable")]
+        let control = {
+            let credits = Parameters::from(parameters)
+                .reply_credits()
+                .map(|_| Arc::new(Semaphore::new(INITIAL_REPLY_CREDITS as usize)));
+            let control = QueryControl {
+                token: CancellationToken::new(),
+                credits,
+                session: Arc::downgrade(self),
+                local,
+            };
+            zlock!(self.received_queries).insert((local, qid), control.clone());
+            Some(control)
+        };
+        #[cfg(not(feature = "unstable"))]
+        let control = None;
         let query_inner = Arc::new(QueryInner {
             key_expr,
             parameters: parameters.to_owned().into(),
@@ -2468,16 +2931,38 @@ impl SessionInner {
             } else {
                 primitives
             },
+            control,
         });
         let mut query = Query {
             inner: query_inner,
             eid: 0,
             value: body.map(|b| (b.payload.into(), b.encoding.into())),
             attachment,
+            permit: None,
         };
-        for (eid, cb) in queryables {
+        for (eid, cb, admission) in queryables {
             query.eid = eid;
-            cb.call(query.clone());
+            match admission {
+                Some(admission) if admission.admit() => {
+                    let session = WeakSession::new(self);
+                    admission.update_redirection(&session, eid);
+                    let mut query = query.clone();
+                    query.permit = Some(Arc::new(QueryPermit {
+                        admission,
+                        session,
+                        id: eid,
+                    }));
+                    cb.call(query);
+                }
+                Some(_) => {
+                    tracing::debug!("Queryable {} overloaded, query rejected", eid);
+                    let err = ReplyError::overloaded();
+                    if let Err(e) = query.reply_err(err.payload).encoding(err.encoding).wait() {
+                        error!("Unable to reject query: {}", e);
+                    }
+                }
+                None => cb.call(query.clone()),
+            }
         }
     }
 }
@@ -2647,6 +3132,8 @@ impl Primitives for WeakSession {
                                         reliability: Reliability::Reliable,
                                         #[cfg(feature = "unstable")]
                                         source_info: SourceInfo::empty(),
+                                        #[cfg(feature = "unstable")]
+                                        lifespan: None,
                                         attachment: None,
                                     }),
                                     #[cfg(feature = "unstable")]
@@ -2749,6 +3236,10 @@ impl Primitives for WeakSession {
 
     fn send_push(&self, msg: Push, _reliability: Reliability) {
         trace!("recv Push {:?}", msg);
+        if is_expired(msg.payload.timestamp(), msg.ext_lifespan) {
+            trace!("Drop expired Push {:?}", msg.wire_expr);
+            return;
+        }
         match msg.payload {
             PushBody::Put(m) => {
                 let info = DataInfo {
@@ -2758,6 +3249,7 @@ impl Primitives for WeakSession {
                     qos: QoS::from(msg.ext_qos),
                     source_id: m.ext_sinfo.as_ref().map(|i| i.id.into()),
                     source_sn: m.ext_sinfo.as_ref().map(|i| i.sn),
+                    lifespan: msg.ext_lifespan,
                 };
                 self.execute_subscriber_callbacks(
                     false,
@@ -2778,6 +3270,7 @@ impl Primitives for WeakSession {
                     qos: QoS::from(msg.ext_qos),
                     source_id: m.ext_sinfo.as_ref().map(|i| i.id.into()),
                     source_sn: m.ext_sinfo.as_ref().map(|i| i.sn),
+                    lifespan: msg.ext_lifespan,
                 };
                 self.execute_subscriber_callbacks(
                     false,
@@ -2790,6 +3283,14 @@ impl Primitives for WeakSession {
                     m.ext_attachment.map(Into::into),
                 )
             }
+            PushBody::Batch(m) => self.execute_batch_callbacks(
+                false,
+                m,
+                QoS::from(msg.ext_qos),
+                #[cfg(feature = "unstable")]
+                _reliability,
+                msg.ext_lifespan,
+            ),
         }
     }
 
@@ -2820,6 +3321,10 @@ impl Primitives for WeakSession {
                 match state.queries.get_mut(&msg.rid) {
                     Some(query) => {
                         let callback = query.callback.clone();
+                        #[cfg(feature = "unstable")]
+                        let responder = msg.ext_respid.as_ref().map(|r| r.zid);
+                        #[cfg(feature = "unstable")]
+                        let grant = query.consume_reply_credit(responder);
                         std::mem::drop(state);
                         let new_reply = Reply {
                             result: Err(ReplyError {
@@ -2830,6 +3335,10 @@ impl Primitives for WeakSession {
                             replier_id: e.ext_sinfo.map(|info| info.id.zid),
                         };
                         callback.call(new_reply);
+                        #[cfg(feature = "unstable")]
+                        if let (Some((credit, destination)), Some(responder)) = (grant, responder) {
+                            self.grant_query_credit(msg.rid, destination, responder, credit);
+                        }
                     }
                     None => {
                         tracing::warn!("Received ReplyData for unknown Query: {}", msg.rid);
@@ -2850,6 +3359,10 @@ impl Primitives for WeakSession {
                 };
                 match state.queries.get_mut(&msg.rid) {
                     Some(query) => {
+                        #[cfg(feature = "unstable")]
+                        let responder = msg.ext_respid.as_ref().map(|r| r.zid);
+                        #[cfg(feature = "unstable")]
+                        let grant = query.consume_reply_credit(responder);
                         let c =
                             zcondfeat!("unstable", !query.parameters.reply_key_expr_any(), true);
                         if c && !query.key_expr.intersects(&key_expr) {
@@ -2859,6 +3372,13 @@ impl Primitives for WeakSession {
                                 msg.ext_respid,
                                 query.selector()
                             );
+                            std::mem::drop(state);
+                            #[cfg(feature = "unstable")]
+                            if let (Some((credit, destination)), Some(responder)) =
+                                (grant, responder)
+                            {
+                                self.grant_query_credit(msg.rid, destination, responder, credit);
+                            }
                             return;
                         }
 
@@ -2888,6 +3408,7 @@ impl Primitives for WeakSession {
                                     qos: QoS::from(msg.ext_qos),
                                     source_id: ext_sinfo.as_ref().map(|i| i.id.into()),
                                     source_sn: ext_sinfo.as_ref().map(|i| i.sn),
+                                    lifespan: None,
                                 },
                                 attachment: _attachment.map(Into::into),
                             },
@@ -2905,9 +3426,17 @@ impl Primitives for WeakSession {
                                     qos: QoS::from(msg.ext_qos),
                                     source_id: ext_sinfo.as_ref().map(|i| i.id.into()),
                                     source_sn: ext_sinfo.as_ref().map(|i| i.sn),
+                                    lifespan: None,
                                 },
                                 attachment: _attachment.map(Into::into),
                             },
+                            ReplyBody::Batch(_) => {
+                                error!(
+                                    "Received batch as Reply for Query {}: dropping Reply.",
+                                    msg.rid
+                                );
+                                return;
+                            }
                         };
                         let sample = info.into_sample(
                             key_expr.into_owned(),
@@ -3004,6 +3533,10 @@ impl Primitives for WeakSession {
                         if let Some((callback, new_reply)) = callback {
                             callback.call(new_reply);
                         }
+                        #[cfg(feature = "unstable")]
+                        if let (Some((credit, destination)), Some(responder)) = (grant, responder) {
+                            self.grant_query_credit(msg.rid, destination, responder, credit);
+                        }
                     }
                     None => {
                         tracing::warn!("Received ReplyData for unknown Query: {}", msg.rid);
@@ -3039,6 +3572,25 @@ impl Primitives for WeakSession {
         }
     }
 
+    fn send_oam(&self, msg: Oam) {
+        trace!("recv OAM {:?}", msg);
+        match (msg.id, &msg.body) {
+            #[cfg(feature = "unstable")]
+            (OAM_QUERY_CANCEL, ZExtBody::Z64(rid)) => {
+                self.cancel_received_query(false, *rid as RequestId)
+            }
+            #[cfg(feature = "unstable")]
+            (OAM_QUERY_CREDIT, _) => match QueryCredit::from_oam(&msg) {
+                Some(credit) if credit.responder == self.zid().into() => {
+                    self.grant_received_query_credit(false, credit.rid, credit.credit)
+                }
+                Some(_) => {}
+                None => error!("Failed to decode query credit"),
+            },
+            (id, _) => trace!("Ignoring OAM {}", id),
+        }
+    }
+
     fn send_close(&self) {
         trace!("recv Close");
     }
@@ -3075,6 +3627,11 @@ impl crate::net::primitives::EPrimitives for WeakSession {
         (self as &dyn Primitives).send_response_final(msg)
     }
 
+    #[inline]
+    fn send_oam(&self, msg: Oam) {
+        (self as &dyn Primitives).send_oam(msg)
+    }
+
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
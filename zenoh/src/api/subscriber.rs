@@ -21,7 +21,10 @@ use tracing::error;
 use zenoh_core::{Resolvable, Wait};
 use zenoh_result::ZResult;
 #[cfg(feature = "unstable")]
-use {zenoh_config::wrappers::EntityGlobalId, zenoh_protocol::core::EntityGlobalIdProto};
+use {
+    crate::api::sample::SampleBatch, zenoh_config::wrappers::EntityGlobalId,
+    zenoh_protocol::core::EntityGlobalIdProto,
+};
 
 use crate::api::{
     handlers::Callback,
@@ -37,6 +40,9 @@ pub(crate) struct SubscriberState {
     pub(crate) key_expr: KeyExpr<'static>,
     pub(crate) origin: Locality,
     pub(crate) callback: Callback<Sample>,
+    /// Called with the batches whose samples all match the subscriber key expression
+    #[cfg(feature = "unstable")]
+    pub(crate) batch_callback: Option<Callback<SampleBatch>>,
 }
 
 impl fmt::Debug for SubscriberState {
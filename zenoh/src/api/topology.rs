@@ -0,0 +1,449 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, RwLock,
+    },
+};
+#[cfg(feature = "unstable")]
+use std::{
+    fmt,
+    future::{IntoFuture, Ready},
+    sync::atomic::AtomicU32,
+};
+
+use zenoh_config::wrappers::ZenohId;
+use zenoh_core::{zlock, zread};
+#[cfg(feature = "unstable")]
+use zenoh_core::{zwrite, Resolvable, Wait};
+use zenoh_link::Link;
+use zenoh_protocol::{
+    core::{Locator, WhatAmI},
+    network::NetworkMessage,
+};
+use zenoh_result::ZResult;
+use zenoh_transport::{
+    multicast::TransportMulticast, unicast::TransportUnicast, TransportEventHandler,
+    TransportMulticastEventHandler, TransportPeer, TransportPeerEventHandler,
+};
+
+use super::Id;
+#[cfg(feature = "unstable")]
+use super::{handlers::Callback, session::UndeclarableSealed};
+
+/// A change of the network topology as seen from a zenoh [`Session`](crate::Session),
+/// received by a [`TopologyListener`].
+///
+/// The transport events are notified for the transports of the session runtime, while the
+/// link-state events are only notified by routers, for the nodes of their routers' link-state graph.
+///
+/// # Examples
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() {
+/// use zenoh::session::TopologyEvent;
+///
+/// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+/// let listener = session.info().topology_listener().await.unwrap();
+/// while let Ok(event) = listener.recv_async().await {
+///     if let TopologyEvent::NodeRemoved { zid } = event {
+///         println!("Router {zid} left the network");
+///     }
+/// }
+/// # }
+/// ```
+#[zenoh_macros::unstable_doc]
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TopologyEvent {
+    /// A transport was opened with a remote node, over links to the given locators.
+    TransportOpened {
+        zid: ZenohId,
+        whatami: WhatAmI,
+        locators: Vec<Locator>,
+    },
+    /// The transport with a remote node was closed.
+    TransportClosed { zid: ZenohId, whatami: WhatAmI },
+    /// A node was added to the link-state graph, its kind being unknown until its own
+    /// link-state is received.
+    NodeAdded {
+        zid: ZenohId,
+        whatami: Option<WhatAmI>,
+    },
+    /// A node was removed from the link-state graph.
+    NodeRemoved { zid: ZenohId },
+    /// The links of a node of the link-state graph changed.
+    LinksChanged { zid: ZenohId, links: Vec<ZenohId> },
+    /// The listener did not keep up with the topology changes and `missed` events were not
+    /// delivered to it, its view of the topology should be resynchronized.
+    Lagged { missed: u64 },
+}
+
+/// Dispatches the topology events of a runtime to the declared listeners.
+///
+/// Each listener has its own queue drained by its own task, so that listeners are called in order,
+/// never from the routing tables or the transports, and a slow listener never delays the others.
+/// The queues are bounded: the events notified while the queue of a listener is full are counted
+/// in a [`TopologyEvent::Lagged`] event delivered after the queued ones.
+pub(crate) struct TopologyNotifier {
+    #[cfg(feature = "unstable")]
+    next_id: AtomicU32,
+    listeners: RwLock<HashMap<Id, Arc<ListenerQueue>>>,
+}
+
+impl TopologyNotifier {
+    const CAPACITY: usize = 1024;
+
+    pub(crate) fn new() -> Arc<Self> {
+        Arc::new(Self {
+            #[cfg(feature = "unstable")]
+            next_id: AtomicU32::new(0),
+            listeners: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// Notifies the event built by `event`, only built if there are listeners.
+    pub(crate) fn notify(&self, event: impl FnOnce() -> TopologyEvent) {
+        let listeners = zread!(self.listeners);
+        if !listeners.is_empty() {
+            let event = event();
+            for queue in listeners.values() {
+                queue.push(event.clone());
+            }
+        }
+    }
+
+    /// Declares a listener, its events being delivered by the returned task.
+    #[cfg(feature = "unstable")]
+    pub(crate) fn declare_listener(
+        &self,
+        callback: Callback<TopologyEvent>,
+    ) -> (Id, impl std::future::Future<Output = ()>) {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let queue = Arc::new(ListenerQueue {
+            callback,
+            events: Mutex::new(VecDeque::new()),
+            ready: tokio::sync::Notify::new(),
+            closed: AtomicBool::new(false),
+        });
+        zwrite!(self.listeners).insert(id, queue.clone());
+        (id, queue.run())
+    }
+
+    #[cfg(feature = "unstable")]
+    pub(crate) fn undeclare_listener(&self, id: Id) -> ZResult<()> {
+        match zwrite!(self.listeners).remove(&id) {
+            Some(queue) => {
+                queue.close();
+                Ok(())
+            }
+            None => zenoh_result::bail!("Unable to find topology listener"),
+        }
+    }
+}
+
+impl Drop for TopologyNotifier {
+    fn drop(&mut self) {
+        for queue in zread!(self.listeners).values() {
+            queue.close();
+        }
+    }
+}
+
+/// The events pending for a listener.
+struct ListenerQueue {
+    #[cfg(feature = "unstable")]
+    callback: Callback<TopologyEvent>,
+    events: Mutex<VecDeque<TopologyEvent>>,
+    ready: tokio::sync::Notify,
+    closed: AtomicBool,
+}
+
+impl ListenerQueue {
+    fn push(&self, event: TopologyEvent) {
+        {
+            let mut events = zlock!(self.events);
+            if events.len() < TopologyNotifier::CAPACITY {
+                events.push_back(event);
+            } else if let Some(TopologyEvent::Lagged { missed }) = events.back_mut() {
+                *missed += 1;
+            } else {
+                events.push_back(TopologyEvent::Lagged { missed: 1 });
+            }
+        }
+        self.ready.notify_one();
+    }
+
+    fn close(&self) {
+        self.closed.store(true, Ordering::Release);
+        self.ready.notify_one();
+    }
+
+    /// Calls the callback on the queued events, until the listener is undeclared.
+    #[cfg(feature = "unstable")]
+    async fn run(self: Arc<Self>) {
+        loop {
+            if self.closed.load(Ordering::Acquire) {
+                break;
+            }
+            let event = zlock!(self.events).pop_front();
+            match event {
+                Some(event) => self.callback.call(event),
+                None => self.ready.notified().await,
+            }
+        }
+    }
+}
+
+/// Notifies the opening and closing of the transports of a runtime.
+pub(crate) struct TopologyHandler {
+    pub(crate) notifier: Arc<TopologyNotifier>,
+}
+
+impl TransportEventHandler for TopologyHandler {
+    fn new_unicast(
+        &self,
+        peer: TransportPeer,
+        _transport: TransportUnicast,
+    ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        self.new_peer(peer)
+    }
+
+    fn new_multicast(
+        &self,
+        _transport: TransportMulticast,
+    ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+        Ok(Arc::new(TopologyHandler {
+            notifier: self.notifier.clone(),
+        }))
+    }
+}
+
+impl TransportMulticastEventHandler for TopologyHandler {
+    fn new_peer(&self, peer: TransportPeer) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        self.notifier.notify(|| TopologyEvent::TransportOpened {
+            zid: peer.zid.into(),
+            whatami: peer.whatami,
+            locators: peer.links.iter().map(|link| link.dst.clone()).collect(),
+        });
+        Ok(Arc::new(TopologyPeerHandler {
+            notifier: self.notifier.clone(),
+            zid: peer.zid.into(),
+            whatami: peer.whatami,
+        }))
+    }
+
+    fn closed(&self) {}
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+pub(crate) struct TopologyPeerHandler {
+    notifier: Arc<TopologyNotifier>,
+    zid: ZenohId,
+    whatami: WhatAmI,
+}
+
+impl TransportPeerEventHandler for TopologyPeerHandler {
+    fn handle_message(&self, _msg: NetworkMessage) -> ZResult<()> {
+        Ok(())
+    }
+
+    fn new_link(&self, _link: Link) {}
+
+    fn del_link(&self, _link: Link) {}
+
+    fn closed(&self) {
+        self.notifier.notify(|| TopologyEvent::TransportClosed {
+            zid: self.zid,
+            whatami: self.whatami,
+        });
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+/// A listener that sends notifications when the network topology seen by a zenoh
+/// [`Session`](crate::Session) changes.
+///
+/// Callback topology listeners will run in background until the session is closed,
+/// or until they are undeclared.
+/// On the other hand, topology listeners with a handler are automatically undeclared when dropped.
+///
+/// # Examples
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() {
+///
+/// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+/// let listener = session.info().topology_listener().await.unwrap();
+/// while let Ok(event) = listener.recv_async().await {
+///     println!("Topology changed: {event:?}");
+/// }
+/// # }
+/// ```
+#[zenoh_macros::unstable]
+pub struct TopologyListener<Handler> {
+    pub(crate) notifier: Arc<TopologyNotifier>,
+    pub(crate) id: Id,
+    pub(crate) undeclare_on_drop: bool,
+    pub(crate) handler: Handler,
+}
+
+#[zenoh_macros::unstable]
+impl<Handler> TopologyListener<Handler> {
+    /// Undeclare the [`TopologyListener`].
+    ///
+    /// # Examples
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    ///
+    /// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+    /// let listener = session.info().topology_listener().await.unwrap();
+    /// listener.undeclare().await.unwrap();
+    /// # }
+    /// ```
+    #[inline]
+    pub fn undeclare(self) -> TopologyListenerUndeclaration<Handler>
+    where
+        Handler: Send,
+    {
+        self.undeclare_inner(())
+    }
+
+    fn undeclare_impl(&mut self) -> ZResult<()> {
+        // set the flag first to avoid double panic if this function panic
+        self.undeclare_on_drop = false;
+        self.notifier.undeclare_listener(self.id)
+    }
+
+    #[zenoh_macros::internal]
+    pub fn set_background(&mut self, background: bool) {
+        self.undeclare_on_drop = !background;
+    }
+}
+
+#[zenoh_macros::unstable]
+impl<Handler> fmt::Debug for TopologyListener<Handler> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TopologyListener")
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+#[cfg(feature = "unstable")]
+impl<Handler> Drop for TopologyListener<Handler> {
+    fn drop(&mut self) {
+        if self.undeclare_on_drop {
+            if let Err(error) = self.undeclare_impl() {
+                tracing::error!(error);
+            }
+        }
+    }
+}
+
+#[zenoh_macros::unstable]
+impl<Handler: Send> UndeclarableSealed<()> for TopologyListener<Handler> {
+    type Undeclaration = TopologyListenerUndeclaration<Handler>;
+
+    fn undeclare_inner(self, _: ()) -> Self::Undeclaration {
+        TopologyListenerUndeclaration(self)
+    }
+}
+
+#[zenoh_macros::unstable]
+impl<Handler> std::ops::Deref for TopologyListener<Handler> {
+    type Target = Handler;
+
+    fn deref(&self) -> &Self::Target {
+        &self.handler
+    }
+}
+#[zenoh_macros::unstable]
+impl<Handler> std::ops::DerefMut for TopologyListener<Handler> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.handler
+    }
+}
+
+#[zenoh_macros::unstable]
+pub struct TopologyListenerUndeclaration<Handler>(TopologyListener<Handler>);
+
+#[zenoh_macros::unstable]
+impl<Handler> Resolvable for TopologyListenerUndeclaration<Handler> {
+    type To = ZResult<()>;
+}
+
+#[zenoh_macros::unstable]
+impl<Handler> Wait for TopologyListenerUndeclaration<Handler> {
+    fn wait(mut self) -> <Self as Resolvable>::To {
+        self.0.undeclare_impl()
+    }
+}
+
+#[zenoh_macros::unstable]
+impl<Handler> IntoFuture for TopologyListenerUndeclaration<Handler> {
+    type Output = <Self as Resolvable>::To;
+    type IntoFuture = Ready<<Self as Resolvable>::To>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        std::future::ready(self.wait())
+    }
+}
+
+#[cfg(all(test, feature = "unstable"))]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use super::{Callback, TopologyEvent, TopologyNotifier};
+
+    #[tokio::test]
+    async fn lagged_listener() {
+        let notifier = TopologyNotifier::new();
+        let events = Arc::new(std::sync::Mutex::new(vec![]));
+        let (id, task) = notifier.declare_listener(Callback::new(Arc::new({
+            let events = events.clone();
+            move |event| events.lock().unwrap().push(event)
+        })));
+
+        // The listener doesn't run yet, the events exceeding its queue are counted
+        let zid = Default::default();
+        for _ in 0..TopologyNotifier::CAPACITY + 5 {
+            notifier.notify(|| TopologyEvent::NodeRemoved { zid });
+        }
+        let task = tokio::spawn(task);
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while events.lock().unwrap().len() <= TopologyNotifier::CAPACITY {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        notifier.undeclare_listener(id).unwrap();
+        task.await.unwrap();
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), TopologyNotifier::CAPACITY + 1);
+        assert_eq!(events.last(), Some(&TopologyEvent::Lagged { missed: 5 }));
+    }
+}
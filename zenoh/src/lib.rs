@@ -206,6 +206,11 @@ pub mod session {
 
     #[zenoh_macros::internal]
     pub use crate::api::builders::session::{init, InitBuilder};
+    #[zenoh_macros::unstable]
+    pub use crate::api::{
+        builders::topology_listener::TopologyListenerBuilder,
+        topology::{TopologyEvent, TopologyListener, TopologyListenerUndeclaration},
+    };
     pub use crate::api::{
         builders::{
             close::CloseBuilder,
@@ -227,6 +232,8 @@ pub mod sample {
     #[zenoh_macros::unstable]
     pub use crate::api::sample::Locality;
     #[zenoh_macros::unstable]
+    pub use crate::api::sample::SampleBatch;
+    #[zenoh_macros::unstable]
     pub use crate::api::sample::{SourceInfo, SourceSn};
     pub use crate::api::{
         builders::sample::{
@@ -263,6 +270,10 @@ pub mod bytes {
 /// declared by a [`Session::declare_subscriber`](crate::Session::declare_subscriber)
 ///
 pub mod pubsub {
+    #[zenoh_macros::unstable]
+    pub use crate::api::builders::batch::BatchBuilder;
+    #[zenoh_macros::unstable]
+    pub use crate::api::deadline::DeadlineMissed;
     pub use crate::api::{
         builders::{
             publisher::{
@@ -293,8 +304,10 @@ pub mod query {
     #[zenoh_macros::unstable]
     pub use crate::api::{
         builders::querier::{QuerierBuilder, QuerierGetBuilder},
+        cancellation::CancellationToken,
         querier::Querier,
         query::ReplyKeyExpr,
+        queryable::OverloadPolicy,
         selector::ZenohParameters,
     };
     pub use crate::api::{
@@ -446,6 +459,31 @@ pub mod time {
     pub use zenoh_protocol::core::{Timestamp, TimestampId, NTP64};
 }
 
+/// Interception of the messages routed through the transports of a session.
+///
+/// Interceptor factories are registered with
+/// [`OpenBuilder::with_interceptor_factory`](crate::session::OpenBuilder::with_interceptor_factory)
+/// and build the [`InterceptorTrait`](crate::interceptor::InterceptorTrait) instances attached to
+/// each transport opened by the session, after the ones configured in its config.
+#[zenoh_macros::unstable]
+pub mod interceptor {
+    pub use zenoh_protocol::network::NetworkMessage;
+    pub use zenoh_transport::{multicast::TransportMulticast, unicast::TransportUnicast};
+
+    pub use crate::net::routing::{
+        interceptor::{
+            ComputeOnMiss, EgressInterceptor, ExplainedMessage, IngressInterceptor, Interceptor,
+            InterceptorFactory, InterceptorFactoryTrait, InterceptorTrait, Resume,
+        },
+        RoutingContext,
+    };
+
+    /// The network and zenoh messages handed to the interceptors.
+    pub mod protocol {
+        pub use zenoh_protocol::{network, zenoh};
+    }
+}
+
 /// Configuration to pass to [`open`] and [`scout`] functions and associated constants.
 ///
 /// The zenoh configurattion is stored in a JSON file. The [`Config`] can be constructed from it using
@@ -116,6 +116,7 @@ where
             whatami,
             locators,
             links,
+            link_weights: None,
         })
     }
 }
@@ -134,6 +135,18 @@ where
         for ls in x.link_states.iter() {
             self.write(&mut *writer, ls)?;
         }
+        if x.link_states.iter().any(|ls| ls.link_weights.is_some()) {
+            for ls in x.link_states.iter() {
+                let weights = ls.link_weights.as_deref().unwrap_or_default();
+                if !weights.is_empty() && weights.len() != ls.links.len() {
+                    return Err(DidntWrite);
+                }
+                codec.write(&mut *writer, weights.len())?;
+                for w in weights.iter() {
+                    codec.write(&mut *writer, *w)?;
+                }
+            }
+        }
 
         Ok(())
     }
@@ -154,6 +167,22 @@ where
             let ls: LinkState = self.read(&mut *reader)?;
             link_states.push(ls);
         }
+        if reader.can_read() {
+            for ls in link_states.iter_mut() {
+                let len: usize = codec.read(&mut *reader)?;
+                let mut weights: Vec<u16> = Vec::with_capacity(len);
+                for _ in 0..len {
+                    let w: u16 = codec.read(&mut *reader)?;
+                    weights.push(w);
+                }
+                if len != 0 {
+                    if len != ls.links.len() {
+                        return Err(DidntRead);
+                    }
+                    ls.link_weights = Some(weights);
+                }
+            }
+        }
 
         Ok(LinkStateList { link_states })
     }
@@ -12,6 +12,7 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 pub(crate) mod linkstate;
+pub(crate) mod query_credit;
 
 #[derive(Clone, Copy)]
 pub struct Zenoh080Routing;
@@ -0,0 +1,57 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use zenoh_buffers::{
+    reader::{DidntRead, Reader},
+    writer::{DidntWrite, Writer},
+};
+use zenoh_codec::{RCodec, WCodec, Zenoh080};
+use zenoh_protocol::{core::ZenohIdProto, network::RequestId};
+
+use super::Zenoh080Routing;
+use crate::net::protocol::query_credit::QueryCredit;
+
+// QueryCredit
+impl<W> WCodec<&QueryCredit, &mut W> for Zenoh080Routing
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: &QueryCredit) -> Self::Output {
+        let codec = Zenoh080::new();
+        codec.write(&mut *writer, x.rid)?;
+        codec.write(&mut *writer, x.credit)?;
+        codec.write(&mut *writer, &x.responder)?;
+        Ok(())
+    }
+}
+
+impl<R> RCodec<QueryCredit, &mut R> for Zenoh080Routing
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<QueryCredit, Self::Error> {
+        let codec = Zenoh080::new();
+        let rid: RequestId = codec.read(&mut *reader)?;
+        let credit: u32 = codec.read(&mut *reader)?;
+        let responder: ZenohIdProto = codec.read(&mut *reader)?;
+        Ok(QueryCredit {
+            rid,
+            credit,
+            responder,
+        })
+    }
+}
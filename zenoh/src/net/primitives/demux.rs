@@ -14,7 +14,10 @@
 use std::{any::Any, sync::Arc};
 
 use zenoh_link::Link;
-use zenoh_protocol::network::{NetworkBody, NetworkMessage};
+use zenoh_protocol::network::{
+    oam::id::{OAM_QUERY_CANCEL, OAM_QUERY_CREDIT},
+    NetworkBody, NetworkMessage,
+};
 use zenoh_result::ZResult;
 use zenoh_transport::{unicast::TransportUnicast, TransportPeerEventHandler};
 
@@ -72,6 +75,9 @@ impl TransportPeerEventHandler for DeMux {
             NetworkBody::Request(m) => self.face.send_request(m),
             NetworkBody::Response(m) => self.face.send_response(m),
             NetworkBody::ResponseFinal(m) => self.face.send_response_final(m),
+            NetworkBody::OAM(m) if m.id == OAM_QUERY_CANCEL || m.id == OAM_QUERY_CREDIT => {
+                self.face.send_oam(m)
+            }
             NetworkBody::OAM(m) => {
                 if let Some(transport) = self.transport.as_ref() {
                     let mut declares = vec![];
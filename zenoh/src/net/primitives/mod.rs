@@ -20,7 +20,7 @@ pub use demux::*;
 pub use mux::*;
 use zenoh_protocol::{
     core::Reliability,
-    network::{interest::Interest, Declare, Push, Request, Response, ResponseFinal},
+    network::{interest::Interest, Declare, Oam, Push, Request, Response, ResponseFinal},
 };
 
 use super::routing::RoutingContext;
@@ -38,6 +38,8 @@ pub trait Primitives: Send + Sync {
 
     fn send_response_final(&self, msg: ResponseFinal);
 
+    fn send_oam(&self, msg: Oam);
+
     fn send_close(&self);
 }
 
@@ -55,6 +57,8 @@ pub(crate) trait EPrimitives: Send + Sync {
     fn send_response(&self, msg: Response);
 
     fn send_response_final(&self, msg: ResponseFinal);
+
+    fn send_oam(&self, msg: Oam);
 }
 
 #[derive(Default)]
@@ -73,6 +77,8 @@ impl Primitives for DummyPrimitives {
 
     fn send_response_final(&self, _msg: ResponseFinal) {}
 
+    fn send_oam(&self, _msg: Oam) {}
+
     fn send_close(&self) {}
 }
 
@@ -89,6 +95,8 @@ impl EPrimitives for DummyPrimitives {
 
     fn send_response_final(&self, _msg: ResponseFinal) {}
 
+    fn send_oam(&self, _msg: Oam) {}
+
     fn as_any(&self) -> &dyn Any {
         self
     }
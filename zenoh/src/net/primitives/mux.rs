@@ -11,38 +11,61 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use std::sync::OnceLock;
+use std::sync::{Arc, OnceLock};
 
 use zenoh_protocol::{
     core::Reliability,
     network::{
-        interest::Interest, Declare, NetworkBody, NetworkMessage, Push, Request, Response,
+        interest::Interest, Declare, NetworkBody, NetworkMessage, Oam, Push, Request, Response,
         ResponseFinal,
     },
 };
 use zenoh_transport::{multicast::TransportMulticast, unicast::TransportUnicast};
 
 use super::EPrimitives;
-use crate::net::routing::{
-    dispatcher::face::{Face, WeakFace},
-    interceptor::{InterceptorTrait, InterceptorsChain},
-    RoutingContext,
+use crate::net::{
+    protocol::batch::{is_batch, split_batch_push},
+    routing::{
+        dispatcher::face::{Face, WeakFace},
+        interceptor::{InterceptorTrait, InterceptorsChain},
+        RoutingContext,
+    },
 };
 
 pub struct Mux {
     pub handler: TransportUnicast,
     pub(crate) face: OnceLock<WeakFace>,
-    pub(crate) interceptor: InterceptorsChain,
+    pub(crate) interceptor: Arc<InterceptorsChain>,
 }
 
 impl Mux {
-    pub(crate) fn new(handler: TransportUnicast, interceptor: InterceptorsChain) -> Mux {
+    pub(crate) fn new(handler: TransportUnicast, interceptor: Arc<InterceptorsChain>) -> Mux {
         Mux {
             handler,
             face: OnceLock::new(),
             interceptor,
         }
     }
+
+    /// Schedules a push, splitting the batch it may carry into individual puts and deletes if
+    /// the transport did not negotiate batches.
+    fn schedule_push(&self, msg: NetworkMessage) {
+        if !is_batch(&msg) {
+            let _ = self.handler.schedule(msg);
+            return;
+        }
+        if self
+            .handler
+            .get_patch()
+            .is_ok_and(|patch| patch.has_batches())
+        {
+            let _ = self.handler.schedule(msg);
+            return;
+        }
+        for msg in split_batches(msg) {
+            let _ = self.handler.schedule(msg);
+        }
+    }
 }
 
 impl EPrimitives for Mux {
@@ -58,6 +81,7 @@ impl EPrimitives for Mux {
             outface: ctx.outface,
             prefix: ctx.prefix,
             full_expr: ctx.full_expr,
+            modified: ctx.modified,
         };
         let prefix = ctx
             .wire_expr()
@@ -84,6 +108,7 @@ impl EPrimitives for Mux {
             outface: ctx.outface,
             prefix: ctx.prefix,
             full_expr: ctx.full_expr,
+            modified: ctx.modified,
         };
         let prefix = ctx
             .wire_expr()
@@ -106,7 +131,7 @@ impl EPrimitives for Mux {
             size: None,
         };
         if self.interceptor.interceptors.is_empty() {
-            let _ = self.handler.schedule(msg);
+            self.schedule_push(msg);
         } else if let Some(face) = self.face.get().and_then(|f| f.upgrade()) {
             let ctx = RoutingContext::new_out(msg, face.clone());
             let prefix = ctx
@@ -116,7 +141,7 @@ impl EPrimitives for Mux {
                 .cloned();
             let cache = prefix.as_ref().and_then(|p| p.get_egress_cache(&face));
             if let Some(ctx) = self.interceptor.intercept(ctx, cache) {
-                let _ = self.handler.schedule(ctx.msg);
+                self.schedule_push(ctx.msg);
             }
         } else {
             tracing::error!("Uninitialized multiplexer!");
@@ -198,6 +223,25 @@ impl EPrimitives for Mux {
         }
     }
 
+    fn send_oam(&self, msg: Oam) {
+        let msg = NetworkMessage {
+            body: NetworkBody::OAM(msg),
+            reliability: Reliability::Reliable,
+            #[cfg(feature = "stats")]
+            size: None,
+        };
+        if self.interceptor.interceptors.is_empty() {
+            let _ = self.handler.schedule(msg);
+        } else if let Some(face) = self.face.get().and_then(|f| f.upgrade()) {
+            let ctx = RoutingContext::new_out(msg, face.clone());
+            if let Some(ctx) = self.interceptor.intercept(ctx, None) {
+                let _ = self.handler.schedule(ctx.msg);
+            }
+        } else {
+            tracing::error!("Uninitialized multiplexer!");
+        }
+    }
+
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
@@ -206,17 +250,32 @@ impl EPrimitives for Mux {
 pub struct McastMux {
     pub handler: TransportMulticast,
     pub(crate) face: OnceLock<Face>,
-    pub(crate) interceptor: InterceptorsChain,
+    pub(crate) interceptor: Arc<InterceptorsChain>,
 }
 
 impl McastMux {
-    pub(crate) fn new(handler: TransportMulticast, interceptor: InterceptorsChain) -> McastMux {
+    pub(crate) fn new(
+        handler: TransportMulticast,
+        interceptor: Arc<InterceptorsChain>,
+    ) -> McastMux {
         McastMux {
             handler,
             face: OnceLock::new(),
             interceptor,
         }
     }
+
+    /// Schedules a push, always splitting the batch it may carry into individual puts and
+    /// deletes since some members of the group may not support batches.
+    fn schedule_push(&self, msg: NetworkMessage) {
+        if !is_batch(&msg) {
+            let _ = self.handler.schedule(msg);
+            return;
+        }
+        for msg in split_batches(msg) {
+            let _ = self.handler.schedule(msg);
+        }
+    }
 }
 
 impl EPrimitives for McastMux {
@@ -232,6 +291,7 @@ impl EPrimitives for McastMux {
             outface: ctx.outface,
             prefix: ctx.prefix,
             full_expr: ctx.full_expr,
+            modified: ctx.modified,
         };
         let prefix = ctx
             .wire_expr()
@@ -258,6 +318,7 @@ impl EPrimitives for McastMux {
             outface: ctx.outface,
             prefix: ctx.prefix,
             full_expr: ctx.full_expr,
+            modified: ctx.modified,
         };
         let prefix = ctx
             .wire_expr()
@@ -280,7 +341,7 @@ impl EPrimitives for McastMux {
             size: None,
         };
         if self.interceptor.interceptors.is_empty() {
-            let _ = self.handler.schedule(msg);
+            self.schedule_push(msg);
         } else if let Some(face) = self.face.get() {
             let ctx = RoutingContext::new_out(msg, face.clone());
             let prefix = ctx
@@ -290,7 +351,7 @@ impl EPrimitives for McastMux {
                 .cloned();
             let cache = prefix.as_ref().and_then(|p| p.get_egress_cache(face));
             if let Some(ctx) = self.interceptor.intercept(ctx, cache) {
-                let _ = self.handler.schedule(ctx.msg);
+                self.schedule_push(ctx.msg);
             }
         } else {
             tracing::error!("Uninitialized multiplexer!");
@@ -372,7 +433,45 @@ impl EPrimitives for McastMux {
         }
     }
 
+    fn send_oam(&self, msg: Oam) {
+        let msg = NetworkMessage {
+            body: NetworkBody::OAM(msg),
+            reliability: Reliability::Reliable,
+            #[cfg(feature = "stats")]
+            size: None,
+        };
+        if self.interceptor.interceptors.is_empty() {
+            let _ = self.handler.schedule(msg);
+        } else if let Some(face) = self.face.get() {
+            let ctx = RoutingContext::new_out(msg, face.clone());
+            if let Some(ctx) = self.interceptor.intercept(ctx, None) {
+                let _ = self.handler.schedule(ctx.msg);
+            }
+        } else {
+            tracing::error!("Uninitialized multiplexer!");
+        }
+    }
+
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
 }
+
+/// Splits the batch carried by a push with [`split_batch_push`].
+fn split_batches(msg: NetworkMessage) -> Vec<NetworkMessage> {
+    let NetworkMessage {
+        body, reliability, ..
+    } = msg;
+    let NetworkBody::Push(push) = body else {
+        return vec![];
+    };
+    split_batch_push(push)
+        .into_iter()
+        .map(|push| NetworkMessage {
+            body: NetworkBody::Push(push),
+            reliability,
+            #[cfg(feature = "stats")]
+            size: None,
+        })
+        .collect()
+}
@@ -0,0 +1,105 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use zenoh_keyexpr::OwnedKeyExpr;
+use zenoh_protocol::{
+    network::{NetworkBody, NetworkMessage, Push},
+    zenoh::{Batch, PushBody},
+};
+
+/// Returns `true` if the message is a push carrying a batch.
+pub(crate) fn is_batch(msg: &NetworkMessage) -> bool {
+    matches!(
+        &msg.body,
+        NetworkBody::Push(Push {
+            payload: PushBody::Batch(_),
+            ..
+        })
+    )
+}
+
+/// The key expression a batch is routed on: the key expression of its entries if they are all
+/// the same, their common prefix followed by `**` otherwise (`**` if they share none).
+pub(crate) fn batch_key_expr<'a>(keys: impl IntoIterator<Item = &'a str>) -> OwnedKeyExpr {
+    let mut keys = keys.into_iter();
+    let Some(first) = keys.next() else {
+        return OwnedKeyExpr::new("**").unwrap();
+    };
+    let mut prefix: Vec<&str> = first.split('/').collect();
+    let mut same = true;
+    for key in keys {
+        same &= key == first;
+        let common = prefix
+            .iter()
+            .zip(key.split('/'))
+            .take_while(|(a, b)| **a == *b)
+            .count();
+        prefix.truncate(common);
+    }
+    if same {
+        if let Ok(key_expr) = OwnedKeyExpr::new(first) {
+            return key_expr;
+        }
+    }
+    if prefix.last() != Some(&"**") {
+        prefix.push("**");
+    }
+    OwnedKeyExpr::autocanonize(prefix.join("/"))
+        .unwrap_or_else(|_| OwnedKeyExpr::new("**").unwrap())
+}
+
+/// Splits a push carrying a batch into the individual puts and deletes of its entries, for the
+/// nodes not supporting batches. Any other push is left untouched.
+pub(crate) fn split_batch_push(mut push: Push) -> Vec<Push> {
+    let batch = match push.payload {
+        PushBody::Batch(batch) => batch,
+        payload => {
+            push.payload = payload;
+            return vec![push];
+        }
+    };
+    let Batch {
+        timestamp,
+        ext_sinfo,
+        entries,
+        ..
+    } = batch;
+    entries
+        .into_iter()
+        .map(|entry| {
+            let payload = match entry.payload {
+                PushBody::Put(mut put) => {
+                    put.timestamp = timestamp;
+                    put.ext_sinfo = ext_sinfo.clone();
+                    PushBody::Put(put)
+                }
+                PushBody::Del(mut del) => {
+                    del.timestamp = timestamp;
+                    del.ext_sinfo = ext_sinfo.clone();
+                    PushBody::Del(del)
+                }
+                payload => payload,
+            };
+            Push {
+                wire_expr: entry.key_expr.into(),
+                ext_qos: push.ext_qos,
+                ext_tstamp: push.ext_tstamp,
+                ext_nodeid: push.ext_nodeid,
+                ext_hop_limit: push.ext_hop_limit,
+                ext_lifespan: push.ext_lifespan,
+                ext_redundant: push.ext_redundant,
+                payload,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,48 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::time::{Duration, SystemTime};
+
+use uhlc::HLC;
+use zenoh_protocol::{core::Timestamp, zenoh::PushBody};
+
+/// Returns `true` if data with the given `timestamp` is older than its `lifespan`.
+///
+/// Data without timestamp or without lifespan never expires: the data carrying a lifespan
+/// without a timestamp is stamped by the first router it goes through with [`stamp`].
+pub(crate) fn is_expired(timestamp: Option<&Timestamp>, lifespan: Option<Duration>) -> bool {
+    match (timestamp, lifespan) {
+        (Some(timestamp), Some(lifespan)) => timestamp
+            .get_time()
+            .to_system_time()
+            .checked_add(lifespan)
+            .is_some_and(|expiry| expiry < SystemTime::now()),
+        _ => false,
+    }
+}
+
+/// Stamps the data carrying a `lifespan` without a timestamp, so that its lifespan is counted
+/// from its reception.
+pub(crate) fn stamp(hlc: Option<&HLC>, payload: &mut PushBody, lifespan: Option<Duration>) {
+    let (Some(hlc), Some(_)) = (hlc, lifespan) else {
+        return;
+    };
+    let timestamp = match payload {
+        PushBody::Put(put) => &mut put.timestamp,
+        PushBody::Del(del) => &mut del.timestamp,
+        PushBody::Batch(batch) => &mut batch.timestamp,
+    };
+    if timestamp.is_none() {
+        *timestamp = Some(hlc.new_timestamp());
+    }
+}
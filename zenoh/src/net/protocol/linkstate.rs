@@ -17,6 +17,9 @@ pub const PID: u64 = 1; // 0x01
 pub const WAI: u64 = 1 << 1; // 0x02
 pub const LOC: u64 = 1 << 2; // 0x04
 
+/// The weight of a link that has no configured or measured weight.
+pub(crate) const DEFAULT_LINK_WEIGHT: u16 = 100;
+
 //  7 6 5 4 3 2 1 0
 // +-+-+-+-+-+-+-+-+
 // ~X|X|X|X|X|L|W|P~
@@ -33,6 +36,8 @@ pub const LOC: u64 = 1 << 2; // 0x04
 // +---------------+
 // ~    [links]    ~
 // +---------------+
+//
+// The weights of the links are encoded in the link state list.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct LinkState {
     pub(crate) psid: u64,
@@ -41,6 +46,7 @@ pub(crate) struct LinkState {
     pub(crate) whatami: Option<WhatAmI>,
     pub(crate) locators: Option<Vec<Locator>>,
     pub(crate) links: Vec<u64>,
+    pub(crate) link_weights: Option<Vec<u16>>,
 }
 
 impl LinkState {
@@ -74,6 +80,11 @@ impl LinkState {
         };
         let n = rng.gen_range(MIN..=MAX);
         let links = (0..n).map(|_| rng.gen()).collect::<Vec<u64>>();
+        let link_weights = if rng.gen_bool(0.5) {
+            Some((0..n).map(|_| rng.gen()).collect::<Vec<u16>>())
+        } else {
+            None
+        };
 
         Self {
             psid,
@@ -82,6 +93,7 @@ impl LinkState {
             whatami,
             locators,
             links,
+            link_weights,
         }
     }
 }
@@ -92,6 +104,11 @@ impl LinkState {
 // +-+-+-+---------+
 // ~ [link_states] ~
 // +---------------+
+// ~   [weights]   ~ if any link state has weights, one list per link state
+// +---------------+
+//
+// The weights are appended after the link states, so that the nodes not supporting them
+// ignore them. The weights of a link state have one weight per link, or are empty.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct LinkStateList {
     pub(crate) link_states: Vec<LinkState>,
@@ -11,4 +11,7 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
+pub(crate) mod batch;
+pub(crate) mod lifespan;
 pub(crate) mod linkstate;
+pub(crate) mod query_credit;
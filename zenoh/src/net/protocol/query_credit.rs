@@ -0,0 +1,70 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use zenoh_buffers::{
+    reader::HasReader,
+    writer::{DidntWrite, HasWriter},
+    ZBuf,
+};
+use zenoh_codec::{RCodec, WCodec};
+use zenoh_protocol::{
+    common::ZExtBody,
+    core::ZenohIdProto,
+    network::{
+        oam::{self, id::OAM_QUERY_CREDIT},
+        Oam, RequestId,
+    },
+};
+
+use crate::net::codec::Zenoh080Routing;
+
+// The body of an OAM_QUERY_CREDIT message, sent in the direction of a query by a querier
+// granting more responses to one of the queryables replying to it.
+//
+//  7 6 5 4 3 2 1 0
+// +-+-+-+-+-+-+-+-+
+// ~ request_id:z32~
+// +---------------+
+// ~  credit:z32   ~
+// +---------------+
+// ~   responder   ~ -- The zid of the session of the queryables granted the credit
+// +---------------+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct QueryCredit {
+    pub(crate) rid: RequestId,
+    pub(crate) credit: u32,
+    pub(crate) responder: ZenohIdProto,
+}
+
+impl QueryCredit {
+    pub(crate) fn to_oam(&self) -> Result<Oam, DidntWrite> {
+        let codec = Zenoh080Routing::new();
+        let mut buf = ZBuf::empty();
+        codec.write(&mut buf.writer(), self)?;
+        Ok(Oam {
+            id: OAM_QUERY_CREDIT,
+            body: ZExtBody::ZBuf(buf),
+            ext_qos: oam::ext::QoSType::OAM,
+            ext_tstamp: None,
+        })
+    }
+
+    pub(crate) fn from_oam(oam: &Oam) -> Option<Self> {
+        match (&oam.id, &oam.body) {
+            (&OAM_QUERY_CREDIT, ZExtBody::ZBuf(buf)) => {
+                Zenoh080Routing::new().read(&mut buf.reader()).ok()
+            }
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,227 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::{collections::BTreeMap, sync::Arc};
+
+use serde::Serialize;
+use zenoh_keyexpr::keyexpr;
+use zenoh_protocol::core::{Parameters, ZenohIdProto};
+use zenoh_result::{bail, zerror, ZResult};
+
+use super::{
+    face::FaceState,
+    tables::{HopLimit, RoutingExpr, Tables},
+};
+use crate::{
+    api::key_expr::KeyExpr,
+    net::{
+        primitives::{McastMux, Mux},
+        routing::{hat::Sources, interceptor::ExplainedMessage},
+    },
+};
+
+/// Whether a complete queryable is reached through a face and the distance of the nearest one.
+type QueryablesInfo = (bool, u16);
+
+/// A declaration matching an explained route and the nodes it was declared by.
+#[derive(Serialize)]
+pub(crate) struct ExplainedDeclaration {
+    key_expr: String,
+    sources: Sources,
+}
+
+/// A face an explained message would be sent to.
+#[derive(Serialize)]
+pub(crate) struct ExplainedHop {
+    zid: String,
+    whatami: &'static str,
+    /// `false` if the routing filters the message out of this face or its hop limit is reached
+    forwarded: bool,
+    /// For queries, whether a complete queryable is reached through this face
+    #[serde(skip_serializing_if = "Option::is_none")]
+    complete: Option<bool>,
+    /// For queries, the distance of the nearest queryable reached through this face
+    #[serde(skip_serializing_if = "Option::is_none")]
+    distance: Option<u16>,
+    /// The matching declarations of the node at the other end of the face
+    declarations: Vec<String>,
+    /// The egress interceptors acting on the message
+    interceptors: Vec<String>,
+}
+
+/// How a message would be routed by this node, see [`explain_route`].
+#[derive(Serialize)]
+pub(crate) struct RouteExplanation {
+    key_expr: String,
+    message: &'static str,
+    source: String,
+    /// `false` if the routing drops the message received from the source
+    accepted: bool,
+    /// The ingress interceptors of the source face acting on the message
+    ingress: Vec<String>,
+    hops: Vec<ExplainedHop>,
+    /// The subscribers (or queryables) matching the key expression
+    declarations: Vec<ExplainedDeclaration>,
+}
+
+/// Explains the route of a message given by the parameters, without sending it.
+///
+/// The parameters are the `ke` key expression, the `kind` of message (`put`, `delete` or
+/// `query`, put by default), and optionally the `src` zid of the neighbour node the message
+/// is received from (published by this node by default) and the `hop_limit` of the received
+/// message.
+pub(crate) fn explain_route(tables: &Tables, parameters: &Parameters) -> ZResult<RouteExplanation> {
+    let Some(key_expr) = parameters.get("ke") else {
+        bail!("Missing ke parameter");
+    };
+    let key_expr = keyexpr::new(key_expr)?;
+    let message = match parameters.get("kind").unwrap_or("put") {
+        "put" => ExplainedMessage::Put,
+        "delete" => ExplainedMessage::Delete,
+        "query" => ExplainedMessage::Query,
+        kind => bail!("Invalid kind '{}' (should be put, delete or query)", kind),
+    };
+    let source = parameters
+        .get("src")
+        .map(|src| {
+            src.parse::<ZenohIdProto>()
+                .map_err(|e| zerror!("Invalid src '{}': {}", src, e))
+        })
+        .transpose()?;
+    let hop_limit = parameters
+        .get("hop_limit")
+        .map(|hops| {
+            hops.parse::<u8>()
+                .map_err(|e| zerror!("Invalid hop_limit '{}': {}", hops, e))
+        })
+        .transpose()?;
+    route_explanation(tables, key_expr, message, source.as_ref(), hop_limit)
+}
+
+/// Explains the route of a `message` on `key_expr` with the given `hop_limit` received from
+/// the `source` node, or published by this node if `None`.
+///
+/// The route is computed on the given key expression, the key expression rewriting of the
+/// ingress interceptors is reported but not applied.
+fn route_explanation(
+    tables: &Tables,
+    key_expr: &keyexpr,
+    message: ExplainedMessage,
+    source: Option<&ZenohIdProto>,
+    hop_limit: Option<u8>,
+) -> ZResult<RouteExplanation> {
+    let source_zid = source.unwrap_or(&tables.zid);
+    let Some(face) = tables
+        .faces
+        .values()
+        .filter(|face| face.zid == *source_zid)
+        .min_by_key(|face| face.id)
+    else {
+        bail!("No face to {}", source_zid);
+    };
+    let ke = KeyExpr::from(key_expr);
+    let mut expr = RoutingExpr::new(&tables.root_res, key_expr.as_str());
+    let hat = &tables.hat_code;
+    let hop_limit = HopLimit::new(tables, face, hop_limit);
+    let accepted = hat.ingress_filter(tables, face, &mut expr);
+    let ingress = face
+        .in_interceptors
+        .as_ref()
+        .map(|chain| chain.explain_all(&ke, message))
+        .unwrap_or_default();
+
+    let declarations = match message {
+        ExplainedMessage::Put | ExplainedMessage::Delete => hat.get_subscriptions(tables),
+        ExplainedMessage::Query => hat.get_queryables(tables),
+    }
+    .into_iter()
+    .filter(|(res, _)| keyexpr::new(res.expr()).is_ok_and(|ke| ke.intersects(key_expr)))
+    .map(|(res, sources)| ExplainedDeclaration {
+        key_expr: res.expr().to_string(),
+        sources,
+    })
+    .collect::<Vec<_>>();
+
+    let context = hat.map_routing_context(tables, face, 0);
+    let source_type = hat.map_face_type(tables, face);
+    let mut route: BTreeMap<usize, (Arc<FaceState>, Option<QueryablesInfo>)> = BTreeMap::new();
+    match message {
+        ExplainedMessage::Put | ExplainedMessage::Delete => {
+            for (outface, _, _) in hat
+                .compute_data_route(tables, &mut expr, context, source_type)
+                .values()
+            {
+                route.insert(outface.id, (outface.clone(), None));
+            }
+        }
+        ExplainedMessage::Query => {
+            for qabl in hat
+                .compute_query_route(tables, &mut expr, context, source_type)
+                .iter()
+            {
+                let complete = qabl.info.is_some_and(|info| info.complete);
+                let distance = qabl.info.map_or(u16::MAX, |info| info.distance);
+                let (_, info) = route
+                    .entry(qabl.direction.0.id)
+                    .or_insert_with(|| (qabl.direction.0.clone(), Some((complete, distance))));
+                if let Some((c, d)) = info {
+                    *c |= complete;
+                    *d = (*d).min(distance);
+                }
+            }
+        }
+    }
+
+    let hops = route
+        .into_values()
+        .map(|(outface, info)| {
+            let interceptors = if let Some(mux) = outface.primitives.as_any().downcast_ref::<Mux>()
+            {
+                mux.interceptor.explain_all(&ke, message)
+            } else if let Some(mux) = outface.primitives.as_any().downcast_ref::<McastMux>() {
+                mux.interceptor.explain_all(&ke, message)
+            } else {
+                vec![]
+            };
+            ExplainedHop {
+                zid: outface.zid.to_string(),
+                whatami: outface.whatami.to_str(),
+                forwarded: outface.id != face.id
+                    && hop_limit.allows(&outface)
+                    && hat.egress_filter(tables, face, &outface, &mut expr),
+                complete: info.map(|(complete, _)| complete),
+                distance: info.map(|(_, distance)| distance),
+                declarations: declarations
+                    .iter()
+                    .filter(|decl| decl.sources.contains(&outface.zid))
+                    .map(|decl| decl.key_expr.clone())
+                    .collect(),
+                interceptors,
+            }
+        })
+        .collect();
+
+    Ok(RouteExplanation {
+        key_expr: key_expr.to_string(),
+        message: match message {
+            ExplainedMessage::Put => "put",
+            ExplainedMessage::Delete => "delete",
+            ExplainedMessage::Query => "query",
+        },
+        source: source_zid.to_string(),
+        accepted,
+        ingress,
+        hops,
+        declarations,
+    })
+}
@@ -15,16 +15,18 @@ use std::{
     any::Any,
     collections::HashMap,
     fmt,
-    sync::{Arc, Weak},
+    sync::{atomic::AtomicUsize, Arc, Mutex, Weak},
     time::Duration,
 };
 
 use tokio_util::sync::CancellationToken;
 use zenoh_protocol::{
+    common::ZExtBody,
     core::{ExprId, Reliability, WhatAmI, WireExpr, ZenohIdProto},
     network::{
         interest::{InterestId, InterestMode, InterestOptions},
-        push, Mapping, Push, Request, RequestId, Response, ResponseFinal,
+        oam::id::{OAM_QUERY_CANCEL, OAM_QUERY_CREDIT},
+        push, Mapping, Oam, Push, Request, RequestId, Response, ResponseFinal,
     },
     zenoh::{PushBody, RequestBody},
 };
@@ -44,6 +46,7 @@ use crate::{
     api::key_expr::KeyExpr,
     net::{
         primitives::{McastMux, Mux, Primitives},
+        protocol::query_credit::QueryCredit,
         routing::{
             dispatcher::interests::finalize_pending_interests,
             interceptor::{InterceptorTrait, InterceptorsChain},
@@ -57,6 +60,9 @@ pub(crate) struct InterestState {
     pub(crate) finalized: bool,
 }
 
+/// The faces and request ids a query was routed to.
+pub(crate) type QueryRoutes = Vec<(Weak<FaceState>, RequestId)>;
+
 pub struct FaceState {
     pub(crate) id: usize,
     pub(crate) zid: ZenohIdProto,
@@ -71,7 +77,14 @@ pub struct FaceState {
     pub(crate) local_mappings: HashMap<ExprId, Arc<Resource>>,
     pub(crate) remote_mappings: HashMap<ExprId, Arc<Resource>>,
     pub(crate) next_qid: RequestId,
-    pub(crate) pending_queries: HashMap<RequestId, (Arc<Query>, CancellationToken)>,
+    pub(crate) pending_queries:
+        HashMap<RequestId, (Arc<Query>, CancellationToken, Option<OutstandingQuery>)>,
+    /// The faces and request ids the pending queries received from this face were routed to.
+    pub(crate) routed_queries: Mutex<HashMap<RequestId, QueryRoutes>>,
+    /// Number of queries routed to this face by the query load balancing.
+    pub(crate) balanced_queries: AtomicUsize,
+    /// Number of balanced queries pending on each queryable of this face.
+    pub(crate) outstanding_queries: Mutex<HashMap<QueryableTarget, Arc<AtomicUsize>>>,
     pub(crate) mcast_group: Option<TransportMulticast>,
     pub(crate) in_interceptors: Option<Arc<InterceptorsChain>>,
     pub(crate) hat: Box<dyn Any + Send + Sync>,
@@ -104,6 +117,9 @@ impl FaceState {
             remote_mappings: HashMap::new(),
             next_qid: 0,
             pending_queries: HashMap::new(),
+            routed_queries: Mutex::new(HashMap::new()),
+            balanced_queries: AtomicUsize::new(0),
+            outstanding_queries: Mutex::new(HashMap::new()),
             mcast_group,
             in_interceptors,
             hat,
@@ -207,12 +223,14 @@ pub struct Face {
 }
 
 impl Face {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn send_push_lazy(
         &self,
         wire_expr: WireExpr,
         qos: push::ext::QoSType,
         ext_tstamp: Option<push::ext::TimestampType>,
         ext_nodeid: push::ext::NodeIdType,
+        ext_lifespan: Option<push::ext::LifespanType>,
         body: impl FnOnce() -> PushBody,
         reliability: Reliability,
     ) {
@@ -223,6 +241,9 @@ impl Face {
             qos,
             ext_tstamp,
             ext_nodeid,
+            None,
+            ext_lifespan,
+            None,
             body,
             reliability,
         );
@@ -404,13 +425,20 @@ impl Primitives for Face {
 
     #[inline]
     fn send_push(&self, msg: Push, reliability: Reliability) {
+        let mut ext_nodeid = msg.ext_nodeid;
+        if msg.ext_redundant.is_some_and(|redundant| redundant.tree) {
+            ext_nodeid.node_id |= REDUNDANT_CONTEXT;
+        }
         route_data(
             &self.tables,
             &self.state,
             msg.wire_expr,
             msg.ext_qos,
             msg.ext_tstamp,
-            msg.ext_nodeid,
+            ext_nodeid,
+            msg.ext_hop_limit,
+            msg.ext_lifespan,
+            msg.ext_redundant,
             move || msg.payload,
             reliability,
         );
@@ -429,6 +457,7 @@ impl Primitives for Face {
                     msg.ext_target,
                     msg.ext_budget,
                     msg.ext_timeout,
+                    msg.ext_hop_limit,
                     msg.payload,
                     msg.ext_nodeid.node_id,
                 );
@@ -453,10 +482,24 @@ impl Primitives for Face {
         route_send_response_final(&self.tables, &mut self.state.clone(), msg.rid);
     }
 
+    fn send_oam(&self, msg: Oam) {
+        match (msg.id, &msg.body) {
+            (OAM_QUERY_CANCEL, ZExtBody::Z64(rid)) => {
+                route_cancel_query(&self.tables, &self.state, *rid as RequestId)
+            }
+            (OAM_QUERY_CREDIT, _) => match QueryCredit::from_oam(&msg) {
+                Some(credit) => route_grant_query_credit(&self.tables, &self.state, credit),
+                None => tracing::debug!("{} Failed to decode query credit", self.state),
+            },
+            (id, _) => tracing::trace!("{} Ignoring OAM {}", self.state, id),
+        }
+    }
+
     fn send_close(&self) {
         tracing::debug!("Close {}", self.state);
         let mut state = self.state.clone();
         state.task_controller.terminate_all(Duration::from_secs(10));
+        cancel_routed_queries(&self.tables, &state);
         finalize_pending_queries(&self.tables, &mut state);
         let mut declares = vec![];
         let ctrl_lock = zlock!(self.tables.ctrl_lock);
@@ -12,13 +12,20 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
-#[zenoh_macros::unstable]
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
 
-use zenoh_core::zread;
+use zenoh_config::WhatAmI;
+use zenoh_core::{zlock, zread};
+use zenoh_keyexpr::OwnedKeyExpr;
 use zenoh_protocol::{
-    core::{key_expr::keyexpr, Reliability, WireExpr},
+    core::{key_expr::keyexpr, Reliability, WireExpr, ZenohIdProto},
     network::{declare::SubscriberId, push::ext, Push},
     zenoh::PushBody,
 };
@@ -26,14 +33,17 @@ use zenoh_sync::get_mut_unchecked;
 
 use super::{
     face::FaceState,
-    resource::{Direction, Resource},
-    tables::{NodeId, Route, RoutingExpr, Tables, TablesLock},
+    resource::{Direction, Resource, REDUNDANT_CONTEXT},
+    tables::{HopLimit, NodeId, Route, RoutingExpr, Tables, TablesLock},
 };
 #[zenoh_macros::unstable]
 use crate::key_expr::KeyExpr;
-use crate::net::routing::{
-    hat::{HatTrait, SendDeclare},
-    router::get_or_set_route,
+use crate::net::{
+    protocol::lifespan::{self, is_expired},
+    routing::{
+        hat::{HatTrait, SendDeclare},
+        router::get_or_set_route,
+    },
 };
 
 #[derive(Copy, Clone)]
@@ -175,8 +185,13 @@ macro_rules! treat_timestamp {
         // if an HLC was configured (via Config.add_timestamp),
         // check DataInfo and add a timestamp if there isn't
         if let Some(hlc) = $hlc {
-            if let PushBody::Put(data) = &mut $payload {
-                if let Some(ref ts) = data.timestamp {
+            let timestamp = match &mut $payload {
+                PushBody::Put(data) => Some(&mut data.timestamp),
+                PushBody::Batch(batch) => Some(&mut batch.timestamp),
+                PushBody::Del(_) => None,
+            };
+            if let Some(timestamp) = timestamp {
+                if let Some(ref ts) = timestamp {
                     // Timestamp is present; update HLC with it (possibly raising error if delta exceed)
                     match hlc.update_with_timestamp(ts) {
                         Ok(()) => (),
@@ -188,18 +203,18 @@ macro_rules! treat_timestamp {
                                 );
                                 return;
                             } else {
-                                data.timestamp = Some(hlc.new_timestamp());
+                                *timestamp = Some(hlc.new_timestamp());
                                 tracing::error!(
                                     "Error treating timestamp for received Data ({}). Replace timestamp: {:?}",
                                     e,
-                                    data.timestamp);
+                                    timestamp);
                             }
                         }
                     }
                 } else {
                     // Timestamp not present; add one
-                    data.timestamp = Some(hlc.new_timestamp());
-                    tracing::trace!("Adding timestamp to DataInfo: {:?}", data.timestamp);
+                    *timestamp = Some(hlc.new_timestamp());
+                    tracing::trace!("Adding timestamp to DataInfo: {:?}", timestamp);
                 }
             }
         }
@@ -213,26 +228,226 @@ fn get_data_route(
     res: &Option<Arc<Resource>>,
     expr: &mut RoutingExpr,
     routing_context: NodeId,
+) -> Arc<Route> {
+    let local_context = tables
+        .hat_code
+        .map_routing_context(tables, face, routing_context);
+    get_local_data_route(tables, face, res, expr, local_context)
+}
+
+fn get_local_data_route(
+    tables: &Tables,
+    face: &FaceState,
+    res: &Option<Arc<Resource>>,
+    expr: &mut RoutingExpr,
+    local_context: NodeId,
 ) -> Arc<Route> {
     let hat = &tables.hat_code;
-    let local_context = hat.map_routing_context(tables, face, routing_context);
-    let mut compute_route = || hat.compute_data_route(tables, expr, local_context, face.whatami);
-    if let Some(data_routes) = res
-        .as_ref()
-        .and_then(|res| res.context.as_ref())
-        .map(|ctx| &ctx.data_routes)
-    {
+    let source_type = hat.map_face_type(tables, face);
+    let mut compute_route = || hat.compute_data_route(tables, expr, local_context, source_type);
+    if let Some(ctx) = res.as_ref().and_then(|res| res.context.as_ref()) {
+        let (data_routes, context) = if local_context & REDUNDANT_CONTEXT != 0 {
+            (
+                &ctx.redundant_data_routes,
+                local_context & !REDUNDANT_CONTEXT,
+            )
+        } else {
+            (&ctx.data_routes, local_context)
+        };
         return get_or_set_route(
             data_routes,
             tables.routes_version,
-            face.whatami,
-            local_context,
+            source_type,
+            context,
             compute_route,
         );
     }
     compute_route()
 }
 
+/// The node id extension of a message routed with the given routing context.
+///
+/// The [`REDUNDANT_CONTEXT`] flag is carried by the redundant extension instead, for the
+/// routers not supporting it to still read the node id.
+#[inline]
+fn nodeid_ext(context: NodeId) -> ext::NodeIdType {
+    ext::NodeIdType {
+        node_id: context & !REDUNDANT_CONTEXT,
+    }
+}
+
+/// The redundant extension of a message identified by `redundant` routed with the given
+/// routing context.
+#[inline]
+fn redundant_ext(
+    context: NodeId,
+    redundant: Option<ext::RedundantType>,
+) -> Option<ext::RedundantType> {
+    redundant.map(|redundant| ext::RedundantType {
+        tree: context & REDUNDANT_CONTEXT != 0,
+        ..redundant
+    })
+}
+
+/// Size of the window of sequence numbers in which duplicates are detected.
+const REDUNDANCY_WINDOW: u32 = u128::BITS;
+
+/// The sequence numbers received from a source, within a window below the highest one.
+struct SnWindow {
+    highest: u32,
+    received: u128,
+    /// The last time a message was received from the source
+    last: Instant,
+}
+
+impl SnWindow {
+    /// Records `sn`, returns `false` if it was already received (or is too old to tell).
+    fn insert(&mut self, sn: u32, now: Instant) -> bool {
+        self.last = now;
+        let ahead = sn.wrapping_sub(self.highest) as i32;
+        if ahead > 0 {
+            self.received = match ahead as u32 {
+                shift if shift < REDUNDANCY_WINDOW => self.received << shift,
+                _ => 0,
+            } | 1;
+            self.highest = sn;
+            return true;
+        }
+        let behind = ahead.unsigned_abs();
+        if behind >= REDUNDANCY_WINDOW || self.received & (1 << behind) != 0 {
+            return false;
+        }
+        self.received |= 1 << behind;
+        true
+    }
+}
+
+/// The windows of the sources messages were recently received from.
+struct SnWindows {
+    windows: HashMap<ZenohIdProto, SnWindow>,
+    last_eviction: Instant,
+}
+
+impl SnWindows {
+    /// Period of the eviction of the windows of the sources no message was received from
+    /// during this period, a copy of their messages would be received within it.
+    const EVICTION_PERIOD: Duration = Duration::from_secs(10);
+
+    /// Records `sn` from `source`, returns `false` if it was already received.
+    fn insert(&mut self, source: ZenohIdProto, sn: u32) -> bool {
+        let now = Instant::now();
+        if now.saturating_duration_since(self.last_eviction) >= Self::EVICTION_PERIOD {
+            self.windows.retain(|_, window| {
+                now.saturating_duration_since(window.last) < Self::EVICTION_PERIOD
+            });
+            self.last_eviction = now;
+        }
+        match self.windows.entry(source) {
+            Entry::Occupied(mut window) => window.get_mut().insert(sn, now),
+            Entry::Vacant(window) => {
+                window.insert(SnWindow {
+                    highest: sn,
+                    received: 1,
+                    last: now,
+                });
+                true
+            }
+        }
+    }
+}
+
+/// The redundant delivery of the puts and deletes on critical key expressions.
+///
+/// The messages published by clients (and local sessions) are forwarded along the tree of
+/// this router and along its redundant tree, see [`REDUNDANT_CONTEXT`]. Each router
+/// forwards the messages along the tree they were received on, but only delivers the first
+/// copy of each message to its clients and peers, based on the redundant extension this
+/// router identified the message with.
+pub(crate) struct Redundancy {
+    key_exprs: Vec<OwnedKeyExpr>,
+    zid: ZenohIdProto,
+    next_sn: AtomicU32,
+    received: Mutex<SnWindows>,
+}
+
+impl Redundancy {
+    pub(crate) fn new(zid: ZenohIdProto, key_exprs: Vec<OwnedKeyExpr>) -> Self {
+        Redundancy {
+            key_exprs,
+            zid,
+            next_sn: AtomicU32::new(0),
+            received: Mutex::new(SnWindows {
+                windows: HashMap::new(),
+                last_eviction: Instant::now(),
+            }),
+        }
+    }
+
+    fn includes(&self, expr: &mut RoutingExpr) -> bool {
+        keyexpr::new(expr.full_expr())
+            .is_ok_and(|ke| self.key_exprs.iter().any(|critical| critical.includes(ke)))
+    }
+
+    /// The directions of a message received from `face` with the given `route`, and the
+    /// redundant extension to forward it with along each of them.
+    fn directions(
+        &self,
+        tables: &Tables,
+        face: &FaceState,
+        res: &Option<Arc<Resource>>,
+        expr: &mut RoutingExpr,
+        route: &Route,
+        redundant: Option<ext::RedundantType>,
+    ) -> Vec<(Direction, Option<ext::RedundantType>)> {
+        let hat = &tables.hat_code;
+        let to_router = |outface: &FaceState| hat.map_face_type(tables, outface) == WhatAmI::Router;
+        // Only the routers are forwarded the redundant extension
+        let with_ext = |direction: &Direction, redundant| {
+            let ext = match to_router(&direction.0) {
+                true => redundant_ext(direction.2, redundant),
+                false => None,
+            };
+            (direction.clone(), ext)
+        };
+        match hat.map_face_type(tables, face) {
+            WhatAmI::Client => {
+                let redundant = Some(ext::RedundantType {
+                    zid: self.zid,
+                    sn: self.next_sn.fetch_add(1, Ordering::Relaxed),
+                    tree: false,
+                });
+                let redundant_route =
+                    get_local_data_route(tables, face, res, expr, REDUNDANT_CONTEXT);
+                route
+                    .values()
+                    .chain(
+                        redundant_route
+                            .values()
+                            .filter(|(outface, _, _)| to_router(outface)),
+                    )
+                    .map(|direction| with_ext(direction, redundant))
+                    .collect()
+            }
+            WhatAmI::Router => {
+                // The copies forwarded by the routers not supporting the redundant extension
+                // cannot be told apart
+                let first = redundant.map_or(true, |redundant| {
+                    zlock!(self.received).insert(redundant.zid, redundant.sn)
+                });
+                route
+                    .values()
+                    .filter(|(outface, _, _)| first || to_router(outface))
+                    .map(|direction| with_ext(direction, redundant))
+                    .collect()
+            }
+            WhatAmI::Peer => route
+                .values()
+                .map(|direction| with_ext(direction, redundant))
+                .collect(),
+        }
+    }
+}
+
 #[zenoh_macros::unstable]
 #[inline]
 pub(crate) fn get_matching_subscriptions(
@@ -253,7 +468,7 @@ macro_rules! inc_stats {
         paste::paste! {
             if let Some(stats) = $face.stats.as_ref() {
                 use zenoh_buffers::buffer::Buffer;
-                match &$body {
+                let inc = |body: &PushBody| match body {
                     PushBody::Put(p) => {
                         stats.[<$txrx _z_put_msgs>].[<inc_ $space>](1);
                         let mut n =  p.payload.len();
@@ -270,6 +485,12 @@ macro_rules! inc_stats {
                         }
                         stats.[<$txrx _z_del_pl_bytes>].[<inc_ $space>](n);
                     }
+                    PushBody::Batch(_) => {}
+                };
+                // The entries of a batch are accounted as individual puts and deletes
+                match &$body {
+                    PushBody::Batch(b) => b.entries.iter().for_each(|e| inc(&e.payload)),
+                    body => inc(body),
                 }
             }
         }
@@ -286,6 +507,9 @@ pub fn route_data(
     ext_qos: ext::QoSType,
     ext_tstamp: Option<ext::TimestampType>,
     ext_nodeid: ext::NodeIdType,
+    ext_hop_limit: Option<ext::HopLimitType>,
+    ext_lifespan: Option<ext::LifespanType>,
+    ext_redundant: Option<ext::RedundantType>,
     payload: impl FnOnce() -> PushBody,
     reliability: Reliability,
 ) {
@@ -319,16 +543,78 @@ pub fn route_data(
 
                 let route = get_data_route(&tables, face, &res, &mut expr, ext_nodeid.node_id);
 
-                if !route.is_empty() {
+                let hop_limit = HopLimit::new(&tables, face, ext_hop_limit.map(|hl| hl.hops));
+                if route
+                    .values()
+                    .any(|(outface, _, _)| !hop_limit.allows(outface))
+                {
+                    hop_limit.expired(face);
+                }
+
+                if let Some(redundancy) = tables
+                    .redundancy
+                    .as_ref()
+                    .filter(|redundancy| redundancy.includes(&mut expr))
+                {
+                    #[cfg(not(feature = "stats"))]
+                    let mut payload = payload();
+                    treat_timestamp!(&tables.hlc, payload, tables.drop_future_timestamp);
+                    lifespan::stamp(tables.hlc.as_deref(), &mut payload, ext_lifespan);
+                    if is_expired(payload.timestamp(), ext_lifespan) {
+                        tracing::debug!("{} Drop expired data for res {}", face, expr.full_expr());
+                        return;
+                    }
+
+                    let route = redundancy
+                        .directions(&tables, face, &res, &mut expr, &route, ext_redundant)
+                        .into_iter()
+                        .filter(|((outface, _key_expr, _context), _redundant)| {
+                            hop_limit.allows(outface)
+                                && tables
+                                    .hat_code
+                                    .egress_filter(&tables, face, outface, &mut expr)
+                        })
+                        .collect::<Vec<_>>();
+
+                    drop(tables);
+                    for ((outface, key_expr, context), redundant) in route {
+                        #[cfg(feature = "stats")]
+                        if !admin {
+                            inc_stats!(outface, tx, user, payload)
+                        } else {
+                            inc_stats!(outface, tx, admin, payload)
+                        }
+
+                        outface.primitives.send_push(
+                            Push {
+                                wire_expr: key_expr,
+                                ext_qos,
+                                ext_tstamp: None,
+                                ext_nodeid: nodeid_ext(context),
+                                ext_hop_limit: hop_limit.egress(&outface),
+                                ext_lifespan,
+                                ext_redundant: redundant,
+                                payload: payload.clone(),
+                            },
+                            reliability,
+                        )
+                    }
+                } else if !route.is_empty() {
                     #[cfg(not(feature = "stats"))]
                     let mut payload = payload();
                     treat_timestamp!(&tables.hlc, payload, tables.drop_future_timestamp);
+                    lifespan::stamp(tables.hlc.as_deref(), &mut payload, ext_lifespan);
+                    if is_expired(payload.timestamp(), ext_lifespan) {
+                        tracing::debug!("{} Drop expired data for res {}", face, expr.full_expr());
+                        return;
+                    }
 
                     if route.len() == 1 {
                         let (outface, key_expr, context) = route.values().next().unwrap();
-                        if tables
-                            .hat_code
-                            .egress_filter(&tables, face, outface, &mut expr)
+                        if hop_limit.allows(outface)
+                            && tables
+                                .hat_code
+                                .egress_filter(&tables, face, outface, &mut expr)
                         {
                             drop(tables);
                             #[cfg(feature = "stats")]
@@ -343,7 +629,10 @@ pub fn route_data(
                                     wire_expr: key_expr.into(),
                                     ext_qos,
                                     ext_tstamp,
-                                    ext_nodeid: ext::NodeIdType { node_id: *context },
+                                    ext_nodeid: nodeid_ext(*context),
+                                    ext_hop_limit: hop_limit.egress(outface),
+                                    ext_lifespan,
+                                    ext_redundant: redundant_ext(*context, ext_redundant),
                                     payload,
                                 },
                                 reliability,
@@ -353,9 +642,10 @@ pub fn route_data(
                         let route = route
                             .values()
                             .filter(|(outface, _key_expr, _context)| {
-                                tables
-                                    .hat_code
-                                    .egress_filter(&tables, face, outface, &mut expr)
+                                hop_limit.allows(outface)
+                                    && tables
+                                        .hat_code
+                                        .egress_filter(&tables, face, outface, &mut expr)
                             })
                             .cloned()
                             .collect::<Vec<Direction>>();
@@ -374,7 +664,10 @@ pub fn route_data(
                                     wire_expr: key_expr,
                                     ext_qos,
                                     ext_tstamp: None,
-                                    ext_nodeid: ext::NodeIdType { node_id: context },
+                                    ext_nodeid: nodeid_ext(context),
+                                    ext_hop_limit: hop_limit.egress(&outface),
+                                    ext_lifespan,
+                                    ext_redundant: redundant_ext(context, ext_redundant),
                                     payload: payload.clone(),
                                 },
                                 reliability,
@@ -12,22 +12,30 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use std::{
-    collections::HashMap,
-    sync::{Arc, Weak},
+    collections::{BTreeMap, HashMap},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Weak,
+    },
     time::Duration,
 };
 
 use async_trait::async_trait;
+use rand::seq::IteratorRandom;
+use serde::Serialize;
 use tokio_util::sync::CancellationToken;
 use zenoh_buffers::ZBuf;
+use zenoh_config::QueryLoadBalancing;
 #[cfg(feature = "stats")]
 use zenoh_protocol::zenoh::reply::ReplyBody;
 use zenoh_protocol::{
+    common::ZExtBody,
     core::{key_expr::keyexpr, Encoding, WireExpr},
     network::{
         declare::{ext, queryable::ext::QueryableInfoType, QueryableId},
+        oam::{self, id::OAM_QUERY_CANCEL, Oam},
         request::{
-            ext::{BudgetType, QueryTarget, TimeoutType},
+            ext::{BudgetType, HopLimitType, QueryTarget, TimeoutType},
             Request, RequestId,
         },
         response::{self, ext::ResponderIdType, Response, ResponseFinal},
@@ -39,14 +47,17 @@ use zenoh_util::Timed;
 
 use super::{
     face::FaceState,
-    resource::{QueryRoute, QueryTargetQablSet, Resource},
-    tables::{NodeId, RoutingExpr, Tables, TablesLock},
+    resource::{QueryRoute, QueryTargetQabl, QueryTargetQablSet, Resource},
+    tables::{HopLimit, NodeId, RoutingExpr, Tables, TablesLock},
 };
 #[cfg(feature = "unstable")]
 use crate::key_expr::KeyExpr;
-use crate::net::routing::{
-    hat::{HatTrait, SendDeclare},
-    router::get_or_set_route,
+use crate::net::{
+    protocol::query_credit::QueryCredit,
+    routing::{
+        hat::{HatTrait, SendDeclare},
+        router::get_or_set_route,
+    },
 };
 
 pub(crate) struct Query {
@@ -54,6 +65,99 @@ pub(crate) struct Query {
     src_qid: RequestId,
 }
 
+/// Counts a query routed by the [`QueryBalancer`] among the outstanding queries of its
+/// queryable until it is removed from the pending queries of the face.
+pub(crate) struct OutstandingQuery(Arc<AtomicUsize>);
+
+impl OutstandingQuery {
+    fn new(qabl: &QueryTargetQabl) -> Self {
+        let mut outstanding = zlock!(qabl.direction.0.outstanding_queries);
+        // Counters without pending query are dropped, they would restart from zero anyway
+        outstanding.retain(|_, count| Arc::strong_count(count) > 1);
+        let count = outstanding.entry(qabl.target).or_default().clone();
+        count.fetch_add(1, Ordering::Relaxed);
+        OutstandingQuery(count)
+    }
+
+    /// The number of outstanding queries of the given queryable.
+    fn count(qabl: &QueryTargetQabl) -> usize {
+        zlock!(qabl.direction.0.outstanding_queries)
+            .get(&qabl.target)
+            .map_or(0, |count| count.load(Ordering::Relaxed))
+    }
+}
+
+impl Drop for OutstandingQuery {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Distributes the queries targeting the best matching queryable among the
+/// complete queryables, according to the configured [`QueryLoadBalancing`].
+pub(crate) struct QueryBalancer {
+    policy: QueryLoadBalancing,
+}
+
+/// The adminspace view of the [`QueryBalancer`].
+#[derive(Serialize)]
+pub(crate) struct QueryBalancerStats {
+    policy: QueryLoadBalancing,
+    routed: BTreeMap<String, usize>,
+}
+
+impl QueryBalancer {
+    pub(crate) fn new(policy: QueryLoadBalancing) -> Self {
+        QueryBalancer { policy }
+    }
+
+    /// Selects a queryable among the `candidates` of `route`, sorted by increasing distance.
+    fn select<'a>(
+        &self,
+        route: &QueryTargetQablSet,
+        mut candidates: impl Iterator<Item = &'a QueryTargetQabl>,
+    ) -> Option<&'a QueryTargetQabl> {
+        let selected = match self.policy {
+            QueryLoadBalancing::First => candidates.next(),
+            QueryLoadBalancing::RoundRobin => {
+                let candidates = candidates.collect::<Vec<_>>();
+                (!candidates.is_empty()).then(|| {
+                    candidates[route.next.fetch_add(1, Ordering::Relaxed) % candidates.len()]
+                })
+            }
+            QueryLoadBalancing::Random => candidates.choose(&mut rand::thread_rng()),
+            QueryLoadBalancing::LeastOutstanding => {
+                candidates.min_by_key(|qabl| OutstandingQuery::count(qabl))
+            }
+        };
+        if let Some(qabl) = selected {
+            qabl.direction
+                .0
+                .balanced_queries
+                .fetch_add(1, Ordering::Relaxed);
+        }
+        selected
+    }
+
+    /// The number of queries routed to each of the given faces.
+    pub(crate) fn stats<'a>(
+        &self,
+        faces: impl Iterator<Item = &'a Arc<FaceState>>,
+    ) -> QueryBalancerStats {
+        let mut routed = BTreeMap::new();
+        for face in faces {
+            let count = face.balanced_queries.load(Ordering::Relaxed);
+            if count != 0 {
+                *routed.entry(face.zid.to_string()).or_default() += count;
+            }
+        }
+        QueryBalancerStats {
+            policy: self.policy,
+            routed,
+        }
+    }
+}
+
 #[zenoh_macros::unstable]
 #[inline]
 pub(crate) fn get_matching_queryables(
@@ -184,7 +288,11 @@ pub(crate) fn undeclare_queryable(
 }
 
 #[inline]
-fn insert_pending_query(outface: &mut Arc<FaceState>, query: Arc<Query>) -> RequestId {
+fn insert_pending_query(
+    outface: &mut Arc<FaceState>,
+    query: Arc<Query>,
+    outstanding: Option<OutstandingQuery>,
+) -> RequestId {
     let outface_mut = get_mut_unchecked(outface);
     // This `wrapping_add` is kind of "safe" because it would require an incredible amount
     // of parallel running queries to conflict a currently used id.
@@ -194,7 +302,11 @@ fn insert_pending_query(outface: &mut Arc<FaceState>, query: Arc<Query>) -> Requ
     let qid = outface_mut.next_qid;
     outface_mut.pending_queries.insert(
         qid,
-        (query, outface_mut.task_controller.get_cancellation_token()),
+        (
+            query,
+            outface_mut.task_controller.get_cancellation_token(),
+            outstanding,
+        ),
     );
     qid
 }
@@ -206,19 +318,21 @@ fn compute_final_route(
     src_face: &Arc<FaceState>,
     expr: &mut RoutingExpr,
     target: &QueryTarget,
+    hop_limit: &HopLimit,
     query: Arc<Query>,
 ) -> QueryRoute {
     match target {
         QueryTarget::All => {
             let mut route = HashMap::new();
             for qabl in qabls.iter() {
-                if tables
-                    .hat_code
-                    .egress_filter(tables, src_face, &qabl.direction.0, expr)
+                if hop_limit.allows(&qabl.direction.0)
+                    && tables
+                        .hat_code
+                        .egress_filter(tables, src_face, &qabl.direction.0, expr)
                 {
                     route.entry(qabl.direction.0.id).or_insert_with(|| {
                         let mut direction = qabl.direction.clone();
-                        let qid = insert_pending_query(&mut direction.0, query.clone());
+                        let qid = insert_pending_query(&mut direction.0, query.clone(), None);
                         (direction, qid)
                     });
                 }
@@ -229,13 +343,14 @@ fn compute_final_route(
             let mut route = HashMap::new();
             for qabl in qabls.iter() {
                 if qabl.info.map(|info| info.complete).unwrap_or(true)
+                    && hop_limit.allows(&qabl.direction.0)
                     && tables
                         .hat_code
                         .egress_filter(tables, src_face, &qabl.direction.0, expr)
                 {
                     route.entry(qabl.direction.0.id).or_insert_with(|| {
                         let mut direction = qabl.direction.clone();
-                        let qid = insert_pending_query(&mut direction.0, query.clone());
+                        let qid = insert_pending_query(&mut direction.0, query.clone(), None);
                         (direction, qid)
                     });
                 }
@@ -243,18 +358,36 @@ fn compute_final_route(
             route
         }
         QueryTarget::BestMatching => {
-            if let Some(qabl) = qabls.iter().find(|qabl| {
-                qabl.direction.0.id != src_face.id && qabl.info.is_some_and(|info| info.complete)
-            }) {
+            if let Some(qabl) = tables.query_balancer.select(
+                qabls,
+                qabls.iter().filter(|qabl| {
+                    qabl.direction.0.id != src_face.id
+                        && qabl.info.is_some_and(|info| info.complete)
+                        && hop_limit.allows(&qabl.direction.0)
+                }),
+            ) {
                 let mut route = HashMap::new();
 
                 let mut direction = qabl.direction.clone();
-                let qid = insert_pending_query(&mut direction.0, query);
+                let qid = insert_pending_query(
+                    &mut direction.0,
+                    query,
+                    (tables.query_balancer.policy == QueryLoadBalancing::LeastOutstanding)
+                        .then(|| OutstandingQuery::new(qabl)),
+                );
                 route.insert(direction.0.id, (direction, qid));
 
                 route
             } else {
-                compute_final_route(tables, qabls, src_face, expr, &QueryTarget::All, query)
+                compute_final_route(
+                    tables,
+                    qabls,
+                    src_face,
+                    expr,
+                    &QueryTarget::All,
+                    hop_limit,
+                    query,
+                )
             }
         }
     }
@@ -281,7 +414,7 @@ impl QueryCleanup {
             qid,
             timeout,
         };
-        if let Some((_, cancellation_token)) = face.pending_queries.get(&qid) {
+        if let Some((_, cancellation_token, _)) = face.pending_queries.get(&qid) {
             let c_cancellation_token = cancellation_token.clone();
             face.task_controller
                 .spawn_with_rt(zenoh_runtime::ZRuntime::Net, async move {
@@ -333,6 +466,9 @@ impl Timed for QueryCleanup {
                     query.0.src_qid,
                     self.timeout,
                 );
+                // The responders still handling the query, e.g. waiting for response credits,
+                // would otherwise never learn it is over
+                send_query_cancel(&face, self.qid);
                 finalize_pending_query(query);
             }
         }
@@ -363,7 +499,8 @@ fn get_query_route(
 ) -> Arc<QueryTargetQablSet> {
     let hat = &tables.hat_code;
     let local_context = hat.map_routing_context(tables, face, routing_context);
-    let mut compute_route = || hat.compute_query_route(tables, expr, local_context, face.whatami);
+    let source_type = hat.map_face_type(tables, face);
+    let mut compute_route = || hat.compute_query_route(tables, expr, local_context, source_type);
     if let Some(query_routes) = res
         .as_ref()
         .and_then(|res| res.context.as_ref())
@@ -372,7 +509,7 @@ fn get_query_route(
         return get_or_set_route(
             query_routes,
             tables.routes_version,
-            face.whatami,
+            source_type,
             local_context,
             compute_route,
         );
@@ -431,6 +568,7 @@ macro_rules! inc_res_stats {
                                    n += a.buffer.len();
                                 }
                             }
+                            ReplyBody::Batch(_) => {}
                         }
                         stats.[<$txrx _z_reply_pl_bytes>].[<inc_ $space>](n);
                     }
@@ -457,6 +595,7 @@ pub fn route_query(
     ext_target: QueryTarget,
     ext_budget: Option<BudgetType>,
     ext_timeout: Option<TimeoutType>,
+    ext_hop_limit: Option<HopLimitType>,
     body: RequestBody,
     routing_context: NodeId,
 ) {
@@ -487,14 +626,38 @@ pub fn route_query(
 
                 let route = get_query_route(&rtables, face, &res, &mut expr, routing_context);
 
+                let hop_limit = HopLimit::new(&rtables, face, ext_hop_limit.map(|hl| hl.hops));
+                if route
+                    .iter()
+                    .any(|qabl| !hop_limit.allows(&qabl.direction.0))
+                {
+                    hop_limit.expired(face);
+                }
+
                 let query = Arc::new(Query {
                     src_face: face.clone(),
                     src_qid: qid,
                 });
 
                 let queries_lock = zwrite!(tables_ref.queries_lock);
-                let route =
-                    compute_final_route(&rtables, &route, face, &mut expr, &ext_target, query);
+                let route = compute_final_route(
+                    &rtables,
+                    &route,
+                    face,
+                    &mut expr,
+                    &ext_target,
+                    &hop_limit,
+                    query,
+                );
+                if !route.is_empty() {
+                    zlock!(face.routed_queries).insert(
+                        qid,
+                        route
+                            .values()
+                            .map(|((outface, _, _), outqid)| (Arc::downgrade(outface), *outqid))
+                            .collect(),
+                    );
+                }
                 let timeout = ext_timeout.unwrap_or(rtables.queries_default_timeout);
                 drop(queries_lock);
                 drop(rtables);
@@ -538,6 +701,7 @@ pub fn route_query(
                             ext_target,
                             ext_budget,
                             ext_timeout,
+                            ext_hop_limit: hop_limit.egress(outface),
                             payload: body.clone(),
                         });
                     }
@@ -590,7 +754,7 @@ pub(crate) fn route_send_response(
     }
 
     match face.pending_queries.get(&qid) {
-        Some((query, _)) => {
+        Some((query, _, _)) => {
             drop(queries_lock);
 
             #[cfg(feature = "stats")]
@@ -640,6 +804,129 @@ pub(crate) fn route_send_response_final(
     }
 }
 
+/// The faces and request ids the query `qid` received from `face` was routed to,
+/// removed from the pending queries if `remove` is set.
+fn routed_queries(
+    tables_ref: &Arc<TablesLock>,
+    face: &Arc<FaceState>,
+    qid: RequestId,
+    remove: bool,
+) -> Vec<(Arc<FaceState>, RequestId)> {
+    let routes = if remove {
+        zlock!(face.routed_queries).remove(&qid)
+    } else {
+        zlock!(face.routed_queries).get(&qid).cloned()
+    };
+    let Some(routes) = routes else {
+        return vec![];
+    };
+    let queries_lock = zwrite!(tables_ref.queries_lock);
+    let routed = routes
+        .into_iter()
+        .filter_map(|(outface, rid)| {
+            let mut outface = outface.upgrade()?;
+            let (query, _, _) = outface.pending_queries.get(&rid)?;
+            if query.src_face.id != face.id || query.src_qid != qid {
+                return None;
+            }
+            if remove {
+                if let Some((_, cancellation_token, _)) =
+                    get_mut_unchecked(&mut outface).pending_queries.remove(&rid)
+                {
+                    cancellation_token.cancel();
+                }
+            }
+            Some((outface, rid))
+        })
+        .collect();
+    drop(queries_lock);
+    routed
+}
+
+/// Cancels the query `qid` received from `face`, propagating the cancellation to the faces
+/// it was routed to. No final reply is propagated back to `face`.
+pub(crate) fn route_cancel_query(
+    tables_ref: &Arc<TablesLock>,
+    face: &Arc<FaceState>,
+    qid: RequestId,
+) {
+    let routed = routed_queries(tables_ref, face, qid, true);
+    if routed.is_empty() {
+        tracing::debug!(
+            "Route query cancellation {}:{}: Query not found!",
+            face,
+            qid
+        );
+    }
+    for (outface, rid) in routed {
+        tracing::debug!(
+            "Propagate query cancellation {}:{} to {}:{}",
+            face,
+            qid,
+            outface,
+            rid
+        );
+        send_query_cancel(&outface, rid);
+    }
+}
+
+/// Cancels the queries received from `face` that are still routed, e.g. when it is closed.
+pub(crate) fn cancel_routed_queries(tables_ref: &Arc<TablesLock>, face: &Arc<FaceState>) {
+    let qids = zlock!(face.routed_queries)
+        .keys()
+        .copied()
+        .collect::<Vec<_>>();
+    for qid in qids {
+        route_cancel_query(tables_ref, face, qid);
+    }
+}
+
+#[inline]
+fn send_query_cancel(outface: &FaceState, rid: RequestId) {
+    outface.primitives.send_oam(Oam {
+        id: OAM_QUERY_CANCEL,
+        body: ZExtBody::Z64(rid as u64),
+        ext_qos: oam::ext::QoSType::OAM,
+        ext_tstamp: None,
+    });
+}
+
+/// Propagates the response credits granted by `face` for the query `credit.rid` to the faces
+/// it was routed to. The responder targeted by the credits is left untouched.
+pub(crate) fn route_grant_query_credit(
+    tables_ref: &Arc<TablesLock>,
+    face: &Arc<FaceState>,
+    credit: QueryCredit,
+) {
+    let routed = routed_queries(tables_ref, face, credit.rid, false);
+    if routed.is_empty() {
+        tracing::debug!(
+            "Route query credit {}:{}: Query not found!",
+            face,
+            credit.rid
+        );
+    }
+    for (outface, rid) in routed {
+        tracing::trace!(
+            "Propagate query credit {}:{} to {}:{} ({} for {})",
+            face,
+            credit.rid,
+            outface,
+            rid,
+            credit.credit,
+            credit.responder
+        );
+        let credit = QueryCredit {
+            rid,
+            ..credit.clone()
+        };
+        match credit.to_oam() {
+            Ok(oam) => outface.primitives.send_oam(oam),
+            Err(_) => tracing::error!("Failed to encode query credit {}:{}", outface, rid),
+        }
+    }
+}
+
 pub(crate) fn finalize_pending_queries(tables_ref: &TablesLock, face: &mut Arc<FaceState>) {
     let queries_lock = zwrite!(tables_ref.queries_lock);
     for (_, query) in get_mut_unchecked(face).pending_queries.drain() {
@@ -648,10 +935,13 @@ pub(crate) fn finalize_pending_queries(tables_ref: &TablesLock, face: &mut Arc<F
     drop(queries_lock);
 }
 
-pub(crate) fn finalize_pending_query(query: (Arc<Query>, CancellationToken)) {
-    let (query, cancellation_token) = query;
+pub(crate) fn finalize_pending_query(
+    query: (Arc<Query>, CancellationToken, Option<OutstandingQuery>),
+) {
+    let (query, cancellation_token, _) = query;
     cancellation_token.cancel();
     if let Some(query) = Arc::into_inner(query) {
+        zlock!(query.src_face.routed_queries).remove(&query.src_qid);
         tracing::debug!("Propagate final reply {}:{}", query.src_face, query.src_qid);
         query
             .src_face
@@ -17,14 +17,18 @@ use std::{
     collections::HashMap,
     convert::TryInto,
     hash::{Hash, Hasher},
-    sync::{Arc, RwLock, Weak},
+    ops::{Deref, DerefMut},
+    sync::{atomic::AtomicUsize, Arc, RwLock, Weak},
 };
 
 use zenoh_config::WhatAmI;
 use zenoh_protocol::{
-    core::{key_expr::keyexpr, ExprId, WireExpr},
+    core::{key_expr::keyexpr, ExprId, WireExpr, ZenohIdProto},
     network::{
-        declare::{ext, queryable::ext::QueryableInfoType, Declare, DeclareBody, DeclareKeyExpr},
+        declare::{
+            ext, queryable::ext::QueryableInfoType, Declare, DeclareBody, DeclareKeyExpr,
+            QueryableId,
+        },
         interest::InterestId,
         Mapping, RequestId,
     },
@@ -44,15 +48,72 @@ use crate::net::routing::{
 
 pub(crate) type NodeId = u16;
 
+/// Flag of the routing contexts designating the redundant tree of their source node,
+/// see [`super::pubsub::Redundancy`].
+pub(crate) const REDUNDANT_CONTEXT: NodeId = 0x8000;
+
 pub(crate) type Direction = (Arc<FaceState>, WireExpr<'static>, NodeId);
 pub(crate) type Route = HashMap<usize, Direction>;
 
 pub(crate) type QueryRoute = HashMap<usize, (Direction, RequestId)>;
+
+/// Identifies a queryable among the ones reached through the face of a [`QueryTargetQabl`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum QueryableTarget {
+    /// The queryable declared by the face with the given id.
+    Declared(QueryableId),
+    /// The queryables of the given remote node.
+    Node(ZenohIdProto),
+}
+
+impl QueryableTarget {
+    /// The lowest id among the `qabls` declared by `face` on `res`.
+    pub(crate) fn declared<'a>(
+        face: &FaceState,
+        res: &Arc<Resource>,
+        qabls: impl Iterator<Item = (&'a QueryableId, &'a Arc<Resource>)>,
+    ) -> Self {
+        qabls
+            .filter(|(_, qabl_res)| Arc::ptr_eq(qabl_res, res))
+            .map(|(id, _)| *id)
+            .min()
+            .map_or(QueryableTarget::Node(face.zid), QueryableTarget::Declared)
+    }
+}
+
 pub(crate) struct QueryTargetQabl {
     pub(crate) direction: Direction,
     pub(crate) info: Option<QueryableInfoType>,
+    pub(crate) target: QueryableTarget,
+}
+
+/// The queryables matching a query, sorted by increasing distance.
+#[derive(Default)]
+pub(crate) struct QueryTargetQablSet {
+    qabls: Vec<QueryTargetQabl>,
+    /// The next queryable selected by the round-robin query load balancing.
+    pub(crate) next: AtomicUsize,
+}
+
+impl QueryTargetQablSet {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Deref for QueryTargetQablSet {
+    type Target = Vec<QueryTargetQabl>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.qabls
+    }
+}
+
+impl DerefMut for QueryTargetQablSet {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.qabls
+    }
 }
-pub(crate) type QueryTargetQablSet = Vec<QueryTargetQabl>;
 
 pub(crate) struct SessionContext {
     pub(crate) face: Arc<FaceState>,
@@ -175,6 +236,8 @@ pub(crate) struct ResourceContext {
     pub(crate) matches: Vec<Weak<Resource>>,
     pub(crate) hat: Box<dyn Any + Send + Sync>,
     pub(crate) data_routes: RwLock<DataRoutes>,
+    /// The data routes along the redundant trees
+    pub(crate) redundant_data_routes: RwLock<DataRoutes>,
     pub(crate) query_routes: RwLock<QueryRoutes>,
 }
 
@@ -184,12 +247,14 @@ impl ResourceContext {
             matches: Vec::new(),
             hat,
             data_routes: Default::default(),
+            redundant_data_routes: Default::default(),
             query_routes: Default::default(),
         }
     }
 
     pub(crate) fn disable_data_routes(&mut self) {
         self.data_routes.get_mut().unwrap().clear();
+        self.redundant_data_routes.get_mut().unwrap().clear();
     }
 
     pub(crate) fn disable_query_routes(&mut self) {
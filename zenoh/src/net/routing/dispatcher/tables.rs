@@ -19,19 +19,22 @@ use std::{
 };
 
 use uhlc::HLC;
-use zenoh_config::{unwrap_or_default, Config};
+use zenoh_config::{unwrap_or_default, Config, RegionConf, RegionGatewayConf};
 use zenoh_protocol::{
-    core::{ExprId, WhatAmI, ZenohIdProto},
-    network::Mapping,
+    core::{
+        key_expr::{keyexpr, OwnedKeyExpr},
+        ExprId, WhatAmI, ZenohIdProto,
+    },
+    network::{ext::HopLimitType, Mapping},
 };
-use zenoh_result::ZResult;
+use zenoh_result::{bail, ZResult};
 
-use super::face::FaceState;
 pub use super::resource::*;
+use super::{face::FaceState, pubsub::Redundancy, queries::QueryBalancer};
 use crate::net::{
     routing::{
         hat::{self, HatTrait},
-        interceptor::{interceptor_factories, InterceptorFactory},
+        interceptor::{interceptor_factories, AclEnforcer, InterceptorFactory},
     },
     runtime::WeakRuntime,
 };
@@ -61,6 +64,146 @@ impl<'a> RoutingExpr<'a> {
     }
 }
 
+/// The hop limit of a routed message, see [`HopLimitType`].
+///
+/// Each link to another node consumes a hop, the messages delivered to the local sessions do not.
+#[derive(Clone, Copy)]
+pub(crate) struct HopLimit {
+    zid: ZenohIdProto,
+    hops: Option<u8>,
+}
+
+impl HopLimit {
+    /// The hop limit of a message received from `face`, the messages originated by this
+    /// node getting the configured default.
+    pub(crate) fn new(tables: &Tables, face: &FaceState, hops: Option<u8>) -> Self {
+        let hops = hops.or(if face.zid == tables.zid {
+            tables.hop_limit
+        } else {
+            None
+        });
+        HopLimit {
+            zid: tables.zid,
+            hops,
+        }
+    }
+
+    /// Whether the message may be sent to `outface`.
+    #[inline]
+    pub(crate) fn allows(&self, outface: &FaceState) -> bool {
+        outface.zid == self.zid || self.hops != Some(0)
+    }
+
+    /// The hop limit of the message sent to `outface`.
+    #[inline]
+    pub(crate) fn egress<const ID: u8>(&self, outface: &FaceState) -> Option<HopLimitType<ID>> {
+        self.hops.map(|hops| HopLimitType {
+            hops: if outface.zid == self.zid {
+                hops
+            } else {
+                hops.saturating_sub(1)
+            },
+        })
+    }
+
+    /// Records that the message received from `face` was dropped for some of its destinations.
+    pub(crate) fn expired(&self, face: &FaceState) {
+        tracing::trace!("{} Hop limit expired, message dropped", face);
+        #[cfg(feature = "stats")]
+        if let Some(stats) = face.stats.as_ref() {
+            stats.inc_rx_n_expired(1);
+        }
+    }
+}
+
+/// The region of a router and the gateway routers of other regions it is connected to.
+///
+/// The transports to these gateways carry no link-state: their faces are handled like client
+/// faces, each gateway declaring the subscribers, queryables and tokens of its whole region.
+/// When several gateway links connect two regions, a single one is elected to carry their
+/// declarations and data, each link being advertised in its region by a token on [`Region::link`].
+pub(crate) struct Region {
+    name: String,
+    gateways: HashMap<ZenohIdProto, RegionGatewayConf>,
+}
+
+impl Region {
+    fn new(conf: &RegionConf) -> ZResult<Option<Self>> {
+        let Some(name) = conf.name() else {
+            if !conf.gateways().is_empty() {
+                bail!("Region gateways are configured but the region name is not");
+            }
+            return Ok(None);
+        };
+        Self::check_name(name)?;
+        let mut gateways = HashMap::new();
+        for gateway in conf.gateways() {
+            Self::check_name(&gateway.region)?;
+            if gateway.region == *name {
+                bail!(
+                    "Region gateway {} should belong to another region than '{}'",
+                    gateway.zid,
+                    name
+                );
+            }
+            gateways.insert(gateway.zid.into(), gateway.clone());
+        }
+        Ok(Some(Region {
+            name: name.clone(),
+            gateways,
+        }))
+    }
+
+    /// Region names are single key expression chunks, as they are part of the [`Region::link`] key expressions.
+    fn check_name(name: &str) -> ZResult<()> {
+        match keyexpr::new(name) {
+            Ok(ke) if !ke.is_wild() && !name.contains('/') && !name.starts_with('@') => Ok(()),
+            _ => bail!(
+                "Invalid region name '{}': expected a single key expression chunk without wildcards",
+                name
+            ),
+        }
+    }
+
+    /// The region of `zid` if it is a gateway router of another region.
+    #[inline]
+    pub(crate) fn gateway(&self, zid: &ZenohIdProto) -> Option<&str> {
+        self.gateways
+            .get(zid)
+            .map(|gateway| gateway.region.as_str())
+    }
+
+    /// The key expressions on which the gateway router `zid` aggregates the declarations of its region.
+    #[inline]
+    pub(crate) fn aggregation(&self, zid: &ZenohIdProto) -> &[OwnedKeyExpr] {
+        self.gateways
+            .get(zid)
+            .map(|gateway| gateway.aggregation.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// The key expression advertising the link between this router `zid` and the gateway router
+    /// `gateway`: `@gateway/<region of the gateway>/<zid>/<zid>`, the zids being sorted so that
+    /// the routers of both regions elect the same link, the one with the lowest key expression.
+    pub(crate) fn link(&self, zid: &ZenohIdProto, gateway: &ZenohIdProto) -> Option<OwnedKeyExpr> {
+        let region = self.gateway(gateway)?;
+        let (min, max) = if zid < gateway {
+            (zid, gateway)
+        } else {
+            (gateway, zid)
+        };
+        OwnedKeyExpr::try_from(format!("{GATEWAY_LINKS}/{region}/{min}/{max}")).ok()
+    }
+
+    /// The key expression matching the links to the gateways of `region`.
+    pub(crate) fn links(region: &str) -> Option<OwnedKeyExpr> {
+        OwnedKeyExpr::try_from(format!("{GATEWAY_LINKS}/{region}/**")).ok()
+    }
+}
+
+/// The prefix of the key expressions advertising the gateway links of a region.
+pub(crate) const GATEWAY_LINKS: &str = "@gateway";
+
 pub struct Tables {
     pub(crate) zid: ZenohIdProto,
     pub(crate) whatami: WhatAmI,
@@ -71,11 +214,19 @@ pub struct Tables {
     pub(crate) drop_future_timestamp: bool,
     pub(crate) queries_default_timeout: Duration,
     pub(crate) interests_timeout: Duration,
+    pub(crate) query_balancer: QueryBalancer,
+    /// The hop limit of the data and query messages originated by this node
+    pub(crate) hop_limit: Option<u8>,
+    pub(crate) redundancy: Option<Redundancy>,
+    /// The region of this router, `None` if regions are not configured
+    pub(crate) region: Option<Region>,
     pub(crate) root_res: Arc<Resource>,
     pub(crate) faces: HashMap<usize, Arc<FaceState>>,
     pub(crate) mcast_groups: Vec<Arc<FaceState>>,
     pub(crate) mcast_faces: Vec<Arc<FaceState>>,
     pub(crate) interceptors: Vec<InterceptorFactory>,
+    /// The access control enforcer, `None` if access control was disabled at startup
+    pub(crate) acl: Option<AclEnforcer>,
     pub(crate) hat: Box<dyn Any + Send + Sync>,
     pub(crate) hat_code: Arc<dyn HatTrait + Send + Sync>, // @TODO make this a Box
     pub(crate) routes_version: RoutesVersion,
@@ -96,7 +247,19 @@ impl Tables {
             Duration::from_millis(unwrap_or_default!(config.queries_default_timeout()));
         let interests_timeout =
             Duration::from_millis(unwrap_or_default!(config.routing().interests().timeout()));
+        let query_load_balancing = unwrap_or_default!(config.routing().queries().load_balancing());
+        let redundant_key_exprs = config.routing().router().redundancy().key_exprs();
+        let redundancy = (whatami == WhatAmI::Router && !redundant_key_exprs.is_empty())
+            .then(|| Redundancy::new(zid, redundant_key_exprs.clone()));
+        let region = match whatami {
+            WhatAmI::Router => Region::new(config.routing().router().region())?,
+            _ => None,
+        };
+        if let Some(region) = &region {
+            tracing::debug!("Router of region '{}'", region.name);
+        }
         let hat_code = hat::new_hat(whatami, config);
+        let acl = AclEnforcer::new(config.access_control())?;
         Ok(Tables {
             zid,
             whatami,
@@ -106,11 +269,16 @@ impl Tables {
             drop_future_timestamp,
             queries_default_timeout,
             interests_timeout,
+            query_balancer: QueryBalancer::new(query_load_balancing),
+            hop_limit: config.routing().hop_limit().map(|hops| hops.get()),
+            redundancy,
+            region,
             root_res: Resource::root(),
             faces: HashMap::new(),
             mcast_groups: vec![],
             mcast_faces: vec![],
-            interceptors: interceptor_factories(config)?,
+            interceptors: interceptor_factories(config, acl.clone())?,
+            acl,
             hat: hat_code.new_tables(router_peers_failover_brokering),
             hat_code: hat_code.into(),
             routes_version: 0,
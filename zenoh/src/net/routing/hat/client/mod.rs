@@ -248,6 +248,11 @@ impl HatBaseTrait for HatCode {
         0
     }
 
+    #[inline]
+    fn map_face_type(&self, _tables: &Tables, face: &FaceState) -> WhatAmI {
+        face.whatami
+    }
+
     #[inline]
     fn ingress_filter(&self, _tables: &Tables, _face: &FaceState, _expr: &mut RoutingExpr) -> bool {
         true
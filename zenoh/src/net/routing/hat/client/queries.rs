@@ -38,7 +38,7 @@ use crate::{
     net::routing::{
         dispatcher::{
             face::FaceState,
-            resource::{NodeId, Resource, SessionContext},
+            resource::{NodeId, QueryableTarget, Resource, SessionContext},
             tables::{QueryTargetQabl, QueryTargetQablSet, RoutingExpr, Tables},
         },
         hat::{HatQueriesTrait, SendDeclare, Sources},
@@ -277,7 +277,7 @@ pub(super) fn queries_new_face(
 }
 
 lazy_static::lazy_static! {
-    static ref EMPTY_ROUTE: Arc<QueryTargetQablSet> = Arc::new(Vec::new());
+    static ref EMPTY_ROUTE: Arc<QueryTargetQablSet> = Arc::new(QueryTargetQablSet::new());
 }
 
 impl HatQueriesTrait for HatCode {
@@ -392,6 +392,7 @@ impl HatQueriesTrait for HatCode {
                     route.push(QueryTargetQabl {
                         direction: (face.clone(), key_expr.to_owned(), NodeId::default()),
                         info: None,
+                        target: QueryableTarget::Node(face.zid),
                     });
                 }
             }
@@ -416,6 +417,11 @@ impl HatQueriesTrait for HatCode {
                             complete: complete && qabl_info.complete,
                             distance: 1,
                         }),
+                        target: QueryableTarget::declared(
+                            &context.face,
+                            &mres,
+                            face_hat!(context.face).remote_qabls.iter(),
+                        ),
                     });
                 }
             }
@@ -0,0 +1,208 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! ⚠️ WARNING ⚠️
+//!
+//! This module is intended for Zenoh's internal use.
+//!
+//! [Click here for Zenoh's documentation](https://docs.rs/zenoh/latest/zenoh)
+//!
+//! Weights of the links of the linkstate networks, either configured or derived
+//! from round-trip time probes exchanged in OAM messages.
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use zenoh_config::LinkstateConf;
+use zenoh_protocol::{
+    common::ZExtBody,
+    core::ZenohIdProto,
+    network::{oam, oam::id::OAM_LINK_RTT, NetworkBody, NetworkMessage, Oam},
+};
+use zenoh_result::{bail, ZResult};
+use zenoh_transport::unicast::TransportUnicast;
+
+use crate::net::{protocol::linkstate::DEFAULT_LINK_WEIGHT, runtime::Runtime};
+
+lazy_static::lazy_static! {
+    static ref RTT_PROBE_EPOCH: Instant = Instant::now();
+}
+
+/// Flag of the probes sent back to their emitter.
+const RTT_PROBE_REPLY: u64 = 1;
+/// Round-trip time corresponding to one unit of weight.
+const RTT_PER_WEIGHT_UNIT: Duration = Duration::from_micros(100);
+
+/// The link weights settings of a linkstate network.
+#[derive(Clone, Default)]
+pub(crate) struct LinkWeights {
+    /// Weights of the links to the given nodes.
+    pub(crate) configured: HashMap<ZenohIdProto, u16>,
+    /// Interval between round-trip time probes, `None` if the links are not probed.
+    pub(crate) rtt_probe_interval: Option<Duration>,
+}
+
+impl LinkWeights {
+    pub(crate) fn new(conf: &LinkstateConf) -> ZResult<Self> {
+        let mut configured = HashMap::new();
+        for tw in conf.transport_weights() {
+            if configured
+                .insert(tw.dst_zid.into(), tw.weight.get())
+                .is_some()
+            {
+                bail!("Duplicated transport weight for {}", tw.dst_zid);
+            }
+        }
+        let rtt_probe_interval = match conf.rtt_probe_interval_ms() {
+            Some(0) => bail!("Invalid rtt_probe_interval_ms: 0 (should be a positive number)"),
+            interval => interval.map(Duration::from_millis),
+        };
+        Ok(Self {
+            configured,
+            rtt_probe_interval,
+        })
+    }
+
+    /// The weight of a link with the given (smoothed) round-trip time.
+    pub(crate) fn rtt_weight(rtt: Duration) -> u16 {
+        let units = rtt.as_nanos() / RTT_PER_WEIGHT_UNIT.as_nanos();
+        units.clamp(1, u16::MAX as u128) as u16
+    }
+
+    /// Sets the configured weight of a new link of this node to `zid` in its `weights`, or
+    /// starts probing the round-trip time of the link if it has none and `probe` is true.
+    pub(crate) fn new_link(
+        &self,
+        runtime: &Runtime,
+        transport: &TransportUnicast,
+        zid: ZenohIdProto,
+        weights: &mut HashMap<ZenohIdProto, u16>,
+        probe: bool,
+    ) {
+        match self.configured.get(&zid) {
+            Some(&weight) if weight != DEFAULT_LINK_WEIGHT => {
+                weights.insert(zid, weight);
+            }
+            Some(_) => {}
+            None if probe => self.spawn_rtt_probes(runtime, transport.clone()),
+            None => {}
+        }
+    }
+
+    /// Smooths the round-trip time `srtt` of the link to `zid` with a new measurement, and
+    /// returns the new weight of the link if it differs by more than 20% from the `current` one.
+    ///
+    /// The links with a configured weight are never updated.
+    pub(crate) fn rtt_update(
+        &self,
+        zid: &ZenohIdProto,
+        srtt: &mut Option<Duration>,
+        rtt: Duration,
+        current: u16,
+    ) -> Option<u16> {
+        if self.rtt_probe_interval.is_none() || self.configured.contains_key(zid) {
+            return None;
+        }
+        let smoothed = match *srtt {
+            Some(srtt) => (srtt * 7 + rtt) / 8,
+            None => rtt,
+        };
+        *srtt = Some(smoothed);
+        let weight = Self::rtt_weight(smoothed);
+        if weight.abs_diff(current) * 5 <= current {
+            return None;
+        }
+        tracing::debug!(
+            "Update weight of link to {}: {} (rtt: {:?})",
+            zid,
+            weight,
+            smoothed
+        );
+        Some(weight)
+    }
+
+    /// Periodically probes the round-trip time of a link until its transport is closed.
+    pub(crate) fn spawn_rtt_probes(&self, runtime: &Runtime, transport: TransportUnicast) {
+        let Some(interval) = self.rtt_probe_interval else {
+            return;
+        };
+        runtime.spawn_abortable(async move {
+            loop {
+                let sent = RTT_PROBE_EPOCH.elapsed().as_micros() as u64;
+                if transport.schedule(rtt_probe_msg(sent << 1)).is_err() {
+                    break;
+                }
+                tokio::time::sleep(interval).await;
+            }
+        });
+    }
+}
+
+/// The weight of the link to `zid` in the link `weights` of a node.
+pub(crate) fn link_weight(weights: &HashMap<ZenohIdProto, u16>, zid: &ZenohIdProto) -> u16 {
+    weights.get(zid).copied().unwrap_or(DEFAULT_LINK_WEIGHT)
+}
+
+/// The weight of the edge between two nodes, both ends of a link may advertise different
+/// weights, the highest one applies.
+pub(crate) fn edge_weight(
+    (zid1, weights1): (&ZenohIdProto, &HashMap<ZenohIdProto, u16>),
+    (zid2, weights2): (&ZenohIdProto, &HashMap<ZenohIdProto, u16>),
+) -> u16 {
+    link_weight(weights1, zid2).max(link_weight(weights2, zid1))
+}
+
+/// The weights of the given `links` to advertise in a link state, `None` if they all have the
+/// default weight so that the link state stays compatible with the nodes ignoring weights.
+pub(crate) fn encode_link_weights<'a>(
+    links: impl Iterator<Item = &'a ZenohIdProto>,
+    weights: &HashMap<ZenohIdProto, u16>,
+) -> Option<Vec<u16>> {
+    (!weights.is_empty()).then(|| links.map(|zid| link_weight(weights, zid)).collect())
+}
+
+/// The weight of the `i`th link of a received link state, `None` if it has the default weight.
+pub(crate) fn decode_link_weight(weights: &Option<Vec<u16>>, i: usize) -> Option<u16> {
+    weights
+        .as_ref()
+        .and_then(|w| w.get(i).copied())
+        .filter(|weight| *weight != DEFAULT_LINK_WEIGHT)
+}
+
+fn rtt_probe_msg(probe: u64) -> NetworkMessage {
+    NetworkBody::OAM(Oam {
+        id: OAM_LINK_RTT,
+        body: ZExtBody::Z64(probe),
+        ext_qos: oam::ext::QoSType::OAM,
+        ext_tstamp: None,
+    })
+    .into()
+}
+
+/// Sends probe requests back to their emitter, and returns the round-trip time of probe replies.
+pub(crate) fn handle_rtt_probe(oam: &Oam, transport: &TransportUnicast) -> Option<Duration> {
+    let ZExtBody::Z64(probe) = oam.body else {
+        return None;
+    };
+    if probe & RTT_PROBE_REPLY == 0 {
+        if let Err(e) = transport.schedule(rtt_probe_msg(probe | RTT_PROBE_REPLY)) {
+            tracing::debug!("Error sending round-trip time probe reply: {}", e);
+        }
+        None
+    } else {
+        let now = RTT_PROBE_EPOCH.elapsed().as_micros() as u64;
+        Some(Duration::from_micros(now.saturating_sub(probe >> 1)))
+    }
+}
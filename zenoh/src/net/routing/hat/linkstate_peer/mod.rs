@@ -31,7 +31,7 @@ use zenoh_protocol::{
     network::{
         declare::{queryable::ext::QueryableInfoType, QueryableId, SubscriberId},
         interest::InterestId,
-        oam::id::OAM_LINKSTATE,
+        oam::id::{OAM_LINKSTATE, OAM_LINK_RTT},
         Oam,
     },
 };
@@ -57,7 +57,10 @@ use crate::net::{
     protocol::linkstate::LinkStateList,
     routing::{
         dispatcher::{face::Face, interests::RemoteInterest},
-        hat::TREES_COMPUTATION_DELAY_MS,
+        hat::{
+            link_weights::{handle_rtt_probe, LinkWeights},
+            TREES_COMPUTATION_DELAY_MS,
+        },
     },
     runtime::Runtime,
 };
@@ -201,6 +204,7 @@ impl HatBaseTrait for HatCode {
             unwrap_or_default!(config.routing().peer().mode()) == *"linkstate";
         let router_peers_failover_brokering =
             unwrap_or_default!(config.routing().router().peers_failover_brokering());
+        let link_weights = LinkWeights::new(config.routing().peer().linkstate())?;
         drop(config_guard);
 
         hat_mut!(tables).linkstatepeers_net = Some(Network::new(
@@ -213,6 +217,7 @@ impl HatBaseTrait for HatCode {
             gossip_multihop,
             gossip_target,
             autoconnect,
+            link_weights,
         ));
         Ok(())
     }
@@ -415,6 +420,16 @@ impl HatBaseTrait for HatCode {
                     };
                 }
             }
+        } else if oam.id == OAM_LINK_RTT {
+            if let Some(rtt) = handle_rtt_probe(&oam, transport) {
+                let recompute = hat_mut!(tables)
+                    .linkstatepeers_net
+                    .as_mut()
+                    .map_or(false, |net| net.link_rtt(transport, rtt));
+                if recompute {
+                    hat_mut!(tables).schedule_compute_trees(tables_ref.clone());
+                }
+            }
         }
 
         Ok(())
@@ -434,6 +449,11 @@ impl HatBaseTrait for HatCode {
             .get_local_context(routing_context, face_hat!(face).link_id)
     }
 
+    #[inline]
+    fn map_face_type(&self, _tables: &Tables, face: &FaceState) -> WhatAmI {
+        face.whatami
+    }
+
     #[inline]
     fn ingress_filter(&self, _tables: &Tables, _face: &FaceState, _expr: &mut RoutingExpr) -> bool {
         true
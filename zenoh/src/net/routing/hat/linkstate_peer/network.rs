@@ -11,7 +11,7 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use std::convert::TryInto;
+use std::{collections::HashMap, convert::TryInto, time::Duration};
 
 use petgraph::{
     graph::NodeIndex,
@@ -36,7 +36,12 @@ use crate::net::{
     codec::Zenoh080Routing,
     common::AutoConnect,
     protocol::linkstate::{LinkState, LinkStateList},
-    routing::dispatcher::tables::NodeId,
+    routing::{
+        dispatcher::tables::NodeId,
+        hat::link_weights::{
+            decode_link_weight, edge_weight, encode_link_weights, link_weight, LinkWeights,
+        },
+    },
     runtime::{Runtime, WeakRuntime},
 };
 
@@ -54,6 +59,8 @@ pub(super) struct Node {
     pub(super) locators: Option<Vec<Locator>>,
     pub(super) sn: u64,
     pub(super) links: Vec<ZenohIdProto>,
+    /// The weights of the links of this node that differ from the default link weight.
+    pub(super) link_weights: HashMap<ZenohIdProto, u16>,
 }
 
 impl std::fmt::Debug for Node {
@@ -67,6 +74,8 @@ pub(super) struct Link {
     zid: ZenohIdProto,
     mappings: VecMap<ZenohIdProto>,
     local_mappings: VecMap<u64>,
+    /// The smoothed round-trip time of the link, if probed.
+    rtt: Option<Duration>,
 }
 
 impl Link {
@@ -77,6 +86,7 @@ impl Link {
             zid,
             mappings: VecMap::new(),
             local_mappings: VecMap::new(),
+            rtt: None,
         }
     }
 
@@ -122,6 +132,7 @@ pub(super) struct Network {
     pub(super) gossip_multihop: bool,
     pub(super) gossip_target: WhatAmIMatcher,
     pub(super) autoconnect: AutoConnect,
+    pub(super) link_weights: LinkWeights,
     pub(super) idx: NodeIndex,
     pub(super) links: VecMap<Link>,
     pub(super) trees: Vec<Tree>,
@@ -142,6 +153,7 @@ impl Network {
         gossip_multihop: bool,
         gossip_target: WhatAmIMatcher,
         autoconnect: AutoConnect,
+        link_weights: LinkWeights,
     ) -> Self {
         let mut graph = petgraph::stable_graph::StableGraph::default();
         tracing::debug!("{} Add node (self) {}", name, zid);
@@ -151,6 +163,7 @@ impl Network {
             locators: None,
             sn: 1,
             links: vec![],
+            link_weights: HashMap::new(),
         });
         Network {
             name,
@@ -160,6 +173,7 @@ impl Network {
             gossip_multihop,
             gossip_target,
             autoconnect,
+            link_weights,
             idx,
             links: VecMap::new(),
             trees: vec![Tree {
@@ -230,6 +244,16 @@ impl Network {
     }
 
     fn make_link_state(&self, idx: NodeIndex, details: &Details) -> LinkState {
+        let node = &self.graph[idx];
+        let link_weights = details
+            .links
+            .then(|| {
+                encode_link_weights(
+                    node.links.iter().filter(|zid| self.get_idx(zid).is_some()),
+                    &node.link_weights,
+                )
+            })
+            .flatten();
         let links = if details.links {
             self.graph[idx]
                 .links
@@ -269,6 +293,7 @@ impl Network {
                 None
             },
             links,
+            link_weights,
         }
     }
 
@@ -352,7 +377,12 @@ impl Network {
             hasher.write(&self.graph[idx1].zid.to_le_bytes());
             hasher.write(&self.graph[idx2].zid.to_le_bytes());
         }
-        let weight = 100.0 + ((hasher.finish() as u32) as f64) / u32::MAX as f64;
+        let (node1, node2) = (&self.graph[idx1], &self.graph[idx2]);
+        let weight = edge_weight(
+            (&node1.zid, &node1.link_weights),
+            (&node2.zid, &node2.link_weights),
+        ) as f64
+            + ((hasher.finish() as u32) as f64) / u32::MAX as f64;
         self.graph.update_edge(idx1, idx2, weight);
     }
 
@@ -397,6 +427,7 @@ impl Network {
                         link_state.locators,
                         link_state.sn,
                         link_state.links,
+                        link_state.link_weights,
                     ))
                 } else {
                     match src_link.get_zid(&link_state.psid) {
@@ -406,6 +437,7 @@ impl Network {
                             link_state.locators,
                             link_state.sn,
                             link_state.links,
+                            link_state.link_weights,
                         )),
                         None => {
                             tracing::error!(
@@ -424,11 +456,16 @@ impl Network {
         let src_link = self.get_link_from_zid(&src).unwrap();
         let link_states = link_states
             .into_iter()
-            .map(|(zid, wai, locs, sn, links)| {
+            .map(|(zid, wai, locs, sn, links, weights)| {
+                let mut link_weights = HashMap::new();
                 let links: Vec<ZenohIdProto> = links
                     .iter()
-                    .filter_map(|l| {
+                    .enumerate()
+                    .filter_map(|(i, l)| {
                         if let Some(zid) = src_link.get_zid(l) {
+                            if let Some(weight) = decode_link_weight(&weights, i) {
+                                link_weights.insert(*zid, weight);
+                            }
                             Some(*zid)
                         } else {
                             tracing::error!(
@@ -441,7 +478,7 @@ impl Network {
                         }
                     })
                     .collect();
-                (zid, wai, locs, sn, links)
+                (zid, wai, locs, sn, links, link_weights)
             })
             .collect::<Vec<_>>();
 
@@ -465,7 +502,7 @@ impl Network {
                 updated_nodes: vec![],
                 removed_nodes: vec![],
             };
-            for (zid, whatami, locators, sn, links) in link_states.into_iter() {
+            for (zid, whatami, locators, sn, links, link_weights) in link_states.into_iter() {
                 let idx = match self.get_idx(&zid) {
                     None => {
                         let idx = self.add_node(Node {
@@ -474,6 +511,7 @@ impl Network {
                             locators: locators.clone(),
                             sn,
                             links,
+                            link_weights,
                         });
                         changes.updated_nodes.push((idx, self.graph[idx].clone()));
                         locators.is_some().then_some(idx)
@@ -485,6 +523,7 @@ impl Network {
                             .then(|| {
                                 node.sn = sn;
                                 node.links.clone_from(&links);
+                                node.link_weights = link_weights;
                                 changes.updated_nodes.push((idx, node.clone()));
                                 (node.locators != locators && locators.is_some()).then(|| {
                                     node.locators.clone_from(&locators);
@@ -541,14 +580,15 @@ impl Network {
         // Add nodes to graph & filter out up to date states
         let mut link_states = link_states
             .into_iter()
-            .filter_map(
-                |(zid, whatami, locators, sn, links)| match self.get_idx(&zid) {
+            .filter_map(|(zid, whatami, locators, sn, links, link_weights)| {
+                match self.get_idx(&zid) {
                     Some(idx) => {
                         let node = &mut self.graph[idx];
                         let oldsn = node.sn;
                         if oldsn < sn {
                             node.sn = sn;
                             node.links.clone_from(&links);
+                            node.link_weights = link_weights;
                             if locators.is_some() {
                                 node.locators = locators;
                             }
@@ -568,13 +608,14 @@ impl Network {
                             locators,
                             sn,
                             links: links.clone(),
+                            link_weights,
                         };
                         tracing::debug!("{} Add node (state) {}", self.name, zid);
                         let idx = self.add_node(node);
                         Some((links, idx, true))
                     }
-                },
-            )
+                }
+            })
             .collect::<Vec<(Vec<ZenohIdProto>, NodeIndex, bool)>>();
 
         // Add/remove edges from graph
@@ -598,6 +639,7 @@ impl Network {
                         locators: None,
                         sn: 0,
                         links: vec![],
+                        link_weights: HashMap::new(),
                     };
                     tracing::debug!("{} Add node (reintroduced) {}", self.name, link.clone());
                     let idx = self.add_node(node);
@@ -744,11 +786,19 @@ impl Network {
                             locators: None,
                             sn: 0,
                             links: vec![],
+                            link_weights: HashMap::new(),
                         }),
                         true,
                     )
                 }
             };
+            self.link_weights.new_link(
+                &self.runtime.upgrade().unwrap(),
+                &transport,
+                zid,
+                &mut self.graph[self.idx].link_weights,
+                self.full_linkstate,
+            );
             if self.full_linkstate && self.graph[idx].links.contains(&self.graph[self.idx].zid) {
                 tracing::trace!("Update edge (link) {} {}", self.graph[self.idx].zid, zid);
                 self.update_edge(self.idx, idx);
@@ -835,6 +885,7 @@ impl Network {
         tracing::trace!("{} remove_link {}", self.name, zid);
         self.links.retain(|_, link| link.zid != *zid);
         self.graph[self.idx].links.retain(|link| *link != *zid);
+        self.graph[self.idx].link_weights.remove(zid);
 
         if self.full_linkstate {
             if let Some((edge, _)) = self
@@ -885,6 +936,51 @@ impl Network {
         }
     }
 
+    /// Updates the weight of the link of `transport` from a round-trip time measurement.
+    ///
+    /// Returns true if the trees should be recomputed, the weight being only updated
+    /// when it differs by more than 20% from the advertised one.
+    pub(super) fn link_rtt(&mut self, transport: &TransportUnicast, rtt: Duration) -> bool {
+        if !self.full_linkstate {
+            return false;
+        }
+        // Only the link the probe was received on is updated
+        let Some(link) = self
+            .links
+            .values_mut()
+            .find(|link| link.transport == *transport)
+        else {
+            return false;
+        };
+        let zid = &link.zid.clone();
+        let current = link_weight(&self.graph[self.idx].link_weights, zid);
+        let Some(weight) = self
+            .link_weights
+            .rtt_update(zid, &mut link.rtt, rtt, current)
+        else {
+            return false;
+        };
+        self.graph[self.idx].link_weights.insert(*zid, weight);
+        if let Some(idx) = self.get_idx(zid) {
+            if self.graph[idx].links.contains(&self.graph[self.idx].zid) {
+                self.update_edge(self.idx, idx);
+            }
+        }
+        self.graph[self.idx].sn += 1;
+        self.send_on_links(
+            vec![(
+                self.idx,
+                Details {
+                    zid: false,
+                    links: true,
+                    ..Default::default()
+                },
+            )],
+            |_| true,
+        );
+        true
+    }
+
     fn remove_detached_nodes(&mut self) -> Vec<(NodeIndex, Node)> {
         let mut dfs_stack = vec![self.idx];
         let mut visit_map = self.graph.visit_map();
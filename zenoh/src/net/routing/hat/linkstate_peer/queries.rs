@@ -45,7 +45,7 @@ use crate::key_expr::KeyExpr;
 use crate::net::routing::{
     dispatcher::{
         face::FaceState,
-        resource::{NodeId, Resource, SessionContext},
+        resource::{NodeId, QueryableTarget, Resource, SessionContext},
         tables::{QueryTargetQabl, QueryTargetQablSet, RoutingExpr, Tables},
     },
     hat::{CurrentFutureTrait, HatQueriesTrait, SendDeclare, Sources},
@@ -719,6 +719,7 @@ fn insert_target_for_qabls(
                                             complete: complete && qabl_info.complete,
                                             distance: net.distances[qabl_idx.index()] as u16,
                                         }),
+                                        target: QueryableTarget::Node(*qabl),
                                     });
                                 }
                             }
@@ -733,7 +734,7 @@ fn insert_target_for_qabls(
 }
 
 lazy_static::lazy_static! {
-    static ref EMPTY_ROUTE: Arc<QueryTargetQablSet> = Arc::new(Vec::new());
+    static ref EMPTY_ROUTE: Arc<QueryTargetQablSet> = Arc::new(QueryTargetQablSet::new());
 }
 
 #[inline]
@@ -1016,6 +1017,11 @@ impl HatQueriesTrait for HatCode {
                                 complete: complete && qabl_info.complete,
                                 distance: 1,
                             }),
+                            target: QueryableTarget::declared(
+                                &context.face,
+                                &mres,
+                                face_hat!(context.face).remote_qabls.iter(),
+                            ),
                         });
                     }
                 }
@@ -44,6 +44,7 @@ use super::{
 use crate::net::runtime::Runtime;
 
 mod client;
+mod link_weights;
 mod linkstate_peer;
 mod p2p_peer;
 mod router;
@@ -67,6 +68,10 @@ impl Sources {
             clients: vec![],
         }
     }
+
+    pub(crate) fn contains(&self, zid: &ZenohIdProto) -> bool {
+        self.routers.contains(zid) || self.peers.contains(zid) || self.clients.contains(zid)
+    }
 }
 
 pub(crate) type SendDeclare<'a> = dyn FnMut(&Arc<dyn crate::net::primitives::EPrimitives + Send + Sync>, RoutingContext<Declare>)
@@ -118,6 +123,9 @@ pub(crate) trait HatBaseTrait {
         routing_context: NodeId,
     ) -> NodeId;
 
+    /// The kind of node `face` is in the routes computed by this hat, as their source or destination.
+    fn map_face_type(&self, tables: &Tables, face: &FaceState) -> WhatAmI;
+
     fn ingress_filter(&self, tables: &Tables, face: &FaceState, expr: &mut RoutingExpr) -> bool;
 
     fn egress_filter(
@@ -41,7 +41,7 @@ use crate::{
     net::routing::{
         dispatcher::{
             face::FaceState,
-            resource::{NodeId, Resource, SessionContext},
+            resource::{NodeId, QueryableTarget, Resource, SessionContext},
             tables::{QueryTargetQabl, QueryTargetQablSet, RoutingExpr, Tables},
         },
         hat::{
@@ -377,7 +377,7 @@ pub(super) fn queries_new_face(
 }
 
 lazy_static::lazy_static! {
-    static ref EMPTY_ROUTE: Arc<QueryTargetQablSet> = Arc::new(Vec::new());
+    static ref EMPTY_ROUTE: Arc<QueryTargetQablSet> = Arc::new(QueryTargetQablSet::new());
 }
 
 #[inline]
@@ -631,6 +631,7 @@ impl HatQueriesTrait for HatCode {
                     route.push(QueryTargetQabl {
                         direction: (face.clone(), key_expr.to_owned(), NodeId::default()),
                         info: None,
+                        target: QueryableTarget::Node(face.zid),
                     });
                 }
             }
@@ -643,6 +644,7 @@ impl HatQueriesTrait for HatCode {
                 route.push(QueryTargetQabl {
                     direction: (face.clone(), key_expr.to_owned(), NodeId::default()),
                     info: None,
+                    target: QueryableTarget::Node(face.zid),
                 });
             }
         }
@@ -671,6 +673,11 @@ impl HatQueriesTrait for HatCode {
                                 complete: complete && qabl_info.complete,
                                 distance: 1,
                             }),
+                            target: QueryableTarget::declared(
+                                &context.face,
+                                &mres,
+                                face_hat!(context.face).remote_qabls.iter(),
+                            ),
                         });
                     }
                 }
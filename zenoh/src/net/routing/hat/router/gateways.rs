@@ -0,0 +1,336 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::{
+    collections::HashMap,
+    sync::{atomic::Ordering, Arc},
+};
+
+use zenoh_protocol::network::{
+    declare::{
+        common::ext::WireExprType, ext, Declare, DeclareBody, UndeclareQueryable,
+        UndeclareSubscriber, UndeclareToken,
+    },
+    interest::{Interest, InterestId, InterestMode, InterestOptions},
+};
+use zenoh_sync::get_mut_unchecked;
+
+use super::{
+    face_hat, face_hat_mut, forget_face_declarations,
+    interests::register_interest,
+    res_hat,
+    token::{declare_router_token, undeclare_router_token},
+    HatContext, HatFace,
+};
+use crate::net::routing::{
+    dispatcher::{
+        face::{FaceState, InterestState},
+        interests::RemoteInterest,
+        tables::{Region, Resource, Tables, GATEWAY_LINKS},
+    },
+    hat::SendDeclare,
+    RoutingContext,
+};
+
+/// The link of a face to a gateway router of another region.
+pub(super) struct GatewayLink {
+    /// The resource of the token advertising this link in the region.
+    res: Arc<Resource>,
+    /// Whether this link is the one elected to connect the two regions.
+    active: bool,
+    /// The interests of the gateway received while this link is not elected.
+    parked_interests: HashMap<InterestId, RemoteInterest>,
+}
+
+/// Whether `face` is a gateway router of another region: it doesn't join the link-state of this
+/// region, the declarations and data exchanged with it being routed like the ones of a client.
+#[inline]
+pub(super) fn is_gateway(face: &FaceState) -> bool {
+    face_hat!(face).gateway.is_some()
+}
+
+/// Whether `face` is a gateway link that is not elected, exchanging neither declarations nor data.
+#[inline]
+pub(super) fn is_parked(face: &FaceState) -> bool {
+    face_hat!(face)
+        .gateway
+        .as_ref()
+        .is_some_and(|link| !link.active)
+}
+
+/// Whether `res` advertises a gateway link.
+#[inline]
+pub(super) fn is_gateway_link(res: &Resource) -> bool {
+    res.expr()
+        .strip_prefix(GATEWAY_LINKS)
+        .is_some_and(|suffix| suffix.starts_with('/'))
+}
+
+/// Parks the interest `id` of the gateway `face` until its link is elected.
+pub(super) fn park_interest(face: &mut Arc<FaceState>, id: InterestId, interest: RemoteInterest) {
+    if let Some(link) = face_hat_mut!(face).gateway.as_mut() {
+        link.parked_interests.insert(id, interest);
+    }
+}
+
+/// Forgets the parked interest `id` of the gateway `face`.
+pub(super) fn unpark_interest(face: &mut Arc<FaceState>, id: InterestId) {
+    if let Some(link) = face_hat_mut!(face).gateway.as_mut() {
+        link.parked_interests.remove(&id);
+    }
+}
+
+/// Advertises the link of the new gateway `face` in the region, the face being parked until its
+/// link is elected.
+pub(super) fn new_gateway_face(
+    tables: &mut Tables,
+    face: &mut Arc<FaceState>,
+    send_declare: &mut SendDeclare,
+) {
+    let Some(key_expr) = tables
+        .region
+        .as_ref()
+        .and_then(|region| region.link(&tables.zid, &face.zid))
+    else {
+        return;
+    };
+    let mut root = tables.root_res.clone();
+    let mut res = Resource::make_resource(tables, &mut root, &key_expr);
+    let matches = Resource::get_matches(tables, &key_expr);
+    Resource::match_resource(tables, &mut res, matches);
+    face_hat_mut!(face).gateway = Some(GatewayLink {
+        res: res.clone(),
+        active: false,
+        parked_interests: HashMap::new(),
+    });
+    let zid = tables.zid;
+    declare_router_token(tables, face, &mut res, zid, send_declare);
+}
+
+/// Withdraws the link of the closed gateway `face` from the region.
+pub(super) fn close_gateway_face(
+    tables: &mut Tables,
+    face: &mut Arc<FaceState>,
+    send_declare: &mut SendDeclare,
+) {
+    if let Some(link) = face_hat_mut!(face).gateway.take() {
+        let mut res = link.res;
+        let zid = tables.zid;
+        undeclare_router_token(tables, None, &mut res, &zid, send_declare);
+        Resource::clean(&mut res);
+    }
+}
+
+/// The key expression of the elected link to the gateways of `region`: the lowest of the
+/// advertised ones.
+fn elected_link(tables: &Tables, region: &str) -> Option<String> {
+    let links = Region::links(region)?;
+    Resource::get_matches(tables, &links)
+        .iter()
+        .filter_map(|res| res.upgrade())
+        .filter(|res| res.context.is_some() && !res_hat!(res).router_tokens.is_empty())
+        .map(|res| res.expr().to_string())
+        .min()
+}
+
+/// Elects the gateway links anew after the advertised links changed, starting or stopping the
+/// exchanges with the gateways whose links were elected or not anymore.
+pub(super) fn update_gateways(tables: &mut Tables, send_declare: &mut SendDeclare) {
+    let Some(region) = tables.region.as_ref() else {
+        return;
+    };
+    let gateways = tables
+        .faces
+        .values()
+        .filter(|face| face_hat!(face).gateway.is_some())
+        .filter_map(|face| Some((face.clone(), region.gateway(&face.zid)?.to_string())))
+        .collect::<Vec<_>>();
+    let mut elected = HashMap::new();
+    for (mut face, region) in gateways {
+        let elected = elected
+            .entry(region)
+            .or_insert_with_key(|region| elected_link(tables, region));
+        let Some(link) = face_hat!(face).gateway.as_ref() else {
+            continue;
+        };
+        let active = elected.as_deref() == Some(link.res.expr());
+        if active != link.active {
+            if active {
+                tracing::debug!("Elected gateway link {}", link.res.expr());
+                activate_gateway(tables, &mut face, send_declare);
+            } else {
+                tracing::debug!("Parked gateway link {}", link.res.expr());
+                park_gateway(tables, &mut face, send_declare);
+            }
+        }
+    }
+}
+
+/// Starts the exchanges with the gateway `face`, sending its interests and answering the ones it
+/// sent while parked.
+fn activate_gateway(
+    tables: &mut Tables,
+    face: &mut Arc<FaceState>,
+    send_declare: &mut SendDeclare,
+) {
+    let Some(link) = face_hat_mut!(face).gateway.as_mut() else {
+        return;
+    };
+    link.active = true;
+    let parked_interests = std::mem::take(&mut link.parked_interests);
+
+    // Gateways of other regions only send the declarations of their region on interest,
+    // the subscribers and queryables being aggregated if configured so
+    let aggregation = tables
+        .region
+        .as_ref()
+        .map(|region| region.aggregation(&face.zid).to_vec())
+        .unwrap_or_default();
+    let interests = if aggregation.is_empty() {
+        vec![(InterestOptions::ALL, None)]
+    } else {
+        aggregation
+            .into_iter()
+            .flat_map(|key_expr| {
+                [
+                    (
+                        InterestOptions::KEYEXPRS
+                            + InterestOptions::SUBSCRIBERS
+                            + InterestOptions::QUERYABLES
+                            + InterestOptions::AGGREGATE,
+                        Some(key_expr.clone()),
+                    ),
+                    (
+                        InterestOptions::KEYEXPRS + InterestOptions::TOKENS,
+                        Some(key_expr),
+                    ),
+                ]
+            })
+            .collect()
+    };
+    for (options, key_expr) in interests {
+        let res = key_expr.as_ref().map(|key_expr| {
+            let mut root = tables.root_res.clone();
+            let mut res = Resource::make_resource(tables, &mut root, key_expr);
+            let matches = Resource::get_matches(tables, key_expr);
+            Resource::match_resource(tables, &mut res, matches);
+            res
+        });
+        let id = face_hat!(face).next_id.fetch_add(1, Ordering::SeqCst);
+        get_mut_unchecked(face).local_interests.insert(
+            id,
+            InterestState {
+                options,
+                res,
+                finalized: false,
+            },
+        );
+        face.primitives.send_interest(RoutingContext::new(Interest {
+            id,
+            mode: InterestMode::CurrentFuture,
+            options,
+            wire_expr: key_expr.map(|key_expr| key_expr.to_string().into()),
+            ext_qos: ext::QoSType::DECLARE,
+            ext_tstamp: None,
+            ext_nodeid: ext::NodeIdType::DEFAULT,
+        }));
+    }
+
+    for (id, interest) in parked_interests {
+        register_interest(
+            tables,
+            face,
+            id,
+            interest.res.clone().as_mut(),
+            InterestMode::CurrentFuture,
+            interest.options,
+            send_declare,
+        );
+    }
+}
+
+/// Stops the exchanges with the gateway `face`: its interests are parked, the declarations sent
+/// to it undeclared, the interests sent to it withdrawn and the declarations it sent forgotten.
+fn park_gateway(tables: &mut Tables, face: &mut Arc<FaceState>, send_declare: &mut SendDeclare) {
+    let HatFace {
+        gateway,
+        remote_interests,
+        local_subs,
+        local_qabls,
+        local_tokens,
+        ..
+    } = face_hat_mut!(face);
+    let Some(link) = gateway.as_mut() else {
+        return;
+    };
+    link.active = false;
+    link.parked_interests.extend(remote_interests.drain());
+    let undeclarations = local_subs
+        .drain()
+        .map(|(res, id)| {
+            let body = DeclareBody::UndeclareSubscriber(UndeclareSubscriber {
+                id,
+                ext_wire_expr: WireExprType::null(),
+            });
+            (res, body)
+        })
+        .chain(local_qabls.drain().map(|(res, (id, _))| {
+            let body = DeclareBody::UndeclareQueryable(UndeclareQueryable {
+                id,
+                ext_wire_expr: WireExprType::null(),
+            });
+            (res, body)
+        }))
+        .chain(local_tokens.drain().map(|(res, id)| {
+            let body = DeclareBody::UndeclareToken(UndeclareToken {
+                id,
+                ext_wire_expr: WireExprType::null(),
+            });
+            (res, body)
+        }))
+        .collect::<Vec<_>>();
+    for (res, body) in undeclarations {
+        send_declare(
+            &face.primitives,
+            RoutingContext::with_expr(
+                Declare {
+                    interest_id: None,
+                    ext_qos: ext::QoSType::DECLARE,
+                    ext_tstamp: None,
+                    ext_nodeid: ext::NodeIdType::DEFAULT,
+                    body,
+                },
+                res.expr().to_string(),
+            ),
+        );
+    }
+
+    for id in get_mut_unchecked(face)
+        .local_interests
+        .drain()
+        .map(|(id, _)| id)
+        .collect::<Vec<_>>()
+    {
+        face.primitives.send_interest(RoutingContext::new(Interest {
+            id,
+            mode: InterestMode::Final,
+            options: InterestOptions::empty(),
+            wire_expr: None,
+            ext_qos: ext::QoSType::DECLARE,
+            ext_tstamp: None,
+            ext_nodeid: ext::NodeIdType::DEFAULT,
+        }));
+    }
+
+    forget_face_declarations(tables, face, send_declare);
+}
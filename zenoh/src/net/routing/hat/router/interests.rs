@@ -24,8 +24,13 @@ use zenoh_protocol::{
 use zenoh_sync::get_mut_unchecked;
 
 use super::{
-    face_hat_mut, hat, pubsub::declare_sub_interest, queries::declare_qabl_interest,
-    token::declare_token_interest, HatCode, HatFace, HatTables,
+    face_hat_mut,
+    gateways::{is_gateway, is_parked, park_interest, unpark_interest},
+    hat,
+    pubsub::declare_sub_interest,
+    queries::declare_qabl_interest,
+    token::declare_token_interest,
+    HatCode, HatFace, HatTables,
 };
 use crate::net::routing::{
     dispatcher::{
@@ -57,65 +62,39 @@ impl HatInterestTrait for HatCode {
             );
             options -= InterestOptions::AGGREGATE;
         }
-        if options.subscribers() {
-            declare_sub_interest(
-                tables,
-                face,
-                id,
-                res.as_ref().map(|r| (*r).clone()).as_mut(),
-                mode,
-                options.aggregate(),
-                send_declare,
-            )
-        }
-        if options.queryables() {
-            declare_qabl_interest(
-                tables,
-                face,
-                id,
-                res.as_ref().map(|r| (*r).clone()).as_mut(),
-                mode,
-                options.aggregate(),
-                send_declare,
-            )
-        }
-        if options.tokens() {
-            declare_token_interest(
-                tables,
-                face,
-                id,
-                res.as_ref().map(|r| (*r).clone()).as_mut(),
-                mode,
-                options.aggregate(),
-                send_declare,
-            )
-        }
-        if mode.future() {
-            face_hat_mut!(face).remote_interests.insert(
-                id,
-                RemoteInterest {
-                    res: res.cloned(),
-                    options,
-                    mode,
-                },
-            );
-        }
-        if mode.current() {
-            send_declare(
-                &face.primitives,
-                RoutingContext::new(Declare {
-                    interest_id: Some(id),
-                    ext_qos: ext::QoSType::DECLARE,
-                    ext_tstamp: None,
-                    ext_nodeid: ext::NodeIdType::DEFAULT,
-                    body: DeclareBody::DeclareFinal(DeclareFinal),
-                }),
-            );
+        if is_parked(face) {
+            // The interests of a gateway are answered once its link is elected
+            if mode.future() {
+                park_interest(
+                    face,
+                    id,
+                    RemoteInterest {
+                        res: res.cloned(),
+                        options,
+                        mode,
+                    },
+                );
+            }
+            if mode.current() {
+                send_declare(
+                    &face.primitives,
+                    RoutingContext::new(Declare {
+                        interest_id: Some(id),
+                        ext_qos: ext::QoSType::DECLARE,
+                        ext_tstamp: None,
+                        ext_nodeid: ext::NodeIdType::DEFAULT,
+                        body: DeclareBody::DeclareFinal(DeclareFinal),
+                    }),
+                );
+            }
+            return;
         }
+        register_interest(tables, face, id, res, mode, options, send_declare);
     }
 
     fn undeclare_interest(&self, _tables: &mut Tables, face: &mut Arc<FaceState>, id: InterestId) {
         face_hat_mut!(face).remote_interests.remove(&id);
+        unpark_interest(face, id);
     }
 
     fn declare_final(&self, _tables: &mut Tables, _face: &mut Arc<FaceState>, _id: InterestId) {
@@ -123,8 +102,76 @@ impl HatInterestTrait for HatCode {
     }
 }
 
+/// Sends the declarations matching the interest `id` of `face` and records it if future.
+pub(super) fn register_interest(
+    tables: &mut Tables,
+    face: &mut Arc<FaceState>,
+    id: InterestId,
+    res: Option<&mut Arc<Resource>>,
+    mode: InterestMode,
+    options: InterestOptions,
+    send_declare: &mut SendDeclare,
+) {
+    if options.subscribers() {
+        declare_sub_interest(
+            tables,
+            face,
+            id,
+            res.as_ref().map(|r| (*r).clone()).as_mut(),
+            mode,
+            options.aggregate(),
+            send_declare,
+        )
+    }
+    if options.queryables() {
+        declare_qabl_interest(
+            tables,
+            face,
+            id,
+            res.as_ref().map(|r| (*r).clone()).as_mut(),
+            mode,
+            options.aggregate(),
+            send_declare,
+        )
+    }
+    if options.tokens() {
+        declare_token_interest(
+            tables,
+            face,
+            id,
+            res.as_ref().map(|r| (*r).clone()).as_mut(),
+            mode,
+            options.aggregate(),
+            send_declare,
+        )
+    }
+    if mode.future() {
+        face_hat_mut!(face).remote_interests.insert(
+            id,
+            RemoteInterest {
+                res: res.cloned(),
+                options,
+                mode,
+            },
+        );
+    }
+    if mode.current() {
+        send_declare(
+            &face.primitives,
+            RoutingContext::new(Declare {
+                interest_id: Some(id),
+                ext_qos: ext::QoSType::DECLARE,
+                ext_tstamp: None,
+                ext_nodeid: ext::NodeIdType::DEFAULT,
+                body: DeclareBody::DeclareFinal(DeclareFinal),
+            }),
+        );
+    }
+}
+
 #[inline]
 pub(super) fn push_declaration_profile(tables: &Tables, face: &FaceState) -> bool {
     !(face.whatami == WhatAmI::Client
+        || is_gateway(face)
         || (face.whatami == WhatAmI::Peer && !hat!(tables).full_net(WhatAmI::Peer)))
 }
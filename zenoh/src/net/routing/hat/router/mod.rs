@@ -32,7 +32,7 @@ use zenoh_protocol::{
     network::{
         declare::{queryable::ext::QueryableInfoType, QueryableId, SubscriberId, TokenId},
         interest::InterestId,
-        oam::id::OAM_LINKSTATE,
+        oam::id::{OAM_LINKSTATE, OAM_LINK_RTT},
         Oam,
     },
 };
@@ -42,8 +42,11 @@ use zenoh_task::TerminatableTask;
 use zenoh_transport::unicast::TransportUnicast;
 
 use self::{
+    gateways::{close_gateway_face, is_gateway, is_parked, new_gateway_face, GatewayLink},
     network::{shared_nodes, Network},
-    pubsub::{pubsub_linkstate_change, pubsub_remove_node, undeclare_simple_subscription},
+    pubsub::{
+        pubsub_linkstate_change, pubsub_remove_node, undeclare_simple_subscription, SubsAggregate,
+    },
     queries::{queries_linkstate_change, queries_remove_node, undeclare_simple_queryable},
 };
 use super::{
@@ -58,11 +61,15 @@ use crate::net::{
     protocol::linkstate::LinkStateList,
     routing::{
         dispatcher::{face::Face, interests::RemoteInterest},
-        hat::TREES_COMPUTATION_DELAY_MS,
+        hat::{
+            link_weights::{handle_rtt_probe, LinkWeights},
+            TREES_COMPUTATION_DELAY_MS,
+        },
     },
     runtime::Runtime,
 };
 
+mod gateways;
 mod interests;
 mod network;
 mod pubsub;
@@ -174,6 +181,8 @@ struct HatTables {
     routers_trees_worker: TreesComputationWorker,
     linkstatepeers_trees_worker: TreesComputationWorker,
     router_peers_failover_brokering: bool,
+    /// The key expressions advertised upstream in place of the subscriptions of clients and peers they include
+    aggregated_subs: Vec<SubsAggregate>,
 }
 
 impl HatTables {
@@ -191,6 +200,7 @@ impl HatTables {
             routers_trees_worker: TreesComputationWorker::new(WhatAmI::Router),
             linkstatepeers_trees_worker: TreesComputationWorker::new(WhatAmI::Peer),
             router_peers_failover_brokering,
+            aggregated_subs: vec![],
         }
     }
 
@@ -329,6 +339,23 @@ impl HatBaseTrait for HatCode {
             unwrap_or_default!(config.routing().peer().mode()) == *"linkstate";
         let router_peers_failover_brokering =
             unwrap_or_default!(config.routing().router().peers_failover_brokering());
+        let router_link_weights = LinkWeights::new(config.routing().router().linkstate())?;
+        let peer_link_weights = LinkWeights::new(config.routing().peer().linkstate())?;
+        let router_redundant = !config
+            .routing()
+            .router()
+            .redundancy()
+            .key_exprs()
+            .is_empty();
+        hat_mut!(tables).aggregated_subs = config
+            .routing()
+            .router()
+            .aggregation()
+            .subscribers()
+            .iter()
+            .cloned()
+            .map(SubsAggregate::new)
+            .collect();
         drop(config_guard);
 
         if router_full_linkstate | gossip {
@@ -342,6 +369,9 @@ impl HatBaseTrait for HatCode {
                 gossip_multihop,
                 gossip_target,
                 autoconnect,
+                router_link_weights,
+                router_redundant,
+                true,
             ));
         }
         if peer_full_linkstate | gossip {
@@ -355,6 +385,9 @@ impl HatBaseTrait for HatCode {
                 gossip_multihop,
                 gossip_target,
                 autoconnect,
+                peer_link_weights,
+                false,
+                false,
             ));
         }
         if router_full_linkstate && peer_full_linkstate {
@@ -395,10 +428,19 @@ impl HatBaseTrait for HatCode {
         tables_ref: &Arc<TablesLock>,
         face: &mut Face,
         transport: &TransportUnicast,
-        _send_declare: &mut SendDeclare,
+        send_declare: &mut SendDeclare,
     ) -> ZResult<()> {
+        if tables
+            .region
+            .as_ref()
+            .is_some_and(|region| region.gateway(&face.state.zid).is_some())
+        {
+            new_gateway_face(tables, &mut face.state, send_declare);
+        }
+
         let link_id = match face.state.whatami {
-            WhatAmI::Router => hat_mut!(tables)
+            // The gateways of other regions don't join the link-state of this region
+            WhatAmI::Router if !is_gateway(&face.state) => hat_mut!(tables)
                 .routers_net
                 .as_mut()
                 .unwrap()
@@ -423,7 +465,7 @@ impl HatBaseTrait for HatCode {
         face_hat_mut!(&mut face.state).link_id = link_id;
 
         match face.state.whatami {
-            WhatAmI::Router => {
+            WhatAmI::Router if !is_gateway(&face.state) => {
                 hat_mut!(tables).schedule_compute_trees(tables_ref.clone(), WhatAmI::Router);
             }
             WhatAmI::Peer => {
@@ -470,71 +512,13 @@ impl HatBaseTrait for HatCode {
         }
         face.local_mappings.clear();
 
-        let mut subs_matches = vec![];
-        for (_id, mut res) in hat_face.remote_subs.drain() {
-            get_mut_unchecked(&mut res).session_ctxs.remove(&face.id);
-            undeclare_simple_subscription(&mut wtables, &mut face_clone, &mut res, send_declare);
-
-            if res.context.is_some() {
-                for match_ in &res.context().matches {
-                    let mut match_ = match_.upgrade().unwrap();
-                    if !Arc::ptr_eq(&match_, &res) {
-                        get_mut_unchecked(&mut match_)
-                            .context_mut()
-                            .disable_data_routes();
-                        subs_matches.push(match_);
-                    }
-                }
-                get_mut_unchecked(&mut res)
-                    .context_mut()
-                    .disable_data_routes();
-                subs_matches.push(res);
-            }
-        }
-
-        let mut qabls_matches = vec![];
-        for (_, mut res) in hat_face.remote_qabls.drain() {
-            get_mut_unchecked(&mut res).session_ctxs.remove(&face.id);
-            undeclare_simple_queryable(&mut wtables, &mut face_clone, &mut res, send_declare);
-
-            if res.context.is_some() {
-                for match_ in &res.context().matches {
-                    let mut match_ = match_.upgrade().unwrap();
-                    if !Arc::ptr_eq(&match_, &res) {
-                        get_mut_unchecked(&mut match_)
-                            .context_mut()
-                            .disable_query_routes();
-                        qabls_matches.push(match_);
-                    }
-                }
-                get_mut_unchecked(&mut res)
-                    .context_mut()
-                    .disable_query_routes();
-                qabls_matches.push(res);
-            }
-        }
-
-        for (_id, mut res) in hat_face.remote_tokens.drain() {
-            get_mut_unchecked(&mut res).session_ctxs.remove(&face.id);
-            undeclare_simple_token(&mut wtables, &mut face_clone, &mut res, send_declare);
-        }
-
-        for mut res in subs_matches {
-            get_mut_unchecked(&mut res)
-                .context_mut()
-                .disable_data_routes();
-            Resource::clean(&mut res);
-        }
-        for mut res in qabls_matches {
-            get_mut_unchecked(&mut res)
-                .context_mut()
-                .disable_query_routes();
-            Resource::clean(&mut res);
-        }
+        forget_face_declarations(&mut wtables, &mut face_clone, send_declare);
         wtables.faces.remove(&face.id);
+        let gateway = is_gateway(face);
+        close_gateway_face(&mut wtables, &mut face_clone, send_declare);
 
         match face.whatami {
-            WhatAmI::Router => {
+            WhatAmI::Router if !gateway => {
                 for (_, removed_node) in hat_mut!(wtables)
                     .routers_net
                     .as_mut()
@@ -621,6 +605,19 @@ impl HatBaseTrait for HatCode {
         transport: &TransportUnicast,
         send_declare: &mut SendDeclare,
     ) -> ZResult<()> {
+        if let Some(region) = tables.region.as_ref() {
+            let zid = transport.get_zid()?;
+            if let Some(gateway_region) = region.gateway(&zid) {
+                // The link-state of a region is not exchanged with the gateways of other regions
+                tracing::trace!(
+                    "Ignoring OAM {} from gateway {} of region '{}'",
+                    oam.id,
+                    zid,
+                    gateway_region
+                );
+                return Ok(());
+            }
+        }
         if oam.id == OAM_LINKSTATE {
             if let ZExtBody::ZBuf(buf) = oam.body {
                 if let Ok(zid) = transport.get_zid() {
@@ -732,6 +729,22 @@ impl HatBaseTrait for HatCode {
                     };
                 }
             }
+        } else if oam.id == OAM_LINK_RTT {
+            if let Some(rtt) = handle_rtt_probe(&oam, transport) {
+                // Only the links of the routers (and linkstate peers) networks are weighted
+                let net_type = transport.get_whatami()?;
+                let recompute = match net_type {
+                    WhatAmI::Router => hat_mut!(tables).routers_net.as_mut(),
+                    WhatAmI::Peer if hat!(tables).full_net(WhatAmI::Peer) => {
+                        hat_mut!(tables).linkstatepeers_net.as_mut()
+                    }
+                    _ => None,
+                }
+                .map_or(false, |net| net.link_rtt(transport, rtt));
+                if recompute {
+                    hat_mut!(tables).schedule_compute_trees(tables_ref.clone(), net_type);
+                }
+            }
         }
 
         Ok(())
@@ -745,7 +758,7 @@ impl HatBaseTrait for HatCode {
         routing_context: NodeId,
     ) -> NodeId {
         match face.whatami {
-            WhatAmI::Router => hat!(tables)
+            WhatAmI::Router if !is_gateway(face) => hat!(tables)
                 .routers_net
                 .as_ref()
                 .unwrap()
@@ -765,8 +778,20 @@ impl HatBaseTrait for HatCode {
         }
     }
 
+    #[inline]
+    fn map_face_type(&self, _tables: &Tables, face: &FaceState) -> WhatAmI {
+        match face.whatami {
+            // The gateways of other regions are routed to and from this router, like its clients
+            WhatAmI::Router if is_gateway(face) => WhatAmI::Client,
+            whatami => whatami,
+        }
+    }
+
     #[inline]
     fn ingress_filter(&self, tables: &Tables, face: &FaceState, expr: &mut RoutingExpr) -> bool {
+        if is_parked(face) {
+            return false;
+        }
         face.whatami != WhatAmI::Peer
             || hat!(tables).linkstatepeers_net.is_none()
             || tables.zid
@@ -787,6 +812,7 @@ impl HatBaseTrait for HatCode {
     ) -> bool {
         if src_face.id != out_face.id
             && (out_face.mcast_group.is_none() || src_face.mcast_group.is_none())
+            && !is_parked(out_face)
         {
             let dst_master = out_face.whatami != WhatAmI::Peer
                 || hat!(tables).linkstatepeers_net.is_none()
@@ -823,6 +849,79 @@ impl HatBaseTrait for HatCode {
     }
 }
 
+/// Forgets the subscribers, queryables and tokens declared by `face`.
+fn forget_face_declarations(
+    tables: &mut Tables,
+    face: &mut Arc<FaceState>,
+    send_declare: &mut SendDeclare,
+) {
+    let mut subs_matches = vec![];
+    for (_id, mut res) in face_hat_mut!(face).remote_subs.drain().collect::<Vec<_>>() {
+        get_mut_unchecked(&mut res).session_ctxs.remove(&face.id);
+        undeclare_simple_subscription(tables, face, &mut res, send_declare);
+
+        if res.context.is_some() {
+            for match_ in &res.context().matches {
+                let mut match_ = match_.upgrade().unwrap();
+                if !Arc::ptr_eq(&match_, &res) {
+                    get_mut_unchecked(&mut match_)
+                        .context_mut()
+                        .disable_data_routes();
+                    subs_matches.push(match_);
+                }
+            }
+            get_mut_unchecked(&mut res)
+                .context_mut()
+                .disable_data_routes();
+            subs_matches.push(res);
+        }
+    }
+
+    let mut qabls_matches = vec![];
+    for (_, mut res) in face_hat_mut!(face).remote_qabls.drain().collect::<Vec<_>>() {
+        get_mut_unchecked(&mut res).session_ctxs.remove(&face.id);
+        undeclare_simple_queryable(tables, face, &mut res, send_declare);
+
+        if res.context.is_some() {
+            for match_ in &res.context().matches {
+                let mut match_ = match_.upgrade().unwrap();
+                if !Arc::ptr_eq(&match_, &res) {
+                    get_mut_unchecked(&mut match_)
+                        .context_mut()
+                        .disable_query_routes();
+                    qabls_matches.push(match_);
+                }
+            }
+            get_mut_unchecked(&mut res)
+                .context_mut()
+                .disable_query_routes();
+            qabls_matches.push(res);
+        }
+    }
+
+    for (_id, mut res) in face_hat_mut!(face)
+        .remote_tokens
+        .drain()
+        .collect::<Vec<_>>()
+    {
+        get_mut_unchecked(&mut res).session_ctxs.remove(&face.id);
+        undeclare_simple_token(tables, face, &mut res, send_declare);
+    }
+
+    for mut res in subs_matches {
+        get_mut_unchecked(&mut res)
+            .context_mut()
+            .disable_data_routes();
+        Resource::clean(&mut res);
+    }
+    for mut res in qabls_matches {
+        get_mut_unchecked(&mut res)
+            .context_mut()
+            .disable_query_routes();
+        Resource::clean(&mut res);
+    }
+}
+
 struct HatContext {
     router_subs: HashSet<ZenohIdProto>,
     linkstatepeer_subs: HashSet<ZenohIdProto>,
@@ -855,6 +954,7 @@ struct HatFace {
     remote_qabls: HashMap<QueryableId, Arc<Resource>>,
     local_tokens: HashMap<Arc<Resource>, TokenId>,
     remote_tokens: HashMap<TokenId, Arc<Resource>>,
+    gateway: Option<GatewayLink>,
 }
 
 impl HatFace {
@@ -869,6 +969,7 @@ impl HatFace {
             remote_qabls: HashMap::new(),
             local_tokens: HashMap::new(),
             remote_tokens: HashMap::new(),
+            gateway: None,
         }
     }
 }
@@ -11,11 +11,16 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use std::convert::TryInto;
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+    convert::TryInto,
+    time::Duration,
+};
 
 use petgraph::{
     graph::NodeIndex,
-    visit::{IntoNodeReferences, VisitMap, Visitable},
+    visit::{EdgeRef, IntoNodeReferences, NodeIndexable, VisitMap, Visitable},
 };
 use rand::Rng;
 use vec_map::VecMap;
@@ -32,11 +37,17 @@ use zenoh_protocol::{
 };
 use zenoh_transport::unicast::TransportUnicast;
 
+use crate::api::topology::TopologyEvent;
 use crate::net::{
     codec::Zenoh080Routing,
     common::AutoConnect,
     protocol::linkstate::{LinkState, LinkStateList},
-    routing::dispatcher::tables::NodeId,
+    routing::{
+        dispatcher::{resource::REDUNDANT_CONTEXT, tables::NodeId},
+        hat::link_weights::{
+            decode_link_weight, edge_weight, encode_link_weights, link_weight, LinkWeights,
+        },
+    },
     runtime::Runtime,
 };
 
@@ -54,6 +65,8 @@ pub(super) struct Node {
     pub(super) locators: Option<Vec<Locator>>,
     pub(super) sn: u64,
     pub(super) links: Vec<ZenohIdProto>,
+    /// The weights of the links of this node that differ from the default link weight.
+    pub(super) link_weights: HashMap<ZenohIdProto, u16>,
 }
 
 impl std::fmt::Debug for Node {
@@ -67,6 +80,8 @@ pub(super) struct Link {
     zid: ZenohIdProto,
     mappings: VecMap<ZenohIdProto>,
     local_mappings: VecMap<u64>,
+    /// The smoothed round-trip time of the link, if probed.
+    rtt: Option<Duration>,
 }
 
 impl Link {
@@ -77,6 +92,7 @@ impl Link {
             zid,
             mappings: VecMap::new(),
             local_mappings: VecMap::new(),
+            rtt: None,
         }
     }
 
@@ -122,9 +138,16 @@ pub(super) struct Network {
     pub(super) gossip_multihop: bool,
     pub(super) gossip_target: WhatAmIMatcher,
     pub(super) autoconnect: AutoConnect,
+    pub(super) link_weights: LinkWeights,
     pub(super) idx: NodeIndex,
     pub(super) links: VecMap<Link>,
     pub(super) trees: Vec<Tree>,
+    /// Whether the redundant trees are computed
+    pub(super) redundant: bool,
+    /// For each source node, a tree avoiding the links of its tree whenever possible
+    pub(super) redundant_trees: Vec<Tree>,
+    /// Whether the changes of the graph are notified to the topology listeners
+    pub(super) topology_events: bool,
     pub(super) distances: Vec<f64>,
     pub(super) graph: petgraph::stable_graph::StableUnGraph<Node, f64>,
     pub(super) runtime: Runtime,
@@ -142,6 +165,9 @@ impl Network {
         gossip_multihop: bool,
         gossip_target: WhatAmIMatcher,
         autoconnect: AutoConnect,
+        link_weights: LinkWeights,
+        redundant: bool,
+        topology_events: bool,
     ) -> Self {
         let mut graph = petgraph::stable_graph::StableGraph::default();
         tracing::debug!("{} Add node (self) {}", name, zid);
@@ -151,6 +177,7 @@ impl Network {
             locators: None,
             sn: 1,
             links: vec![],
+            link_weights: HashMap::new(),
         });
         Network {
             name,
@@ -160,6 +187,7 @@ impl Network {
             gossip_multihop,
             gossip_target,
             autoconnect,
+            link_weights,
             idx,
             links: VecMap::new(),
             trees: vec![Tree {
@@ -167,6 +195,9 @@ impl Network {
                 children: vec![],
                 directions: vec![None],
             }],
+            redundant,
+            redundant_trees: vec![],
+            topology_events,
             distances: vec![0.0],
             graph,
             runtime,
@@ -204,9 +235,11 @@ impl Network {
 
     #[inline]
     pub(super) fn get_local_context(&self, context: NodeId, link_id: usize) -> NodeId {
+        let redundant = context & REDUNDANT_CONTEXT;
+        let context = context & !REDUNDANT_CONTEXT;
         match self.get_link(link_id) {
             Some(link) => match link.get_local_psid(&(context as u64)) {
-                Some(psid) => (*psid).try_into().unwrap_or(0),
+                Some(psid) => NodeId::try_from(*psid).unwrap_or(0) | redundant,
                 None => {
                     tracing::error!(
                         "Cannot find local psid for context {} on link {}",
@@ -225,16 +258,47 @@ impl Network {
 
     fn add_node(&mut self, node: Node) -> NodeIndex {
         let zid = node.zid;
+        let whatami = node.whatami;
         let idx = self.graph.add_node(node);
         for link in self.links.values_mut() {
             if let Some((psid, _)) = link.mappings.iter().find(|(_, p)| **p == zid) {
                 link.local_mappings.insert(psid, idx.index() as u64);
             }
         }
+        self.notify(|| TopologyEvent::NodeAdded {
+            zid: zid.into(),
+            whatami,
+        });
         idx
     }
 
+    fn notify(&self, event: impl FnOnce() -> TopologyEvent) {
+        if self.topology_events {
+            self.runtime.topology().notify(event);
+        }
+    }
+
+    fn notify_links(&self, idx: NodeIndex) {
+        self.notify(|| {
+            let node = &self.graph[idx];
+            TopologyEvent::LinksChanged {
+                zid: node.zid.into(),
+                links: node.links.iter().map(|zid| (*zid).into()).collect(),
+            }
+        });
+    }
+
     fn make_link_state(&self, idx: NodeIndex, details: &Details) -> LinkState {
+        let node = &self.graph[idx];
+        let link_weights = details
+            .links
+            .then(|| {
+                encode_link_weights(
+                    node.links.iter().filter(|zid| self.get_idx(zid).is_some()),
+                    &node.link_weights,
+                )
+            })
+            .flatten();
         let links = if details.links {
             self.graph[idx]
                 .links
@@ -274,6 +338,7 @@ impl Network {
                 None
             },
             links,
+            link_weights,
         }
     }
 
@@ -357,7 +422,12 @@ impl Network {
             hasher.write(&self.graph[idx1].zid.to_le_bytes());
             hasher.write(&self.graph[idx2].zid.to_le_bytes());
         }
-        let weight = 100.0 + ((hasher.finish() as u32) as f64) / u32::MAX as f64;
+        let (node1, node2) = (&self.graph[idx1], &self.graph[idx2]);
+        let weight = edge_weight(
+            (&node1.zid, &node1.link_weights),
+            (&node2.zid, &node2.link_weights),
+        ) as f64
+            + ((hasher.finish() as u32) as f64) / u32::MAX as f64;
         self.graph.update_edge(idx1, idx2, weight);
     }
 
@@ -401,6 +471,7 @@ impl Network {
                         link_state.locators,
                         link_state.sn,
                         link_state.links,
+                        link_state.link_weights,
                     ))
                 } else {
                     match src_link.get_zid(&link_state.psid) {
@@ -410,6 +481,7 @@ impl Network {
                             link_state.locators,
                             link_state.sn,
                             link_state.links,
+                            link_state.link_weights,
                         )),
                         None => {
                             tracing::error!(
@@ -428,11 +500,16 @@ impl Network {
         let src_link = self.get_link_from_zid(&src).unwrap();
         let link_states = link_states
             .into_iter()
-            .map(|(zid, wai, locs, sn, links)| {
+            .map(|(zid, wai, locs, sn, links, weights)| {
+                let mut link_weights = HashMap::new();
                 let links: Vec<ZenohIdProto> = links
                     .iter()
-                    .filter_map(|l| {
+                    .enumerate()
+                    .filter_map(|(i, l)| {
                         if let Some(zid) = src_link.get_zid(l) {
+                            if let Some(weight) = decode_link_weight(&weights, i) {
+                                link_weights.insert(*zid, weight);
+                            }
                             Some(*zid)
                         } else {
                             tracing::error!(
@@ -445,7 +522,7 @@ impl Network {
                         }
                     })
                     .collect();
-                (zid, wai, locs, sn, links)
+                (zid, wai, locs, sn, links, link_weights)
             })
             .collect::<Vec<_>>();
 
@@ -469,33 +546,45 @@ impl Network {
                 updated_nodes: vec![],
                 removed_nodes: vec![],
             };
-            for (zid, whatami, locators, sn, links) in link_states.into_iter() {
+            for (zid, whatami, locators, sn, links, link_weights) in link_states.into_iter() {
+                let mut links_changed = false;
                 let idx = match self.get_idx(&zid) {
                     None => {
+                        links_changed = !links.is_empty();
                         let idx = self.add_node(Node {
                             zid,
                             whatami: Some(whatami),
                             locators: locators.clone(),
                             sn,
                             links,
+                            link_weights,
                         });
                         changes.updated_nodes.push((idx, self.graph[idx].clone()));
+                        if links_changed {
+                            self.notify_links(idx);
+                        }
                         locators.is_some().then_some(idx)
                     }
                     Some(idx) => {
                         let node = &mut self.graph[idx];
                         let oldsn = node.sn;
-                        (oldsn < sn)
+                        let propagated = (oldsn < sn)
                             .then(|| {
                                 node.sn = sn;
+                                links_changed = node.links != links;
                                 node.links.clone_from(&links);
+                                node.link_weights = link_weights;
                                 changes.updated_nodes.push((idx, node.clone()));
                                 (node.locators != locators && locators.is_some()).then(|| {
                                     node.locators.clone_from(&locators);
                                     idx
                                 })
                             })
-                            .flatten()
+                            .flatten();
+                        if links_changed {
+                            self.notify_links(idx);
+                        }
+                        propagated
                     }
                 };
 
@@ -543,16 +632,21 @@ impl Network {
         }
 
         // Add nodes to graph & filter out up to date states
+        let mut changed_links = vec![];
         let mut link_states = link_states
             .into_iter()
-            .filter_map(
-                |(zid, whatami, locators, sn, links)| match self.get_idx(&zid) {
+            .filter_map(|(zid, whatami, locators, sn, links, link_weights)| {
+                match self.get_idx(&zid) {
                     Some(idx) => {
                         let node = &mut self.graph[idx];
                         let oldsn = node.sn;
                         if oldsn < sn {
                             node.sn = sn;
+                            if node.links != links {
+                                changed_links.push(idx);
+                            }
                             node.links.clone_from(&links);
+                            node.link_weights = link_weights;
                             if locators.is_some() {
                                 node.locators = locators;
                             }
@@ -572,13 +666,17 @@ impl Network {
                             locators,
                             sn,
                             links: links.clone(),
+                            link_weights,
                         };
                         tracing::debug!("{} Add node (state) {}", self.name, zid);
                         let idx = self.add_node(node);
+                        if !links.is_empty() {
+                            changed_links.push(idx);
+                        }
                         Some((links, idx, true))
                     }
-                },
-            )
+                }
+            })
             .collect::<Vec<(Vec<ZenohIdProto>, NodeIndex, bool)>>();
 
         // Add/remove edges from graph
@@ -602,6 +700,7 @@ impl Network {
                         locators: None,
                         sn: 0,
                         links: vec![],
+                        link_weights: HashMap::new(),
                     };
                     tracing::debug!("{} Add node (reintroduced) {}", self.name, link.clone());
                     let idx = self.add_node(node);
@@ -628,6 +727,11 @@ impl Network {
         link_states.extend(reintroduced_nodes);
 
         let removed = self.remove_detached_nodes();
+        for idx in changed_links {
+            if !removed.iter().any(|(removed, _)| *removed == idx) {
+                self.notify_links(idx);
+            }
+        }
         let link_states = link_states
             .into_iter()
             .filter(|ls| !removed.iter().any(|(idx, _)| idx == &ls.1))
@@ -748,17 +852,26 @@ impl Network {
                             locators: None,
                             sn: 0,
                             links: vec![],
+                            link_weights: HashMap::new(),
                         }),
                         true,
                     )
                 }
             };
+            self.link_weights.new_link(
+                &self.runtime,
+                &transport,
+                zid,
+                &mut self.graph[self.idx].link_weights,
+                self.full_linkstate,
+            );
             if self.full_linkstate && self.graph[idx].links.contains(&self.graph[self.idx].zid) {
                 tracing::trace!("Update edge (link) {} {}", self.graph[self.idx].zid, zid);
                 self.update_edge(self.idx, idx);
             }
             self.graph[self.idx].links.push(zid);
             self.graph[self.idx].sn += 1;
+            self.notify_links(self.idx);
 
             // Send updated self linkstate on all existing links except new one
             self.links
@@ -838,7 +951,12 @@ impl Network {
     pub(super) fn remove_link(&mut self, zid: &ZenohIdProto) -> Vec<(NodeIndex, Node)> {
         tracing::trace!("{} remove_link {}", self.name, zid);
         self.links.retain(|_, link| link.zid != *zid);
+        let links = self.graph[self.idx].links.len();
         self.graph[self.idx].links.retain(|link| *link != *zid);
+        self.graph[self.idx].link_weights.remove(zid);
+        if self.graph[self.idx].links.len() != links {
+            self.notify_links(self.idx);
+        }
 
         if self.full_linkstate {
             if let Some((edge, _)) = self
@@ -867,6 +985,7 @@ impl Network {
         } else {
             if let Some(idx) = self.get_idx(zid) {
                 self.graph.remove_node(idx);
+                self.notify(|| TopologyEvent::NodeRemoved { zid: (*zid).into() });
             }
             if self.router_peers_failover_brokering {
                 self.send_on_links(
@@ -889,6 +1008,51 @@ impl Network {
         }
     }
 
+    /// Updates the weight of the link of `transport` from a round-trip time measurement.
+    ///
+    /// Returns true if the trees should be recomputed, the weight being only updated
+    /// when it differs by more than 20% from the advertised one.
+    pub(super) fn link_rtt(&mut self, transport: &TransportUnicast, rtt: Duration) -> bool {
+        if !self.full_linkstate {
+            return false;
+        }
+        // Only the link the probe was received on is updated
+        let Some(link) = self
+            .links
+            .values_mut()
+            .find(|link| link.transport == *transport)
+        else {
+            return false;
+        };
+        let zid = &link.zid.clone();
+        let current = link_weight(&self.graph[self.idx].link_weights, zid);
+        let Some(weight) = self
+            .link_weights
+            .rtt_update(zid, &mut link.rtt, rtt, current)
+        else {
+            return false;
+        };
+        self.graph[self.idx].link_weights.insert(*zid, weight);
+        if let Some(idx) = self.get_idx(zid) {
+            if self.graph[idx].links.contains(&self.graph[self.idx].zid) {
+                self.update_edge(self.idx, idx);
+            }
+        }
+        self.graph[self.idx].sn += 1;
+        self.send_on_links(
+            vec![(
+                self.idx,
+                Details {
+                    zid: false,
+                    links: true,
+                    ..Default::default()
+                },
+            )],
+            |_| true,
+        );
+        true
+    }
+
     fn remove_detached_nodes(&mut self) -> Vec<(NodeIndex, Node)> {
         let mut dfs_stack = vec![self.idx];
         let mut visit_map = self.graph.visit_map();
@@ -908,15 +1072,120 @@ impl Network {
         for idx in self.graph.node_indices().collect::<Vec<NodeIndex>>() {
             if !visit_map.is_visited(&idx) {
                 tracing::debug!("Remove node {}", &self.graph[idx].zid);
-                removed.push((idx, self.graph.remove_node(idx).unwrap()));
+                let node = self.graph.remove_node(idx).unwrap();
+                self.notify(|| TopologyEvent::NodeRemoved {
+                    zid: node.zid.into(),
+                });
+                removed.push((idx, node));
             }
         }
         removed
     }
 
+    /// The tree of the paths of the given predecessors, as seen from this node.
+    fn tree(
+        &self,
+        predecessors: &[Option<NodeIndex>],
+        indexes: &[NodeIndex],
+        max_idx: NodeIndex,
+    ) -> Tree {
+        let mut tree = Tree {
+            parent: predecessors[self.idx.index()],
+            children: vec![],
+            directions: vec![None; max_idx.index() + 1],
+        };
+
+        for idx in indexes {
+            if let Some(parent_idx) = predecessors[idx.index()] {
+                if parent_idx == self.idx {
+                    tree.children.push(*idx);
+                }
+            }
+        }
+
+        let mut dfs = petgraph::algo::DfsSpace::new(&self.graph);
+        for destination in indexes {
+            if self.idx != *destination
+                && petgraph::algo::has_path_connecting(
+                    &self.graph,
+                    self.idx,
+                    *destination,
+                    Some(&mut dfs),
+                )
+            {
+                let mut direction = None;
+                let mut current = *destination;
+                while let Some(parent) = predecessors[current.index()] {
+                    if parent == self.idx {
+                        direction = Some(current);
+                        break;
+                    } else {
+                        current = parent;
+                    }
+                }
+
+                tree.directions[destination.index()] = match direction {
+                    Some(direction) => Some(direction),
+                    None => tree.parent,
+                };
+            }
+        }
+        tree
+    }
+
+    /// The predecessors of the lowest weight paths from `root` once the links of the paths
+    /// of the given predecessors are made heavier than any path avoiding them.
+    fn redundant_predecessors(
+        &self,
+        root: NodeIndex,
+        predecessors: &[Option<NodeIndex>],
+    ) -> Vec<Option<NodeIndex>> {
+        let penalty = self.graph.edge_weights().sum::<f64>() + 1.0;
+        let penalized = predecessors
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, parent)| {
+                parent.and_then(|parent| self.graph.find_edge(NodeIndex::new(idx), parent))
+            })
+            .collect::<HashSet<_>>();
+
+        // Dijkstra's algorithm, on the graph itself with the weights of the penalized links
+        // increased on the fly. The weights are positive, the bit patterns of positive floats
+        // are ordered like the floats themselves.
+        let bound = NodeIndexable::node_bound(&self.graph);
+        let mut distances = vec![f64::INFINITY; bound];
+        let mut redundant = vec![None; bound];
+        let mut heap = BinaryHeap::new();
+        distances[root.index()] = 0.0;
+        heap.push(Reverse((0f64.to_bits(), root)));
+        while let Some(Reverse((distance, idx))) = heap.pop() {
+            let distance = f64::from_bits(distance);
+            if distance > distances[idx.index()] {
+                continue;
+            }
+            for edge in self.graph.edges(idx) {
+                let next = if edge.source() == idx {
+                    edge.target()
+                } else {
+                    edge.source()
+                };
+                let mut weight = *edge.weight();
+                if penalized.contains(&edge.id()) {
+                    weight += penalty;
+                }
+                if distance + weight < distances[next.index()] {
+                    distances[next.index()] = distance + weight;
+                    redundant[next.index()] = Some(idx);
+                    heap.push(Reverse(((distance + weight).to_bits(), next)));
+                }
+            }
+        }
+        redundant
+    }
+
     pub(super) fn compute_trees(&mut self) -> Vec<Vec<NodeIndex>> {
         let indexes = self.graph.node_indices().collect::<Vec<NodeIndex>>();
-        let max_idx = indexes.iter().max().unwrap();
+        let max_idx = *indexes.iter().max().unwrap();
 
         let old_children: Vec<Vec<NodeIndex>> =
             self.trees.iter().map(|t| t.children.clone()).collect();
@@ -927,6 +1196,10 @@ impl Network {
             children: vec![],
             directions: vec![],
         });
+        self.redundant_trees.clear();
+        if self.redundant {
+            self.redundant_trees = self.trees.clone();
+        }
 
         for tree_root_idx in &indexes {
             let paths = petgraph::algo::bellman_ford(&self.graph, *tree_root_idx).unwrap();
@@ -953,46 +1226,11 @@ impl Network {
                 tracing::debug!("Tree {} {:?}", self.graph[*tree_root_idx].zid, ps);
             }
 
-            self.trees[tree_root_idx.index()].parent = paths.predecessors[self.idx.index()];
-
-            for idx in &indexes {
-                if let Some(parent_idx) = paths.predecessors[idx.index()] {
-                    if parent_idx == self.idx {
-                        self.trees[tree_root_idx.index()].children.push(*idx);
-                    }
-                }
-            }
-
-            self.trees[tree_root_idx.index()]
-                .directions
-                .resize_with(max_idx.index() + 1, || None);
-            let mut dfs = petgraph::algo::DfsSpace::new(&self.graph);
-            for destination in &indexes {
-                if self.idx != *destination
-                    && petgraph::algo::has_path_connecting(
-                        &self.graph,
-                        self.idx,
-                        *destination,
-                        Some(&mut dfs),
-                    )
-                {
-                    let mut direction = None;
-                    let mut current = *destination;
-                    while let Some(parent) = paths.predecessors[current.index()] {
-                        if parent == self.idx {
-                            direction = Some(current);
-                            break;
-                        } else {
-                            current = parent;
-                        }
-                    }
-
-                    self.trees[tree_root_idx.index()].directions[destination.index()] =
-                        match direction {
-                            Some(direction) => Some(direction),
-                            None => self.trees[tree_root_idx.index()].parent,
-                        };
-                }
+            self.trees[tree_root_idx.index()] = self.tree(&paths.predecessors, &indexes, max_idx);
+            if self.redundant {
+                let predecessors = self.redundant_predecessors(*tree_root_idx, &paths.predecessors);
+                self.redundant_trees[tree_root_idx.index()] =
+                    self.tree(&predecessors, &indexes, max_idx);
             }
         }
 
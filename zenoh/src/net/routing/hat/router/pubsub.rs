@@ -19,7 +19,10 @@ use std::{
 
 use petgraph::graph::NodeIndex;
 use zenoh_protocol::{
-    core::{key_expr::OwnedKeyExpr, WhatAmI, ZenohIdProto},
+    core::{
+        key_expr::{keyexpr, OwnedKeyExpr},
+        WhatAmI, ZenohIdProto,
+    },
     network::{
         declare::{
             common::ext::WireExprType, ext, Declare, DeclareBody, DeclareSubscriber, SubscriberId,
@@ -31,9 +34,12 @@ use zenoh_protocol::{
 use zenoh_sync::get_mut_unchecked;
 
 use super::{
-    face_hat, face_hat_mut, get_peer, get_router, hat, hat_mut,
-    interests::push_declaration_profile, network::Network, res_hat, res_hat_mut, HatCode,
-    HatContext, HatFace, HatTables,
+    face_hat, face_hat_mut,
+    gateways::{is_gateway, is_parked},
+    get_peer, get_router, hat, hat_mut,
+    interests::push_declaration_profile,
+    network::Network,
+    res_hat, res_hat_mut, HatCode, HatContext, HatFace, HatTables,
 };
 #[cfg(feature = "unstable")]
 use crate::key_expr::KeyExpr;
@@ -42,7 +48,7 @@ use crate::net::routing::{
         face::FaceState,
         interests::RemoteInterest,
         pubsub::SubscriberInfo,
-        resource::{NodeId, Resource, SessionContext},
+        resource::{NodeId, Resource, SessionContext, REDUNDANT_CONTEXT},
         tables::{Route, RoutingExpr, Tables},
     },
     hat::{CurrentFutureTrait, HatPubSubTrait, SendDeclare, Sources},
@@ -106,10 +112,11 @@ fn propagate_simple_subscription_to(
 ) {
     if src_face.id != dst_face.id
         && !face_hat!(dst_face).local_subs.contains_key(res)
+        && advertised(tables, res, dst_face)
         && if full_peer_net {
-            dst_face.whatami == WhatAmI::Client
+            dst_face.whatami == WhatAmI::Client || is_gateway(dst_face)
         } else {
-            dst_face.whatami != WhatAmI::Router
+            (dst_face.whatami != WhatAmI::Router || is_gateway(dst_face))
                 && (src_face.whatami != WhatAmI::Peer
                     || dst_face.whatami != WhatAmI::Peer
                     || hat!(tables).failover_brokering(src_face.zid, dst_face.zid))
@@ -193,6 +200,10 @@ fn propagate_sourced_subscription(
     source: &ZenohIdProto,
     net_type: WhatAmI,
 ) {
+    if net_type == WhatAmI::Router && *source == tables.zid && aggregated(tables, res) {
+        // Advertised through the aggregate
+        return;
+    }
     let net = hat!(tables).get_net(net_type).unwrap();
     match net.get_idx(source) {
         Some(tree_sid) => {
@@ -292,6 +303,7 @@ fn declare_linkstatepeer_subscription(
     let propa_sub_info = *sub_info;
     let zid = tables.zid;
     register_router_subscription(tables, face, res, &propa_sub_info, zid, send_declare);
+    declare_aggregate(tables, face, res, &propa_sub_info, send_declare);
 }
 
 fn register_simple_subscription(
@@ -333,6 +345,174 @@ fn declare_simple_subscription(
     register_simple_subscription(tables, face, id, res, sub_info);
     let zid = tables.zid;
     register_router_subscription(tables, face, res, sub_info, zid, send_declare);
+    declare_aggregate(tables, face, res, sub_info, send_declare);
+}
+
+/// The resource of the configured aggregate including `res`, if any.
+///
+/// This router advertises a single subscription on the aggregate to the other routers and to the
+/// gateways of other regions in place of the subscriptions of its clients and peers it includes,
+/// the data being filtered when routed to these subscribers. The clients and peers still get the
+/// subscriptions themselves, so that matching stays exact.
+fn aggregated_subscription(tables: &mut Tables, res: &Arc<Resource>) -> Option<Arc<Resource>> {
+    let key_expr = keyexpr::new(res.expr()).ok()?;
+    let aggregate = hat!(tables)
+        .aggregated_subs
+        .iter()
+        .find(|aggregate| aggregate.key_expr.includes(key_expr))?
+        .key_expr
+        .clone();
+    let mut root = tables.root_res.clone();
+    match Resource::get_resource(&root, &aggregate) {
+        Some(res) if res.context.is_some() => Some(res),
+        _ => {
+            let mut matches = Resource::get_matches(tables, &aggregate);
+            let mut res = Resource::make_resource(tables, &mut root, &aggregate);
+            matches.push(Arc::downgrade(&res));
+            Resource::match_resource(tables, &mut res, matches);
+            Some(res)
+        }
+    }
+}
+
+/// A configured aggregate, with the resources it includes having subscriptions of clients or peers.
+pub(super) struct SubsAggregate {
+    key_expr: OwnedKeyExpr,
+    /// Kept up to date on each (un)declaration of an included subscription, the aggregate being
+    /// advertised as long as it is not empty
+    subscribed: HashSet<Arc<Resource>>,
+}
+
+impl SubsAggregate {
+    pub(super) fn new(key_expr: OwnedKeyExpr) -> Self {
+        Self {
+            key_expr,
+            subscribed: HashSet::new(),
+        }
+    }
+}
+
+/// Whether there are subscriptions of clients or peers on `res`.
+fn client_or_peer_subs(tables: &Tables, res: &Arc<Resource>) -> bool {
+    res.context.is_some()
+        && (!simple_subs(res).is_empty() || remote_linkstatepeer_subs(tables, res))
+}
+
+/// Updates the subscribed resources of the aggregate including `res`, if any, after a change of the
+/// subscriptions on `res`. Returns the number of subscribed resources left in the aggregate.
+fn update_aggregated_subs(tables: &mut Tables, res: &Arc<Resource>) -> usize {
+    let subscribed = client_or_peer_subs(tables, res);
+    let Ok(key_expr) = keyexpr::new(res.expr()) else {
+        return 0;
+    };
+    let Some(aggregate) = hat_mut!(tables)
+        .aggregated_subs
+        .iter_mut()
+        .find(|aggregate| aggregate.key_expr.includes(key_expr))
+    else {
+        return 0;
+    };
+    if subscribed {
+        aggregate.subscribed.insert(res.clone());
+    } else {
+        aggregate.subscribed.remove(res);
+    }
+    aggregate.subscribed.len()
+}
+
+/// Whether `res` is strictly included in a configured aggregate.
+fn aggregated(tables: &Tables, res: &Resource) -> bool {
+    keyexpr::new(res.expr()).is_ok_and(|key_expr| {
+        hat!(tables).aggregated_subs.iter().any(|aggregate| {
+            aggregate.key_expr.as_ref() != key_expr && aggregate.key_expr.includes(key_expr)
+        })
+    })
+}
+
+/// Whether `res` is a configured aggregate.
+fn is_aggregate(tables: &Tables, res: &Resource) -> bool {
+    hat!(tables)
+        .aggregated_subs
+        .iter()
+        .any(|aggregate| aggregate.key_expr.as_str() == res.expr())
+}
+
+/// Whether `res` is a configured aggregate including subscriptions of clients or peers, which keeps it subscribed.
+fn subscribed_aggregate(tables: &Tables, res: &Arc<Resource>) -> bool {
+    hat!(tables).aggregated_subs.iter().any(|aggregate| {
+        aggregate.key_expr.as_str() == res.expr()
+            // The subscriptions on the aggregate itself may just have changed
+            && (client_or_peer_subs(tables, res)
+                || aggregate.subscribed.iter().any(|sub| !Arc::ptr_eq(sub, res)))
+    })
+}
+
+/// Whether `res` is a configured aggregate only subscribed on behalf of the subscriptions it includes.
+fn aggregate_only(tables: &Tables, res: &Arc<Resource>) -> bool {
+    res.context.is_some()
+        && res_hat!(res).router_subs.contains(&tables.zid)
+        && is_aggregate(tables, res)
+        && simple_subs(res).is_empty()
+        && !remote_router_subs(tables, res)
+        && !remote_linkstatepeer_subs(tables, res)
+}
+
+/// Whether the subscriptions on `res` are advertised to the client or peer `face`: the gateways
+/// of other regions get the aggregates in place of the subscriptions of clients and peers they
+/// include, and only them get the aggregates.
+fn advertised(tables: &Tables, res: &Arc<Resource>, face: &FaceState) -> bool {
+    if is_gateway(face) {
+        !aggregated(tables, res) || remote_router_subs(tables, res)
+    } else {
+        !aggregate_only(tables, res)
+    }
+}
+
+/// Whether `res` is an aggregate advertised to the gateway `face` on behalf of the subscriptions it includes.
+fn upstream_aggregate(tables: &Tables, res: &Arc<Resource>, face: &FaceState) -> bool {
+    is_gateway(face) && aggregate_only(tables, res)
+}
+
+/// Advertises the aggregate including `res`, if any, to the routers and gateways.
+fn declare_aggregate(
+    tables: &mut Tables,
+    face: &mut Arc<FaceState>,
+    res: &Arc<Resource>,
+    sub_info: &SubscriberInfo,
+    send_declare: &mut SendDeclare,
+) {
+    update_aggregated_subs(tables, res);
+    let Some(mut aggregate) = aggregated_subscription(tables, res) else {
+        return;
+    };
+    let zid = tables.zid;
+    if !res_hat!(aggregate).router_subs.contains(&zid) {
+        res_hat_mut!(&mut aggregate).router_subs.insert(zid);
+        hat_mut!(tables).router_subs.insert(aggregate.clone());
+        propagate_sourced_subscription(
+            tables,
+            &aggregate,
+            sub_info,
+            Some(face),
+            &zid,
+            WhatAmI::Router,
+        );
+    }
+    propagate_simple_subscription(tables, &aggregate, sub_info, face, send_declare);
+    disable_matches_data_routes(tables, &mut aggregate);
+}
+
+/// Withdraws the aggregate including `res`, if any, once it includes no more subscriptions of
+/// clients and peers.
+fn undeclare_aggregate(tables: &mut Tables, res: &Arc<Resource>, send_declare: &mut SendDeclare) {
+    if update_aggregated_subs(tables, res) == 0 {
+        if let Some(mut aggregate) = aggregated_subscription(tables, res) {
+            let zid = tables.zid;
+            undeclare_router_subscription(tables, None, &mut aggregate, &zid, send_declare);
+            disable_matches_data_routes(tables, &mut aggregate);
+            Resource::clean(&mut aggregate);
+        }
+    }
 }
 
 #[inline]
@@ -499,6 +679,7 @@ fn propagate_forget_simple_subscription_to_peers(
                     face.zid != s.face.zid
                         && s.subs.is_some()
                         && (s.face.whatami == WhatAmI::Client
+                            || is_gateway(&s.face)
                             || (s.face.whatami == WhatAmI::Peer
                                 && hat!(tables).failover_brokering(s.face.zid, face.zid)))
                 })
@@ -526,6 +707,38 @@ fn propagate_forget_simple_subscription_to_peers(
     }
 }
 
+/// Undeclares `res` to the faces it is not advertised to anymore, such as the clients once an
+/// aggregate is only subscribed on behalf of the subscriptions it includes.
+fn propagate_forget_unadvertised_subscription(
+    tables: &mut Tables,
+    res: &Arc<Resource>,
+    send_declare: &mut SendDeclare,
+) {
+    for mut face in tables.faces.values().cloned() {
+        if advertised(tables, res, &face) {
+            continue;
+        }
+        if let Some(id) = face_hat_mut!(&mut face).local_subs.remove(res) {
+            send_declare(
+                &face.primitives,
+                RoutingContext::with_expr(
+                    Declare {
+                        interest_id: None,
+                        ext_qos: ext::QoSType::DECLARE,
+                        ext_tstamp: None,
+                        ext_nodeid: ext::NodeIdType::DEFAULT,
+                        body: DeclareBody::UndeclareSubscriber(UndeclareSubscriber {
+                            id,
+                            ext_wire_expr: WireExprType::null(),
+                        }),
+                    },
+                    res.expr().to_string(),
+                ),
+            );
+        }
+    }
+}
+
 fn propagate_forget_sourced_subscription(
     tables: &Tables,
     res: &Arc<Resource>,
@@ -533,6 +746,10 @@ fn propagate_forget_sourced_subscription(
     source: &ZenohIdProto,
     net_type: WhatAmI,
 ) {
+    if net_type == WhatAmI::Router && *source == tables.zid && aggregated(tables, res) {
+        // Advertised through the aggregate
+        return;
+    }
     let net = hat!(tables).get_net(net_type).unwrap();
     match net.get_idx(source) {
         Some(tree_sid) => {
@@ -643,8 +860,13 @@ fn forget_linkstatepeer_subscription(
     let linkstatepeer_subs = remote_linkstatepeer_subs(tables, res);
     let zid = tables.zid;
     if !simple_subs && !linkstatepeer_subs {
-        undeclare_router_subscription(tables, None, res, &zid, send_declare);
+        if subscribed_aggregate(tables, res) {
+            propagate_forget_unadvertised_subscription(tables, res, send_declare);
+        } else {
+            undeclare_router_subscription(tables, None, res, &zid, send_declare);
+        }
     }
+    undeclare_aggregate(tables, res, send_declare);
 }
 
 pub(super) fn undeclare_simple_subscription(
@@ -662,10 +884,15 @@ pub(super) fn undeclare_simple_subscription(
         let router_subs = remote_router_subs(tables, res);
         let linkstatepeer_subs = remote_linkstatepeer_subs(tables, res);
         if simple_subs.is_empty() && !linkstatepeer_subs {
-            undeclare_router_subscription(tables, None, res, &tables.zid.clone(), send_declare);
+            if subscribed_aggregate(tables, res) {
+                propagate_forget_unadvertised_subscription(tables, res, send_declare);
+            } else {
+                undeclare_router_subscription(tables, None, res, &tables.zid.clone(), send_declare);
+            }
         } else {
             propagate_forget_simple_subscription_to_peers(tables, res, send_declare);
         }
+        undeclare_aggregate(tables, res, send_declare);
 
         if simple_subs.len() == 1 && !router_subs && !linkstatepeer_subs {
             let mut face = &mut simple_subs[0];
@@ -772,14 +999,19 @@ pub(super) fn pubsub_remove_node(
                 let simple_subs = res.session_ctxs.values().any(|ctx| ctx.subs.is_some());
                 let linkstatepeer_subs = remote_linkstatepeer_subs(tables, &res);
                 if !simple_subs && !linkstatepeer_subs {
-                    undeclare_router_subscription(
-                        tables,
-                        None,
-                        &mut res,
-                        &tables.zid.clone(),
-                        send_declare,
-                    );
+                    if subscribed_aggregate(tables, &res) {
+                        propagate_forget_unadvertised_subscription(tables, &res, send_declare);
+                    } else {
+                        undeclare_router_subscription(
+                            tables,
+                            None,
+                            &mut res,
+                            &tables.zid.clone(),
+                            send_declare,
+                        );
+                    }
                 }
+                undeclare_aggregate(tables, &res, send_declare);
 
                 disable_matches_data_routes(tables, &mut res);
                 Resource::clean(&mut res)
@@ -819,7 +1051,11 @@ pub(super) fn pubsub_tree_change(
                         _ => &res_hat!(res).linkstatepeer_subs,
                     };
                     for sub in subs {
-                        if *sub == tree_id {
+                        if *sub == tree_id
+                            && !(net_type == WhatAmI::Router
+                                && tree_id == tables.zid
+                                && aggregated(tables, res))
+                        {
                             let sub_info = SubscriberInfo;
                             send_sourced_subscription_to_net_children(
                                 tables,
@@ -850,10 +1086,10 @@ pub(super) fn pubsub_linkstate_change(
                 .local_subs
                 .keys()
                 .filter(|res| {
-                    let client_subs = res
-                        .session_ctxs
-                        .values()
-                        .any(|ctx| ctx.face.whatami == WhatAmI::Client && ctx.subs.is_some());
+                    let client_subs = res.session_ctxs.values().any(|ctx| {
+                        (ctx.face.whatami == WhatAmI::Client || is_gateway(&ctx.face))
+                            && ctx.subs.is_some()
+                    });
                     !remote_router_subs(tables, res)
                         && !client_subs
                         && !res.session_ctxs.values().any(|ctx| {
@@ -981,7 +1217,9 @@ pub(crate) fn declare_sub_interest(
                 for sub in &hat!(tables).router_subs {
                     if sub.context.is_some()
                         && sub.matches(res)
-                        && (res_hat!(sub).router_subs.iter().any(|r| *r != tables.zid)
+                        && advertised(tables, sub, face)
+                        && (upstream_aggregate(tables, sub, face)
+                            || res_hat!(sub).router_subs.iter().any(|r| *r != tables.zid)
                             || res_hat!(sub)
                                 .linkstatepeer_subs
                                 .iter()
@@ -990,7 +1228,9 @@ pub(crate) fn declare_sub_interest(
                                 s.face.id != face.id
                                     && s.subs.is_some()
                                     && (s.face.whatami == WhatAmI::Client
+                                        || is_gateway(&s.face)
                                         || face.whatami == WhatAmI::Client
+                                        || is_gateway(face)
                                         || (s.face.whatami == WhatAmI::Peer
                                             && hat!(tables)
                                                 .failover_brokering(s.face.zid, face.zid)))
@@ -1021,7 +1261,9 @@ pub(crate) fn declare_sub_interest(
         } else {
             for sub in &hat!(tables).router_subs {
                 if sub.context.is_some()
-                    && (res_hat!(sub).router_subs.iter().any(|r| *r != tables.zid)
+                    && advertised(tables, sub, face)
+                    && (upstream_aggregate(tables, sub, face)
+                        || res_hat!(sub).router_subs.iter().any(|r| *r != tables.zid)
                         || res_hat!(sub)
                             .linkstatepeer_subs
                             .iter()
@@ -1069,8 +1311,11 @@ impl HatPubSubTrait for HatCode {
         node_id: NodeId,
         send_declare: &mut SendDeclare,
     ) {
+        if is_parked(face) {
+            return;
+        }
         match face.whatami {
-            WhatAmI::Router => {
+            WhatAmI::Router if !is_gateway(face) => {
                 if let Some(router) = get_router(tables, face, node_id) {
                     declare_router_subscription(tables, face, res, sub_info, router, send_declare)
                 }
@@ -1105,7 +1350,7 @@ impl HatPubSubTrait for HatCode {
         send_declare: &mut SendDeclare,
     ) -> Option<Arc<Resource>> {
         match face.whatami {
-            WhatAmI::Router => {
+            WhatAmI::Router if !is_gateway(face) => {
                 if let Some(mut res) = res {
                     if let Some(router) = get_router(tables, face, node_id) {
                         forget_router_subscription(tables, face, &mut res, &router, send_declare);
@@ -1215,13 +1460,16 @@ impl HatPubSubTrait for HatCode {
             source: NodeId,
             subs: &HashSet<ZenohIdProto>,
         ) {
-            if net.trees.len() > source as usize {
+            let (trees, tree) = if source & REDUNDANT_CONTEXT != 0 {
+                (&net.redundant_trees, (source & !REDUNDANT_CONTEXT) as usize)
+            } else {
+                (&net.trees, source as usize)
+            };
+            if trees.len() > tree {
                 for sub in subs {
                     if let Some(sub_idx) = net.get_idx(sub) {
-                        if net.trees[source as usize].directions.len() > sub_idx.index() {
-                            if let Some(direction) =
-                                net.trees[source as usize].directions[sub_idx.index()]
-                            {
+                        if trees[tree].directions.len() > sub_idx.index() {
+                            if let Some(direction) = trees[tree].directions[sub_idx.index()] {
                                 if net.graph.contains_node(direction) {
                                     if let Some(face) = tables.get_face(&net.graph[direction].zid) {
                                         route.entry(face.id).or_insert_with(|| {
@@ -1279,7 +1527,7 @@ impl HatPubSubTrait for HatCode {
                 let net = hat!(tables).routers_net.as_ref().unwrap();
                 let router_source = match source_type {
                     WhatAmI::Router => source,
-                    _ => net.idx.index() as NodeId,
+                    _ => net.idx.index() as NodeId | (source & REDUNDANT_CONTEXT),
                 };
                 insert_faces_for_subs(
                     &mut route,
@@ -1309,7 +1557,9 @@ impl HatPubSubTrait for HatCode {
 
             if master || source_type == WhatAmI::Router {
                 for (sid, context) in &mres.session_ctxs {
-                    if context.subs.is_some() && context.face.whatami != WhatAmI::Router {
+                    if context.subs.is_some()
+                        && (context.face.whatami != WhatAmI::Router || is_gateway(&context.face))
+                    {
                         route.entry(*sid).or_insert_with(|| {
                             let key_expr = Resource::get_best_key(expr.prefix, expr.suffix, *sid);
                             (context.face.clone(), key_expr.to_owned(), NodeId::default())
@@ -1408,7 +1658,9 @@ impl HatPubSubTrait for HatCode {
 
             if master {
                 for (sid, context) in &mres.session_ctxs {
-                    if context.subs.is_some() && context.face.whatami != WhatAmI::Router {
+                    if context.subs.is_some()
+                        && (context.face.whatami != WhatAmI::Router || is_gateway(&context.face))
+                    {
                         matching_subscriptions
                             .entry(*sid)
                             .or_insert_with(|| context.face.clone());
@@ -37,16 +37,19 @@ use zenoh_protocol::{
 use zenoh_sync::get_mut_unchecked;
 
 use super::{
-    face_hat, face_hat_mut, get_peer, get_router, hat, hat_mut,
-    interests::push_declaration_profile, network::Network, res_hat, res_hat_mut, HatCode,
-    HatContext, HatFace, HatTables,
+    face_hat, face_hat_mut,
+    gateways::{is_gateway, is_parked},
+    get_peer, get_router, hat, hat_mut,
+    interests::push_declaration_profile,
+    network::Network,
+    res_hat, res_hat_mut, HatCode, HatContext, HatFace, HatTables,
 };
 #[cfg(feature = "unstable")]
 use crate::key_expr::KeyExpr;
 use crate::net::routing::{
     dispatcher::{
         face::FaceState,
-        resource::{NodeId, Resource, SessionContext},
+        resource::{NodeId, QueryableTarget, Resource, SessionContext},
         tables::{QueryTargetQabl, QueryTargetQablSet, RoutingExpr, Tables},
     },
     hat::{CurrentFutureTrait, HatQueriesTrait, SendDeclare, Sources},
@@ -243,6 +246,10 @@ fn propagate_simple_queryable(
     let full_peers_net = hat!(tables).full_net(WhatAmI::Peer);
     let faces = tables.faces.values().cloned();
     for mut dst_face in faces {
+        let Some(res) = declared_qabl_res(&dst_face, res) else {
+            continue;
+        };
+        let res = &res;
         let info = local_qabl_info(tables, res, &dst_face);
         let current = face_hat!(dst_face).local_qabls.get(res);
         if src_face
@@ -250,14 +257,10 @@ fn propagate_simple_queryable(
             .map(|src_face| dst_face.id != src_face.id)
             .unwrap_or(true)
             && (current.is_none() || current.unwrap().1 != info)
-            && face_hat!(dst_face)
-                .remote_interests
-                .values()
-                .any(|i| i.options.queryables() && i.matches(res))
             && if full_peers_net {
-                dst_face.whatami == WhatAmI::Client
+                dst_face.whatami == WhatAmI::Client || is_gateway(&dst_face)
             } else {
-                dst_face.whatami != WhatAmI::Router
+                (dst_face.whatami != WhatAmI::Router || is_gateway(&dst_face))
                     && src_face
                         .as_ref()
                         .map(|src_face| {
@@ -297,6 +300,21 @@ fn propagate_simple_queryable(
     }
 }
 
+/// The resource on which the queryables on `res` are declared to `face`, if it is interested in
+/// them: the key expression of the interest for the aggregate interests of the gateways of other
+/// regions, `res` itself otherwise.
+fn declared_qabl_res(face: &FaceState, res: &Arc<Resource>) -> Option<Arc<Resource>> {
+    let gateway = is_gateway(face);
+    face_hat!(face)
+        .remote_interests
+        .values()
+        .find(|i| i.options.queryables() && i.matches(res))
+        .map(|i| match &i.res {
+            Some(int_res) if gateway && i.options.aggregate() => int_res.clone(),
+            _ => res.clone(),
+        })
+}
+
 fn propagate_sourced_queryable(
     tables: &Tables,
     res: &Arc<Resource>,
@@ -501,6 +519,19 @@ fn remote_simple_qabls(res: &Arc<Resource>, face: &Arc<FaceState>) -> bool {
         .any(|ctx| ctx.face.id != face.id && ctx.qabl.is_some())
 }
 
+/// Whether queryables of other nodes than `face` match `res`.
+#[inline]
+fn remote_matching_qabls(tables: &Tables, res: &Arc<Resource>, face: &Arc<FaceState>) -> bool {
+    res.context().matches.iter().any(|m| {
+        m.upgrade().is_some_and(|m| {
+            m.context.is_some()
+                && (remote_simple_qabls(&m, face)
+                    || remote_linkstatepeer_qabls(tables, &m)
+                    || remote_router_qabls(tables, &m))
+        })
+    })
+}
+
 #[inline]
 fn send_forget_sourced_queryable_to_net_children(
     tables: &Tables,
@@ -550,23 +581,33 @@ fn propagate_forget_simple_queryable(
     send_declare: &mut SendDeclare,
 ) {
     for mut face in tables.faces.values().cloned() {
-        if let Some((id, _)) = face_hat_mut!(&mut face).local_qabls.remove(res) {
-            send_declare(
-                &face.primitives,
-                RoutingContext::with_expr(
-                    Declare {
-                        interest_id: None,
-                        ext_qos: ext::QoSType::DECLARE,
-                        ext_tstamp: None,
-                        ext_nodeid: ext::NodeIdType::DEFAULT,
-                        body: DeclareBody::UndeclareQueryable(UndeclareQueryable {
-                            id,
-                            ext_wire_expr: WireExprType::null(),
-                        }),
-                    },
-                    res.expr().to_string(),
-                ),
-            );
+        // The queryable declared on an aggregate interest of a gateway is undeclared with the last
+        // queryable matching the aggregate
+        let declared = match declared_qabl_res(&face, res) {
+            Some(aggregate) if !Arc::ptr_eq(&aggregate, res) => {
+                (!remote_matching_qabls(tables, &aggregate, &face)).then_some(aggregate)
+            }
+            _ => Some(res.clone()),
+        };
+        if let Some(declared) = declared {
+            if let Some((id, _)) = face_hat_mut!(&mut face).local_qabls.remove(&declared) {
+                send_declare(
+                    &face.primitives,
+                    RoutingContext::with_expr(
+                        Declare {
+                            interest_id: None,
+                            ext_qos: ext::QoSType::DECLARE,
+                            ext_tstamp: None,
+                            ext_nodeid: ext::NodeIdType::DEFAULT,
+                            body: DeclareBody::UndeclareQueryable(UndeclareQueryable {
+                                id,
+                                ext_wire_expr: WireExprType::null(),
+                            }),
+                        },
+                        declared.expr().to_string(),
+                    ),
+                );
+            }
         }
         for res in face_hat!(&mut face)
             .local_qabls
@@ -574,14 +615,7 @@ fn propagate_forget_simple_queryable(
             .cloned()
             .collect::<Vec<Arc<Resource>>>()
         {
-            if !res.context().matches.iter().any(|m| {
-                m.upgrade().is_some_and(|m| {
-                    m.context.is_some()
-                        && (remote_simple_qabls(&m, &face)
-                            || remote_linkstatepeer_qabls(tables, &m)
-                            || remote_router_qabls(tables, &m))
-                })
-            }) {
+            if !remote_matching_qabls(tables, &res, &face) {
                 if let Some((id, _)) = face_hat_mut!(&mut face).local_qabls.remove(&res) {
                     send_declare(
                         &face.primitives,
@@ -626,6 +660,7 @@ fn propagate_forget_simple_queryable_to_peers(
                     face.zid != s.face.zid
                         && s.qabl.is_some()
                         && (s.face.whatami == WhatAmI::Client
+                            || is_gateway(&s.face)
                             || (s.face.whatami == WhatAmI::Peer
                                 && hat!(tables).failover_brokering(s.face.zid, face.zid)))
                 })
@@ -833,14 +868,7 @@ pub(super) fn undeclare_simple_queryable(
                 .cloned()
                 .collect::<Vec<Arc<Resource>>>()
             {
-                if !res.context().matches.iter().any(|m| {
-                    m.upgrade().is_some_and(|m| {
-                        m.context.is_some()
-                            && (remote_simple_qabls(&m, face)
-                                || remote_linkstatepeer_qabls(tables, &m)
-                                || remote_router_qabls(tables, &m))
-                    })
-                }) {
+                if !remote_matching_qabls(tables, &res, face) {
                     if let Some((id, _)) = face_hat_mut!(&mut face).local_qabls.remove(&res) {
                         send_declare(
                             &face.primitives,
@@ -956,10 +984,10 @@ pub(super) fn queries_linkstate_change(
                 .local_qabls
                 .keys()
                 .filter(|res| {
-                    let client_qabls = res
-                        .session_ctxs
-                        .values()
-                        .any(|ctx| ctx.face.whatami == WhatAmI::Client && ctx.qabl.is_some());
+                    let client_qabls = res.session_ctxs.values().any(|ctx| {
+                        (ctx.face.whatami == WhatAmI::Client || is_gateway(&ctx.face))
+                            && ctx.qabl.is_some()
+                    });
                     !remote_router_qabls(tables, res)
                         && !client_qabls
                         && !res.session_ctxs.values().any(|ctx| {
@@ -1103,6 +1131,7 @@ fn insert_target_for_qabls(
                                             complete: complete && qabl_info.complete,
                                             distance: net.distances[qabl_idx.index()] as u16,
                                         }),
+                                        target: QueryableTarget::Node(*qabl),
                                     });
                                 }
                             }
@@ -1117,7 +1146,7 @@ fn insert_target_for_qabls(
 }
 
 lazy_static::lazy_static! {
-    static ref EMPTY_ROUTE: Arc<QueryTargetQablSet> = Arc::new(Vec::new());
+    static ref EMPTY_ROUTE: Arc<QueryTargetQablSet> = Arc::new(QueryTargetQablSet::new());
 }
 
 #[inline]
@@ -1167,7 +1196,9 @@ pub(crate) fn declare_qabl_interest(
                                 s.face.id != face.id
                                     && s.qabl.is_some()
                                     && (s.face.whatami == WhatAmI::Client
+                                        || is_gateway(&s.face)
                                         || face.whatami == WhatAmI::Client
+                                        || is_gateway(face)
                                         || (s.face.whatami == WhatAmI::Peer
                                             && hat!(tables)
                                                 .failover_brokering(s.face.zid, face.zid)))
@@ -1280,8 +1311,11 @@ impl HatQueriesTrait for HatCode {
         node_id: NodeId,
         send_declare: &mut SendDeclare,
     ) {
+        if is_parked(face) {
+            return;
+        }
         match face.whatami {
-            WhatAmI::Router => {
+            WhatAmI::Router if !is_gateway(face) => {
                 if let Some(router) = get_router(tables, face, node_id) {
                     declare_router_queryable(tables, face, res, qabl_info, router, send_declare)
                 }
@@ -1316,7 +1350,7 @@ impl HatQueriesTrait for HatCode {
         send_declare: &mut SendDeclare,
     ) -> Option<Arc<Resource>> {
         match face.whatami {
-            WhatAmI::Router => {
+            WhatAmI::Router if !is_gateway(face) => {
                 if let Some(mut res) = res {
                     if let Some(router) = get_router(tables, face, node_id) {
                         forget_router_queryable(tables, face, &mut res, &router, send_declare);
@@ -1485,7 +1519,7 @@ impl HatQueriesTrait for HatCode {
 
             if master || source_type == WhatAmI::Router {
                 for (sid, context) in &mres.session_ctxs {
-                    if context.face.whatami != WhatAmI::Router {
+                    if context.face.whatami != WhatAmI::Router || is_gateway(&context.face) {
                         let key_expr = Resource::get_best_key(expr.prefix, expr.suffix, *sid);
                         if let Some(qabl_info) = context.qabl.as_ref() {
                             route.push(QueryTargetQabl {
@@ -1498,6 +1532,11 @@ impl HatQueriesTrait for HatCode {
                                     complete: complete && qabl_info.complete,
                                     distance: 1,
                                 }),
+                                target: QueryableTarget::declared(
+                                    &context.face,
+                                    &mres,
+                                    face_hat!(context.face).remote_qabls.iter(),
+                                ),
                             });
                         }
                     }
@@ -1569,7 +1608,7 @@ impl HatQueriesTrait for HatCode {
                     if match complete {
                         true => context.qabl.is_some_and(|q| q.complete),
                         false => context.qabl.is_some(),
-                    } && context.face.whatami != WhatAmI::Router
+                    } && (context.face.whatami != WhatAmI::Router || is_gateway(&context.face))
                     {
                         matching_queryables
                             .entry(*sid)
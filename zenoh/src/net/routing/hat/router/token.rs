@@ -27,9 +27,12 @@ use zenoh_protocol::{
 use zenoh_sync::get_mut_unchecked;
 
 use super::{
-    face_hat, face_hat_mut, get_peer, get_router, hat, hat_mut,
-    interests::push_declaration_profile, network::Network, res_hat, res_hat_mut, HatCode,
-    HatContext, HatFace, HatTables,
+    face_hat, face_hat_mut,
+    gateways::{is_gateway, is_gateway_link, is_parked, update_gateways},
+    get_peer, get_router, hat, hat_mut,
+    interests::push_declaration_profile,
+    network::Network,
+    res_hat, res_hat_mut, HatCode, HatContext, HatFace, HatTables,
 };
 use crate::net::routing::{
     dispatcher::{face::FaceState, interests::RemoteInterest, tables::Tables},
@@ -93,9 +96,9 @@ fn propagate_simple_token_to(
     if (src_face.id != dst_face.id || dst_face.zid == tables.zid)
         && !face_hat!(dst_face).local_tokens.contains_key(res)
         && if full_peer_net {
-            dst_face.whatami == WhatAmI::Client
+            dst_face.whatami == WhatAmI::Client || is_gateway(dst_face)
         } else {
-            dst_face.whatami != WhatAmI::Router
+            (dst_face.whatami != WhatAmI::Router || is_gateway(dst_face))
                 && (src_face.whatami != WhatAmI::Peer
                     || dst_face.whatami != WhatAmI::Peer
                     || hat!(tables).failover_brokering(src_face.zid, dst_face.zid))
@@ -229,9 +232,13 @@ fn register_router_token(
 
     // Propagate liveliness to clients
     propagate_simple_token(tables, res, face, send_declare);
+
+    if is_gateway_link(res) {
+        update_gateways(tables, send_declare);
+    }
 }
 
-fn declare_router_token(
+pub(super) fn declare_router_token(
     tables: &mut Tables,
     face: &mut Arc<FaceState>,
     res: &mut Arc<Resource>,
@@ -539,6 +546,7 @@ fn propagate_forget_simple_token_to_peers(
                     face.zid != s.face.zid
                         && s.token
                         && (s.face.whatami == WhatAmI::Client
+                            || is_gateway(&s.face)
                             || (s.face.whatami == WhatAmI::Peer
                                 && hat!(tables).failover_brokering(s.face.zid, face.zid)))
                 })
@@ -622,12 +630,16 @@ fn unregister_router_token(
             undeclare_linkstatepeer_token(tables, None, res, &tables.zid.clone());
         }
         propagate_forget_simple_token(tables, res, face, send_declare);
+
+        if is_gateway_link(res) {
+            update_gateways(tables, send_declare);
+        }
     }
 
     propagate_forget_simple_token_to_peers(tables, res, send_declare);
 }
 
-fn undeclare_router_token(
+pub(super) fn undeclare_router_token(
     tables: &mut Tables,
     face: Option<&Arc<FaceState>>,
     res: &mut Arc<Resource>,
@@ -720,7 +732,7 @@ pub(super) fn undeclare_simple_token(
 
         if simple_tokens.len() == 1 && !router_tokens && !linkstatepeer_tokens {
             let mut face = &mut simple_tokens[0];
-            if face.whatami != WhatAmI::Client {
+            if face.whatami != WhatAmI::Client && !is_gateway(face) {
                 if let Some(id) = face_hat_mut!(face).local_tokens.remove(res) {
                     send_declare(
                         &face.primitives,
@@ -897,10 +909,9 @@ pub(super) fn token_linkstate_change(
                 .local_tokens
                 .keys()
                 .filter(|res| {
-                    let client_tokens = res
-                        .session_ctxs
-                        .values()
-                        .any(|ctx| ctx.face.whatami == WhatAmI::Client && ctx.token);
+                    let client_tokens = res.session_ctxs.values().any(|ctx| {
+                        (ctx.face.whatami == WhatAmI::Client || is_gateway(&ctx.face)) && ctx.token
+                    });
                     !remote_router_tokens(tables, res)
                         && !client_tokens
                         && !res.session_ctxs.values().any(|ctx| {
@@ -995,6 +1006,7 @@ pub(crate) fn declare_token_interest(
 ) {
     if mode.current()
         && (face.whatami == WhatAmI::Client
+            || is_gateway(face)
             || (face.whatami == WhatAmI::Peer && !hat!(tables).full_net(WhatAmI::Peer)))
     {
         let interest_id = Some(id);
@@ -1040,7 +1052,9 @@ pub(crate) fn declare_token_interest(
                                 s.face.id != face.id
                                     && s.token
                                     && (s.face.whatami == WhatAmI::Client
+                                        || is_gateway(&s.face)
                                         || face.whatami == WhatAmI::Client
+                                        || is_gateway(face)
                                         || (s.face.whatami == WhatAmI::Peer
                                             && hat!(tables)
                                                 .failover_brokering(s.face.zid, face.zid)))
@@ -1116,8 +1130,15 @@ impl HatTokenTrait for HatCode {
         _interest_id: Option<InterestId>,
         send_declare: &mut SendDeclare,
     ) {
+        if is_parked(face) {
+            return;
+        }
+        if is_gateway(face) && is_gateway_link(res) {
+            // The gateway links are only advertised in their own region
+            return;
+        }
         match face.whatami {
-            WhatAmI::Router => {
+            WhatAmI::Router if !is_gateway(face) => {
                 if let Some(router) = get_router(tables, face, node_id) {
                     declare_router_token(tables, face, res, router, send_declare)
                 }
@@ -1145,7 +1166,7 @@ impl HatTokenTrait for HatCode {
         send_declare: &mut SendDeclare,
     ) -> Option<Arc<Resource>> {
         match face.whatami {
-            WhatAmI::Router => {
+            WhatAmI::Router if !is_gateway(face) => {
                 if let Some(mut res) = res {
                     if let Some(router) = get_router(tables, face, node_id) {
                         forget_router_token(tables, face, &mut res, &router, send_declare);
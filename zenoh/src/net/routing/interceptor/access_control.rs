@@ -18,188 +18,369 @@
 //!
 //! [Click here for Zenoh's documentation](https://docs.rs/zenoh/latest/zenoh)
 
-use std::{any::Any, collections::HashSet, iter, sync::Arc};
+use std::{
+    any::Any,
+    fmt::{Display, Write},
+    net::IpAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
 
+use arc_swap::ArcSwap;
 use itertools::Itertools;
 use zenoh_config::{
-    AclConfig, AclMessage, CertCommonName, InterceptorFlow, Interface, Permission, Username,
+    AclConfig, AclEnforcement, AclMessage, CertCommonName, InterceptorFlow, Interface, Permission,
+    Username,
 };
+use zenoh_keyexpr::keyexpr;
 use zenoh_protocol::{
-    core::ZenohIdProto,
+    core::{Parameters, ZenohIdProto},
     network::{
-        interest::InterestMode, Declare, DeclareBody, Interest, NetworkBody, NetworkMessage, Push,
-        Request, Response,
+        interest::{InterestMode, InterestOptions},
+        Declare, DeclareBody, Interest, NetworkBody, NetworkMessage, Push, Request, Response,
     },
     zenoh::{PushBody, RequestBody},
 };
 use zenoh_result::ZResult;
-use zenoh_transport::{
-    multicast::TransportMulticast,
-    unicast::{authentication::AuthId, TransportUnicast},
-};
+use zenoh_transport::{multicast::TransportMulticast, unicast::TransportUnicast};
 
 use super::{
-    authorization::PolicyEnforcer, EgressInterceptor, IngressInterceptor, InterceptorFactory,
-    InterceptorFactoryTrait, InterceptorTrait,
+    authorization::PolicyEnforcer, subject::TransportSubject, EgressInterceptor, ExplainedMessage,
+    IngressInterceptor, InterceptorFactoryTrait, InterceptorTrait,
 };
 use crate::{
     api::key_expr::KeyExpr,
     net::routing::{interceptor::authorization::SubjectQuery, RoutingContext},
 };
+
+/// The access control enforcer.
+///
+/// The policy can be replaced at runtime with [`AclEnforcer::reload`], the interceptors of all the
+/// transports then switch to the new policy on their next message. The interceptors are only
+/// installed when access control is enabled at startup, it can't be enabled at runtime otherwise.
+#[derive(Clone)]
 pub struct AclEnforcer {
-    enforcer: Arc<PolicyEnforcer>,
+    shared: Arc<SharedPolicy>,
+}
+
+/// The current policy, with a version incremented on each reload.
+///
+/// The transports keep a snapshot of the policy, only resolved again when the version changed,
+/// so that checking a message never takes a lock.
+struct SharedPolicy {
+    enforcer: ArcSwap<PolicyEnforcer>,
+    version: AtomicU64,
 }
+
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct AuthSubject {
     id: usize,
     name: String,
 }
 
-struct EgressAclEnforcer {
-    policy_enforcer: Arc<PolicyEnforcer>,
-    subject: Vec<AuthSubject>,
+/// A policy and the subjects a transport matches in this policy.
+#[derive(Clone)]
+pub struct AclPolicy {
+    enforcer: Arc<PolicyEnforcer>,
+    subjects: Arc<Vec<AuthSubject>>,
+}
+
+/// The ACL state of a transport, following the reloads of the policy.
+pub struct TransportAcl {
     zid: ZenohIdProto,
+    queries: Vec<SubjectQuery>,
+    shared: Arc<SharedPolicy>,
+    policy: ArcSwap<VersionedPolicy>,
+}
+
+struct VersionedPolicy {
+    version: u64,
+    policy: AclPolicy,
+}
+
+struct EgressAclEnforcer {
+    acl: Arc<TransportAcl>,
 }
 
 struct IngressAclEnforcer {
-    policy_enforcer: Arc<PolicyEnforcer>,
-    subject: Vec<AuthSubject>,
-    zid: ZenohIdProto,
+    acl: Arc<TransportAcl>,
 }
 
-pub(crate) fn acl_interceptor_factories(
-    acl_config: &AclConfig,
-) -> ZResult<Vec<InterceptorFactory>> {
-    let mut res: Vec<InterceptorFactory> = vec![];
+impl AclEnforcer {
+    /// Builds the ACL enforcer from the config, `None` if access control is disabled.
+    pub(crate) fn new(acl_config: &AclConfig) -> ZResult<Option<Self>> {
+        if acl_config.enabled {
+            match Self::policy_enforcer(acl_config) {
+                Ok(policy_enforcer) => {
+                    tracing::debug!("Access control is enabled");
+                    Ok(Some(AclEnforcer {
+                        shared: Arc::new(SharedPolicy {
+                            enforcer: ArcSwap::from_pointee(policy_enforcer),
+                            version: AtomicU64::new(0),
+                        }),
+                    }))
+                }
+                Err(e) => bail!("Access control not enabled due to: {}", e),
+            }
+        } else {
+            tracing::debug!("Access control is disabled");
+            Ok(None)
+        }
+    }
 
-    if acl_config.enabled {
+    fn policy_enforcer(acl_config: &AclConfig) -> ZResult<PolicyEnforcer> {
         let mut policy_enforcer = PolicyEnforcer::new();
-        match policy_enforcer.init(acl_config) {
-            Ok(_) => {
-                tracing::debug!("Access control is enabled");
-                res.push(Box::new(AclEnforcer {
-                    enforcer: Arc::new(policy_enforcer),
-                }))
-            }
-            Err(e) => bail!("Access control not enabled due to: {}", e),
-        }
-    } else {
-        tracing::debug!("Access control is disabled");
+        policy_enforcer.init(acl_config)?;
+        Ok(policy_enforcer)
     }
 
-    Ok(res)
-}
+    /// Checks that a configuration can be applied with [`AclEnforcer::reload`].
+    pub(crate) fn check(acl_config: &AclConfig) -> ZResult<()> {
+        Self::policy_enforcer(acl_config).map(|_| ())
+    }
 
-impl InterceptorFactoryTrait for AclEnforcer {
-    fn new_transport_unicast(
-        &self,
-        transport: &TransportUnicast,
-    ) -> (Option<IngressInterceptor>, Option<EgressInterceptor>) {
-        let auth_ids = match transport.get_auth_ids() {
-            Ok(auth_ids) => auth_ids,
+    /// Replaces the policy of all the transports, the hit counters are reset.
+    ///
+    /// Disabling access control lets all the messages through, until it is enabled again.
+    pub(crate) fn reload(&self, acl_config: &AclConfig) -> ZResult<()> {
+        let policy_enforcer = Self::policy_enforcer(acl_config)?;
+        self.shared.enforcer.store(Arc::new(policy_enforcer));
+        self.shared.version.fetch_add(1, Ordering::Release);
+        tracing::info!(
+            "Access control policy reloaded (enabled: {})",
+            acl_config.enabled
+        );
+        Ok(())
+    }
+
+    /// The configured rules and their hit counters.
+    pub(crate) fn rules_stats(&self) -> serde_json::Value {
+        let enforcer = self.shared.enforcer.load_full();
+        serde_json::json!({
+            "enabled": enforcer.acl_enabled,
+            "default_permission": enforcer.default_permission,
+            "enforcement": enforcer.enforcement,
+            "denials": enforcer.denials.load(Ordering::Relaxed),
+            "rules": enforcer.rules.iter().map(|rule| serde_json::json!({
+                "id": rule.id,
+                "permission": rule.permission,
+                "hits": rule.hits.load(Ordering::Relaxed),
+            })).collect::<Vec<_>>(),
+        })
+    }
+
+    /// The decision of the current policy on a message from (or to) a hypothetical transport.
+    ///
+    /// The parameters are the subject properties of the transport (`zid`, `interface`,
+    /// `cert_common_name`, `username`, `link_protocol`, `remote_address`), the `key_expr` and
+    /// `message` type, and optionally the `flow` (ingress by default).
+    pub(crate) fn what_if(&self, parameters: &Parameters) -> ZResult<serde_json::Value> {
+        fn parse<T: serde::de::DeserializeOwned>(name: &str, value: &str) -> ZResult<T> {
+            serde_json::from_value(serde_json::Value::String(value.to_string()))
+                .map_err(|e| zerror!("Invalid {} '{}': {}", name, value, e).into())
+        }
+
+        let query = SubjectQuery {
+            interface: parameters
+                .get("interface")
+                .map(|v| Interface(v.to_string())),
+            cert_common_name: parameters
+                .get("cert_common_name")
+                .map(|v| CertCommonName(v.to_string())),
+            username: parameters.get("username").map(|v| Username(v.to_string())),
+            zid: parameters.get("zid").map(|v| parse("zid", v)).transpose()?,
+            link_protocol: parameters
+                .get("link_protocol")
+                .map(|v| parse("link_protocol", v))
+                .transpose()?,
+            remote_address: parameters
+                .get("remote_address")
+                .map(|v| {
+                    v.parse::<IpAddr>()
+                        .map_err(|e| zerror!("Invalid remote_address '{}': {}", v, e))
+                })
+                .transpose()?,
+        };
+        let Some(key_expr) = parameters.get("key_expr") else {
+            bail!("Missing key_expr parameter");
+        };
+        keyexpr::new(key_expr)?;
+        let Some(message) = parameters.get("message") else {
+            bail!("Missing message parameter");
+        };
+        let message: AclMessage = parse("message", message)?;
+        let flow: InterceptorFlow = parameters
+            .get("flow")
+            .map(|v| parse("flow", v))
+            .transpose()?
+            .unwrap_or(InterceptorFlow::Ingress);
+
+        let enforcer = self.shared.enforcer.load_full();
+        let policy = AclPolicy::new(enforcer, &"what-if", &[query]);
+        let (permission, rules) = policy.decision(&"what-if", flow, message, "What-if", key_expr);
+        Ok(serde_json::json!({
+            "permission": permission,
+            "subjects": policy.subjects.iter().map(|s| s.name.clone()).collect::<Vec<_>>(),
+            "rules": rules.iter().filter_map(|rule| policy.enforcer.rules.get(*rule).map(|r| r.id.clone())).collect::<Vec<_>>(),
+        }))
+    }
+
+    /// The ACL state of a transport.
+    fn transport_acl(&self, transport: &TransportUnicast) -> Option<Arc<TransportAcl>> {
+        let TransportSubject { zid, queries } = match TransportSubject::new(transport) {
+            Ok(subject) => subject,
             Err(err) => {
-                tracing::error!("Couldn't get Transport Auth IDs: {}", err);
-                return (None, None);
+                tracing::error!("Couldn't get Transport properties: {}", err);
+                return None;
             }
         };
+        let version = self.shared.version.load(Ordering::Acquire);
+        let enforcer = self.shared.enforcer.load_full();
+        let policy = AclPolicy::new(enforcer, &zid, &queries);
+        Some(Arc::new(TransportAcl {
+            zid,
+            queries,
+            shared: self.shared.clone(),
+            policy: ArcSwap::from_pointee(VersionedPolicy { version, policy }),
+        }))
+    }
 
-        let mut cert_common_names = Vec::new();
-        let mut username = None;
+    /// ACL decisions on the messages of a transport in the given flow.
+    pub(crate) fn transport_enforcer(
+        &self,
+        transport: &TransportUnicast,
+        flow: InterceptorFlow,
+    ) -> Option<Box<dyn AclActionMethods + Send + Sync>> {
+        let acl = self.transport_acl(transport)?;
+        Some(match flow {
+            InterceptorFlow::Ingress => Box::new(IngressAclEnforcer { acl }),
+            InterceptorFlow::Egress => Box::new(EgressAclEnforcer { acl }),
+        })
+    }
+}
 
-        for auth_id in auth_ids {
-            match auth_id {
-                AuthId::CertCommonName(value) => {
-                    cert_common_names.push(Some(CertCommonName(value)));
-                }
-                AuthId::Username(value) => {
-                    if username.is_some() {
-                        tracing::error!("Transport should not report more than one username");
-                        return (None, None);
-                    }
-                    username = Some(Username(value));
+impl AclPolicy {
+    /// Resolves the subjects of a transport in a policy.
+    fn new(enforcer: Arc<PolicyEnforcer>, zid: &dyn Display, queries: &[SubjectQuery]) -> Self {
+        let mut auth_subjects: Vec<AuthSubject> = vec![];
+        for query in queries {
+            if let Some(entry) = enforcer.subject_store.query(query) {
+                // Several combinations of the transport properties may match the same subject
+                // (e.g. a link with both a network and the shm protocol), decided once
+                if !auth_subjects.iter().any(|s| s.id == entry.id) {
+                    auth_subjects.push(AuthSubject {
+                        id: entry.id,
+                        name: format!("{query}"),
+                    });
                 }
-                AuthId::None => {}
             }
         }
-        if cert_common_names.is_empty() {
-            cert_common_names.push(None);
+        if auth_subjects.is_empty() && enforcer.acl_enabled {
+            tracing::info!(
+                "{zid} did not match any configured ACL subject. Default permission `{:?}` will be applied on all messages",
+                enforcer.default_permission
+            );
         }
-
-        let links = match transport.get_links() {
-            Ok(links) => links,
-            Err(err) => {
-                tracing::error!("Couldn't get Transport links: {}", err);
-                return (None, None);
-            }
-        };
-        let mut interfaces = links
-            .into_iter()
-            .flat_map(|link| {
-                link.interfaces
-                    .into_iter()
-                    .map(|interface| Some(Interface(interface)))
-            })
-            .collect::<Vec<_>>();
-        if interfaces.is_empty() {
-            interfaces.push(None);
-        } else if interfaces.len() > 1 {
-            tracing::warn!("Transport returned multiple network interfaces, current ACL logic might incorrectly apply filters in this case!");
+        Self {
+            enforcer,
+            subjects: Arc::new(auth_subjects),
         }
+    }
 
-        let mut auth_subjects = HashSet::new();
-
-        for ((username, interface), cert_common_name) in iter::once(username)
-            .cartesian_product(interfaces.into_iter())
-            .cartesian_product(cert_common_names.into_iter())
-        {
-            let query = SubjectQuery {
-                interface,
-                cert_common_name,
-                username,
-            };
+    /// The permission for a message and the indexes of the rules leading to it.
+    fn decision(
+        &self,
+        zid: &dyn Display,
+        flow: InterceptorFlow,
+        action: AclMessage,
+        log_msg: &str,
+        key_expr: &str,
+    ) -> (Permission, Vec<usize>) {
+        let policy_enforcer = &self.enforcer;
+        if !policy_enforcer.interface_enabled.flow(flow) {
+            // Access control is disabled or no rule applies to this flow
+            return (Permission::Allow, vec![]);
+        }
+        let mut decision = policy_enforcer.default_permission;
+        let mut rules = vec![];
+        for subject in self.subjects.iter() {
+            match policy_enforcer.policy_decision_point(subject.id, flow, action, key_expr) {
+                Ok((Permission::Allow, allow_rules)) => {
+                    tracing::trace!(
+                        "{} on {} is authorized to {} on {}",
+                        zid,
+                        subject.name,
+                        log_msg,
+                        key_expr
+                    );
+                    decision = Permission::Allow;
+                    rules = allow_rules;
+                    break;
+                }
+                Ok((Permission::Deny, deny_rules)) => {
+                    tracing::debug!(
+                        "{} on {} is unauthorized to {} on {}",
+                        zid,
+                        subject.name,
+                        log_msg,
+                        key_expr
+                    );
 
-            if let Some(entry) = self.enforcer.subject_store.query(&query) {
-                auth_subjects.insert(AuthSubject {
-                    id: entry.id,
-                    name: format!("{query}"),
-                });
+                    decision = Permission::Deny;
+                    rules.extend(deny_rules);
+                    continue;
+                }
+                Err(e) => {
+                    tracing::debug!(
+                        "{} on {} has an authorization error to {} on {}: {}",
+                        zid,
+                        subject.name,
+                        log_msg,
+                        key_expr,
+                        e
+                    );
+                    return (Permission::Deny, vec![]);
+                }
             }
         }
+        (decision, rules)
+    }
+}
 
-        let zid = match transport.get_zid() {
-            Ok(zid) => zid,
-            Err(err) => {
-                tracing::error!("Couldn't get Transport zid: {}", err);
-                return (None, None);
-            }
-        };
-        // FIXME: Investigate if `AuthSubject` can have duplicates above and try to avoid this conversion
-        let auth_subjects = auth_subjects.into_iter().collect::<Vec<AuthSubject>>();
-        if auth_subjects.is_empty() {
-            tracing::info!(
-                "{zid} did not match any configured ACL subject. Default permission `{:?}` will be applied on all messages",
-                self.enforcer.default_permission
-            );
+impl TransportAcl {
+    /// Calls `f` with the current policy, the subjects are resolved again after a reload.
+    fn with_policy<R>(&self, f: impl FnOnce(&AclPolicy) -> R) -> R {
+        let version = self.shared.version.load(Ordering::Acquire);
+        let current = self.policy.load();
+        if current.version == version {
+            return f(&current.policy);
         }
-        let ingress_interceptor = Box::new(IngressAclEnforcer {
-            policy_enforcer: self.enforcer.clone(),
-            zid,
-            subject: auth_subjects.clone(),
-        });
-        let egress_interceptor = Box::new(EgressAclEnforcer {
-            policy_enforcer: self.enforcer.clone(),
-            zid,
-            subject: auth_subjects,
+        let enforcer = self.shared.enforcer.load_full();
+        let refreshed = Arc::new(VersionedPolicy {
+            version,
+            policy: AclPolicy::new(enforcer, &self.zid, &self.queries),
         });
+        // Another thread may have refreshed the policy concurrently, a stale one is resolved again
+        // on the next message
+        self.policy.compare_and_swap(&current, refreshed.clone());
+        f(&refreshed.policy)
+    }
+}
+
+impl InterceptorFactoryTrait for AclEnforcer {
+    fn new_transport_unicast(
+        &self,
+        transport: &TransportUnicast,
+    ) -> (Option<IngressInterceptor>, Option<EgressInterceptor>) {
+        // Both flows are intercepted, as a reloaded policy may apply to a flow the current one does not
+        let Some(acl) = self.transport_acl(transport) else {
+            return (None, None);
+        };
         (
-            self.enforcer
-                .interface_enabled
-                .ingress
-                .then_some(ingress_interceptor),
-            self.enforcer
-                .interface_enabled
-                .egress
-                .then_some(egress_interceptor),
+            Some(Box::new(IngressAclEnforcer { acl: acl.clone() })),
+            Some(Box::new(EgressAclEnforcer { acl })),
         )
     }
 
@@ -215,6 +396,20 @@ impl InterceptorFactoryTrait for AclEnforcer {
         tracing::debug!("Peer Multicast is disabled in interceptor");
         None
     }
+
+    fn openmetrics_text(&self) -> Option<String> {
+        let enforcer = self.shared.enforcer.load_full();
+        let enforcement = match enforcer.enforcement {
+            AclEnforcement::Enforce => "enforce",
+            AclEnforcement::Audit => "audit",
+        };
+        Some(format!(
+            "# HELP zenoh_acl_denied Messages denied by access control, or that would be denied in audit mode.\n\
+             # TYPE zenoh_acl_denied counter\n\
+             zenoh_acl_denied{{enforcement=\"{enforcement}\"}} {}\n",
+            enforcer.denials.load(Ordering::Relaxed)
+        ))
+    }
 }
 
 impl InterceptorTrait for IngressAclEnforcer {
@@ -222,6 +417,10 @@ impl InterceptorTrait for IngressAclEnforcer {
         Some(Box::new(key_expr.to_string()))
     }
 
+    fn explain(&self, key_expr: &KeyExpr<'_>, message: ExplainedMessage) -> Option<String> {
+        Some(self.explain_decision(message, key_expr))
+    }
+
     fn intercept<'a>(
         &self,
         ctx: RoutingContext<NetworkMessage>,
@@ -237,7 +436,7 @@ impl InterceptorTrait for IngressAclEnforcer {
             })
             .or_else(|| ctx.full_expr());
 
-        match &ctx.msg.body {
+        match &ctx.msg().body {
             NetworkBody::Request(Request {
                 payload: RequestBody::Query(_),
                 ..
@@ -271,6 +470,24 @@ impl InterceptorTrait for IngressAclEnforcer {
                     return None;
                 }
             }
+            NetworkBody::Push(Push {
+                payload: PushBody::Batch(batch),
+                ..
+            }) => {
+                // A batch is delivered atomically: it is denied as a whole if any of its entries is
+                if batch.entries.iter().any(|entry| match entry.payload {
+                    PushBody::Del(_) => {
+                        self.action(AclMessage::Delete, "Delete (ingress)", &entry.key_expr)
+                            == Permission::Deny
+                    }
+                    _ => {
+                        self.action(AclMessage::Put, "Put (ingress)", &entry.key_expr)
+                            == Permission::Deny
+                    }
+                }) {
+                    return None;
+                }
+            }
             NetworkBody::Declare(Declare {
                 body: DeclareBody::DeclareSubscriber(_),
                 ..
@@ -372,31 +589,17 @@ impl InterceptorTrait for IngressAclEnforcer {
                 }
             }
             NetworkBody::Interest(Interest {
-                mode: InterestMode::Current,
+                mode:
+                    mode @ (InterestMode::Current | InterestMode::Future | InterestMode::CurrentFuture),
                 options,
                 ..
-            }) if options.tokens() => {
-                if self.action(
-                    AclMessage::LivelinessQuery,
-                    "Liveliness Query (ingress)",
-                    key_expr?,
-                ) == Permission::Deny
-                {
-                    return None;
-                }
-            }
-            NetworkBody::Interest(Interest {
-                mode: InterestMode::Future | InterestMode::CurrentFuture,
-                options,
-                ..
-            }) if options.tokens() => {
-                if self.action(
-                    AclMessage::DeclareLivelinessSubscriber,
-                    "Declare Liveliness Subscriber (ingress)",
-                    key_expr?,
-                ) == Permission::Deny
-                {
-                    return None;
+            }) => {
+                // Interests without a key expression (e.g. the routers' initial interest in all
+                // the declarations) are not filtered, the declarations they lead to are
+                if let Some(key_expr) = key_expr {
+                    if self.interest_action(*mode, *options, key_expr) == Permission::Deny {
+                        return None;
+                    }
                 }
             }
             NetworkBody::Interest(Interest {
@@ -421,7 +624,7 @@ impl InterceptorTrait for IngressAclEnforcer {
                 ..
             }) => {}
             // Unfiltered remaining message types
-            NetworkBody::Interest(_) | NetworkBody::OAM(_) | NetworkBody::ResponseFinal(_) => {}
+            NetworkBody::OAM(_) | NetworkBody::ResponseFinal(_) => {}
         }
         Some(ctx)
     }
@@ -432,6 +635,10 @@ impl InterceptorTrait for EgressAclEnforcer {
         Some(Box::new(key_expr.to_string()))
     }
 
+    fn explain(&self, key_expr: &KeyExpr<'_>, message: ExplainedMessage) -> Option<String> {
+        Some(self.explain_decision(message, key_expr))
+    }
+
     fn intercept(
         &self,
         ctx: RoutingContext<NetworkMessage>,
@@ -447,7 +654,7 @@ impl InterceptorTrait for EgressAclEnforcer {
             })
             .or_else(|| ctx.full_expr());
 
-        match &ctx.msg.body {
+        match &ctx.msg().body {
             NetworkBody::Request(Request {
                 payload: RequestBody::Query(_),
                 ..
@@ -478,6 +685,24 @@ impl InterceptorTrait for EgressAclEnforcer {
                     return None;
                 }
             }
+            NetworkBody::Push(Push {
+                payload: PushBody::Batch(batch),
+                ..
+            }) => {
+                // A batch is delivered atomically: it is denied as a whole if any of its entries is
+                if batch.entries.iter().any(|entry| match entry.payload {
+                    PushBody::Del(_) => {
+                        self.action(AclMessage::Delete, "Delete (egress)", &entry.key_expr)
+                            == Permission::Deny
+                    }
+                    _ => {
+                        self.action(AclMessage::Put, "Put (egress)", &entry.key_expr)
+                            == Permission::Deny
+                    }
+                }) {
+                    return None;
+                }
+            }
             NetworkBody::Declare(Declare {
                 body: DeclareBody::DeclareSubscriber(_),
                 ..
@@ -563,47 +788,30 @@ impl InterceptorTrait for EgressAclEnforcer {
                 }
             }
             NetworkBody::Interest(Interest {
-                mode: InterestMode::Current,
-                options,
-                ..
-            }) if options.tokens() => {
-                if self.action(
-                    AclMessage::LivelinessQuery,
-                    "Liveliness Query (egress)",
-                    key_expr?,
-                ) == Permission::Deny
-                {
-                    return None;
-                }
-            }
-            NetworkBody::Interest(Interest {
-                mode: InterestMode::Future | InterestMode::CurrentFuture,
+                mode:
+                    mode @ (InterestMode::Current | InterestMode::Future | InterestMode::CurrentFuture),
                 options,
                 ..
-            }) if options.tokens() => {
-                if self.action(
-                    AclMessage::DeclareLivelinessSubscriber,
-                    "Declare Liveliness Subscriber (egress)",
-                    key_expr?,
-                ) == Permission::Deny
-                {
-                    return None;
+            }) => {
+                // Interests without a key expression (e.g. the routers' initial interest in all
+                // the declarations) are not filtered, the declarations they lead to are
+                if let Some(key_expr) = key_expr {
+                    if self.interest_action(*mode, *options, key_expr) == Permission::Deny {
+                        return None;
+                    }
                 }
             }
             NetworkBody::Interest(Interest {
                 mode: InterestMode::Final,
                 options,
                 ..
-            }) if options.tokens() => {
+            }) if options.tokens() || options.subscribers() || options.queryables() => {
                 // Note: options are set for InterestMode::Final for internal use only by egress interceptors.
 
                 // InterestMode::Final filtering diverges between ingress and egress:
                 // in egress the keyexpr has to be provided in the RoutingContext
-                if self.action(
-                    AclMessage::DeclareLivelinessSubscriber,
-                    "Undeclare Liveliness Subscriber (egress)",
-                    key_expr?,
-                ) == Permission::Deny
+                if self.interest_action(InterestMode::Final, *options, key_expr?)
+                    == Permission::Deny
                 {
                     return None;
                 }
@@ -629,89 +837,161 @@ impl InterceptorTrait for EgressAclEnforcer {
     }
 }
 pub trait AclActionMethods {
-    fn policy_enforcer(&self) -> Arc<PolicyEnforcer>;
+    fn acl(&self) -> &TransportAcl;
     fn zid(&self) -> ZenohIdProto;
     fn flow(&self) -> InterceptorFlow;
-    fn authn_ids(&self) -> Vec<AuthSubject>;
+    /// The permission for a message, counted in the hits of the rules leading to it.
+    ///
+    /// In audit mode, denials are only logged (at debug level, being on the data path) and counted,
+    /// the message is allowed.
     fn action(&self, action: AclMessage, log_msg: &str, key_expr: &str) -> Permission {
-        let policy_enforcer = self.policy_enforcer();
-        let authn_ids: Vec<AuthSubject> = self.authn_ids();
-        let zid = self.zid();
-        let mut decision = policy_enforcer.default_permission;
-        for subject in &authn_ids {
-            match policy_enforcer.policy_decision_point(subject.id, self.flow(), action, key_expr) {
-                Ok(Permission::Allow) => {
-                    tracing::trace!(
-                        "{} on {} is authorized to {} on {}",
-                        zid,
-                        subject.name,
-                        log_msg,
-                        key_expr
-                    );
-                    decision = Permission::Allow;
-                    break;
-                }
-                Ok(Permission::Deny) => {
+        self.acl().with_policy(|policy| {
+            let (decision, rules) =
+                policy.decision(&self.zid(), self.flow(), action, log_msg, key_expr);
+            policy.enforcer.record_hits(&rules);
+            if decision == Permission::Deny {
+                policy.enforcer.denials.fetch_add(1, Ordering::Relaxed);
+                if policy.enforcer.enforcement == AclEnforcement::Audit {
                     tracing::debug!(
-                        "{} on {} is unauthorized to {} on {}",
-                        zid,
-                        subject.name,
+                        "{} would be unauthorized to {} on {} (audit mode)",
+                        self.zid(),
                         log_msg,
                         key_expr
                     );
-
-                    decision = Permission::Deny;
-                    continue;
-                }
-                Err(e) => {
-                    tracing::debug!(
-                        "{} on {} has an authorization error to {} on {}: {}",
-                        zid,
-                        subject.name,
-                        log_msg,
-                        key_expr,
-                        e
-                    );
-                    return Permission::Deny;
+                    return Permission::Allow;
                 }
             }
+            decision
+        })
+    }
+    /// The permission for an interest, denied if any of the declarations it is interested in
+    /// is denied.
+    ///
+    /// The interests of publishers, queriers and the other interests are only checked if the
+    /// rules of the policy name the corresponding message, so that the policies predating these
+    /// messages (e.g. denying by default and only allowing some puts) keep letting them through.
+    ///
+    /// The options of [`InterestMode::Final`] interests are only set for local egress
+    /// interceptors, an interest with no known option is allowed in that mode.
+    fn interest_action(
+        &self,
+        mode: InterestMode,
+        options: InterestOptions,
+        key_expr: &str,
+    ) -> Permission {
+        let flow = match self.flow() {
+            InterceptorFlow::Ingress => "ingress",
+            InterceptorFlow::Egress => "egress",
+        };
+        let undeclare = if mode == InterestMode::Final {
+            "Undeclare"
+        } else {
+            "Declare"
+        };
+        let mut messages = vec![];
+        if options.tokens() {
+            messages.push(match mode {
+                InterestMode::Current => (AclMessage::LivelinessQuery, "Liveliness Query".into()),
+                _ => (
+                    AclMessage::DeclareLivelinessSubscriber,
+                    format!("{undeclare} Liveliness Subscriber"),
+                ),
+            });
+        }
+        if options.subscribers() {
+            messages.push((
+                AclMessage::DeclarePublisher,
+                format!("{undeclare} Publisher"),
+            ));
+        }
+        if options.queryables() {
+            messages.push((AclMessage::DeclareQuerier, format!("{undeclare} Querier")));
+        }
+        if messages.is_empty() && mode != InterestMode::Final {
+            messages.push((AclMessage::Interest, "Interest".into()));
+        }
+        messages.retain(|(message, _)| {
+            !matches!(
+                message,
+                AclMessage::DeclarePublisher | AclMessage::DeclareQuerier | AclMessage::Interest
+            ) || self
+                .acl()
+                .with_policy(|policy| policy.enforcer.messages.contains(message))
+        });
+        for (message, log_msg) in messages {
+            let log_msg = format!("{log_msg} ({flow})");
+            if self.action(message, &log_msg, key_expr) == Permission::Deny {
+                return Permission::Deny;
+            }
         }
-        decision
+        Permission::Allow
+    }
+    /// Describes the decision for a message and the rules leading to it, without counting it in
+    /// the hits of the rules.
+    fn explain_decision(&self, message: ExplainedMessage, key_expr: &str) -> String {
+        let (action, log_msg) = match message {
+            ExplainedMessage::Put => (AclMessage::Put, "Put (explain)"),
+            ExplainedMessage::Delete => (AclMessage::Delete, "Delete (explain)"),
+            ExplainedMessage::Query => (AclMessage::Query, "Query (explain)"),
+        };
+        self.acl().with_policy(|policy| {
+            let (permission, rules) =
+                policy.decision(&self.zid(), self.flow(), action, log_msg, key_expr);
+            let mut explanation = match permission {
+                Permission::Allow => "access_control: allow".to_string(),
+                Permission::Deny if policy.enforcer.enforcement == AclEnforcement::Audit => {
+                    "access_control: deny (audit mode, not dropped)".to_string()
+                }
+                Permission::Deny => "access_control: deny".to_string(),
+            };
+            if !rules.is_empty() {
+                let _ = write!(
+                    explanation,
+                    " by rules {}",
+                    rules
+                        .iter()
+                        .filter_map(|rule| policy.enforcer.rules.get(*rule).map(|r| r.id.as_str()))
+                        .join(", ")
+                );
+            }
+            explanation
+        })
+    }
+    /// The permission for a message whatever the enforcement mode, without counting it in the
+    /// hits of the rules.
+    fn decision(&self, action: AclMessage, log_msg: &str, key_expr: &str) -> Permission {
+        self.acl().with_policy(|policy| {
+            policy
+                .decision(&self.zid(), self.flow(), action, log_msg, key_expr)
+                .0
+        })
     }
 }
 
 impl AclActionMethods for EgressAclEnforcer {
-    fn policy_enforcer(&self) -> Arc<PolicyEnforcer> {
-        self.policy_enforcer.clone()
+    fn acl(&self) -> &TransportAcl {
+        &self.acl
     }
 
     fn zid(&self) -> ZenohIdProto {
-        self.zid
+        self.acl.zid
     }
 
     fn flow(&self) -> InterceptorFlow {
         InterceptorFlow::Egress
     }
-
-    fn authn_ids(&self) -> Vec<AuthSubject> {
-        self.subject.clone()
-    }
 }
 
 impl AclActionMethods for IngressAclEnforcer {
-    fn policy_enforcer(&self) -> Arc<PolicyEnforcer> {
-        self.policy_enforcer.clone()
+    fn acl(&self) -> &TransportAcl {
+        &self.acl
     }
 
     fn zid(&self) -> ZenohIdProto {
-        self.zid
+        self.acl.zid
     }
 
     fn flow(&self) -> InterceptorFlow {
         InterceptorFlow::Ingress
     }
-
-    fn authn_ids(&self) -> Vec<AuthSubject> {
-        self.subject.clone()
-    }
 }
@@ -0,0 +1,499 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! ⚠️ WARNING ⚠️
+//!
+//! This module is intended for Zenoh's internal use.
+//!
+//! [Click here for Zenoh's documentation](https://docs.rs/zenoh/latest/zenoh)
+//!
+//! Audit log of the data messages crossing the transports.
+//!
+//! In the `json` format, each record is a JSON object on its own line.
+//!
+//! In the `binary` format, each record is made of the following little-endian fields:
+//! ```text
+//! u32        length of the rest of the record
+//! u64        timestamp in nanoseconds since UNIX epoch
+//! u8         flow (0: ingress, 1: egress)
+//! u8         message (0: put, 1: delete, 2: query, 3: reply)
+//! u8         ACL decision (0: none, 1: allow, 2: deny)
+//! [u8; 16]   zid of the remote node of the transport
+//! [u8; 16]   zid of the source of the message (zeros if unknown)
+//! u32        size of the payload and attachment
+//! u16 + utf8 username (empty if none)
+//! u16 + utf8 comma-separated certificate common names
+//! u16 + utf8 key expression
+//! ```
+
+use std::{
+    ffi::OsString,
+    fs::{File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+use zenoh_config::{AclMessage, AuditConf, AuditFormat, InterceptorFlow, Permission};
+use zenoh_keyexpr::{keyexpr, OwnedKeyExpr};
+use zenoh_protocol::{
+    core::ZenohIdProto,
+    network::{NetworkBody, Push},
+    zenoh::{PushBody, RequestBody, ResponseBody},
+};
+use zenoh_result::{bail, zerror, ZResult};
+
+use super::{
+    access_control::{AclActionMethods, AclEnforcer},
+    subject::TransportSubject,
+};
+use crate::net::routing::interceptor::*;
+
+const DEFAULT_MAX_FILES: usize = 10;
+/// Maximum number of records waiting to be written, the exceeding ones are dropped.
+const QUEUE_SIZE: usize = 16 * 1024;
+
+pub(crate) fn audit_interceptor_factories(
+    conf: &AuditConf,
+    acl: Option<AclEnforcer>,
+) -> ZResult<Vec<InterceptorFactory>> {
+    let mut res: Vec<InterceptorFactory> = vec![];
+
+    if conf.enabled {
+        res.push(Box::new(AuditInterceptorFactory::new(conf, acl)?));
+        tracing::debug!("Audit log is enabled");
+    }
+
+    Ok(res)
+}
+
+struct AuditFile {
+    file: BufWriter<File>,
+    size: u64,
+}
+
+/// An append-only audit log file with size-based rotation.
+///
+/// The records are serialized by the interceptors and queued to a dedicated thread
+/// writing them, so that the routing never waits for the file system.
+struct AuditLog {
+    format: AuditFormat,
+    sender: flume::Sender<Vec<u8>>,
+    dropped: AtomicUsize,
+}
+
+/// The writer of the audit log file, running on its own thread.
+struct AuditWriter {
+    path: PathBuf,
+    max_file_size: Option<u64>,
+    max_files: usize,
+}
+
+impl AuditLog {
+    fn open(conf: &AuditConf) -> ZResult<Self> {
+        let Some(path) = &conf.path else {
+            bail!("Audit log is enabled but no path is configured");
+        };
+        let path = PathBuf::from(path);
+        let file = AuditWriter::open_file(&path)
+            .map_err(|e| zerror!("Couldn't open audit log {}: {}", path.display(), e))?;
+        let writer = AuditWriter {
+            path,
+            max_file_size: conf.max_file_size,
+            max_files: conf.max_files.unwrap_or(DEFAULT_MAX_FILES),
+        };
+        let (sender, receiver) = flume::bounded(QUEUE_SIZE);
+        std::thread::Builder::new()
+            .name("zenoh-audit".to_string())
+            .spawn(move || writer.run(file, receiver))
+            .map_err(|e| zerror!("Couldn't start audit log writer: {}", e))?;
+        Ok(Self {
+            format: conf.format,
+            sender,
+            dropped: AtomicUsize::new(0),
+        })
+    }
+
+    fn write(&self, record: &AuditRecord) {
+        let bytes = match self.format {
+            AuditFormat::Json => match serde_json::to_vec(record) {
+                Ok(mut bytes) => {
+                    bytes.push(b'\n');
+                    bytes
+                }
+                Err(e) => {
+                    tracing::error!("Couldn't serialize audit record: {}", e);
+                    return;
+                }
+            },
+            AuditFormat::Binary => match record.to_binary() {
+                Some(bytes) => bytes,
+                None => {
+                    tracing::error!("Couldn't encode audit record of {:?}", record.message);
+                    return;
+                }
+            },
+        };
+        if self.sender.try_send(bytes).is_err() {
+            if self.dropped.fetch_add(1, Ordering::Relaxed) == 0 {
+                tracing::warn!("Audit log writer is too slow, audit records are dropped");
+            } else {
+                tracing::trace!("Audit log writer is too slow, audit record dropped");
+            }
+        }
+    }
+}
+
+impl AuditWriter {
+    fn open_file(path: &Path) -> io::Result<AuditFile> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(AuditFile {
+            file: BufWriter::new(file),
+            size,
+        })
+    }
+
+    /// Writes the queued records until the log is dropped,
+    /// flushing the file each time the queue is drained.
+    fn run(self, mut file: AuditFile, receiver: flume::Receiver<Vec<u8>>) {
+        while let Ok(bytes) = receiver.recv() {
+            self.write(&mut file, &bytes);
+            if receiver.is_empty() {
+                if let Err(e) = file.file.flush() {
+                    tracing::error!("Couldn't write audit log {}: {}", self.path.display(), e);
+                }
+            }
+        }
+        let _ = file.file.flush();
+    }
+
+    fn rotated_path(&self, idx: usize) -> PathBuf {
+        let mut path = OsString::from(self.path.as_os_str());
+        path.push(format!(".{idx}"));
+        path.into()
+    }
+
+    /// Shifts `path.N-1` to `path.N`, ..., `path` to `path.1` and reopens `path`.
+    fn rotate(&self, file: &mut AuditFile) -> io::Result<()> {
+        file.file.flush()?;
+        if self.max_files == 0 {
+            std::fs::remove_file(&self.path)?;
+        } else {
+            for idx in (1..self.max_files).rev() {
+                let from = self.rotated_path(idx);
+                if from.exists() {
+                    std::fs::rename(from, self.rotated_path(idx + 1))?;
+                }
+            }
+            std::fs::rename(&self.path, self.rotated_path(1))?;
+        }
+        *file = Self::open_file(&self.path)?;
+        Ok(())
+    }
+
+    fn write(&self, file: &mut AuditFile, bytes: &[u8]) {
+        if let Some(max_file_size) = self.max_file_size {
+            if file.size > 0 && file.size + bytes.len() as u64 > max_file_size {
+                if let Err(e) = self.rotate(file) {
+                    tracing::error!("Couldn't rotate audit log {}: {}", self.path.display(), e);
+                }
+            }
+        }
+        match file.file.write_all(bytes) {
+            Ok(()) => file.size += bytes.len() as u64,
+            Err(e) => tracing::error!("Couldn't write audit log {}: {}", self.path.display(), e),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct AuditRecord<'a> {
+    #[serde(serialize_with = "serialize_timestamp")]
+    timestamp: SystemTime,
+    flow: InterceptorFlow,
+    message: AclMessage,
+    decision: Option<Permission>,
+    zid: ZenohIdProto,
+    source_zid: Option<ZenohIdProto>,
+    username: Option<&'a str>,
+    cert_common_names: &'a [String],
+    key_expr: &'a str,
+    size: usize,
+}
+
+fn serialize_timestamp<S: serde::Serializer>(t: &SystemTime, s: S) -> Result<S::Ok, S::Error> {
+    s.collect_str(&humantime::format_rfc3339_nanos(*t))
+}
+
+impl AuditRecord<'_> {
+    /// The record in the binary format, `None` for the messages it has no tag for
+    /// (only data messages are audited).
+    fn to_binary(&self) -> Option<Vec<u8>> {
+        fn push_str(buf: &mut Vec<u8>, s: &str) {
+            let len = s.len().min(u16::MAX as usize);
+            buf.extend_from_slice(&(len as u16).to_le_bytes());
+            buf.extend_from_slice(&s.as_bytes()[..len]);
+        }
+
+        let mut buf = vec![0u8; 4];
+        let nanos = self
+            .timestamp
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64);
+        buf.extend_from_slice(&nanos.to_le_bytes());
+        buf.push(match self.flow {
+            InterceptorFlow::Ingress => 0,
+            InterceptorFlow::Egress => 1,
+        });
+        buf.push(match self.message {
+            AclMessage::Put => 0,
+            AclMessage::Delete => 1,
+            AclMessage::Query => 2,
+            AclMessage::Reply => 3,
+            AclMessage::DeclareSubscriber
+            | AclMessage::DeclareQueryable
+            | AclMessage::LivelinessToken
+            | AclMessage::DeclareLivelinessSubscriber
+            | AclMessage::LivelinessQuery
+            | AclMessage::DeclarePublisher
+            | AclMessage::DeclareQuerier
+            | AclMessage::Interest => return None,
+        });
+        buf.push(match self.decision {
+            None => 0,
+            Some(Permission::Allow) => 1,
+            Some(Permission::Deny) => 2,
+        });
+        buf.extend_from_slice(&self.zid.to_le_bytes());
+        buf.extend_from_slice(&self.source_zid.map_or([0; 16], |zid| zid.to_le_bytes()));
+        buf.extend_from_slice(&(self.size.min(u32::MAX as usize) as u32).to_le_bytes());
+        push_str(&mut buf, self.username.unwrap_or_default());
+        push_str(&mut buf, &self.cert_common_names.join(","));
+        push_str(&mut buf, self.key_expr);
+        let len = (buf.len() - 4) as u32;
+        buf[..4].copy_from_slice(&len.to_le_bytes());
+        Some(buf)
+    }
+}
+
+pub struct AuditInterceptorFactory {
+    log: Arc<AuditLog>,
+    key_exprs: Option<Arc<Vec<OwnedKeyExpr>>>,
+    flows: Option<Vec<InterceptorFlow>>,
+    acl: Option<AclEnforcer>,
+}
+
+impl AuditInterceptorFactory {
+    fn new(conf: &AuditConf, acl: Option<AclEnforcer>) -> ZResult<Self> {
+        Ok(Self {
+            log: Arc::new(AuditLog::open(conf)?),
+            key_exprs: conf.key_exprs.clone().map(Arc::new),
+            flows: conf.flows.clone(),
+            acl,
+        })
+    }
+
+    fn new_interceptor(
+        &self,
+        transport: &TransportUnicast,
+        subject: &TransportSubject,
+        flow: InterceptorFlow,
+    ) -> Option<Interceptor> {
+        if let Some(flows) = &self.flows {
+            if !flows.contains(&flow) {
+                return None;
+            }
+        }
+        Some(Box::new(ComputeOnMiss::new(AuditInterceptor {
+            log: self.log.clone(),
+            key_exprs: self.key_exprs.clone(),
+            flow,
+            zid: subject.zid,
+            username: subject.username().map(|u| u.0.clone()),
+            cert_common_names: subject
+                .cert_common_names()
+                .into_iter()
+                .map(|cn| cn.0.clone())
+                .collect(),
+            acl: self
+                .acl
+                .as_ref()
+                .and_then(|acl| acl.transport_enforcer(transport, flow)),
+        })))
+    }
+}
+
+impl InterceptorFactoryTrait for AuditInterceptorFactory {
+    fn new_transport_unicast(
+        &self,
+        transport: &TransportUnicast,
+    ) -> (Option<IngressInterceptor>, Option<EgressInterceptor>) {
+        let subject = match TransportSubject::new(transport) {
+            Ok(subject) => subject,
+            Err(err) => {
+                tracing::error!("Couldn't get Transport properties: {}", err);
+                return (None, None);
+            }
+        };
+        (
+            self.new_interceptor(transport, &subject, InterceptorFlow::Ingress),
+            self.new_interceptor(transport, &subject, InterceptorFlow::Egress),
+        )
+    }
+
+    fn openmetrics_text(&self) -> Option<String> {
+        Some(format!(
+            "# HELP zenoh_audit_dropped Audit records dropped because the audit log writer was too slow.\n\
+             # TYPE zenoh_audit_dropped counter\n\
+             zenoh_audit_dropped {}\n",
+            self.log.dropped.load(Ordering::Relaxed)
+        ))
+    }
+}
+
+pub(crate) struct AuditInterceptor {
+    log: Arc<AuditLog>,
+    key_exprs: Option<Arc<Vec<OwnedKeyExpr>>>,
+    flow: InterceptorFlow,
+    zid: ZenohIdProto,
+    username: Option<String>,
+    cert_common_names: Vec<String>,
+    acl: Option<Box<dyn AclActionMethods + Send + Sync>>,
+}
+
+impl AuditInterceptor {
+    /// The kind and the source zid of the audited messages.
+    fn audited_message(msg: &NetworkMessage) -> Option<(AclMessage, Option<ZenohIdProto>)> {
+        match &msg.body {
+            NetworkBody::Push(push) => match &push.payload {
+                PushBody::Put(put) => {
+                    Some((AclMessage::Put, put.ext_sinfo.as_ref().map(|s| s.id.zid)))
+                }
+                PushBody::Del(del) => {
+                    Some((AclMessage::Delete, del.ext_sinfo.as_ref().map(|s| s.id.zid)))
+                }
+                // Recorded per entry
+                PushBody::Batch(_) => None,
+            },
+            NetworkBody::Request(request) => match &request.payload {
+                RequestBody::Query(query) => Some((
+                    AclMessage::Query,
+                    query.ext_sinfo.as_ref().map(|s| s.id.zid),
+                )),
+            },
+            NetworkBody::Response(response) => match &response.payload {
+                ResponseBody::Reply(reply) => match &reply.payload {
+                    PushBody::Put(put) => {
+                        Some((AclMessage::Reply, put.ext_sinfo.as_ref().map(|s| s.id.zid)))
+                    }
+                    PushBody::Del(del) => {
+                        Some((AclMessage::Reply, del.ext_sinfo.as_ref().map(|s| s.id.zid)))
+                    }
+                    PushBody::Batch(batch) => Some((
+                        AclMessage::Reply,
+                        batch.ext_sinfo.as_ref().map(|s| s.id.zid),
+                    )),
+                },
+                ResponseBody::Err(err) => {
+                    Some((AclMessage::Reply, err.ext_sinfo.as_ref().map(|s| s.id.zid)))
+                }
+            },
+            _ => None,
+        }
+    }
+
+    fn record(
+        &self,
+        message: AclMessage,
+        source_zid: Option<ZenohIdProto>,
+        key_expr: &str,
+        size: usize,
+    ) {
+        let decision = self
+            .acl
+            .as_ref()
+            .map(|acl| acl.decision(message, &format!("{message:?} ({:?})", self.flow), key_expr));
+        self.log.write(&AuditRecord {
+            timestamp: SystemTime::now(),
+            flow: self.flow,
+            message,
+            decision,
+            zid: self.zid,
+            source_zid,
+            username: self.username.as_deref(),
+            cert_common_names: &self.cert_common_names,
+            key_expr,
+            size,
+        });
+    }
+}
+
+impl InterceptorTrait for AuditInterceptor {
+    fn compute_keyexpr_cache(&self, key_expr: &KeyExpr<'_>) -> Option<Box<dyn Any + Send + Sync>> {
+        Some(Box::new(
+            matches_key_exprs(&self.key_exprs, key_expr).then(|| key_expr.to_string()),
+        ))
+    }
+
+    fn intercept(
+        &self,
+        ctx: RoutingContext<NetworkMessage>,
+        cache: Option<&Box<dyn Any + Send + Sync>>,
+    ) -> Option<RoutingContext<NetworkMessage>> {
+        // The entries of a batch are recorded individually, with their own kind and key expression
+        if let NetworkBody::Push(Push {
+            payload: PushBody::Batch(batch),
+            ..
+        }) = &ctx.msg().body
+        {
+            let source_zid = batch.ext_sinfo.as_ref().map(|s| s.id.zid);
+            for entry in &batch.entries {
+                let message = match entry.payload {
+                    PushBody::Del(_) => AclMessage::Delete,
+                    _ => AclMessage::Put,
+                };
+                if keyexpr::new(&entry.key_expr)
+                    .is_ok_and(|ke| matches_key_exprs(&self.key_exprs, ke))
+                {
+                    self.record(
+                        message,
+                        source_zid,
+                        &entry.key_expr,
+                        push_size(&entry.payload),
+                    );
+                }
+            }
+            return Some(ctx);
+        }
+        let Some((message, source_zid)) = Self::audited_message(ctx.msg()) else {
+            return Some(ctx);
+        };
+        let Some(Some(key_expr)) = cache.and_then(|c| c.downcast_ref::<Option<String>>()) else {
+            return Some(ctx);
+        };
+        self.record(
+            message,
+            source_zid,
+            key_expr,
+            payload_size(ctx.msg()).unwrap_or_default(),
+        );
+        Some(ctx)
+    }
+}
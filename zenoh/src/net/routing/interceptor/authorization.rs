@@ -17,17 +17,22 @@
 //! This module is intended for Zenoh's internal use.
 //!
 //! [Click here for Zenoh's documentation](https://docs.rs/zenoh/latest/zenoh)
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    net::IpAddr,
+    sync::atomic::{AtomicU64, Ordering},
+};
 
 use ahash::RandomState;
 use itertools::Itertools;
 use zenoh_config::{
-    AclConfig, AclConfigPolicyEntry, AclConfigRule, AclConfigSubjects, AclMessage, CertCommonName,
-    InterceptorFlow, Interface, Permission, PolicyRule, Username,
+    AclConfig, AclConfigPolicyEntry, AclConfigRule, AclConfigSubjects, AclEnforcement, AclMessage,
+    CertCommonName, InterceptorFlow, InterceptorSubjectConf, Interface, IpNetwork, LinkProtocol,
+    Permission, PolicyRule, Username, ZenohId,
 };
 use zenoh_keyexpr::{
     keyexpr,
-    keyexpr_tree::{IKeyExprTree, IKeyExprTreeMut, KeBoxTree},
+    keyexpr_tree::{IKeyExprTree, IKeyExprTreeMut, IKeyExprTreeNode, KeBoxTree},
 };
 use zenoh_result::ZResult;
 type PolicyForSubject = FlowPolicy;
@@ -39,15 +44,60 @@ pub(crate) struct Subject {
     pub(crate) interface: SubjectProperty<Interface>,
     pub(crate) cert_common_name: SubjectProperty<CertCommonName>,
     pub(crate) username: SubjectProperty<Username>,
+    pub(crate) zid: SubjectProperty<ZenohId>,
+    pub(crate) link_protocol: SubjectProperty<LinkProtocol>,
+    pub(crate) remote_network: SubjectProperty<IpNetwork>,
 }
 
 impl Subject {
-    fn matches(&self, query: &SubjectQuery) -> bool {
+    /// The combinations of the values of the properties of a configured subject,
+    /// an unspecified property being a wildcard.
+    pub(crate) fn combinations(subject: &InterceptorSubjectConf) -> Vec<Subject> {
+        fn property<T: Clone>(values: &Option<Vec<T>>) -> Vec<SubjectProperty<T>> {
+            match values {
+                Some(values) => values
+                    .iter()
+                    .cloned()
+                    .map(SubjectProperty::Exactly)
+                    .collect(),
+                None => vec![SubjectProperty::Wildcard],
+            }
+        }
+
+        property(&subject.interfaces)
+            .into_iter()
+            .cartesian_product(property(&subject.cert_common_names))
+            .cartesian_product(property(&subject.usernames))
+            .cartesian_product(property(&subject.zids))
+            .cartesian_product(property(&subject.link_protocols))
+            .cartesian_product(property(&subject.remote_networks))
+            .map(
+                |(
+                    ((((interface, cert_common_name), username), zid), link_protocol),
+                    remote_network,
+                )| Subject {
+                    interface,
+                    cert_common_name,
+                    username,
+                    zid,
+                    link_protocol,
+                    remote_network,
+                },
+            )
+            .collect()
+    }
+
+    pub(crate) fn matches(&self, query: &SubjectQuery) -> bool {
         self.interface.matches(query.interface.as_ref())
             && self.username.matches(query.username.as_ref())
             && self
                 .cert_common_name
                 .matches(query.cert_common_name.as_ref())
+            && self.zid.matches(query.zid.as_ref())
+            && self.link_protocol.matches(query.link_protocol.as_ref())
+            && self
+                .remote_network
+                .matches_with(query.remote_address.as_ref(), IpNetwork::contains)
     }
 }
 
@@ -59,12 +109,20 @@ pub(crate) enum SubjectProperty<T> {
 
 impl<T: PartialEq + Eq> SubjectProperty<T> {
     fn matches(&self, other: Option<&T>) -> bool {
+        self.matches_with(other, T::eq)
+    }
+}
+
+impl<T> SubjectProperty<T> {
+    /// Matches a queried value which may be of another type than the property, e.g. an IP address
+    /// against an IP network.
+    fn matches_with<Q>(&self, other: Option<&Q>, matches: impl Fn(&T, &Q) -> bool) -> bool {
         match (self, other) {
             (SubjectProperty::Wildcard, None) => true,
             // NOTE: This match arm is the reason why `SubjectProperty` cannot simply be `Option`
             (SubjectProperty::Wildcard, Some(_)) => true,
             (SubjectProperty::Exactly(_), None) => false,
-            (SubjectProperty::Exactly(lhs), Some(rhs)) => lhs == rhs,
+            (SubjectProperty::Exactly(lhs), Some(rhs)) => matches(lhs, rhs),
         }
     }
 }
@@ -74,6 +132,9 @@ pub(crate) struct SubjectQuery {
     pub(crate) interface: Option<Interface>,
     pub(crate) cert_common_name: Option<CertCommonName>,
     pub(crate) username: Option<Username>,
+    pub(crate) zid: Option<ZenohId>,
+    pub(crate) link_protocol: Option<LinkProtocol>,
+    pub(crate) remote_address: Option<IpAddr>,
 }
 
 impl std::fmt::Display for SubjectQuery {
@@ -82,6 +143,13 @@ impl std::fmt::Display for SubjectQuery {
             self.interface.as_ref().map(|face| format!("{face}")),
             self.cert_common_name.as_ref().map(|ccn| format!("{ccn}")),
             self.username.as_ref().map(|username| format!("{username}")),
+            self.zid.as_ref().map(|zid| format!("Zid({zid})")),
+            self.link_protocol
+                .as_ref()
+                .map(|protocol| format!("{protocol}")),
+            self.remote_address
+                .as_ref()
+                .map(|addr| format!("RemoteAddress({addr})")),
         ];
         write!(
             f,
@@ -157,7 +225,8 @@ impl SubjectMapBuilder {
     }
 }
 
-type KeTreeRule = KeBoxTree<bool>;
+/// The indexes of the rules (in [`PolicyEnforcer::rules`]) defining each key expression.
+type KeTreeRule = KeBoxTree<Vec<usize>>;
 
 #[derive(Default)]
 struct PermissionPolicy {
@@ -191,6 +260,9 @@ struct ActionPolicy {
     liveliness_token: PermissionPolicy,
     declare_liveliness_sub: PermissionPolicy,
     liveliness_query: PermissionPolicy,
+    declare_publisher: PermissionPolicy,
+    declare_querier: PermissionPolicy,
+    interest: PermissionPolicy,
 }
 
 impl ActionPolicy {
@@ -205,6 +277,9 @@ impl ActionPolicy {
             AclMessage::LivelinessToken => &self.liveliness_token,
             AclMessage::DeclareLivelinessSubscriber => &self.declare_liveliness_sub,
             AclMessage::LivelinessQuery => &self.liveliness_query,
+            AclMessage::DeclarePublisher => &self.declare_publisher,
+            AclMessage::DeclareQuerier => &self.declare_querier,
+            AclMessage::Interest => &self.interest,
         }
     }
     fn action_mut(&mut self, action: AclMessage) -> &mut PermissionPolicy {
@@ -218,6 +293,9 @@ impl ActionPolicy {
             AclMessage::LivelinessToken => &mut self.liveliness_token,
             AclMessage::DeclareLivelinessSubscriber => &mut self.declare_liveliness_sub,
             AclMessage::LivelinessQuery => &mut self.liveliness_query,
+            AclMessage::DeclarePublisher => &mut self.declare_publisher,
+            AclMessage::DeclareQuerier => &mut self.declare_querier,
+            AclMessage::Interest => &mut self.interest,
         }
     }
 }
@@ -249,18 +327,43 @@ pub struct InterfaceEnabled {
     pub egress: bool,
 }
 
+impl InterfaceEnabled {
+    pub(crate) fn flow(&self, flow: InterceptorFlow) -> bool {
+        match flow {
+            InterceptorFlow::Ingress => self.ingress,
+            InterceptorFlow::Egress => self.egress,
+        }
+    }
+}
+
+/// A configured rule and the number of decisions it contributed to.
+pub(crate) struct RuleStats {
+    pub(crate) id: String,
+    pub(crate) permission: Permission,
+    pub(crate) hits: AtomicU64,
+}
+
 pub struct PolicyEnforcer {
     pub(crate) acl_enabled: bool,
     pub(crate) default_permission: Permission,
     pub(crate) subject_store: SubjectStore,
     pub(crate) policy_map: PolicyMap,
     pub(crate) interface_enabled: InterfaceEnabled,
+    pub(crate) rules: Vec<RuleStats>,
+    /// The messages named by the rules of the policies
+    pub(crate) messages: HashSet<AclMessage>,
+    pub(crate) enforcement: AclEnforcement,
+    /// The number of denied messages, or of would-be denied messages in audit mode
+    pub(crate) denials: AtomicU64,
 }
 
 #[derive(Debug, Clone)]
 pub struct PolicyInformation {
     subject_map: SubjectStore,
-    policy_rules: Vec<PolicyRule>,
+    /// The policy rules and the index of the configured rule they come from
+    policy_rules: Vec<(usize, PolicyRule)>,
+    /// The ids and permissions of the configured rules
+    rules: Vec<(String, Permission)>,
 }
 
 impl PolicyEnforcer {
@@ -271,6 +374,19 @@ impl PolicyEnforcer {
             subject_store: SubjectStore::default(),
             policy_map: PolicyMap::default(),
             interface_enabled: InterfaceEnabled::default(),
+            rules: Vec::new(),
+            messages: HashSet::new(),
+            enforcement: AclEnforcement::Enforce,
+            denials: AtomicU64::new(0),
+        }
+    }
+
+    /// Increments the hit counters of the given rules.
+    pub(crate) fn record_hits(&self, rules: &[usize]) {
+        for rule in rules {
+            if let Some(rule) = self.rules.get(*rule) {
+                rule.hits.fetch_add(1, Ordering::Relaxed);
+            }
         }
     }
 
@@ -281,6 +397,7 @@ impl PolicyEnforcer {
         let mut_acl_config = acl_config.clone();
         self.acl_enabled = mut_acl_config.enabled;
         self.default_permission = mut_acl_config.default_permission;
+        self.enforcement = mut_acl_config.enforcement;
         if self.acl_enabled {
             if let (Some(mut rules), Some(mut subjects), Some(policies)) = (
                 mut_acl_config.rules,
@@ -324,6 +441,7 @@ impl PolicyEnforcer {
                         }
 
                         if subject
+                            .subject
                             .cert_common_names
                             .as_ref()
                             .is_some_and(Vec::is_empty)
@@ -331,25 +449,65 @@ impl PolicyEnforcer {
                             bail!("Subject property `cert_common_names` cannot be empty");
                         }
 
-                        if subject.usernames.as_ref().is_some_and(Vec::is_empty) {
+                        if subject
+                            .subject
+                            .usernames
+                            .as_ref()
+                            .is_some_and(Vec::is_empty)
+                        {
                             bail!("Subject property `usernames` cannot be empty");
                         }
 
-                        if subject.interfaces.as_ref().is_some_and(Vec::is_empty) {
+                        if subject
+                            .subject
+                            .interfaces
+                            .as_ref()
+                            .is_some_and(Vec::is_empty)
+                        {
                             bail!("Subject property `interfaces` cannot be empty");
                         }
+
+                        if subject.subject.zids.as_ref().is_some_and(Vec::is_empty) {
+                            bail!("Subject property `zids` cannot be empty");
+                        }
+
+                        if subject
+                            .subject
+                            .link_protocols
+                            .as_ref()
+                            .is_some_and(Vec::is_empty)
+                        {
+                            bail!("Subject property `link_protocols` cannot be empty");
+                        }
+
+                        if subject
+                            .subject
+                            .remote_networks
+                            .as_ref()
+                            .is_some_and(Vec::is_empty)
+                        {
+                            bail!("Subject property `remote_networks` cannot be empty");
+                        }
                     }
                     let policy_information =
                         self.policy_information_point(subjects, rules, policies)?;
 
                     let mut main_policy: PolicyMap = PolicyMap::default();
-                    for rule in policy_information.policy_rules {
+                    for (rule_idx, rule) in policy_information.policy_rules {
+                        self.messages.insert(rule.message);
                         let subject_policy = main_policy.entry(rule.subject_id).or_default();
-                        subject_policy
+                        let ke_tree = subject_policy
                             .flow_mut(rule.flow)
                             .action_mut(rule.message)
-                            .permission_mut(rule.permission)
-                            .insert(keyexpr::new(&rule.key_expr)?, true);
+                            .permission_mut(rule.permission);
+                        let key_expr = keyexpr::new(&rule.key_expr)?;
+                        if let Some(rule_idxs) = ke_tree.weight_at_mut(key_expr) {
+                            if !rule_idxs.contains(&rule_idx) {
+                                rule_idxs.push(rule_idx);
+                            }
+                        } else {
+                            ke_tree.insert(key_expr, vec![rule_idx]);
+                        }
 
                         if self.default_permission == Permission::Deny {
                             self.interface_enabled = InterfaceEnabled {
@@ -369,6 +527,15 @@ impl PolicyEnforcer {
                     }
                     self.policy_map = main_policy;
                     self.subject_store = policy_information.subject_map;
+                    self.rules = policy_information
+                        .rules
+                        .into_iter()
+                        .map(|(id, permission)| RuleStats {
+                            id,
+                            permission,
+                            hits: AtomicU64::new(0),
+                        })
+                        .collect();
                 }
             } else {
                 bail!("All ACL rules/subjects/policies config lists must be provided");
@@ -386,8 +553,9 @@ impl PolicyEnforcer {
         rules: Vec<AclConfigRule>,
         policies: Vec<AclConfigPolicyEntry>,
     ) -> ZResult<PolicyInformation> {
-        let mut policy_rules: Vec<PolicyRule> = Vec::new();
+        let mut policy_rules: Vec<(usize, PolicyRule)> = Vec::new();
         let mut rule_map = HashMap::new();
+        let mut rule_ids = Vec::new();
         let mut subject_id_map = HashMap::<String, Vec<usize>>::new();
         let mut subject_map_builder = SubjectMapBuilder::new();
 
@@ -418,7 +586,8 @@ impl PolicyEnforcer {
                     bail!("Found empty key expression in rule '{}'", config_rule.id);
                 }
             }
-            rule_map.insert(config_rule.id.clone(), config_rule);
+            rule_ids.push((config_rule.id.clone(), config_rule.permission));
+            rule_map.insert(config_rule.id.clone(), (rule_ids.len() - 1, config_rule));
         }
 
         for config_subject in subjects.into_iter() {
@@ -430,6 +599,7 @@ impl PolicyEnforcer {
             }
             // validate subject config fields
             if config_subject
+                .subject
                 .interfaces
                 .as_ref()
                 .is_some_and(|interfaces| interfaces.iter().any(|face| face.0.trim().is_empty()))
@@ -440,6 +610,7 @@ impl PolicyEnforcer {
                 );
             }
             if config_subject
+                .subject
                 .cert_common_names
                 .as_ref()
                 .is_some_and(|cert_common_names| {
@@ -451,61 +622,25 @@ impl PolicyEnforcer {
                     config_subject.id
                 );
             }
-            if config_subject.usernames.as_ref().is_some_and(|usernames| {
-                usernames
-                    .iter()
-                    .any(|username| username.0.trim().is_empty())
-            }) {
+            if config_subject
+                .subject
+                .usernames
+                .as_ref()
+                .is_some_and(|usernames| {
+                    usernames
+                        .iter()
+                        .any(|username| username.0.trim().is_empty())
+                })
+            {
                 bail!(
                     "Found empty username value in subject '{}'",
                     config_subject.id
                 );
             }
-            // Map properties to SubjectProperty type
-            // FIXME: Unnecessary .collect() because of different iterator types
-            let interfaces = config_subject
-                .interfaces
-                .map(|interfaces| {
-                    interfaces
-                        .into_iter()
-                        .map(SubjectProperty::Exactly)
-                        .collect::<Vec<_>>()
-                })
-                .unwrap_or(vec![SubjectProperty::Wildcard]);
-            // FIXME: Unnecessary .collect() because of different iterator types
-            let cert_common_names = config_subject
-                .cert_common_names
-                .map(|cert_common_names| {
-                    cert_common_names
-                        .into_iter()
-                        .map(SubjectProperty::Exactly)
-                        .collect::<Vec<_>>()
-                })
-                .unwrap_or(vec![SubjectProperty::Wildcard]);
-            // FIXME: Unnecessary .collect() because of different iterator types
-            let usernames = config_subject
-                .usernames
-                .map(|usernames| {
-                    usernames
-                        .into_iter()
-                        .map(SubjectProperty::Exactly)
-                        .collect::<Vec<_>>()
-                })
-                .unwrap_or(vec![SubjectProperty::Wildcard]);
-
             // create ACL subject combinations
-            let subject_combination_ids = interfaces
+            let subject_combination_ids = Subject::combinations(&config_subject.subject)
                 .into_iter()
-                .cartesian_product(cert_common_names)
-                .cartesian_product(usernames)
-                .map(|((interface, cert_common_name), username)| {
-                    let subject = Subject {
-                        interface,
-                        cert_common_name,
-                        username,
-                    };
-                    subject_map_builder.insert_or_get(subject)
-                })
+                .map(|subject| subject_map_builder.insert_or_get(subject))
                 .collect();
             subject_id_map.insert(config_subject.id.clone(), subject_combination_ids);
         }
@@ -535,7 +670,7 @@ impl PolicyEnforcer {
                 if rule_id.trim().is_empty() {
                     bail!("Found empty rule id in policy #{}", entry_id)
                 }
-                let rule = rule_map.get(rule_id).ok_or(zerror!(
+                let (rule_idx, rule) = rule_map.get(rule_id).ok_or(zerror!(
                     "Rule '{}' in policy #{} does not exist in rules list",
                     rule_id,
                     entry_id
@@ -552,13 +687,16 @@ impl PolicyEnforcer {
                         {
                             for message in &rule.messages {
                                 for key_expr in &rule.key_exprs {
-                                    policy_rules.push(PolicyRule {
-                                        subject_id: *subject_id,
-                                        key_expr: key_expr.clone(),
-                                        message: *message,
-                                        permission: rule.permission,
-                                        flow: *flow,
-                                    });
+                                    policy_rules.push((
+                                        *rule_idx,
+                                        PolicyRule {
+                                            subject_id: *subject_id,
+                                            key_expr: key_expr.clone(),
+                                            message: *message,
+                                            permission: rule.permission,
+                                            flow: *flow,
+                                        },
+                                    ));
                                 }
                             }
                         }
@@ -569,11 +707,13 @@ impl PolicyEnforcer {
         Ok(PolicyInformation {
             subject_map: subject_map_builder.build(),
             policy_rules,
+            rules: rule_ids,
         })
     }
 
     /**
-     * Check each msg against the ACL ruleset for allow/deny
+     * Check each msg against the ACL ruleset for allow/deny,
+     * also returns the indexes of the rules leading to the decision
      */
     pub fn policy_decision_point(
         &self,
@@ -581,40 +721,41 @@ impl PolicyEnforcer {
         flow: InterceptorFlow,
         message: AclMessage,
         key_expr: &str,
-    ) -> ZResult<Permission> {
+    ) -> ZResult<(Permission, Vec<usize>)> {
+        fn matching_rules(tree: &KeTreeRule, key_expr: &keyexpr) -> Vec<usize> {
+            tree.nodes_including(key_expr)
+                .filter_map(|node| node.weight())
+                .flatten()
+                .copied()
+                .collect()
+        }
+
         let policy_map = &self.policy_map;
         if policy_map.is_empty() {
-            return Ok(self.default_permission);
+            return Ok((self.default_permission, vec![]));
         }
         match policy_map.get(&subject) {
             Some(single_policy) => {
-                let deny_result = single_policy
-                    .flow(flow)
-                    .action(message)
-                    .deny
-                    .nodes_including(keyexpr::new(&key_expr)?)
-                    .count();
-                if deny_result != 0 {
-                    return Ok(Permission::Deny);
+                let key_expr = keyexpr::new(&key_expr)?;
+                let deny_result =
+                    matching_rules(&single_policy.flow(flow).action(message).deny, key_expr);
+                if !deny_result.is_empty() {
+                    return Ok((Permission::Deny, deny_result));
                 }
                 if self.default_permission == Permission::Allow {
-                    Ok(Permission::Allow)
+                    Ok((Permission::Allow, vec![]))
                 } else {
-                    let allow_result = single_policy
-                        .flow(flow)
-                        .action(message)
-                        .allow
-                        .nodes_including(keyexpr::new(&key_expr)?)
-                        .count();
-
-                    if allow_result != 0 {
-                        Ok(Permission::Allow)
+                    let allow_result =
+                        matching_rules(&single_policy.flow(flow).action(message).allow, key_expr);
+
+                    if !allow_result.is_empty() {
+                        Ok((Permission::Allow, allow_result))
                     } else {
-                        Ok(Permission::Deny)
+                        Ok((Permission::Deny, vec![]))
                     }
                 }
             }
-            None => Ok(self.default_permission),
+            None => Ok((self.default_permission, vec![])),
         }
     }
 }
@@ -20,16 +20,20 @@
 
 use std::{
     collections::HashMap,
-    sync::{Arc, Mutex},
+    sync::{Arc, Mutex, OnceLock, Weak},
 };
 
-use zenoh_config::{DownsamplingItemConf, DownsamplingRuleConf, InterceptorFlow};
+use zenoh_config::{DownsamplingItemConf, DownsamplingMode, DownsamplingRuleConf, InterceptorFlow};
 use zenoh_core::zlock;
 use zenoh_keyexpr::keyexpr_tree::{
     impls::KeyedSetProvider, support::UnknownWildness, IKeyExprTree, IKeyExprTreeMut, KeBoxTree,
 };
-use zenoh_protocol::network::NetworkBody;
-use zenoh_result::ZResult;
+use zenoh_protocol::{
+    core::EntityGlobalIdProto,
+    network::{NetworkBody, Push},
+    zenoh::PushBody,
+};
+use zenoh_result::{bail, ZResult};
 
 use crate::net::routing::interceptor::*;
 
@@ -39,6 +43,15 @@ pub(crate) fn downsampling_interceptor_factories(
     let mut res: Vec<InterceptorFactory> = vec![];
 
     for ds in config {
+        for rule in &ds.rules {
+            if rule.mode == DownsamplingMode::Latest && (rule.freq <= 0.0 || rule.freq.is_nan()) {
+                bail!(
+                    "Invalid downsampling rule for {}: the latest mode requires a positive frequency, got {}",
+                    rule.key_expr,
+                    rule.freq
+                );
+            }
+        }
         res.push(Box::new(DownsamplingInterceptorFactory::new(ds.clone())));
     }
 
@@ -116,22 +129,76 @@ impl InterceptorFactoryTrait for DownsamplingInterceptorFactory {
 struct Timestate {
     pub threshold: tokio::time::Duration,
     pub latest_message_timestamp: tokio::time::Instant,
+    held: Option<NetworkMessage>,
+    flush_scheduled: bool,
+}
+
+impl Timestate {
+    /// A state is expired once its period is over without held sample, it is then
+    /// equivalent to the initial state of the next sample and can be evicted.
+    fn is_expired(&self, now: tokio::time::Instant) -> bool {
+        self.held.is_none()
+            && !self.flush_scheduled
+            && now.saturating_duration_since(self.latest_message_timestamp) >= self.threshold
+    }
+}
+
+/// The downsampling states, with the expired ones regularly evicted so that the states of
+/// the sources which stopped publishing do not accumulate.
+struct States {
+    states: HashMap<StateId, Timestate>,
+    last_eviction: tokio::time::Instant,
+}
+
+impl States {
+    const EVICTION_PERIOD: tokio::time::Duration = tokio::time::Duration::from_secs(10);
+
+    fn evict_expired(&mut self, now: tokio::time::Instant) {
+        if now.saturating_duration_since(self.last_eviction) >= Self::EVICTION_PERIOD {
+            self.states.retain(|_, state| !state.is_expired(now));
+            self.last_eviction = now;
+        }
+    }
 }
 
+struct Rule {
+    threshold: tokio::time::Duration,
+    mode: DownsamplingMode,
+    per_source: bool,
+}
+
+/// The downsampling state of a rule, for a given source if the rule is per source.
+/// In latest mode the state is also per key expression, so that the held samples of
+/// the different keys matching the rule do not supersede each other.
+type StateId = (usize, Option<String>, Option<EntityGlobalIdProto>);
+
 pub(crate) struct DownsamplingInterceptor {
     ke_id: Arc<Mutex<KeBoxTree<usize, UnknownWildness, KeyedSetProvider>>>,
-    ke_state: Arc<Mutex<HashMap<usize, Timestate>>>,
+    rules: Vec<Rule>,
+    ke_state: Arc<Mutex<States>>,
+    resume: OnceLock<Resume>,
 }
 
 impl InterceptorTrait for DownsamplingInterceptor {
     fn compute_keyexpr_cache(&self, key_expr: &KeyExpr<'_>) -> Option<Box<dyn Any + Send + Sync>> {
-        let ke_id = zlock!(self.ke_id);
-        if let Some(node) = ke_id.intersecting_keys(key_expr).next() {
-            if let Some(id) = ke_id.weight_at(&node) {
-                return Some(Box::new(Some(*id)));
-            }
+        Some(Box::new(self.rule_id(key_expr)))
+    }
+
+    fn explain(&self, key_expr: &KeyExpr<'_>, message: ExplainedMessage) -> Option<String> {
+        if message == ExplainedMessage::Query {
+            return None;
         }
-        Some(Box::new(None::<usize>))
+        let cache = self.compute_keyexpr_cache(key_expr)?;
+        let rule = self.rules.get((*cache.downcast_ref::<Option<usize>>()?)?)?;
+        Some(format!(
+            "downsampling: at most {} messages per second{}, exceeding ones {}",
+            1.0 / rule.threshold.as_secs_f64(),
+            if rule.per_source { " per source" } else { "" },
+            match rule.mode {
+                DownsamplingMode::Drop => "dropped",
+                DownsamplingMode::Latest => "held until the end of the period",
+            }
+        ))
     }
 
     fn intercept(
@@ -139,22 +206,66 @@ impl InterceptorTrait for DownsamplingInterceptor {
         ctx: RoutingContext<NetworkMessage>,
         cache: Option<&Box<dyn Any + Send + Sync>>,
     ) -> Option<RoutingContext<NetworkMessage>> {
-        if matches!(ctx.msg.body, NetworkBody::Push(_)) {
+        if let NetworkBody::Push(
+            push @ Push {
+                payload: PushBody::Batch(_),
+                ..
+            },
+        ) = &ctx.msg().body
+        {
+            let state_ids = self.batch_state_ids(push);
+            return self.admit_batch(state_ids).then_some(ctx);
+        }
+        if let NetworkBody::Push(push) = &ctx.msg().body {
             if let Some(cache) = cache {
                 if let Some(id) = cache.downcast_ref::<Option<usize>>() {
                     if let Some(id) = id {
+                        let Some(rule) = self.rules.get(*id) else {
+                            tracing::debug!("unexpected cache ID {}", id);
+                            return Some(ctx);
+                        };
+                        let state_id = (
+                            *id,
+                            (rule.mode == DownsamplingMode::Latest)
+                                .then(|| ctx.full_expr().map(str::to_string))
+                                .flatten(),
+                            rule.per_source.then(|| source(push)).flatten(),
+                        );
+                        let timestamp = tokio::time::Instant::now();
                         let mut ke_state = zlock!(self.ke_state);
-                        if let Some(state) = ke_state.get_mut(id) {
-                            let timestamp = tokio::time::Instant::now();
-
-                            if timestamp - state.latest_message_timestamp >= state.threshold {
-                                state.latest_message_timestamp = timestamp;
-                                return Some(ctx);
-                            } else {
-                                return None;
-                            }
+                        ke_state.evict_expired(timestamp);
+                        let state =
+                            ke_state
+                                .states
+                                .entry(state_id.clone())
+                                .or_insert_with(|| Timestate {
+                                    threshold: rule.threshold,
+                                    latest_message_timestamp: timestamp
+                                        .checked_sub(rule.threshold)
+                                        .unwrap_or(timestamp),
+                                    held: None,
+                                    flush_scheduled: false,
+                                });
+
+                        if timestamp - state.latest_message_timestamp >= state.threshold {
+                            state.latest_message_timestamp = timestamp;
+                            // This sample supersedes any held one
+                            state.held = None;
+                            return Some(ctx);
                         } else {
-                            tracing::debug!("unexpected cache ID {}", id);
+                            if rule.mode == DownsamplingMode::Latest {
+                                let Some(deadline) =
+                                    state.latest_message_timestamp.checked_add(state.threshold)
+                                else {
+                                    return None;
+                                };
+                                state.held = Some(ctx.msg);
+                                if !state.flush_scheduled {
+                                    state.flush_scheduled = true;
+                                    self.schedule_flush(state_id, deadline);
+                                }
+                            }
+                            return None;
                         }
                     }
                 } else {
@@ -165,39 +276,148 @@ impl InterceptorTrait for DownsamplingInterceptor {
 
         Some(ctx)
     }
+
+    fn set_resume(&self, resume: Resume) {
+        let _ = self.resume.set(resume);
+    }
+}
+
+/// The source entity of a sample, if provided by the publisher.
+fn source(push: &Push) -> Option<EntityGlobalIdProto> {
+    match &push.payload {
+        PushBody::Put(put) => put.ext_sinfo.as_ref().map(|sinfo| sinfo.id),
+        PushBody::Del(del) => del.ext_sinfo.as_ref().map(|sinfo| sinfo.id),
+        PushBody::Batch(batch) => batch.ext_sinfo.as_ref().map(|sinfo| sinfo.id),
+    }
 }
 
 const NANOS_PER_SEC: f64 = 1_000_000_000.0;
 
 impl DownsamplingInterceptor {
-    pub fn new(rules: Vec<DownsamplingRuleConf>) -> Self {
+    fn new(conf: Vec<DownsamplingRuleConf>) -> Self {
         let mut ke_id = KeBoxTree::default();
-        let mut ke_state = HashMap::default();
-        for (id, rule) in rules.into_iter().enumerate() {
+        let mut rules = vec![];
+        for (id, rule) in conf.into_iter().enumerate() {
             let mut threshold = tokio::time::Duration::MAX;
-            let mut latest_message_timestamp = tokio::time::Instant::now();
             if rule.freq != 0.0 {
                 threshold =
                     tokio::time::Duration::from_nanos((1. / rule.freq * NANOS_PER_SEC) as u64);
-                latest_message_timestamp -= threshold;
             }
             ke_id.insert(&rule.key_expr, id);
-            ke_state.insert(
-                id,
-                Timestate {
-                    threshold,
-                    latest_message_timestamp,
-                },
-            );
+            rules.push(Rule {
+                threshold,
+                mode: rule.mode,
+                per_source: rule.per_source,
+            });
             tracing::debug!(
-                "New downsampler rule enabled: key_expr={:?}, threshold={:?}",
+                "New downsampler rule enabled: key_expr={:?}, threshold={:?}, mode={:?}, per_source={}",
                 rule.key_expr,
-                threshold
+                threshold,
+                rule.mode,
+                rule.per_source
             );
         }
         Self {
             ke_id: Arc::new(Mutex::new(ke_id)),
-            ke_state: Arc::new(Mutex::new(ke_state)),
+            rules,
+            ke_state: Arc::new(Mutex::new(States {
+                states: HashMap::default(),
+                last_eviction: tokio::time::Instant::now(),
+            })),
+            resume: OnceLock::new(),
+        }
+    }
+
+    fn rule_id(&self, key_expr: &keyexpr) -> Option<usize> {
+        let ke_id = zlock!(self.ke_id);
+        let node = ke_id.intersecting_keys(key_expr).next()?;
+        ke_id.weight_at(&node).copied()
+    }
+
+    /// The states of the rules matching the entries of a batch.
+    fn batch_state_ids(&self, push: &Push) -> Vec<StateId> {
+        let PushBody::Batch(batch) = &push.payload else {
+            return vec![];
+        };
+        let source = source(push);
+        batch
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                let id = self.rule_id(keyexpr::new(entry.key_expr.as_str()).ok()?)?;
+                let rule = self.rules.get(id)?;
+                Some((
+                    id,
+                    (rule.mode == DownsamplingMode::Latest).then(|| entry.key_expr.clone()),
+                    rule.per_source.then_some(source).flatten(),
+                ))
+            })
+            .collect()
+    }
+
+    /// A batch is downsampled as a whole: it is sent if each of its entries matching a rule would be,
+    /// and dropped otherwise, also in latest mode since its entries cannot be held separately.
+    fn admit_batch(&self, state_ids: Vec<StateId>) -> bool {
+        let timestamp = tokio::time::Instant::now();
+        let mut ke_state = zlock!(self.ke_state);
+        ke_state.evict_expired(timestamp);
+        let admitted = state_ids.iter().all(|state_id| {
+            ke_state.states.get(state_id).map_or(true, |state| {
+                timestamp.saturating_duration_since(state.latest_message_timestamp)
+                    >= state.threshold
+            })
+        });
+        if admitted {
+            for state_id in state_ids {
+                let threshold = self.rules[state_id.0].threshold;
+                let state = ke_state
+                    .states
+                    .entry(state_id)
+                    .or_insert_with(|| Timestate {
+                        threshold,
+                        latest_message_timestamp: timestamp,
+                        held: None,
+                        flush_scheduled: false,
+                    });
+                state.latest_message_timestamp = timestamp;
+                // The batch supersedes any held sample
+                state.held = None;
+            }
+        }
+        admitted
+    }
+
+    /// Sends the held sample of the given state at the end of its period,
+    /// through the interceptors following this one.
+    fn schedule_flush(&self, state_id: StateId, deadline: tokio::time::Instant) {
+        let ke_state = Arc::downgrade(&self.ke_state);
+        let resume = self.resume.get().cloned();
+        zenoh_runtime::ZRuntime::Net.spawn(async move {
+            tokio::time::sleep_until(deadline).await;
+            Self::flush(ke_state, state_id, resume);
+        });
+    }
+
+    fn flush(ke_state: Weak<Mutex<States>>, state_id: StateId, resume: Option<Resume>) {
+        let Some(ke_state) = ke_state.upgrade() else {
+            return;
+        };
+        let held = {
+            let mut ke_state = zlock!(ke_state);
+            let Some(state) = ke_state.states.get_mut(&state_id) else {
+                return;
+            };
+            state.flush_scheduled = false;
+            let Some(held) = state.held.take() else {
+                return;
+            };
+            state.latest_message_timestamp = tokio::time::Instant::now();
+            held
+        };
+
+        match resume {
+            Some(resume) => resume.send(held),
+            None => tracing::debug!("Held sample dropped: downsampling not attached"),
         }
     }
 }
@@ -19,72 +19,392 @@
 //! [Click here for Zenoh's documentation](https://docs.rs/zenoh/latest/zenoh)
 //!
 mod access_control;
-use access_control::acl_interceptor_factories;
+pub(crate) use access_control::AclEnforcer;
+
+mod audit;
+use audit::audit_interceptor_factories;
 
 mod authorization;
-use std::any::Any;
+use std::{
+    any::Any,
+    sync::{Arc, OnceLock, Weak},
+};
+
+mod subject;
 
+use zenoh_buffers::buffer::Buffer;
 use zenoh_config::Config;
-use zenoh_protocol::network::NetworkMessage;
+use zenoh_keyexpr::{keyexpr, OwnedKeyExpr};
+use zenoh_protocol::{
+    network::{NetworkBody, NetworkMessage, Push},
+    zenoh::{ext::AttachmentType, PushBody, RequestBody, ResponseBody},
+};
 use zenoh_result::ZResult;
 use zenoh_transport::{multicast::TransportMulticast, unicast::TransportUnicast};
 
-use super::RoutingContext;
-use crate::api::key_expr::KeyExpr;
+use super::{dispatcher::face::WeakFace, RoutingContext};
+use crate::{
+    api::key_expr::KeyExpr,
+    net::{
+        primitives::Primitives,
+        protocol::batch::{batch_key_expr, is_batch},
+    },
+};
 
 pub mod downsampling;
 use crate::net::routing::interceptor::downsampling::downsampling_interceptor_factories;
 
-pub(crate) trait InterceptorTrait {
-    fn compute_keyexpr_cache(&self, key_expr: &KeyExpr<'_>) -> Option<Box<dyn Any + Send + Sync>>;
+pub mod namespace;
+use crate::net::routing::interceptor::namespace::namespace_interceptor_factories;
+
+pub mod qos_overwrite;
+use crate::net::routing::interceptor::qos_overwrite::qos_overwrite_interceptor_factories;
+
+pub mod quota;
+use crate::net::routing::interceptor::quota::quota_interceptor_factories;
+
+pub mod rewrite;
+use crate::net::routing::interceptor::rewrite::rewrite_interceptor_factories;
+
+/// An interceptor is attached to one direction (ingress or egress) of a single transport
+/// and sees every [`NetworkMessage`] flowing through it.
+///
+/// Returning `None` from [`InterceptorTrait::intercept`] drops the message, returning a
+/// (possibly modified) [`RoutingContext`] lets it continue through the routing.
+pub trait InterceptorTrait {
+    /// Computes a value that will be cached per key expression and handed back to
+    /// [`InterceptorTrait::intercept`] for every message on this key expression.
+    fn compute_keyexpr_cache(&self, _key_expr: &KeyExpr<'_>) -> Option<Box<dyn Any + Send + Sync>> {
+        None
+    }
 
     fn intercept(
         &self,
         ctx: RoutingContext<NetworkMessage>,
         cache: Option<&Box<dyn Any + Send + Sync>>,
     ) -> Option<RoutingContext<NetworkMessage>>;
+
+    /// Describes how this interceptor would act on a `message` on `key_expr`, without
+    /// side effects. `None` if the message would go through untouched.
+    fn explain(&self, _key_expr: &KeyExpr<'_>, _message: ExplainedMessage) -> Option<String> {
+        None
+    }
+
+    /// Called once the chain of this interceptor is attached to its transport, with the handle
+    /// allowing to [`Resume`] the routing of the messages it held (e.g. delayed or downsampled).
+    fn set_resume(&self, _resume: Resume) {}
 }
 
-pub(crate) type Interceptor = Box<dyn InterceptorTrait + Send + Sync>;
-pub(crate) type IngressInterceptor = Interceptor;
-pub(crate) type EgressInterceptor = Interceptor;
+/// The messages whose handling can be explained, see [`InterceptorTrait::explain`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExplainedMessage {
+    Put,
+    Delete,
+    Query,
+}
+
+pub type Interceptor = Box<dyn InterceptorTrait + Send + Sync>;
+pub type IngressInterceptor = Interceptor;
+pub type EgressInterceptor = Interceptor;
 
-pub(crate) trait InterceptorFactoryTrait {
+/// A factory of [`InterceptorTrait`] instances, called each time a new transport is opened.
+///
+/// The transport given to the factory identifies the remote (zid, whatami, links,
+/// authentication ids) for the whole lifetime of the returned interceptors.
+pub trait InterceptorFactoryTrait {
     fn new_transport_unicast(
         &self,
         transport: &TransportUnicast,
     ) -> (Option<IngressInterceptor>, Option<EgressInterceptor>);
-    fn new_transport_multicast(&self, transport: &TransportMulticast) -> Option<EgressInterceptor>;
-    fn new_peer_multicast(&self, transport: &TransportMulticast) -> Option<IngressInterceptor>;
+    fn new_transport_multicast(
+        &self,
+        _transport: &TransportMulticast,
+    ) -> Option<EgressInterceptor> {
+        None
+    }
+    fn new_peer_multicast(&self, _transport: &TransportMulticast) -> Option<IngressInterceptor> {
+        None
+    }
+    /// Metrics exposed by this factory and its interceptors in the OpenMetrics text format,
+    /// appended to the `@/<zid>/<whatami>/metrics` admin space key.
+    fn openmetrics_text(&self) -> Option<String> {
+        None
+    }
 }
 
-pub(crate) type InterceptorFactory = Box<dyn InterceptorFactoryTrait + Send + Sync>;
+pub type InterceptorFactory = Box<dyn InterceptorFactoryTrait + Send + Sync>;
 
-pub(crate) fn interceptor_factories(config: &Config) -> ZResult<Vec<InterceptorFactory>> {
+/// The interceptor factories of the config, `acl` being the access control enforcer built from it.
+pub(crate) fn interceptor_factories(
+    config: &Config,
+    acl: Option<AclEnforcer>,
+) -> ZResult<Vec<InterceptorFactory>> {
     let mut res: Vec<InterceptorFactory> = vec![];
     // Uncomment to log the interceptors initialisation
     // res.push(Box::new(LoggerInterceptor {}));
+    let (namespace_ingress, namespace_egress) =
+        namespace_interceptor_factories(config.namespaces())?;
+    // Namespaces first on ingress and after access control on egress, so that the other
+    // interceptors apply on the global key space
+    res.extend(namespace_ingress);
+    // Rewriting next, so that the other interceptors apply on the local key space on ingress
+    res.extend(rewrite_interceptor_factories(config.rewrite())?);
     res.extend(downsampling_interceptor_factories(config.downsampling())?);
-    res.extend(acl_interceptor_factories(config.access_control())?);
+    res.extend(qos_overwrite_interceptor_factories(config.qos().network())?);
+    res.extend(quota_interceptor_factories(config.quota())?);
+    // Audit before access control, so that denied messages are recorded with their decision
+    res.extend(audit_interceptor_factories(config.audit(), acl.clone())?);
+    res.extend(acl.map(|acl| Box::new(acl) as InterceptorFactory));
+    res.extend(namespace_egress);
     Ok(res)
 }
 
+/// Whether the messages on `key_expr` are processed by an interceptor filtering them on `key_exprs`:
+/// all of them if `None`, those on key expressions intersecting one of them otherwise.
+pub(crate) fn matches_key_exprs(
+    key_exprs: &Option<Arc<Vec<OwnedKeyExpr>>>,
+    key_expr: &keyexpr,
+) -> bool {
+    key_exprs
+        .as_ref()
+        .map_or(true, |kes| kes.iter().any(|ke| ke.intersects(key_expr)))
+}
+
+/// The entries of the batch carried by a message, with their key expression, `None` for any other
+/// message. The interceptors matching key expressions evaluate the entries of a batch one by one,
+/// as the key expression it is routed on may include keys none of them is published on, see
+/// [`batch_key_expr`].
+pub(crate) fn batch_entries(
+    msg: &NetworkMessage,
+) -> Option<impl Iterator<Item = (&keyexpr, &PushBody)> + '_> {
+    let NetworkBody::Push(Push {
+        payload: PushBody::Batch(batch),
+        ..
+    }) = &msg.body
+    else {
+        return None;
+    };
+    Some(
+        batch.entries.iter().filter_map(|entry| {
+            Some((keyexpr::new(entry.key_expr.as_str()).ok()?, &entry.payload))
+        }),
+    )
+}
+
+fn attachment_len<const ID: u8>(a: &Option<AttachmentType<ID>>) -> usize {
+    a.as_ref().map_or(0, |a| a.buffer.len())
+}
+
+/// Size of the data carried by a push body (payload and attachment), summed over the entries of
+/// a batch.
+pub(crate) fn push_size(body: &PushBody) -> usize {
+    match body {
+        PushBody::Put(put) => put.payload.len() + attachment_len(&put.ext_attachment),
+        PushBody::Del(del) => attachment_len(&del.ext_attachment),
+        PushBody::Batch(batch) => batch.entries.iter().map(|e| push_size(&e.payload)).sum(),
+    }
+}
+
+/// Size of the data carried by a message (payload and attachment), `None` for non data messages.
+pub(crate) fn payload_size(msg: &NetworkMessage) -> Option<usize> {
+    match &msg.body {
+        NetworkBody::Push(push) => Some(push_size(&push.payload)),
+        NetworkBody::Request(request) => match &request.payload {
+            RequestBody::Query(query) => Some(
+                query.ext_body.as_ref().map_or(0, |b| b.payload.len())
+                    + attachment_len(&query.ext_attachment),
+            ),
+        },
+        NetworkBody::Response(response) => match &response.payload {
+            ResponseBody::Reply(reply) => match &reply.payload {
+                PushBody::Batch(_) => None,
+                payload => Some(push_size(payload)),
+            },
+            ResponseBody::Err(err) => Some(err.payload.len()),
+        },
+        _ => None,
+    }
+}
+
+/// Maps the key expressions of the entries of the batch carried by a message with `map`, dropping
+/// the entries it returns `None` for, and routes the message on the key expression including them
+/// all, see [`batch_key_expr`]. Returns `None` if no entry is left.
+pub(crate) fn map_batch_entries(
+    mut ctx: RoutingContext<NetworkMessage>,
+    map: impl Fn(&keyexpr) -> Option<OwnedKeyExpr>,
+) -> Option<RoutingContext<NetworkMessage>> {
+    if !is_batch(ctx.msg()) {
+        return Some(ctx);
+    }
+    let NetworkBody::Push(Push {
+        payload: PushBody::Batch(batch),
+        ..
+    }) = &mut ctx.msg_mut().body
+    else {
+        return Some(ctx);
+    };
+    batch.entries.retain_mut(|entry| {
+        match keyexpr::new(entry.key_expr.as_str()).ok().and_then(&map) {
+            Some(key_expr) => {
+                entry.key_expr = key_expr.to_string();
+                true
+            }
+            None => false,
+        }
+    });
+    if batch.entries.is_empty() {
+        return None;
+    }
+    let key_expr = batch_key_expr(batch.entries.iter().map(|e| e.key_expr.as_str()));
+    ctx.set_key_expr(&key_expr);
+    Some(ctx)
+}
+
+/// Where the messages going through an [`InterceptorsChain`] are sent.
+pub(crate) enum ChainSink {
+    /// Routed as received from the face
+    Ingress(WeakFace),
+    /// Scheduled on the transport of the face
+    Egress(TransportUnicast, WeakFace),
+    /// Scheduled on the multicast transport
+    EgressMulticast(TransportMulticast),
+}
+
 pub(crate) struct InterceptorsChain {
     pub(crate) interceptors: Vec<Interceptor>,
+    sink: OnceLock<ChainSink>,
 }
 
 impl InterceptorsChain {
     #[allow(dead_code)]
     pub(crate) fn empty() -> Self {
-        Self {
-            interceptors: vec![],
+        Self::from(vec![])
+    }
+
+    /// Attaches the chain to the destination of its messages, and hands their [`Resume`]
+    /// handle to its interceptors.
+    pub(crate) fn bind(self: &Arc<Self>, sink: ChainSink) {
+        if self.sink.set(sink).is_ok() {
+            for (idx, interceptor) in self.interceptors.iter().enumerate() {
+                interceptor.set_resume(Resume {
+                    chain: Arc::downgrade(self),
+                    idx,
+                });
+            }
         }
     }
+
+    /// Runs the interceptors of the chain starting from the `start`th one.
+    fn intercept_from(
+        &self,
+        start: usize,
+        mut ctx: RoutingContext<NetworkMessage>,
+        caches: Option<&Box<dyn Any + Send + Sync>>,
+    ) -> Option<RoutingContext<NetworkMessage>> {
+        let mut caches =
+            caches.and_then(|i| i.downcast_ref::<Vec<Option<Box<dyn Any + Send + Sync>>>>());
+        for (idx, interceptor) in self.interceptors.iter().enumerate().skip(start) {
+            let cache = caches
+                .and_then(|caches| caches.get(idx).map(|k| k.as_ref()))
+                .flatten();
+            match interceptor.intercept(ctx, cache) {
+                Some(newctx) => {
+                    ctx = newctx;
+                    // The caches were computed for the original message
+                    if ctx.modified {
+                        caches = None;
+                    }
+                }
+                None => {
+                    tracing::trace!("Msg intercepted!");
+                    return None;
+                }
+            }
+        }
+        Some(ctx)
+    }
+}
+
+/// The handle allowing an interceptor to resume the routing of the messages it held
+/// (see [`InterceptorTrait::set_resume`]).
+///
+/// Resumed messages go through the interceptors following this one in its chain,
+/// before being routed (ingress) or sent on the transport (egress).
+#[derive(Clone)]
+pub struct Resume {
+    chain: Weak<InterceptorsChain>,
+    idx: usize,
+}
+
+impl Resume {
+    pub fn send(&self, msg: NetworkMessage) {
+        let Some(chain) = self.chain.upgrade() else {
+            return;
+        };
+        match chain.sink.get() {
+            Some(ChainSink::Ingress(face)) => {
+                let Some(face) = face.upgrade() else {
+                    return;
+                };
+                let ctx = RoutingContext::new_in(msg, face.clone());
+                let Some(ctx) = chain.intercept_from(self.idx + 1, ctx, None) else {
+                    return;
+                };
+                let msg = ctx.msg;
+                match msg.body {
+                    NetworkBody::Push(m) => face.send_push(m, msg.reliability),
+                    NetworkBody::Declare(m) => face.send_declare(m),
+                    NetworkBody::Interest(m) => face.send_interest(m),
+                    NetworkBody::Request(m) => face.send_request(m),
+                    NetworkBody::Response(m) => face.send_response(m),
+                    NetworkBody::ResponseFinal(m) => face.send_response_final(m),
+                    NetworkBody::OAM(_) => tracing::debug!("Resumed OAM message dropped"),
+                }
+            }
+            Some(ChainSink::Egress(transport, face)) => {
+                let ctx = match face.upgrade() {
+                    Some(face) => RoutingContext::new_out(msg, face),
+                    None => RoutingContext::new(msg),
+                };
+                if let Some(ctx) = chain.intercept_from(self.idx + 1, ctx, None) {
+                    if let Err(e) = transport.schedule(ctx.msg) {
+                        tracing::debug!("Failed to send resumed message: {}", e);
+                    }
+                }
+            }
+            Some(ChainSink::EgressMulticast(transport)) => {
+                let ctx = RoutingContext::new(msg);
+                if let Some(ctx) = chain.intercept_from(self.idx + 1, ctx, None) {
+                    if let Err(e) = transport.schedule(ctx.msg) {
+                        tracing::debug!("Failed to send resumed message: {}", e);
+                    }
+                }
+            }
+            None => tracing::debug!("Resumed message dropped: chain not attached"),
+        }
+    }
+}
+
+impl InterceptorsChain {
+    /// The explanations of the interceptors of the chain acting on a `message` on `key_expr`.
+    pub(crate) fn explain_all(
+        &self,
+        key_expr: &KeyExpr<'_>,
+        message: ExplainedMessage,
+    ) -> Vec<String> {
+        self.interceptors
+            .iter()
+            .filter_map(|i| i.explain(key_expr, message))
+            .collect()
+    }
 }
 
 impl From<Vec<Interceptor>> for InterceptorsChain {
     fn from(interceptors: Vec<Interceptor>) -> Self {
-        InterceptorsChain { interceptors }
+        InterceptorsChain {
+            interceptors,
+            sink: OnceLock::new(),
+        }
     }
 }
 
@@ -100,34 +420,21 @@ impl InterceptorTrait for InterceptorsChain {
 
     fn intercept<'a>(
         &self,
-        mut ctx: RoutingContext<NetworkMessage>,
+        ctx: RoutingContext<NetworkMessage>,
         caches: Option<&Box<dyn Any + Send + Sync>>,
     ) -> Option<RoutingContext<NetworkMessage>> {
-        let caches =
-            caches.and_then(|i| i.downcast_ref::<Vec<Option<Box<dyn Any + Send + Sync>>>>());
-        for (idx, interceptor) in self.interceptors.iter().enumerate() {
-            let cache = caches
-                .and_then(|caches| caches.get(idx).map(|k| k.as_ref()))
-                .flatten();
-            match interceptor.intercept(ctx, cache) {
-                Some(newctx) => ctx = newctx,
-                None => {
-                    tracing::trace!("Msg intercepted!");
-                    return None;
-                }
-            }
-        }
-        Some(ctx)
+        self.intercept_from(0, ctx, caches)
     }
 }
 
-pub(crate) struct ComputeOnMiss<T: InterceptorTrait> {
+/// Wraps an interceptor so that its key expression cache is computed on the fly
+/// when the routing could not provide one (e.g. for non-declared key expressions).
+pub struct ComputeOnMiss<T: InterceptorTrait> {
     interceptor: T,
 }
 
 impl<T: InterceptorTrait> ComputeOnMiss<T> {
-    #[allow(dead_code)]
-    pub(crate) fn new(interceptor: T) -> Self {
+    pub fn new(interceptor: T) -> Self {
         Self { interceptor }
     }
 }
@@ -157,6 +464,16 @@ impl<T: InterceptorTrait> InterceptorTrait for ComputeOnMiss<T> {
             self.interceptor.intercept(ctx, cache)
         }
     }
+
+    #[inline]
+    fn explain(&self, key_expr: &KeyExpr<'_>, message: ExplainedMessage) -> Option<String> {
+        self.interceptor.explain(key_expr, message)
+    }
+
+    #[inline]
+    fn set_resume(&self, resume: Resume) {
+        self.interceptor.set_resume(resume)
+    }
 }
 
 #[allow(dead_code)]
@@ -181,7 +498,7 @@ impl InterceptorTrait for IngressMsgLogger {
             ctx.inface()
                 .map(|f| f.to_string())
                 .unwrap_or("None".to_string()),
-            ctx.msg,
+            ctx.msg(),
             expr,
         );
         Some(ctx)
@@ -209,7 +526,7 @@ impl InterceptorTrait for EgressMsgLogger {
             ctx.outface()
                 .map(|f| f.to_string())
                 .unwrap_or("None".to_string()),
-            ctx.msg,
+            ctx.msg(),
             expr
         );
         Some(ctx)
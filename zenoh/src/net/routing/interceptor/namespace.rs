@@ -0,0 +1,236 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! ⚠️ WARNING ⚠️
+//!
+//! This module is intended for Zenoh's internal use.
+//!
+//! [Click here for Zenoh's documentation](https://docs.rs/zenoh/latest/zenoh)
+
+use std::sync::Arc;
+
+use zenoh_config::{InterceptorFlow, InterceptorSubjectConf, NamespaceConf};
+use zenoh_keyexpr::{keyexpr, OwnedKeyExpr};
+use zenoh_protocol::network::{Declare, DeclareBody, NetworkBody};
+use zenoh_result::{bail, ZResult};
+
+use super::subject::TransportSubject;
+use crate::net::routing::interceptor::*;
+
+/// The namespace interceptor factories, one for each flow.
+///
+/// The ingress one should come first in the chain, so that the other interceptors apply on the
+/// prefixed key-expressions, and the egress one after the interceptors acting on key-expressions.
+pub(crate) fn namespace_interceptor_factories(
+    config: &[NamespaceConf],
+) -> ZResult<(Option<InterceptorFactory>, Option<InterceptorFactory>)> {
+    if config.is_empty() {
+        return Ok((None, None));
+    }
+    let mut namespaces = vec![];
+    for (idx, conf) in config.iter().enumerate() {
+        let id = conf.id.clone().unwrap_or_else(|| idx.to_string());
+        if conf.prefix.is_wild() {
+            bail!(
+                "Invalid namespace '{}' prefix '{}': only wildcard-free prefixes are supported",
+                id,
+                conf.prefix
+            );
+        }
+        if conf.subjects.is_empty() {
+            bail!("Namespace '{}' should define at least one subject", id);
+        }
+        tracing::debug!("New namespace enabled: id={}, prefix={}", id, conf.prefix);
+        namespaces.push(Namespace {
+            id,
+            prefix: conf.prefix.clone(),
+            subjects: conf.subjects.clone(),
+        });
+    }
+    let namespaces = Arc::new(namespaces);
+    Ok((
+        Some(Box::new(NamespaceInterceptorFactory {
+            namespaces: namespaces.clone(),
+            flow: InterceptorFlow::Ingress,
+        })),
+        Some(Box::new(NamespaceInterceptorFactory {
+            namespaces,
+            flow: InterceptorFlow::Egress,
+        })),
+    ))
+}
+
+struct Namespace {
+    id: String,
+    prefix: OwnedKeyExpr,
+    subjects: Vec<InterceptorSubjectConf>,
+}
+
+impl Namespace {
+    /// Maps a key expression of the namespace to the global key space.
+    fn prefix(&self, key_expr: &keyexpr) -> Option<OwnedKeyExpr> {
+        self.prefix.join(key_expr).ok()
+    }
+
+    /// Maps a key expression of the global key space to the namespace,
+    /// `None` if it is not included in the namespace.
+    fn strip(&self, key_expr: &keyexpr) -> Option<OwnedKeyExpr> {
+        match key_expr.as_str().strip_prefix(self.prefix.as_str()) {
+            Some(rest) => OwnedKeyExpr::new(rest.strip_prefix('/')?).ok(),
+            // Key expressions including the whole namespace (e.g. `**`) include the whole key space
+            // of the subject, the ones only intersecting it are not forwarded
+            None => {
+                let namespace = self.prefix.join("**").ok()?;
+                key_expr
+                    .includes(&namespace)
+                    .then(|| OwnedKeyExpr::new("**").unwrap())
+            }
+        }
+    }
+}
+
+pub struct NamespaceInterceptorFactory {
+    namespaces: Arc<Vec<Namespace>>,
+    flow: InterceptorFlow,
+}
+
+impl InterceptorFactoryTrait for NamespaceInterceptorFactory {
+    fn new_transport_unicast(
+        &self,
+        transport: &TransportUnicast,
+    ) -> (Option<IngressInterceptor>, Option<EgressInterceptor>) {
+        let subject = match TransportSubject::new(transport) {
+            Ok(subject) => subject,
+            Err(err) => {
+                tracing::error!("Couldn't get Transport properties: {}", err);
+                return (None, None);
+            }
+        };
+        let Some(idx) = self
+            .namespaces
+            .iter()
+            .position(|ns| ns.subjects.iter().any(|s| subject.matches(s)))
+        else {
+            return (None, None);
+        };
+        tracing::debug!(
+            "Transport {} confined to namespace '{}'",
+            subject.zid,
+            self.namespaces[idx].id
+        );
+
+        let interceptor: Interceptor = Box::new(ComputeOnMiss::new(NamespaceInterceptor {
+            namespaces: self.namespaces.clone(),
+            idx,
+            flow: self.flow,
+        }));
+        match self.flow {
+            InterceptorFlow::Ingress => (Some(interceptor), None),
+            InterceptorFlow::Egress => (None, Some(interceptor)),
+        }
+    }
+}
+
+pub(crate) struct NamespaceInterceptor {
+    namespaces: Arc<Vec<Namespace>>,
+    idx: usize,
+    flow: InterceptorFlow,
+}
+
+impl NamespaceInterceptor {
+    fn namespace(&self) -> &Namespace {
+        &self.namespaces[self.idx]
+    }
+
+    fn map(&self, key_expr: &keyexpr) -> Option<OwnedKeyExpr> {
+        match self.flow {
+            InterceptorFlow::Ingress => self.namespace().prefix(key_expr),
+            InterceptorFlow::Egress => self.namespace().strip(key_expr),
+        }
+    }
+}
+
+impl InterceptorTrait for NamespaceInterceptor {
+    fn compute_keyexpr_cache(&self, key_expr: &KeyExpr<'_>) -> Option<Box<dyn Any + Send + Sync>> {
+        Some(Box::new(self.map(key_expr)))
+    }
+
+    fn explain(&self, key_expr: &KeyExpr<'_>, _message: ExplainedMessage) -> Option<String> {
+        let namespace = self.namespace();
+        Some(match self.map(key_expr) {
+            Some(key_expr) => format!("namespace '{}': as {}", namespace.id, key_expr),
+            None => format!(
+                "namespace '{}': outside the namespace, dropped",
+                namespace.id
+            ),
+        })
+    }
+
+    fn intercept(
+        &self,
+        mut ctx: RoutingContext<NetworkMessage>,
+        cache: Option<&Box<dyn Any + Send + Sync>>,
+    ) -> Option<RoutingContext<NetworkMessage>> {
+        if let NetworkBody::Declare(Declare {
+            body: DeclareBody::DeclareKeyExpr(_),
+            ..
+        }) = &ctx.msg().body
+        {
+            // Received key expression declarations are left untouched, the messages using them are
+            // prefixed. The sent ones are stripped or dropped like any other message.
+            if self.flow == InterceptorFlow::Ingress {
+                return Some(ctx);
+            }
+        }
+        // The entries of the batches are mapped one by one, the ones outside the namespace dropped
+        if is_batch(ctx.msg()) {
+            return map_batch_entries(ctx, |key_expr| self.map(key_expr));
+        }
+        match cache.and_then(|c| c.downcast_ref::<Option<OwnedKeyExpr>>()) {
+            Some(Some(key_expr)) => {
+                tracing::trace!(
+                    "Namespace '{}': {:?} mapped to {}",
+                    self.namespace().id,
+                    ctx.full_expr(),
+                    key_expr
+                );
+                ctx.set_key_expr(key_expr);
+                Some(ctx)
+            }
+            Some(None) => {
+                tracing::trace!(
+                    "Namespace '{}': {:?} dropped",
+                    self.namespace().id,
+                    ctx.full_expr()
+                );
+                None
+            }
+            // Only the messages that do not carry a key expression (or undeclarations only
+            // referring to a declaration id) can go through without being mapped
+            None => match &ctx.msg().body {
+                NetworkBody::Push(_) | NetworkBody::Request(_) | NetworkBody::Response(_) => None,
+                NetworkBody::Declare(Declare {
+                    body:
+                        DeclareBody::DeclareKeyExpr(_)
+                        | DeclareBody::DeclareSubscriber(_)
+                        | DeclareBody::DeclareQueryable(_)
+                        | DeclareBody::DeclareToken(_),
+                    ..
+                }) => None,
+                NetworkBody::Interest(interest) if interest.wire_expr.is_some() => None,
+                _ => Some(ctx),
+            },
+        }
+    }
+}
@@ -0,0 +1,244 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! ⚠️ WARNING ⚠️
+//!
+//! This module is intended for Zenoh's internal use.
+//!
+//! [Click here for Zenoh's documentation](https://docs.rs/zenoh/latest/zenoh)
+
+use std::sync::Arc;
+
+use zenoh_config::{
+    qos::{QosOverwriteItemConf, QosOverwriteMessage},
+    InterceptorFlow, InterceptorSubjectConf,
+};
+use zenoh_keyexpr::OwnedKeyExpr;
+use zenoh_protocol::{
+    core::{CongestionControl, Priority},
+    network::{ext::QoSType, NetworkBody, Push, Request},
+    zenoh::{PushBody, RequestBody},
+};
+use zenoh_result::{bail, ZResult};
+
+use super::subject::TransportSubject;
+use crate::{api::publisher::Priority as ApiPriority, net::routing::interceptor::*};
+
+pub(crate) fn qos_overwrite_interceptor_factories(
+    config: &[QosOverwriteItemConf],
+) -> ZResult<Vec<InterceptorFactory>> {
+    let mut res: Vec<InterceptorFactory> = vec![];
+
+    for (idx, item) in config.iter().enumerate() {
+        res.push(Box::new(QosOverwriteInterceptorFactory::new(
+            item.clone(),
+            idx,
+        )?));
+    }
+
+    Ok(res)
+}
+
+/// The QoS options written into the matching messages, `None` leaving an option untouched.
+#[derive(Clone, Copy)]
+struct QosOverwrites {
+    priority: Option<Priority>,
+    congestion_control: Option<CongestionControl>,
+    express: Option<bool>,
+}
+
+pub struct QosOverwriteInterceptorFactory {
+    id: String,
+    subjects: Option<Vec<InterceptorSubjectConf>>,
+    messages: Option<Arc<Vec<QosOverwriteMessage>>>,
+    key_exprs: Option<Arc<Vec<OwnedKeyExpr>>>,
+    overwrites: QosOverwrites,
+    flow: InterceptorFlow,
+}
+
+impl QosOverwriteInterceptorFactory {
+    pub fn new(conf: QosOverwriteItemConf, idx: usize) -> ZResult<Self> {
+        let id = conf.id.unwrap_or_else(|| idx.to_string());
+        let overwrite = conf.overwrite;
+        if overwrite.priority.is_none()
+            && overwrite.congestion_control.is_none()
+            && overwrite.express.is_none()
+        {
+            bail!(
+                "QoS overwrite '{}' should define at least one of priority, congestion_control or express",
+                id
+            );
+        }
+        Ok(Self {
+            id,
+            subjects: conf.subjects,
+            messages: conf.messages.map(Arc::new),
+            key_exprs: conf.key_exprs.map(Arc::new),
+            overwrites: QosOverwrites {
+                priority: overwrite.priority.map(|p| ApiPriority::from(p).into()),
+                congestion_control: overwrite.congestion_control.map(Into::into),
+                express: overwrite.express,
+            },
+            flow: conf.flow,
+        })
+    }
+
+    fn new_interceptor(&self) -> Interceptor {
+        Box::new(ComputeOnMiss::new(QosOverwriteInterceptor {
+            messages: self.messages.clone(),
+            key_exprs: self.key_exprs.clone(),
+            overwrites: self.overwrites,
+        }))
+    }
+}
+
+impl InterceptorFactoryTrait for QosOverwriteInterceptorFactory {
+    fn new_transport_unicast(
+        &self,
+        transport: &TransportUnicast,
+    ) -> (Option<IngressInterceptor>, Option<EgressInterceptor>) {
+        let subject = match TransportSubject::new(transport) {
+            Ok(subject) => subject,
+            Err(err) => {
+                tracing::error!("Couldn't get Transport properties: {}", err);
+                return (None, None);
+            }
+        };
+        if !subject.matches_any(&self.subjects) {
+            return (None, None);
+        }
+        tracing::debug!(
+            "QoS overwrite '{}' enabled for transport {}",
+            self.id,
+            subject.zid
+        );
+
+        match self.flow {
+            InterceptorFlow::Ingress => (Some(self.new_interceptor()), None),
+            InterceptorFlow::Egress => (None, Some(self.new_interceptor())),
+        }
+    }
+}
+
+pub(crate) struct QosOverwriteInterceptor {
+    messages: Option<Arc<Vec<QosOverwriteMessage>>>,
+    key_exprs: Option<Arc<Vec<OwnedKeyExpr>>>,
+    overwrites: QosOverwrites,
+}
+
+impl QosOverwriteInterceptor {
+    fn matches_message(&self, message: &QosOverwriteMessage) -> bool {
+        self.messages
+            .as_ref()
+            .map_or(true, |messages| messages.contains(message))
+    }
+
+    fn overwrite<const ID: u8>(&self, qos: &mut QoSType<ID>) {
+        if let Some(priority) = self.overwrites.priority {
+            qos.set_priority(priority);
+        }
+        if let Some(congestion_control) = self.overwrites.congestion_control {
+            qos.set_congestion_control(congestion_control);
+        }
+        if let Some(express) = self.overwrites.express {
+            qos.set_is_express(express);
+        }
+    }
+}
+
+impl InterceptorTrait for QosOverwriteInterceptor {
+    fn compute_keyexpr_cache(&self, key_expr: &KeyExpr<'_>) -> Option<Box<dyn Any + Send + Sync>> {
+        Some(Box::new(matches_key_exprs(&self.key_exprs, key_expr)))
+    }
+
+    fn explain(&self, key_expr: &KeyExpr<'_>, message: ExplainedMessage) -> Option<String> {
+        let message = match message {
+            ExplainedMessage::Put => QosOverwriteMessage::Put,
+            ExplainedMessage::Delete => QosOverwriteMessage::Delete,
+            ExplainedMessage::Query => QosOverwriteMessage::Query,
+        };
+        if !self.matches_message(&message) || !matches_key_exprs(&self.key_exprs, key_expr) {
+            return None;
+        }
+        let mut overwrites = vec![];
+        if let Some(priority) = self.overwrites.priority {
+            overwrites.push(format!("priority={priority:?}"));
+        }
+        if let Some(congestion_control) = self.overwrites.congestion_control {
+            overwrites.push(format!("congestion_control={congestion_control:?}"));
+        }
+        if let Some(express) = self.overwrites.express {
+            overwrites.push(format!("express={express}"));
+        }
+        Some(format!("qos_overwrite: {}", overwrites.join(", ")))
+    }
+
+    fn intercept(
+        &self,
+        mut ctx: RoutingContext<NetworkMessage>,
+        cache: Option<&Box<dyn Any + Send + Sync>>,
+    ) -> Option<RoutingContext<NetworkMessage>> {
+        // A batch has a single QoS, overwritten if any of its entries is matched
+        let batch_matched = batch_entries(ctx.msg()).map(|mut entries| {
+            entries.any(|(key_expr, payload)| {
+                let message = match payload {
+                    PushBody::Del(_) => QosOverwriteMessage::Delete,
+                    _ => QosOverwriteMessage::Put,
+                };
+                self.matches_message(&message) && matches_key_exprs(&self.key_exprs, key_expr)
+            })
+        });
+        if let Some(matched) = batch_matched {
+            if let (true, NetworkBody::Push(push)) = (matched, &mut ctx.msg.body) {
+                self.overwrite(&mut push.ext_qos);
+            }
+            return Some(ctx);
+        }
+
+        let message = match &ctx.msg().body {
+            NetworkBody::Push(Push {
+                payload: PushBody::Put(_),
+                ..
+            }) => QosOverwriteMessage::Put,
+            NetworkBody::Push(Push {
+                payload: PushBody::Del(_),
+                ..
+            }) => QosOverwriteMessage::Delete,
+            NetworkBody::Request(Request {
+                payload: RequestBody::Query(_),
+                ..
+            }) => QosOverwriteMessage::Query,
+            NetworkBody::Response(_) => QosOverwriteMessage::Reply,
+            _ => return Some(ctx),
+        };
+        if !self.matches_message(&message) {
+            return Some(ctx);
+        }
+        let overwritten = match cache.and_then(|c| c.downcast_ref::<bool>()) {
+            Some(overwritten) => *overwritten,
+            None => self.key_exprs.is_none(),
+        };
+        if !overwritten {
+            return Some(ctx);
+        }
+
+        match &mut ctx.msg.body {
+            NetworkBody::Push(push) => self.overwrite(&mut push.ext_qos),
+            NetworkBody::Request(request) => self.overwrite(&mut request.ext_qos),
+            NetworkBody::Response(response) => self.overwrite(&mut response.ext_qos),
+            _ => {}
+        }
+        Some(ctx)
+    }
+}
@@ -0,0 +1,456 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! ⚠️ WARNING ⚠️
+//!
+//! This module is intended for Zenoh's internal use.
+//!
+//! [Click here for Zenoh's documentation](https://docs.rs/zenoh/latest/zenoh)
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, OnceLock, Weak,
+    },
+    time::{Duration, Instant},
+};
+
+use zenoh_config::{InterceptorFlow, InterceptorSubjectConf, QuotaAction, QuotaItemConf};
+use zenoh_core::zlock;
+use zenoh_keyexpr::OwnedKeyExpr;
+use zenoh_result::{bail, ZResult};
+
+use super::subject::TransportSubject;
+use crate::net::routing::interceptor::*;
+
+const DEFAULT_BURST: f64 = 1.0;
+const DEFAULT_MAX_DELAY_MS: u64 = 1000;
+/// Maximum number of messages delayed at once per transport, the exceeding ones are dropped.
+const DELAY_QUEUE_SIZE: usize = 1024;
+
+pub(crate) fn quota_interceptor_factories(
+    config: &[QuotaItemConf],
+) -> ZResult<Vec<InterceptorFactory>> {
+    let mut res: Vec<InterceptorFactory> = vec![];
+
+    for (idx, quota) in config.iter().enumerate() {
+        res.push(Box::new(QuotaInterceptorFactory::new(quota.clone(), idx)?));
+    }
+
+    Ok(res)
+}
+
+/// Number of messages dropped and delayed because of a quota.
+#[derive(Default)]
+struct QuotaCounters {
+    dropped: AtomicUsize,
+    delayed: AtomicUsize,
+}
+
+/// What shares the token buckets of a quota: the transports matching the same subject,
+/// or a single remote (zid or multicast group) for the quotas without subjects.
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum QuotaOwner {
+    Subject(usize),
+    Remote(String),
+}
+
+pub struct QuotaInterceptorFactory {
+    id: String,
+    subjects: Option<Vec<InterceptorSubjectConf>>,
+    key_exprs: Option<Arc<Vec<OwnedKeyExpr>>>,
+    bytes_per_sec: Option<f64>,
+    messages_per_sec: Option<f64>,
+    burst: f64,
+    action: QuotaAction,
+    max_delay: Duration,
+    flow: InterceptorFlow,
+    /// The counters of the open transports per remote (zid or multicast group),
+    /// held by their interceptors
+    counters: Mutex<BTreeMap<String, Weak<QuotaCounters>>>,
+    /// The token buckets per owner, kept after the transports close so that reconnecting
+    /// doesn't reset the quota
+    buckets: Mutex<HashMap<QuotaOwner, Arc<Mutex<QuotaBuckets>>>>,
+}
+
+impl QuotaInterceptorFactory {
+    pub fn new(conf: QuotaItemConf, idx: usize) -> ZResult<Self> {
+        let id = conf.id.unwrap_or_else(|| idx.to_string());
+        if conf.bytes_per_sec.is_none() && conf.messages_per_sec.is_none() {
+            bail!(
+                "Quota '{}' should define at least one of bytes_per_sec or messages_per_sec",
+                id
+            );
+        }
+        for (name, value) in [
+            ("bytes_per_sec", conf.bytes_per_sec),
+            ("messages_per_sec", conf.messages_per_sec),
+            ("burst", conf.burst),
+        ] {
+            if let Some(value) = value {
+                if !(value.is_finite() && value > 0.0) {
+                    bail!(
+                        "Quota '{}' has an invalid {}: {} (should be a positive number)",
+                        id,
+                        name,
+                        value
+                    );
+                }
+            }
+        }
+        Ok(Self {
+            id,
+            subjects: conf.subjects,
+            key_exprs: conf.key_exprs.map(Arc::new),
+            bytes_per_sec: conf.bytes_per_sec,
+            messages_per_sec: conf.messages_per_sec,
+            burst: conf.burst.unwrap_or(DEFAULT_BURST),
+            action: conf.action,
+            max_delay: Duration::from_millis(conf.max_delay_ms.unwrap_or(DEFAULT_MAX_DELAY_MS)),
+            flow: conf.flow,
+            counters: Mutex::new(BTreeMap::new()),
+            buckets: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn new_interceptor(&self, owner: QuotaOwner, remote: String) -> Interceptor {
+        let counters = {
+            let mut counters = zlock!(self.counters);
+            counters.retain(|_, c| c.strong_count() > 0);
+            match counters.get(&remote).and_then(Weak::upgrade) {
+                Some(c) => c,
+                None => {
+                    let c = Arc::new(QuotaCounters::default());
+                    counters.insert(remote, Arc::downgrade(&c));
+                    c
+                }
+            }
+        };
+        let buckets = {
+            let now = Instant::now();
+            let mut buckets = zlock!(self.buckets);
+            // Unused buckets that refilled are equivalent to new ones
+            buckets.retain(|_, b| Arc::strong_count(b) > 1 || !zlock!(b).is_full(now));
+            buckets
+                .entry(owner)
+                .or_insert_with(|| {
+                    Arc::new(Mutex::new(QuotaBuckets {
+                        bytes: self
+                            .bytes_per_sec
+                            .map(|rate| TokenBucket::new(rate, self.burst, now)),
+                        messages: self
+                            .messages_per_sec
+                            .map(|rate| TokenBucket::new(rate, self.burst, now)),
+                    }))
+                })
+                .clone()
+        };
+        Box::new(ComputeOnMiss::new(QuotaInterceptor {
+            key_exprs: self.key_exprs.clone(),
+            buckets,
+            action: self.action,
+            max_delay: self.max_delay,
+            counters,
+            delayed: OnceLock::new(),
+            delayed_count: Arc::new(AtomicUsize::new(0)),
+        }))
+    }
+}
+
+impl QuotaInterceptorFactory {
+    /// The multicast group the quota applies to in the given flow, if any. The quotas with
+    /// subjects only apply to unicast transports, whose remote can be authenticated.
+    fn multicast_group(
+        &self,
+        transport: &TransportMulticast,
+        flow: InterceptorFlow,
+    ) -> Option<String> {
+        if self.subjects.is_some() || self.flow != flow {
+            return None;
+        }
+        match transport.get_link() {
+            Ok(link) => {
+                tracing::debug!("Quota '{}' enabled for multicast {}", self.id, link.dst);
+                Some(link.dst.to_string())
+            }
+            Err(err) => {
+                tracing::error!("Couldn't get Transport link: {}", err);
+                None
+            }
+        }
+    }
+}
+
+impl InterceptorFactoryTrait for QuotaInterceptorFactory {
+    fn new_transport_unicast(
+        &self,
+        transport: &TransportUnicast,
+    ) -> (Option<IngressInterceptor>, Option<EgressInterceptor>) {
+        let subject = match TransportSubject::new(transport) {
+            Ok(subject) => subject,
+            Err(err) => {
+                tracing::error!("Couldn't get Transport properties: {}", err);
+                return (None, None);
+            }
+        };
+        let zid = subject.zid.to_string();
+        let owner = match &self.subjects {
+            Some(subjects) => match subjects.iter().position(|s| subject.matches(s)) {
+                Some(idx) => QuotaOwner::Subject(idx),
+                None => return (None, None),
+            },
+            None => QuotaOwner::Remote(zid.clone()),
+        };
+        tracing::debug!("Quota '{}' enabled for transport {}", self.id, zid);
+
+        match self.flow {
+            InterceptorFlow::Ingress => (Some(self.new_interceptor(owner, zid)), None),
+            InterceptorFlow::Egress => (None, Some(self.new_interceptor(owner, zid))),
+        }
+    }
+
+    fn new_transport_multicast(&self, transport: &TransportMulticast) -> Option<EgressInterceptor> {
+        let group = self.multicast_group(transport, InterceptorFlow::Egress)?;
+        Some(self.new_interceptor(QuotaOwner::Remote(group.clone()), group))
+    }
+
+    fn new_peer_multicast(&self, transport: &TransportMulticast) -> Option<IngressInterceptor> {
+        let group = self.multicast_group(transport, InterceptorFlow::Ingress)?;
+        Some(self.new_interceptor(QuotaOwner::Remote(group.clone()), group))
+    }
+
+    fn openmetrics_text(&self) -> Option<String> {
+        let counters = zlock!(self.counters)
+            .iter()
+            .filter_map(|(remote, c)| Some((remote.clone(), c.upgrade()?)))
+            .collect::<BTreeMap<_, _>>();
+        if counters.is_empty() {
+            return None;
+        }
+        let mut s = String::new();
+        write_counter(&mut s, &self.id, "dropped", &counters, |c| &c.dropped);
+        write_counter(&mut s, &self.id, "delayed", &counters, |c| &c.delayed);
+        Some(s)
+    }
+}
+
+fn write_counter(
+    s: &mut String,
+    quota: &str,
+    action: &str,
+    counters: &BTreeMap<String, Arc<QuotaCounters>>,
+    counter: impl Fn(&QuotaCounters) -> &AtomicUsize,
+) {
+    let _ = writeln!(
+        s,
+        "# HELP zenoh_quota_{action} Messages {action} because of a quota."
+    );
+    let _ = writeln!(s, "# TYPE zenoh_quota_{action} counter");
+    for (remote, c) in counters.iter() {
+        let _ = writeln!(
+            s,
+            "zenoh_quota_{action}{{quota=\"{quota}\",remote=\"{remote}\"}} {}",
+            counter(c).load(Ordering::Relaxed)
+        );
+    }
+}
+
+struct TokenBucket {
+    rate: f64,
+    capacity: f64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: f64, burst: f64, now: Instant) -> Self {
+        let capacity = rate * burst;
+        Self {
+            rate,
+            capacity,
+            tokens: capacity,
+            last: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.last = now;
+    }
+
+    /// Time to wait before `amount` tokens are available.
+    /// Amounts larger than the bucket capacity only require a full bucket.
+    fn wait_time(&self, amount: f64) -> Duration {
+        let missing = amount.min(self.capacity) - self.tokens;
+        if missing <= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(missing / self.rate)
+        }
+    }
+
+    /// Consumes `amount` tokens, the bucket may go into debt for delayed messages.
+    fn consume(&mut self, amount: f64) {
+        self.tokens -= amount;
+    }
+}
+
+struct QuotaBuckets {
+    bytes: Option<TokenBucket>,
+    messages: Option<TokenBucket>,
+}
+
+impl QuotaBuckets {
+    fn is_full(&mut self, now: Instant) -> bool {
+        [self.bytes.as_mut(), self.messages.as_mut()]
+            .into_iter()
+            .flatten()
+            .all(|bucket| {
+                bucket.refill(now);
+                bucket.tokens >= bucket.capacity
+            })
+    }
+}
+
+pub(crate) struct QuotaInterceptor {
+    key_exprs: Option<Arc<Vec<OwnedKeyExpr>>>,
+    /// The token buckets, shared with the other transports of the same owner
+    buckets: Arc<Mutex<QuotaBuckets>>,
+    action: QuotaAction,
+    max_delay: Duration,
+    counters: Arc<QuotaCounters>,
+    /// The queue of the delayed messages, resumed in order by a task once their delay elapsed
+    delayed: OnceLock<flume::Sender<(tokio::time::Instant, NetworkMessage)>>,
+    delayed_count: Arc<AtomicUsize>,
+}
+
+impl InterceptorTrait for QuotaInterceptor {
+    fn compute_keyexpr_cache(&self, key_expr: &KeyExpr<'_>) -> Option<Box<dyn Any + Send + Sync>> {
+        Some(Box::new(matches_key_exprs(&self.key_exprs, key_expr)))
+    }
+
+    fn explain(&self, key_expr: &KeyExpr<'_>, _message: ExplainedMessage) -> Option<String> {
+        matches_key_exprs(&self.key_exprs, key_expr).then(|| {
+            format!(
+                "quota: messages exceeding the quota are {}",
+                match self.action {
+                    QuotaAction::Drop => "dropped",
+                    QuotaAction::Delay => "delayed",
+                }
+            )
+        })
+    }
+
+    fn intercept(
+        &self,
+        ctx: RoutingContext<NetworkMessage>,
+        cache: Option<&Box<dyn Any + Send + Sync>>,
+    ) -> Option<RoutingContext<NetworkMessage>> {
+        let accounted = match cache.and_then(|c| c.downcast_ref::<bool>()) {
+            Some(accounted) => *accounted,
+            None => self.key_exprs.is_none(),
+        };
+        // The messages without payload or not accounted by the quota are not limited,
+        // but still go through the queue while messages are delayed
+        let size = match batch_entries(ctx.msg()) {
+            // A batch is limited as a whole, on the size of its entries accounted by the quota
+            Some(entries) => entries
+                .filter(|(key_expr, _)| matches_key_exprs(&self.key_exprs, key_expr))
+                .fold(None, |size, (_, payload)| {
+                    Some(size.unwrap_or(0) + push_size(payload))
+                }),
+            None => payload_size(ctx.msg()).filter(|_| accounted),
+        };
+        let wait = match size {
+            Some(size) => {
+                let buckets = &mut *zlock!(self.buckets);
+                let now = Instant::now();
+                let mut wait = Duration::ZERO;
+                for (bucket, amount) in [
+                    (buckets.bytes.as_mut(), size as f64),
+                    (buckets.messages.as_mut(), 1.0),
+                ] {
+                    if let Some(bucket) = bucket {
+                        bucket.refill(now);
+                        wait = wait.max(bucket.wait_time(amount));
+                    }
+                }
+                let admitted = match self.action {
+                    QuotaAction::Drop => wait.is_zero(),
+                    QuotaAction::Delay => wait <= self.max_delay,
+                };
+                if !admitted {
+                    self.counters.dropped.fetch_add(1, Ordering::Relaxed);
+                    tracing::trace!("Quota exceeded, message dropped: {:?}", ctx.full_expr());
+                    return None;
+                }
+                if let Some(bucket) = buckets.bytes.as_mut() {
+                    bucket.consume(size as f64);
+                }
+                if let Some(bucket) = buckets.messages.as_mut() {
+                    bucket.consume(1.0);
+                }
+                wait
+            }
+            None => Duration::ZERO,
+        };
+
+        // Messages are also queued while previous ones are delayed, to keep their order
+        if wait.is_zero() && self.delayed_count.load(Ordering::Acquire) == 0 {
+            return Some(ctx);
+        }
+        let Some(delayed) = self.delayed.get() else {
+            self.counters.dropped.fetch_add(1, Ordering::Relaxed);
+            tracing::trace!("Quota exceeded, message dropped: {:?}", ctx.full_expr());
+            return None;
+        };
+        tracing::trace!(
+            "Quota exceeded, message delayed by {:?}: {:?}",
+            wait,
+            ctx.full_expr()
+        );
+        self.delayed_count.fetch_add(1, Ordering::AcqRel);
+        // The tokens have been reserved above, so concurrent messages are delayed after this one
+        match delayed.try_send((tokio::time::Instant::now() + wait, ctx.msg)) {
+            Ok(()) => {
+                self.counters.delayed.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(_) => {
+                self.delayed_count.fetch_sub(1, Ordering::AcqRel);
+                self.counters.dropped.fetch_add(1, Ordering::Relaxed);
+                tracing::trace!("Quota delay queue full, message dropped");
+            }
+        }
+        None
+    }
+
+    fn set_resume(&self, resume: Resume) {
+        let (sender, receiver) = flume::bounded(DELAY_QUEUE_SIZE);
+        if self.delayed.set(sender).is_err() {
+            return;
+        }
+        let delayed_count = self.delayed_count.clone();
+        // The task ends with the interceptor, when the sender is dropped
+        zenoh_runtime::ZRuntime::Net.spawn(async move {
+            while let Ok((deadline, msg)) = receiver.recv_async().await {
+                tokio::time::sleep_until(deadline).await;
+                resume.send(msg);
+                delayed_count.fetch_sub(1, Ordering::AcqRel);
+            }
+        });
+    }
+}
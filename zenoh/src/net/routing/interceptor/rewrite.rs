@@ -0,0 +1,245 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! ⚠️ WARNING ⚠️
+//!
+//! This module is intended for Zenoh's internal use.
+//!
+//! [Click here for Zenoh's documentation](https://docs.rs/zenoh/latest/zenoh)
+
+use std::sync::{Arc, Mutex};
+
+use zenoh_config::{InterceptorFlow, InterceptorSubjectConf, RewriteItemConf, RewriteRuleConf};
+use zenoh_core::zlock;
+use zenoh_keyexpr::{
+    keyexpr,
+    keyexpr_tree::{
+        impls::KeyedSetProvider, support::UnknownWildness, IKeyExprTree, IKeyExprTreeMut,
+        IKeyExprTreeNode, KeBoxTree,
+    },
+    OwnedKeyExpr,
+};
+use zenoh_protocol::network::{Declare, DeclareBody, NetworkBody};
+use zenoh_result::{bail, ZResult};
+
+use super::subject::TransportSubject;
+use crate::net::routing::interceptor::*;
+
+pub(crate) fn rewrite_interceptor_factories(
+    config: &[RewriteItemConf],
+) -> ZResult<Vec<InterceptorFactory>> {
+    let mut res: Vec<InterceptorFactory> = vec![];
+
+    for rw in config {
+        res.push(Box::new(RewriteInterceptorFactory::new(rw.clone())?));
+    }
+
+    Ok(res)
+}
+
+/// A rewrite rule, mapping either a single key or all the keys under a prefix.
+struct RewriteRule {
+    /// The prefix (or key) to replace, `None` for the root.
+    from: Option<OwnedKeyExpr>,
+    /// The replacement prefix (or key), `None` for the root.
+    to: Option<OwnedKeyExpr>,
+    is_prefix: bool,
+}
+
+impl RewriteRule {
+    fn new(conf: &RewriteRuleConf) -> ZResult<Self> {
+        /// Splits a `prefix/**` key-expression into its wildcard-free prefix.
+        fn parse(ke: &keyexpr) -> ZResult<(Option<OwnedKeyExpr>, bool)> {
+            if ke.as_str() == "**" {
+                return Ok((None, true));
+            }
+            let (prefix, is_prefix) = match ke.as_str().strip_suffix("/**") {
+                Some(prefix) => (keyexpr::new(prefix)?, true),
+                None => (ke, false),
+            };
+            if prefix.is_wild() {
+                bail!(
+                    "Invalid rewrite key-expression '{}': only a wildcard-free key optionally followed by '/**' is supported",
+                    ke
+                );
+            }
+            Ok((Some(prefix.to_owned()), is_prefix))
+        }
+
+        let (from, from_is_prefix) = parse(&conf.from)?;
+        let (to, to_is_prefix) = parse(&conf.to)?;
+        if from_is_prefix != to_is_prefix {
+            bail!(
+                "Invalid rewrite rule '{}' -> '{}': both key-expressions should either end with '/**' or not",
+                conf.from,
+                conf.to
+            );
+        }
+        if !from_is_prefix && to.is_none() {
+            bail!("Invalid rewrite rule '{}' -> '{}'", conf.from, conf.to);
+        }
+        Ok(Self {
+            from,
+            to,
+            is_prefix: from_is_prefix,
+        })
+    }
+
+    /// Rewrites a key expression included in the `from` key expression of the rule.
+    fn apply(&self, key_expr: &keyexpr) -> Option<OwnedKeyExpr> {
+        if !self.is_prefix {
+            return self.to.clone();
+        }
+        let rest = match &self.from {
+            Some(from) => match key_expr.as_str().strip_prefix(from.as_str())? {
+                "" => "",
+                rest => rest.strip_prefix('/')?,
+            },
+            None => key_expr.as_str(),
+        };
+        match (&self.to, rest) {
+            (Some(to), "") => Some(to.clone()),
+            (Some(to), rest) => to.join(rest).ok(),
+            (None, "") => None,
+            (None, rest) => OwnedKeyExpr::new(rest).ok(),
+        }
+    }
+
+    fn specificity(&self) -> (usize, bool) {
+        (
+            self.from.as_ref().map_or(0, |from| from.len()),
+            !self.is_prefix,
+        )
+    }
+}
+
+pub struct RewriteInterceptorFactory {
+    subjects: Option<Vec<InterceptorSubjectConf>>,
+    ke_rules: Arc<Mutex<KeBoxTree<usize, UnknownWildness, KeyedSetProvider>>>,
+    rules: Arc<Vec<RewriteRule>>,
+    flow: InterceptorFlow,
+}
+
+impl RewriteInterceptorFactory {
+    pub fn new(conf: RewriteItemConf) -> ZResult<Self> {
+        let mut ke_rules = KeBoxTree::default();
+        let mut rules = vec![];
+        for (idx, rule) in conf.rules.iter().enumerate() {
+            if ke_rules.insert(&rule.from, idx).is_some() {
+                bail!("Duplicated rewrite rule for '{}'", rule.from);
+            }
+            rules.push(RewriteRule::new(rule)?);
+            tracing::debug!(
+                "New rewrite rule enabled: from={}, to={}",
+                rule.from,
+                rule.to
+            );
+        }
+        Ok(Self {
+            subjects: conf.subjects,
+            ke_rules: Arc::new(Mutex::new(ke_rules)),
+            rules: Arc::new(rules),
+            flow: conf.flow,
+        })
+    }
+
+    fn new_interceptor(&self) -> Interceptor {
+        Box::new(ComputeOnMiss::new(RewriteInterceptor {
+            ke_rules: self.ke_rules.clone(),
+            rules: self.rules.clone(),
+        }))
+    }
+}
+
+impl InterceptorFactoryTrait for RewriteInterceptorFactory {
+    fn new_transport_unicast(
+        &self,
+        transport: &TransportUnicast,
+    ) -> (Option<IngressInterceptor>, Option<EgressInterceptor>) {
+        let subject = match TransportSubject::new(transport) {
+            Ok(subject) => subject,
+            Err(err) => {
+                tracing::error!("Couldn't get Transport properties: {}", err);
+                return (None, None);
+            }
+        };
+        if !subject.matches_any(&self.subjects) {
+            return (None, None);
+        }
+        tracing::debug!("New rewriter transport unicast {}", subject.zid);
+
+        match self.flow {
+            InterceptorFlow::Ingress => (Some(self.new_interceptor()), None),
+            InterceptorFlow::Egress => (None, Some(self.new_interceptor())),
+        }
+    }
+}
+
+pub(crate) struct RewriteInterceptor {
+    ke_rules: Arc<Mutex<KeBoxTree<usize, UnknownWildness, KeyedSetProvider>>>,
+    rules: Arc<Vec<RewriteRule>>,
+}
+
+impl RewriteInterceptor {
+    /// Rewrites a key expression with the most specific rule including it, `None` if none does.
+    fn rewrite(&self, key_expr: &keyexpr) -> Option<OwnedKeyExpr> {
+        let ke_rules = zlock!(self.ke_rules);
+        let rule = ke_rules
+            .nodes_including(key_expr)
+            .filter_map(|node| node.weight().and_then(|idx| self.rules.get(*idx)))
+            .max_by_key(|rule| rule.specificity());
+        rule.and_then(|rule| rule.apply(key_expr))
+    }
+}
+
+impl InterceptorTrait for RewriteInterceptor {
+    fn compute_keyexpr_cache(&self, key_expr: &KeyExpr<'_>) -> Option<Box<dyn Any + Send + Sync>> {
+        Some(Box::new(self.rewrite(key_expr)))
+    }
+
+    fn explain(&self, key_expr: &KeyExpr<'_>, _message: ExplainedMessage) -> Option<String> {
+        let cache = self.compute_keyexpr_cache(key_expr)?;
+        let rewritten = cache.downcast_ref::<Option<OwnedKeyExpr>>()?.as_ref()?;
+        Some(format!("rewrite: to {rewritten}"))
+    }
+
+    fn intercept(
+        &self,
+        mut ctx: RoutingContext<NetworkMessage>,
+        cache: Option<&Box<dyn Any + Send + Sync>>,
+    ) -> Option<RoutingContext<NetworkMessage>> {
+        // Key expression declarations are left untouched, the messages using them are rewritten
+        if let NetworkBody::Declare(Declare {
+            body: DeclareBody::DeclareKeyExpr(_),
+            ..
+        }) = &ctx.msg().body
+        {
+            return Some(ctx);
+        }
+        // The entries of the batches are rewritten one by one
+        if is_batch(ctx.msg()) {
+            return map_batch_entries(ctx, |key_expr| {
+                Some(
+                    self.rewrite(key_expr)
+                        .unwrap_or_else(|| key_expr.to_owned()),
+                )
+            });
+        }
+        if let Some(Some(key_expr)) = cache.and_then(|c| c.downcast_ref::<Option<OwnedKeyExpr>>()) {
+            tracing::trace!("Rewrite {:?} to {}", ctx.full_expr(), key_expr);
+            ctx.set_key_expr(key_expr);
+        }
+        Some(ctx)
+    }
+}
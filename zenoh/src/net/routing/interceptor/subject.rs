@@ -0,0 +1,156 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! ⚠️ WARNING ⚠️
+//!
+//! This module is intended for Zenoh's internal use.
+//!
+//! [Click here for Zenoh's documentation](https://docs.rs/zenoh/latest/zenoh)
+
+use std::{iter, net::SocketAddr};
+
+use itertools::Itertools;
+use zenoh_config::{CertCommonName, InterceptorSubjectConf, Interface, LinkProtocol, Username};
+use zenoh_protocol::core::ZenohIdProto;
+use zenoh_result::{bail, ZResult};
+use zenoh_transport::unicast::{authentication::AuthId, TransportUnicast};
+
+use super::authorization::{Subject, SubjectQuery};
+
+/// The properties of a unicast transport, as the [`SubjectQuery`] combinations matched against the
+/// subjects of the access control and of the other interceptors.
+pub(crate) struct TransportSubject {
+    pub(crate) zid: ZenohIdProto,
+    pub(crate) queries: Vec<SubjectQuery>,
+}
+
+impl TransportSubject {
+    pub(crate) fn new(transport: &TransportUnicast) -> ZResult<Self> {
+        let mut cert_common_names = Vec::new();
+        let mut username = None;
+        for auth_id in transport.get_auth_ids()? {
+            match auth_id {
+                AuthId::CertCommonName(value) => {
+                    cert_common_names.push(Some(CertCommonName(value)));
+                }
+                AuthId::Username(value) => {
+                    if username.is_some() {
+                        bail!("Transport should not report more than one username");
+                    }
+                    username = Some(Username(value));
+                }
+                AuthId::None => {}
+            }
+        }
+        if cert_common_names.is_empty() {
+            cert_common_names.push(None);
+        }
+
+        let zid = transport.get_zid()?;
+        let links = transport.get_links()?;
+        #[cfg(feature = "shared-memory")]
+        let is_shm = transport.is_shm()?;
+        #[cfg(not(feature = "shared-memory"))]
+        let is_shm = false;
+        if links
+            .iter()
+            .map(|link| link.interfaces.len())
+            .sum::<usize>()
+            > 1
+        {
+            tracing::warn!("Transport returned multiple network interfaces, current ACL logic might incorrectly apply filters in this case!");
+        }
+        // The (interface, link protocol, remote address) combinations of the transport links
+        let mut link_properties = Vec::new();
+        for link in links {
+            let mut interfaces = link
+                .interfaces
+                .into_iter()
+                .map(|interface| Some(Interface(interface)))
+                .collect::<Vec<_>>();
+            if interfaces.is_empty() {
+                interfaces.push(None);
+            }
+            let mut link_protocols = vec![LinkProtocol::from_locator_protocol(
+                link.dst.protocol().as_str(),
+            )];
+            if is_shm {
+                link_protocols.push(Some(LinkProtocol::Shm));
+            }
+            let remote_address = link
+                .dst
+                .address()
+                .as_str()
+                .parse::<SocketAddr>()
+                .ok()
+                .map(|addr| addr.ip().to_canonical());
+            link_properties.extend(
+                interfaces
+                    .into_iter()
+                    .cartesian_product(link_protocols)
+                    .map(|(interface, link_protocol)| (interface, link_protocol, remote_address)),
+            );
+        }
+        if link_properties.is_empty() {
+            link_properties.push((None, None, None));
+        }
+
+        let queries = iter::once(username)
+            .cartesian_product(link_properties)
+            .cartesian_product(cert_common_names)
+            .map(
+                |((username, (interface, link_protocol, remote_address)), cert_common_name)| {
+                    SubjectQuery {
+                        interface,
+                        cert_common_name,
+                        username,
+                        zid: Some(zid.into()),
+                        link_protocol,
+                        remote_address,
+                    }
+                },
+            )
+            .collect();
+        Ok(Self { zid, queries })
+    }
+
+    /// The username the transport authenticated with, if any.
+    pub(crate) fn username(&self) -> Option<&Username> {
+        self.queries.iter().find_map(|q| q.username.as_ref())
+    }
+
+    /// The common names of the certificates the transport authenticated with.
+    pub(crate) fn cert_common_names(&self) -> Vec<&CertCommonName> {
+        self.queries
+            .iter()
+            .filter_map(|q| q.cert_common_name.as_ref())
+            .unique()
+            .collect()
+    }
+
+    /// Matches a subject if any of its combinations matches any of the transport properties
+    /// combinations, like an access control subject.
+    pub(crate) fn matches(&self, subject: &InterceptorSubjectConf) -> bool {
+        Subject::combinations(subject)
+            .iter()
+            .any(|s| self.queries.iter().any(|q| s.matches(q)))
+    }
+
+    /// Matches any of the given subjects, or any transport if `subjects` is `None`.
+    pub(crate) fn matches_any(&self, subjects: &Option<Vec<InterceptorSubjectConf>>) -> bool {
+        subjects
+            .as_ref()
+            .map_or(true, |subjects| subjects.iter().any(|s| self.matches(s)))
+    }
+}
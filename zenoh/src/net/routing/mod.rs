@@ -32,12 +32,15 @@ use zenoh_protocol::{
 use self::{dispatcher::face::Face, router::Resource};
 use super::runtime;
 
-pub(crate) struct RoutingContext<Msg> {
+/// The context of a message being routed, as handed to interceptors.
+pub struct RoutingContext<Msg> {
     pub(crate) msg: Msg,
     pub(crate) inface: OnceCell<Face>,
     pub(crate) outface: OnceCell<Face>,
     pub(crate) prefix: OnceCell<Arc<Resource>>,
     pub(crate) full_expr: OnceCell<String>,
+    /// Set when the message has been modified by an interceptor.
+    pub(crate) modified: bool,
 }
 
 impl<Msg> RoutingContext<Msg> {
@@ -49,6 +52,7 @@ impl<Msg> RoutingContext<Msg> {
             outface: OnceCell::new(),
             prefix: OnceCell::new(),
             full_expr: OnceCell::new(),
+            modified: false,
         }
     }
 
@@ -60,6 +64,7 @@ impl<Msg> RoutingContext<Msg> {
             outface: OnceCell::new(),
             prefix: OnceCell::new(),
             full_expr: OnceCell::new(),
+            modified: false,
         }
     }
 
@@ -71,6 +76,7 @@ impl<Msg> RoutingContext<Msg> {
             outface: OnceCell::from(outface),
             prefix: OnceCell::new(),
             full_expr: OnceCell::new(),
+            modified: false,
         }
     }
 
@@ -82,6 +88,7 @@ impl<Msg> RoutingContext<Msg> {
             outface: OnceCell::new(),
             prefix: OnceCell::new(),
             full_expr: OnceCell::from(expr),
+            modified: false,
         }
     }
 
@@ -94,11 +101,57 @@ impl<Msg> RoutingContext<Msg> {
     pub(crate) fn outface(&self) -> Option<&Face> {
         self.outface.get()
     }
+
+    /// The routed message.
+    #[inline]
+    pub fn msg(&self) -> &Msg {
+        &self.msg
+    }
+
+    /// Mutable access to the routed message.
+    ///
+    /// The key expression resolved for the previous content of the message is discarded,
+    /// so that a modified (re-routed) key expression is taken into account by
+    /// [`RoutingContext::full_expr`].
+    #[inline]
+    pub fn msg_mut(&mut self) -> &mut Msg {
+        self.prefix = OnceCell::new();
+        self.full_expr = OnceCell::new();
+        self.modified = true;
+        &mut self.msg
+    }
+}
+
+#[zenoh_macros::unstable]
+impl<Msg> RoutingContext<Msg> {
+    /// The id of the node this message was received from, if any.
+    #[inline]
+    pub fn inface_zid(&self) -> Option<zenoh_config::wrappers::ZenohId> {
+        self.inface.get().map(|f| f.state.zid.into())
+    }
+
+    /// The kind of the node this message was received from, if any.
+    #[inline]
+    pub fn inface_whatami(&self) -> Option<zenoh_protocol::core::WhatAmI> {
+        self.inface.get().map(|f| f.state.whatami)
+    }
+
+    /// The id of the node this message is sent to, if any.
+    #[inline]
+    pub fn outface_zid(&self) -> Option<zenoh_config::wrappers::ZenohId> {
+        self.outface.get().map(|f| f.state.zid.into())
+    }
+
+    /// The kind of the node this message is sent to, if any.
+    #[inline]
+    pub fn outface_whatami(&self) -> Option<zenoh_protocol::core::WhatAmI> {
+        self.outface.get().map(|f| f.state.whatami)
+    }
 }
 
 impl RoutingContext<NetworkMessage> {
     #[inline]
-    pub(crate) fn wire_expr(&self) -> Option<&WireExpr> {
+    pub fn wire_expr(&self) -> Option<&WireExpr> {
         use zenoh_protocol::network::{DeclareBody, NetworkBody};
         match &self.msg.body {
             NetworkBody::Push(m) => Some(&m.wire_expr),
@@ -121,6 +174,40 @@ impl RoutingContext<NetworkMessage> {
         }
     }
 
+    /// Replaces the key expression of the message by the given full key expression.
+    ///
+    /// Returns `false` if the message carries no key expression.
+    pub fn set_key_expr(&mut self, key_expr: &OwnedKeyExpr) -> bool {
+        use zenoh_protocol::network::{DeclareBody, NetworkBody};
+        let wire_expr = match &mut self.msg_mut().body {
+            NetworkBody::Push(m) => &mut m.wire_expr,
+            NetworkBody::Request(m) => &mut m.wire_expr,
+            NetworkBody::Response(m) => &mut m.wire_expr,
+            NetworkBody::Interest(m) => match m.wire_expr.as_mut() {
+                Some(wire_expr) => wire_expr,
+                None => return false,
+            },
+            NetworkBody::Declare(m) => match &mut m.body {
+                DeclareBody::DeclareKeyExpr(m) => &mut m.wire_expr,
+                DeclareBody::DeclareSubscriber(m) => &mut m.wire_expr,
+                DeclareBody::UndeclareSubscriber(m) => &mut m.ext_wire_expr.wire_expr,
+                DeclareBody::DeclareQueryable(m) => &mut m.wire_expr,
+                DeclareBody::UndeclareQueryable(m) => &mut m.ext_wire_expr.wire_expr,
+                DeclareBody::DeclareToken(m) => &mut m.wire_expr,
+                DeclareBody::UndeclareToken(m) => &mut m.ext_wire_expr.wire_expr,
+                DeclareBody::UndeclareKeyExpr(_) | DeclareBody::DeclareFinal(_) => return false,
+            },
+            NetworkBody::ResponseFinal(_) | NetworkBody::OAM(_) => return false,
+        };
+        *wire_expr = WireExpr {
+            scope: 0,
+            suffix: key_expr.to_string().into(),
+            mapping: wire_expr.mapping,
+        };
+        let _ = self.full_expr.set(key_expr.to_string());
+        true
+    }
+
     #[inline]
     pub(crate) fn prefix(&self) -> Option<&Arc<Resource>> {
         if let Some(face) = self.outface.get() {
@@ -154,9 +241,9 @@ impl RoutingContext<NetworkMessage> {
         None
     }
 
+    /// The full key expression of the message, resolving declared key expression ids.
     #[inline]
-    #[allow(dead_code)]
-    pub(crate) fn full_expr(&self) -> Option<&str> {
+    pub fn full_expr(&self) -> Option<&str> {
         if self.full_expr.get().is_some() {
             return Some(self.full_expr.get().as_ref().unwrap());
         }
@@ -170,7 +257,7 @@ impl RoutingContext<NetworkMessage> {
     }
 
     #[inline]
-    pub(crate) fn full_key_expr(&self) -> Option<OwnedKeyExpr> {
+    pub fn full_key_expr(&self) -> Option<OwnedKeyExpr> {
         let full_expr = self.full_expr()?;
         OwnedKeyExpr::new(full_expr).ok()
     }
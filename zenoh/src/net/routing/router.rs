@@ -17,10 +17,10 @@ use std::{
 };
 
 use uhlc::HLC;
-use zenoh_config::Config;
+use zenoh_config::{AclConfig, Config};
 use zenoh_protocol::core::{WhatAmI, ZenohIdProto};
 // use zenoh_collections::Timer;
-use zenoh_result::ZResult;
+use zenoh_result::{bail, ZResult};
 use zenoh_transport::{multicast::TransportMulticast, unicast::TransportUnicast, TransportPeer};
 
 pub(crate) use super::dispatcher::token::*;
@@ -31,7 +31,9 @@ use super::{
         tables::{Tables, TablesLock},
     },
     hat,
-    interceptor::{EgressInterceptor, InterceptorsChain},
+    interceptor::{
+        AclEnforcer, ChainSink, EgressInterceptor, InterceptorFactory, InterceptorsChain,
+    },
     runtime::Runtime,
 };
 use crate::net::{
@@ -68,6 +70,33 @@ impl Router {
         ctrl_lock.init(&mut tables, runtime)
     }
 
+    /// Registers an additional interceptor factory.
+    ///
+    /// The interceptors of this factory are only attached to transports opened after this call.
+    pub fn add_interceptor_factory(&self, factory: InterceptorFactory) {
+        zwrite!(self.tables.tables).interceptors.push(factory);
+    }
+
+    /// Checks that an access control configuration can be applied with
+    /// [`Router::reload_access_control`].
+    pub(crate) fn check_access_control(&self, config: &AclConfig) -> ZResult<()> {
+        if config.enabled && zread!(self.tables.tables).acl.is_none() {
+            bail!("Access control was disabled at startup and cannot be enabled at runtime");
+        }
+        AclEnforcer::check(config)
+    }
+
+    /// Replaces the access control policy applied to all the transports.
+    pub(crate) fn reload_access_control(&self, config: &AclConfig) -> ZResult<()> {
+        match &zread!(self.tables.tables).acl {
+            Some(acl) => acl.reload(config),
+            None if config.enabled => {
+                bail!("Access control was disabled at startup and cannot be enabled at runtime")
+            }
+            None => Ok(()),
+        }
+    }
+
     pub(crate) fn new_primitives(
         &self,
         primitives: Arc<dyn EPrimitives + Send + Sync>,
@@ -134,7 +163,9 @@ impl Router {
             Arc::new(InterceptorsChain::from(
                 ingress.into_iter().flatten().collect::<Vec<_>>(),
             )),
-            InterceptorsChain::from(egress.into_iter().flatten().collect::<Vec<_>>()),
+            Arc::new(InterceptorsChain::from(
+                egress.into_iter().flatten().collect::<Vec<_>>(),
+            )),
         );
         let mux = Arc::new(Mux::new(transport.clone(), egress));
         let newface = tables
@@ -162,6 +193,9 @@ impl Router {
         };
 
         let _ = mux.face.set(Face::downgrade(&face));
+        ingress.bind(ChainSink::Ingress(Face::downgrade(&face)));
+        mux.interceptor
+            .bind(ChainSink::Egress(transport.clone(), Face::downgrade(&face)));
 
         let mut declares = vec![];
         ctrl_lock.new_transport_unicast_face(
@@ -185,13 +219,14 @@ impl Router {
         let mut tables = zwrite!(self.tables.tables);
         let fid = tables.face_counter;
         tables.face_counter += 1;
-        let interceptor = InterceptorsChain::from(
+        let interceptor = Arc::new(InterceptorsChain::from(
             tables
                 .interceptors
                 .iter()
                 .filter_map(|itor| itor.new_transport_multicast(&transport))
                 .collect::<Vec<EgressInterceptor>>(),
-        );
+        ));
+        interceptor.bind(ChainSink::EgressMulticast(transport.clone()));
         let mux = Arc::new(McastMux::new(transport.clone(), interceptor));
         let face = FaceState::new(
             fid,
@@ -244,13 +279,11 @@ impl Router {
         tables.mcast_faces.push(face_state.clone());
 
         tables.disable_all_routes();
-        Ok(Arc::new(DeMux::new(
-            Face {
-                tables: self.tables.clone(),
-                state: face_state,
-            },
-            None,
-            interceptor,
-        )))
+        let face = Face {
+            tables: self.tables.clone(),
+            state: face_state,
+        };
+        interceptor.bind(ChainSink::Ingress(Face::downgrade(&face)));
+        Ok(Arc::new(DeMux::new(face, None, interceptor)))
     }
 }
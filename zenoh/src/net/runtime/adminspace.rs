@@ -29,15 +29,18 @@ use zenoh_protocol::{
     core::{key_expr::OwnedKeyExpr, ExprId, Reliability, WireExpr, EMPTY_EXPR_ID},
     network::{
         declare::{queryable::ext::QueryableInfoType, QueryableId},
-        ext, Declare, DeclareBody, DeclareQueryable, DeclareSubscriber, Interest, Push, Request,
-        Response, ResponseFinal,
+        ext, Declare, DeclareBody, DeclareQueryable, DeclareSubscriber, Interest, Oam, Push,
+        Request, Response, ResponseFinal,
     },
     zenoh::{PushBody, RequestBody},
 };
-use zenoh_result::ZResult;
+use zenoh_result::{zerror, ZResult};
 use zenoh_transport::unicast::TransportUnicast;
 
-use super::{routing::dispatcher::face::Face, Runtime};
+use super::{
+    routing::dispatcher::{explain::explain_route, face::Face},
+    Runtime,
+};
 #[cfg(feature = "plugins")]
 use crate::api::plugins::PluginsManager;
 use crate::{
@@ -203,6 +206,33 @@ impl AdminSpace {
             Arc::new(queriers_data),
         );
 
+        handlers.insert(
+            format!("@/{zid_str}/{whatami_str}/queries/load_balancing")
+                .try_into()
+                .unwrap(),
+            Arc::new(queries_load_balancing),
+        );
+
+        handlers.insert(
+            format!("@/{zid_str}/{whatami_str}/route")
+                .try_into()
+                .unwrap(),
+            Arc::new(route_explanation),
+        );
+
+        handlers.insert(
+            format!("@/{zid_str}/{whatami_str}/access_control/rules")
+                .try_into()
+                .unwrap(),
+            Arc::new(access_control_rules),
+        );
+        handlers.insert(
+            format!("@/{zid_str}/{whatami_str}/access_control/decision")
+                .try_into()
+                .unwrap(),
+            Arc::new(access_control_decision),
+        );
+
         #[cfg(feature = "plugins")]
         handlers.insert(
             format!("@/{zid_str}/{whatami_str}/plugins/**")
@@ -341,6 +371,28 @@ impl AdminSpace {
         });
     }
 
+    /// Inserts a configuration value, access control changes are validated before being applied.
+    fn insert_config(&self, key: &str, json: &str) -> ZResult<()> {
+        let config = &self.context.runtime.state.config;
+        if !key.trim_start_matches('/').starts_with("access_control") {
+            return config.insert_json5(key, json);
+        }
+        let mut guard = config.lock();
+        let mut candidate = guard.0.clone();
+        candidate
+            .insert_json5(key, json)
+            .map_err(|e| zerror!("{e}"))?;
+        self.context
+            .runtime
+            .state
+            .router
+            .check_access_control(candidate.access_control())?;
+        guard.0 = candidate;
+        drop(guard);
+        config.notify(key);
+        Ok(())
+    }
+
     pub fn key_expr_to_string<'a>(&self, key_expr: &'a WireExpr) -> ZResult<KeyExpr<'a>> {
         if key_expr.scope == EMPTY_EXPR_ID {
             key_expr.suffix.as_ref().try_into()
@@ -402,7 +454,7 @@ impl Primitives for AdminSpace {
                             key,
                             json
                         );
-                        if let Err(e) = self.context.runtime.state.config.insert_json5(key, json) {
+                        if let Err(e) = self.insert_config(key, json) {
                             error!(
                                 "Error inserting conf value @/{}/{}/config/{} : {} - {}",
                                 self.context.runtime.state.zid,
@@ -429,6 +481,10 @@ impl Primitives for AdminSpace {
                         tracing::error!("Error deleting conf value {} : {}", msg.wire_expr, e)
                     }
                 }
+                PushBody::Batch(_) => error!(
+                    "Received batch on @/{}/{}/config/{} : conf values cannot be batched",
+                    self.context.runtime.state.zid, self.context.runtime.state.whatami, key
+                ),
             }
         }
     }
@@ -475,12 +531,14 @@ impl Primitives for AdminSpace {
                         qid: msg.id,
                         zid: zid.into(),
                         primitives,
+                        control: None,
                     }),
                     eid: self.queryable_id,
                     value: query
                         .ext_body
                         .map(|b| (b.payload.into(), b.encoding.into())),
                     attachment: query.ext_attachment.map(Into::into),
+                    permit: None,
                 };
 
                 for (key, handler) in &self.handlers {
@@ -500,6 +558,10 @@ impl Primitives for AdminSpace {
         trace!("recv ResponseFinal {:?}", msg);
     }
 
+    fn send_oam(&self, msg: Oam) {
+        trace!("recv OAM {:?}", msg);
+    }
+
     fn send_close(&self) {
         trace!("recv Close");
     }
@@ -536,6 +598,11 @@ impl crate::net::primitives::EPrimitives for AdminSpace {
         (self as &dyn Primitives).send_response_final(msg)
     }
 
+    #[inline]
+    fn send_oam(&self, msg: Oam) {
+        (self as &dyn Primitives).send_oam(msg)
+    }
+
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
@@ -652,7 +719,6 @@ fn metrics(context: &AdminContext, query: Query) {
     )
     .try_into()
     .unwrap();
-    #[allow(unused_mut)]
     let mut metrics = format!(
         r#"# HELP zenoh_build Information about zenoh.
 # TYPE zenoh_build gauge
@@ -671,6 +737,13 @@ zenoh_build{{version="{}"}} 1
             .openmetrics_text(),
     );
 
+    let interceptors_metrics = zread!(context.runtime.state.router.tables.tables)
+        .interceptors
+        .iter()
+        .filter_map(|interceptor| interceptor.openmetrics_text())
+        .collect::<Vec<_>>();
+    metrics.push_str(&merge_metric_families(&interceptors_metrics));
+
     if let Err(e) = query
         .reply(reply_key, metrics)
         .encoding(Encoding::TEXT_PLAIN)
@@ -680,6 +753,158 @@ zenoh_build{{version="{}"}} 1
     }
 }
 
+/// Merges OpenMetrics texts, so that the metric families reported by several interceptors
+/// (e.g. several quotas) are described once, with all their samples grouped under it.
+fn merge_metric_families(texts: &[String]) -> String {
+    let mut families: Vec<(&str, Vec<&str>, Vec<&str>)> = vec![];
+    for line in texts.iter().flat_map(|text| text.lines()) {
+        let (name, is_descriptor) = match line.strip_prefix("# ") {
+            Some(descriptor) => (descriptor.split(' ').nth(1).unwrap_or_default(), true),
+            None => (line.split(['{', ' ']).next().unwrap_or_default(), false),
+        };
+        let idx = match families.iter().position(|(n, _, _)| *n == name) {
+            Some(idx) => idx,
+            None => {
+                families.push((name, vec![], vec![]));
+                families.len() - 1
+            }
+        };
+        let (_, descriptors, samples) = &mut families[idx];
+        if !is_descriptor {
+            samples.push(line);
+        } else if !descriptors.contains(&line) {
+            descriptors.push(line);
+        }
+    }
+    let mut merged = String::new();
+    for (_, descriptors, samples) in families {
+        for line in descriptors.into_iter().chain(samples) {
+            merged.push_str(line);
+            merged.push('\n');
+        }
+    }
+    merged
+}
+
+fn queries_load_balancing(context: &AdminContext, query: Query) {
+    let reply_key: OwnedKeyExpr = format!(
+        "@/{}/{}/queries/load_balancing",
+        context.runtime.state.zid, context.runtime.state.whatami
+    )
+    .try_into()
+    .unwrap();
+
+    let tables = zread!(context.runtime.state.router.tables.tables);
+    let stats = tables.query_balancer.stats(tables.faces.values());
+    drop(tables);
+    let payload = match serde_json::to_vec(&stats) {
+        Ok(bytes) => ZBytes::from(bytes),
+        Err(e) => {
+            tracing::error!("Error serializing AdminSpace reply: {:?}", e);
+            return;
+        }
+    };
+    if let Err(e) = query
+        .reply(reply_key, payload)
+        .encoding(Encoding::APPLICATION_JSON)
+        .wait()
+    {
+        tracing::error!("Error sending AdminSpace reply: {:?}", e);
+    }
+}
+
+fn route_explanation(context: &AdminContext, query: Query) {
+    let reply_key: OwnedKeyExpr = format!(
+        "@/{}/{}/route",
+        context.runtime.state.zid, context.runtime.state.whatami
+    )
+    .try_into()
+    .unwrap();
+
+    let explanation = {
+        let tables = zread!(context.runtime.state.router.tables.tables);
+        explain_route(&tables, query.parameters())
+    };
+    let res = match explanation {
+        Ok(explanation) => match serde_json::to_vec(&explanation) {
+            Ok(bytes) => query
+                .reply(reply_key, ZBytes::from(bytes))
+                .encoding(Encoding::APPLICATION_JSON)
+                .wait(),
+            Err(e) => {
+                tracing::error!("Error serializing AdminSpace reply: {:?}", e);
+                return;
+            }
+        },
+        Err(e) => query.reply_err(e.to_string()).wait(),
+    };
+    if let Err(e) = res {
+        tracing::error!("Error sending AdminSpace reply: {:?}", e);
+    }
+}
+
+fn access_control_rules(context: &AdminContext, query: Query) {
+    let reply_key: OwnedKeyExpr = format!(
+        "@/{}/{}/access_control/rules",
+        context.runtime.state.zid, context.runtime.state.whatami
+    )
+    .try_into()
+    .unwrap();
+
+    let Some(acl) = zread!(context.runtime.state.router.tables.tables)
+        .acl
+        .clone()
+    else {
+        return;
+    };
+    let payload = match serde_json::to_vec(&acl.rules_stats()) {
+        Ok(bytes) => ZBytes::from(bytes),
+        Err(e) => {
+            tracing::error!("Error serializing AdminSpace reply: {:?}", e);
+            return;
+        }
+    };
+    if let Err(e) = query
+        .reply(reply_key, payload)
+        .encoding(Encoding::APPLICATION_JSON)
+        .wait()
+    {
+        tracing::error!("Error sending AdminSpace reply: {:?}", e);
+    }
+}
+
+fn access_control_decision(context: &AdminContext, query: Query) {
+    let reply_key: OwnedKeyExpr = format!(
+        "@/{}/{}/access_control/decision",
+        context.runtime.state.zid, context.runtime.state.whatami
+    )
+    .try_into()
+    .unwrap();
+
+    let Some(acl) = zread!(context.runtime.state.router.tables.tables)
+        .acl
+        .clone()
+    else {
+        return;
+    };
+    let res = match acl.what_if(query.parameters()) {
+        Ok(decision) => match serde_json::to_vec(&decision) {
+            Ok(bytes) => query
+                .reply(reply_key, ZBytes::from(bytes))
+                .encoding(Encoding::APPLICATION_JSON)
+                .wait(),
+            Err(e) => {
+                tracing::error!("Error serializing AdminSpace reply: {:?}", e);
+                return;
+            }
+        },
+        Err(e) => query.reply_err(e.to_string()).wait(),
+    };
+    if let Err(e) = res {
+        tracing::error!("Error sending AdminSpace reply: {:?}", e);
+    }
+}
+
 fn routers_linkstate_data(context: &AdminContext, query: Query) {
     let reply_key: OwnedKeyExpr = format!(
         "@/{}/{}/linkstate/routers",
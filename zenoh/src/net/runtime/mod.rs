@@ -57,7 +57,11 @@ use zenoh_transport::{
 };
 
 use self::orchestrator::StartConditions;
-use super::{primitives::DeMux, routing, routing::router::Router};
+use super::{
+    primitives::DeMux,
+    routing,
+    routing::{interceptor::InterceptorFactory, router::Router},
+};
 #[cfg(feature = "plugins")]
 use crate::api::loader::{load_plugins, start_plugins};
 #[cfg(feature = "plugins")]
@@ -68,6 +72,7 @@ use crate::{
     api::{
         builders::close::{Closeable, Closee},
         config::{Config, Notifier},
+        topology::{TopologyHandler, TopologyNotifier},
     },
     GIT_VERSION, LONG_VERSION,
 };
@@ -80,6 +85,7 @@ pub(crate) struct RuntimeState {
     config: Notifier<Config>,
     manager: TransportManager,
     transport_handlers: std::sync::RwLock<Vec<Arc<dyn TransportEventHandler>>>,
+    topology: Arc<TopologyNotifier>,
     locators: std::sync::RwLock<Vec<Locator>>,
     hlc: Option<Arc<HLC>>,
     task_controller: TaskController,
@@ -105,6 +111,7 @@ pub struct RuntimeBuilder {
     plugins_manager: Option<PluginsManager>,
     #[cfg(feature = "shared-memory")]
     shm_clients: Option<Arc<ShmClientStorage>>,
+    interceptor_factories: Vec<InterceptorFactory>,
 }
 
 impl RuntimeBuilder {
@@ -115,9 +122,17 @@ impl RuntimeBuilder {
             plugins_manager: None,
             #[cfg(feature = "shared-memory")]
             shm_clients: None,
+            interceptor_factories: vec![],
         }
     }
 
+    /// Additional interceptor factories, attached to every transport after the ones built from the config.
+    #[cfg(feature = "unstable")]
+    pub fn interceptor_factories(mut self, interceptor_factories: Vec<InterceptorFactory>) -> Self {
+        self.interceptor_factories = interceptor_factories;
+        self
+    }
+
     #[cfg(all(feature = "plugins", feature = "internal"))]
     pub fn plugins_manager<T: Into<Option<PluginsManager>>>(mut self, plugins_manager: T) -> Self {
         self.plugins_manager = plugins_manager.into();
@@ -137,6 +152,7 @@ impl RuntimeBuilder {
             mut plugins_manager,
             #[cfg(feature = "shared-memory")]
             shm_clients,
+            interceptor_factories,
         } = self;
 
         tracing::debug!("Zenoh Rust API {}", GIT_VERSION);
@@ -148,6 +164,9 @@ impl RuntimeBuilder {
             .then(|| Arc::new(HLCBuilder::new().with_id(uhlc::ID::from(&zid)).build()));
 
         let router = Arc::new(Router::new(zid, whatami, hlc.clone(), &config)?);
+        for factory in interceptor_factories {
+            router.add_interceptor_factory(factory);
+        }
 
         let handler = Arc::new(RuntimeTransportEventHandler {
             runtime: std::sync::RwLock::new(WeakRuntime { state: Weak::new() }),
@@ -177,6 +196,10 @@ impl RuntimeBuilder {
         let shm_init_mode = *config.transport.shared_memory.mode();
 
         let config = Notifier::new(crate::config::Config(config));
+        let topology = TopologyNotifier::new();
+        let topology_handler: Arc<dyn TransportEventHandler> = Arc::new(TopologyHandler {
+            notifier: topology.clone(),
+        });
         let runtime = Runtime {
             state: Arc::new(RuntimeState {
                 zid: zid.into(),
@@ -185,7 +208,8 @@ impl RuntimeBuilder {
                 router,
                 config: config.clone(),
                 manager: transport_manager,
-                transport_handlers: std::sync::RwLock::new(vec![]),
+                transport_handlers: std::sync::RwLock::new(vec![topology_handler]),
+                topology,
                 locators: std::sync::RwLock::new(vec![]),
                 hlc,
                 task_controller: TaskController::default(),
@@ -224,6 +248,12 @@ impl RuntimeBuilder {
                                             tracing::error!("Error updating peers: {}", e);
                                         }
                                     }
+                                    if event.trim_start_matches('/').starts_with("access_control") {
+                                        let acl_config = runtime2.config().lock().0.access_control().clone();
+                                        if let Err(e) = runtime2.state.router.reload_access_control(&acl_config) {
+                                            tracing::error!("Error reloading access control: {}", e);
+                                        }
+                                    }
                                 },
                                 None => { break; }
                             }
@@ -272,6 +302,18 @@ impl Runtime {
         zlock!(self.state.plugins_manager)
     }
 
+    /// Registers an additional interceptor factory, e.g. from a plugin.
+    ///
+    /// The interceptors of this factory are only attached to transports opened after this call.
+    #[cfg(feature = "internal")]
+    pub fn add_interceptor_factory(&self, factory: InterceptorFactory) {
+        self.state.router.add_interceptor_factory(factory);
+    }
+
+    pub(crate) fn topology(&self) -> &Arc<TopologyNotifier> {
+        &self.state.topology
+    }
+
     pub(crate) fn new_handler(&self, handler: Arc<dyn TransportEventHandler>) {
         zwrite!(self.state.transport_handlers).push(handler);
     }
@@ -0,0 +1,99 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use zenoh_buffers::ZBuf;
+use zenoh_protocol::{
+    core::Encoding,
+    network::{push, Push},
+    zenoh::{Batch, BatchEntry, Del, PushBody, Put},
+};
+
+use crate::net::protocol::batch::{batch_key_expr, split_batch_push};
+
+fn batch_push(keys: &[&str]) -> Push {
+    let entries = keys
+        .iter()
+        .enumerate()
+        .map(|(i, key)| BatchEntry {
+            key_expr: key.to_string(),
+            payload: if i % 2 == 0 {
+                PushBody::Put(Put {
+                    timestamp: None,
+                    encoding: Encoding::empty(),
+                    ext_sinfo: None,
+                    #[cfg(feature = "shared-memory")]
+                    ext_shm: None,
+                    ext_unknown: vec![],
+                    payload: ZBuf::from(vec![i as u8]),
+                    ext_attachment: None,
+                })
+            } else {
+                PushBody::Del(Del {
+                    timestamp: None,
+                    ext_sinfo: None,
+                    ext_attachment: None,
+                    ext_unknown: vec![],
+                })
+            },
+        })
+        .collect();
+    Push {
+        wire_expr: "**".into(),
+        ext_qos: push::ext::QoSType::DEFAULT,
+        ext_tstamp: None,
+        ext_nodeid: push::ext::NodeIdType::DEFAULT,
+        ext_hop_limit: None,
+        ext_lifespan: None,
+        ext_redundant: None,
+        payload: PushBody::Batch(Batch {
+            timestamp: Some(uhlc::HLC::default().new_timestamp()),
+            ext_sinfo: None,
+            ext_unknown: vec![],
+            entries,
+        }),
+    }
+}
+
+#[test]
+fn batch_key_expr_test() {
+    assert_eq!(batch_key_expr(["a/b", "a/b"]).as_str(), "a/b");
+    assert_eq!(batch_key_expr(["a/b/c", "a/b/d"]).as_str(), "a/b/**");
+    assert_eq!(batch_key_expr(["a", "a/b"]).as_str(), "a/**");
+    assert_eq!(batch_key_expr(["a/b", "c/b"]).as_str(), "**");
+}
+
+#[test]
+fn split_batch_push_test() {
+    let keys = |pushes: &[Push]| {
+        pushes
+            .iter()
+            .map(|push| push.wire_expr.suffix.to_string())
+            .collect::<Vec<_>>()
+    };
+
+    // The entries are sent individually to the nodes not supporting batches
+    let push = batch_push(&["a/1", "a/2"]);
+    let PushBody::Batch(batch) = &push.payload else {
+        unreachable!()
+    };
+    let timestamp = batch.timestamp;
+    let pushes = split_batch_push(push);
+    assert_eq!(keys(&pushes), ["a/1", "a/2"]);
+    match (&pushes[0].payload, &pushes[1].payload) {
+        (PushBody::Put(put), PushBody::Del(del)) => {
+            assert_eq!(put.timestamp, timestamp);
+            assert_eq!(del.timestamp, timestamp);
+        }
+        _ => panic!("Unexpected payloads"),
+    }
+}
@@ -1 +1,2 @@
+pub(crate) mod batch;
 pub(crate) mod tables;
@@ -536,6 +536,8 @@ impl Primitives for ClientPrimitives {
 
     fn send_response_final(&self, _msg: zenoh_protocol::network::ResponseFinal) {}
 
+    fn send_oam(&self, _msg: zenoh_protocol::network::Oam) {}
+
     fn send_close(&self) {}
 }
 
@@ -569,6 +571,8 @@ impl EPrimitives for ClientPrimitives {
 
     fn send_response_final(&self, _msg: zenoh_protocol::network::ResponseFinal) {}
 
+    fn send_oam(&self, _msg: zenoh_protocol::network::Oam) {}
+
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
@@ -734,6 +738,9 @@ fn client_test() {
             ext::QoSType::DEFAULT,
             None,
             ext::NodeIdType { node_id: 0 },
+            None,
+            None,
+            None,
             || {
                 PushBody::Put(Put {
                     timestamp: None,
@@ -21,7 +21,7 @@ use std::{
 
 use tokio::runtime::Handle;
 use zenoh::{config::WhatAmI, sample::SampleKind, Config, Session};
-use zenoh_config::{EndPoint, ModeDependentValue};
+use zenoh_config::{EndPoint, ModeDependentValue, ZenohId};
 use zenoh_core::{zlock, ztimeout};
 
 const TIMEOUT: Duration = Duration::from_secs(60);
@@ -72,6 +72,12 @@ async fn test_acl_liveliness() {
     test_liveliness_deny_allow_query(27450).await;
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_acl_subject_properties() {
+    zenoh::init_log_from_env_or("error");
+    test_pub_sub_subject_properties(27451).await;
+}
+
 async fn get_basic_router_config(port: u16) -> Config {
     let mut config = Config::default();
     config.set_mode(Some(WhatAmI::Router)).unwrap();
@@ -1529,3 +1535,477 @@ async fn test_liveliness_deny_allow_query(port: u16) {
     close_sessions(reader_session, writer_session).await;
     close_router_session(session).await;
 }
+
+async fn test_pub_sub_subject_properties(port: u16) {
+    println!("test_pub_sub_subject_properties");
+
+    let pub_zid = "a1";
+    // (subject, is the publisher denied)
+    let cases = [
+        (
+            r#"{ "id": "s1", "zids": ["a1"], "link_protocols": ["tcp"], "remote_networks": ["127.0.0.0/8"] }"#,
+            true,
+        ),
+        (r#"{ "id": "s1", "remote_networks": ["127.0.0.1"] }"#, true),
+        (r#"{ "id": "s1", "zids": ["b1"] }"#, false),
+        (
+            r#"{ "id": "s1", "link_protocols": ["quic", "tls"] }"#,
+            false,
+        ),
+        (
+            r#"{ "id": "s1", "zids": ["a1"], "remote_networks": ["10.0.0.0/8", "::1"] }"#,
+            false,
+        ),
+    ];
+
+    for (subject, denied) in cases {
+        println!("subject: {subject}, denied: {denied}");
+        let mut config_router = get_basic_router_config(port).await;
+        config_router
+            .insert_json5(
+                "access_control",
+                &format!(
+                    r#"{{
+                        "enabled": true,
+                        "default_permission": "allow",
+                        "rules": [
+                            {{
+                                "id": "r1",
+                                "permission": "deny",
+                                "flows": ["ingress"],
+                                "messages": ["put"],
+                                "key_exprs": ["test/demo"],
+                            }},
+                        ],
+                        "subjects": [{subject}],
+                        "policies": [
+                            {{
+                                "rules": ["r1"],
+                                "subjects": ["s1"],
+                            }}
+                        ]
+                    }}"#
+                ),
+            )
+            .unwrap();
+        let session = ztimeout!(zenoh::open(config_router)).unwrap();
+
+        let mut config = zenoh::Config::default();
+        config.set_mode(Some(WhatAmI::Client)).unwrap();
+        config
+            .connect
+            .set_endpoints(ModeDependentValue::Unique(vec![format!(
+                "tcp/127.0.0.1:{port}"
+            )
+            .parse::<EndPoint>()
+            .unwrap()]))
+            .unwrap();
+        let sub_session = ztimeout!(zenoh::open(config.clone())).unwrap();
+        config.set_id(pub_zid.parse::<ZenohId>().unwrap()).unwrap();
+        let pub_session = ztimeout!(zenoh::open(config)).unwrap();
+        {
+            let publisher = ztimeout!(pub_session.declare_publisher(KEY_EXPR)).unwrap();
+            let received_value = Arc::new(Mutex::new(String::new()));
+            let temp_recv_value = received_value.clone();
+            let subscriber = sub_session
+                .declare_subscriber(KEY_EXPR)
+                .callback(move |sample| {
+                    let mut temp_value = zlock!(temp_recv_value);
+                    *temp_value = sample.payload().try_to_string().unwrap().into_owned();
+                })
+                .await
+                .unwrap();
+
+            tokio::time::sleep(SLEEP).await;
+            publisher.put(VALUE).await.unwrap();
+            tokio::time::sleep(SLEEP).await;
+            assert_eq!(*zlock!(received_value) == VALUE, !denied);
+            ztimeout!(subscriber.undeclare()).unwrap();
+        }
+        close_sessions(sub_session, pub_session).await;
+        close_router_session(session).await;
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_acl_reload() {
+    zenoh::init_log_from_env_or("error");
+    test_pub_sub_reload(27453).await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_acl_reload_enable_rejected() {
+    zenoh::init_log_from_env_or("error");
+    test_pub_sub_reload_enable_rejected(27456).await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_acl_audit_mode() {
+    zenoh::init_log_from_env_or("error");
+    test_pub_sub_audit_mode(27454).await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_acl_matching_status() {
+    zenoh::init_log_from_env_or("error");
+    test_matching_status_deny(27455).await;
+    test_matching_status_deny_by_default(27455).await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_acl_subject_config_error() {
+    zenoh::init_log_from_env_or("error");
+    // Invalid values are rejected by the config, empty lists when opening the session
+    for (subject, config_error) in [
+        (r#"{ "id": "s1", "zids": [] }"#, false),
+        (
+            r#"{ "id": "s1", "remote_networks": ["10.0.0.0/33"] }"#,
+            true,
+        ),
+    ] {
+        let mut config_router = get_basic_router_config(27452).await;
+        let res = config_router.insert_json5(
+            "access_control",
+            &format!(
+                r#"{{
+                    "enabled": true,
+                    "default_permission": "allow",
+                    "rules": [{{ "id": "r1", "permission": "deny", "messages": ["put"], "key_exprs": ["test/demo"] }}],
+                    "subjects": [{subject}],
+                    "policies": [{{ "rules": ["r1"], "subjects": ["s1"] }}]
+                }}"#
+            ),
+        );
+        if config_error {
+            assert!(res.is_err(), "{subject} should be rejected by the config");
+        } else {
+            res.unwrap();
+            assert!(
+                ztimeout!(zenoh::open(config_router)).is_err(),
+                "{subject} should be rejected when opening the session"
+            );
+        }
+    }
+}
+
+fn acl_deny_put_config(key_expr: &str) -> String {
+    format!(
+        r#"{{
+            "enabled": true,
+            "default_permission": "allow",
+            "rules": [
+                {{
+                    "id": "deny_put",
+                    "permission": "deny",
+                    "messages": ["put"],
+                    "key_exprs": ["{key_expr}"],
+                }},
+            ],
+            "subjects": [{{ "id": "all" }}],
+            "policies": [{{ "rules": ["deny_put"], "subjects": ["all"] }}]
+        }}"#
+    )
+}
+
+async fn get_admin_json(session: &Session, key_expr: &str) -> serde_json::Value {
+    let replies = ztimeout!(session.get(key_expr)).unwrap();
+    let reply = ztimeout!(replies.recv_async()).unwrap();
+    let payload = reply.result().unwrap().payload().to_bytes();
+    serde_json::from_slice(&payload).unwrap()
+}
+
+async fn test_pub_sub_reload(port: u16) {
+    println!("test_pub_sub_reload");
+
+    let mut config_router = get_basic_router_config(port).await;
+    config_router
+        .insert_json5("access_control", &acl_deny_put_config(KEY_EXPR))
+        .unwrap();
+    config_router
+        .insert_json5(
+            "adminspace",
+            r#"{ "enabled": true, "permissions": { "read": true, "write": true } }"#,
+        )
+        .unwrap();
+    let session = ztimeout!(zenoh::open(config_router)).unwrap();
+    let admin_prefix = format!("@/{}/router", session.zid());
+    let (sub_session, pub_session) = get_client_sessions(port).await;
+
+    let publisher = ztimeout!(pub_session.declare_publisher(KEY_EXPR)).unwrap();
+    let received_value = Arc::new(Mutex::new(String::new()));
+    let temp_recv_value = received_value.clone();
+    let subscriber = sub_session
+        .declare_subscriber(KEY_EXPR)
+        .callback(move |sample| {
+            let mut temp_value = zlock!(temp_recv_value);
+            *temp_value = sample.payload().try_to_string().unwrap().into_owned();
+        })
+        .await
+        .unwrap();
+    tokio::time::sleep(SLEEP).await;
+
+    publisher.put(VALUE).await.unwrap();
+    tokio::time::sleep(SLEEP).await;
+    assert_eq!(*zlock!(received_value), "");
+
+    let rules = get_admin_json(&session, &format!("{admin_prefix}/access_control/rules")).await;
+    assert_eq!(rules["rules"][0]["id"], "deny_put");
+    assert_eq!(rules["rules"][0]["hits"], 1);
+
+    let decision = get_admin_json(
+        &session,
+        &format!("{admin_prefix}/access_control/decision?key_expr={KEY_EXPR};message=put"),
+    )
+    .await;
+    assert_eq!(decision["permission"], "deny");
+    assert_eq!(decision["rules"][0], "deny_put");
+
+    // An invalid policy is rejected, the current one is kept
+    ztimeout!(session.put(
+        format!("{admin_prefix}/config/access_control/rules"),
+        r#"[{ "id": "", "permission": "allow", "messages": ["put"], "key_exprs": ["**"] }]"#
+    ))
+    .unwrap();
+    tokio::time::sleep(SLEEP).await;
+    publisher.put(VALUE).await.unwrap();
+    tokio::time::sleep(SLEEP).await;
+    assert_eq!(*zlock!(received_value), "");
+
+    // A valid policy is applied to the existing transports
+    ztimeout!(session.put(
+        format!("{admin_prefix}/config/access_control"),
+        acl_deny_put_config("test/other")
+    ))
+    .unwrap();
+    tokio::time::sleep(SLEEP).await;
+    publisher.put(VALUE).await.unwrap();
+    tokio::time::sleep(SLEEP).await;
+    assert_eq!(*zlock!(received_value), VALUE);
+
+    let rules = get_admin_json(&session, &format!("{admin_prefix}/access_control/rules")).await;
+    assert_eq!(rules["rules"][0]["hits"], 0);
+    let decision = get_admin_json(
+        &session,
+        &format!("{admin_prefix}/access_control/decision?key_expr={KEY_EXPR};message=put"),
+    )
+    .await;
+    assert_eq!(decision["permission"], "allow");
+
+    ztimeout!(subscriber.undeclare()).unwrap();
+    close_sessions(sub_session, pub_session).await;
+    close_router_session(session).await;
+}
+
+async fn test_pub_sub_reload_enable_rejected(port: u16) {
+    println!("test_pub_sub_reload_enable_rejected");
+
+    let mut config_router = get_basic_router_config(port).await;
+    config_router
+        .insert_json5(
+            "adminspace",
+            r#"{ "enabled": true, "permissions": { "read": true, "write": true } }"#,
+        )
+        .unwrap();
+    let session = ztimeout!(zenoh::open(config_router)).unwrap();
+    let admin_prefix = format!("@/{}/router", session.zid());
+    let (sub_session, pub_session) = get_client_sessions(port).await;
+
+    let publisher = ztimeout!(pub_session.declare_publisher(KEY_EXPR)).unwrap();
+    let received_value = Arc::new(Mutex::new(String::new()));
+    let temp_recv_value = received_value.clone();
+    let subscriber = sub_session
+        .declare_subscriber(KEY_EXPR)
+        .callback(move |sample| {
+            let mut temp_value = zlock!(temp_recv_value);
+            *temp_value = sample.payload().try_to_string().unwrap().into_owned();
+        })
+        .await
+        .unwrap();
+    tokio::time::sleep(SLEEP).await;
+
+    // Access control disabled at startup can't be enabled at runtime, the config is left unchanged
+    ztimeout!(session.put(
+        format!("{admin_prefix}/config/access_control"),
+        acl_deny_put_config(KEY_EXPR)
+    ))
+    .unwrap();
+    tokio::time::sleep(SLEEP).await;
+    assert_eq!(
+        session
+            .config()
+            .lock()
+            .get_json("access_control/enabled")
+            .unwrap(),
+        "false"
+    );
+    publisher.put(VALUE).await.unwrap();
+    tokio::time::sleep(SLEEP).await;
+    assert_eq!(*zlock!(received_value), VALUE);
+
+    ztimeout!(subscriber.undeclare()).unwrap();
+    close_sessions(sub_session, pub_session).await;
+    close_router_session(session).await;
+}
+
+async fn test_pub_sub_audit_mode(port: u16) {
+    println!("test_pub_sub_audit_mode");
+
+    let mut config_router = get_basic_router_config(port).await;
+    config_router
+        .insert_json5("access_control", &acl_deny_put_config(KEY_EXPR))
+        .unwrap();
+    config_router
+        .insert_json5("access_control/enforcement", r#""audit""#)
+        .unwrap();
+    config_router
+        .insert_json5("adminspace", r#"{ "enabled": true }"#)
+        .unwrap();
+    let session = ztimeout!(zenoh::open(config_router)).unwrap();
+    let admin_prefix = format!("@/{}/router", session.zid());
+    let (sub_session, pub_session) = get_client_sessions(port).await;
+
+    let publisher = ztimeout!(pub_session.declare_publisher(KEY_EXPR)).unwrap();
+    let received_value = Arc::new(Mutex::new(String::new()));
+    let temp_recv_value = received_value.clone();
+    let subscriber = sub_session
+        .declare_subscriber(KEY_EXPR)
+        .callback(move |sample| {
+            let mut temp_value = zlock!(temp_recv_value);
+            *temp_value = sample.payload().try_to_string().unwrap().into_owned();
+        })
+        .await
+        .unwrap();
+    tokio::time::sleep(SLEEP).await;
+
+    // The message would be denied, but is delivered
+    publisher.put(VALUE).await.unwrap();
+    tokio::time::sleep(SLEEP).await;
+    assert_eq!(*zlock!(received_value), VALUE);
+
+    let rules = get_admin_json(&session, &format!("{admin_prefix}/access_control/rules")).await;
+    // Denied in both the ingress (from the publisher) and egress (to the subscriber) flows
+    assert_eq!(rules["enforcement"], "audit");
+    assert_eq!(rules["denials"], 2);
+    assert_eq!(rules["rules"][0]["hits"], 2);
+
+    let replies = ztimeout!(session.get(format!("{admin_prefix}/metrics"))).unwrap();
+    let reply = ztimeout!(replies.recv_async()).unwrap();
+    let metrics = reply
+        .result()
+        .unwrap()
+        .payload()
+        .try_to_string()
+        .unwrap()
+        .into_owned();
+    assert!(metrics.contains(r#"zenoh_acl_denied{enforcement="audit"} 2"#));
+
+    ztimeout!(subscriber.undeclare()).unwrap();
+    close_sessions(sub_session, pub_session).await;
+    close_router_session(session).await;
+}
+
+async fn test_matching_status_deny(port: u16) {
+    println!("test_matching_status_deny");
+
+    let mut config_router = get_basic_router_config(port).await;
+    config_router
+        .insert_json5(
+            "access_control",
+            r#"{
+                "enabled": true,
+                "default_permission": "allow",
+                "rules": [
+                    {
+                        "id": "deny_matching",
+                        "permission": "deny",
+                        "flows": ["ingress"],
+                        "messages": ["declare_publisher", "declare_querier"],
+                        "key_exprs": ["test/demo"],
+                    },
+                ],
+                "subjects": [{ "id": "all" }],
+                "policies": [{ "rules": ["deny_matching"], "subjects": ["all"] }]
+            }"#,
+        )
+        .unwrap();
+    let session = ztimeout!(zenoh::open(config_router)).unwrap();
+    let (sub_session, pub_session) = get_client_sessions(port).await;
+
+    let subscriber = ztimeout!(sub_session.declare_subscriber("test/**")).unwrap();
+    let queryable = ztimeout!(sub_session.declare_queryable("test/**")).unwrap();
+    tokio::time::sleep(SLEEP).await;
+
+    let publisher = ztimeout!(pub_session.declare_publisher(KEY_EXPR)).unwrap();
+    let querier = ztimeout!(pub_session.declare_querier(KEY_EXPR)).unwrap();
+    let other_publisher = ztimeout!(pub_session.declare_publisher("test/other")).unwrap();
+    let other_querier = ztimeout!(pub_session.declare_querier("test/other")).unwrap();
+    tokio::time::sleep(SLEEP).await;
+
+    // The router doesn't tell the client which subscribers and queryables match the denied key
+    assert!(!ztimeout!(publisher.matching_status()).unwrap().matching());
+    assert!(!ztimeout!(querier.matching_status()).unwrap().matching());
+    assert!(ztimeout!(other_publisher.matching_status())
+        .unwrap()
+        .matching());
+    assert!(ztimeout!(other_querier.matching_status())
+        .unwrap()
+        .matching());
+
+    ztimeout!(subscriber.undeclare()).unwrap();
+    ztimeout!(queryable.undeclare()).unwrap();
+    close_sessions(sub_session, pub_session).await;
+    close_router_session(session).await;
+}
+
+async fn test_matching_status_deny_by_default(port: u16) {
+    println!("test_matching_status_deny_by_default");
+
+    // A policy naming none of the interest messages doesn't filter them
+    let mut config_router = get_basic_router_config(port).await;
+    config_router
+        .insert_json5(
+            "access_control",
+            r#"{
+                "enabled": true,
+                "default_permission": "deny",
+                "rules": [
+                    {
+                        "id": "r1",
+                        "permission": "allow",
+                        "flows": ["egress", "ingress"],
+                        "messages": ["put", "delete", "declare_subscriber"],
+                        "key_exprs": ["test/demo"],
+                    },
+                ],
+                "subjects": [{ "id": "all" }],
+                "policies": [{ "rules": ["r1"], "subjects": ["all"] }]
+            }"#,
+        )
+        .unwrap();
+    let session = ztimeout!(zenoh::open(config_router)).unwrap();
+    let (sub_session, pub_session) = get_client_sessions(port).await;
+
+    let received_value = Arc::new(Mutex::new(String::new()));
+    let temp_recv_value = received_value.clone();
+    let subscriber = sub_session
+        .declare_subscriber(KEY_EXPR)
+        .callback(move |sample| {
+            let mut temp_value = zlock!(temp_recv_value);
+            *temp_value = sample.payload().try_to_string().unwrap().into_owned();
+        })
+        .await
+        .unwrap();
+    tokio::time::sleep(SLEEP).await;
+
+    let publisher = ztimeout!(pub_session.declare_publisher(KEY_EXPR)).unwrap();
+    tokio::time::sleep(SLEEP).await;
+    assert!(ztimeout!(publisher.matching_status()).unwrap().matching());
+
+    publisher.put(VALUE).await.unwrap();
+    tokio::time::sleep(SLEEP).await;
+    assert_eq!(*zlock!(received_value), VALUE);
+
+    ztimeout!(subscriber.undeclare()).unwrap();
+    close_sessions(sub_session, pub_session).await;
+    close_router_session(session).await;
+}
@@ -17,14 +17,24 @@
 
 use std::{
     collections::HashMap,
+    iter,
     sync::{
         atomic::{AtomicBool, AtomicUsize, Ordering},
         Arc,
     },
 };
 
-use zenoh::{key_expr::KeyExpr, Config, Wait};
-use zenoh_config::{DownsamplingItemConf, DownsamplingRuleConf, InterceptorFlow};
+use zenoh::{
+    config::WhatAmI,
+    key_expr::KeyExpr,
+    qos::{CongestionControl, Priority},
+    Config, Wait,
+};
+use zenoh_config::{
+    DownsamplingItemConf, DownsamplingMode, DownsamplingRuleConf, InterceptorFlow,
+    InterceptorSubjectConf, LinkProtocol, QuotaAction, QuotaItemConf, RewriteItemConf,
+    RewriteRuleConf,
+};
 
 // Tokio's time granularity on different platforms
 #[cfg(target_os = "windows")]
@@ -158,10 +168,14 @@ fn downsampling_by_keyexpr_impl(flow: InterceptorFlow) {
             DownsamplingRuleConf {
                 key_expr: ke_10hz.clone().into(),
                 freq: 10.0,
+                mode: DownsamplingMode::Drop,
+                per_source: false,
             },
             DownsamplingRuleConf {
                 key_expr: ke_20hz.clone().into(),
                 freq: 20.0,
+                mode: DownsamplingMode::Drop,
+                per_source: false,
             },
         ],
     };
@@ -213,6 +227,8 @@ fn downsampling_by_interface_impl(flow: InterceptorFlow) {
             rules: vec![DownsamplingRuleConf {
                 key_expr: ke_10hz.clone().into(),
                 freq: 10.0,
+                mode: DownsamplingMode::Drop,
+                per_source: false,
             }],
         },
         DownsamplingItemConf {
@@ -222,6 +238,8 @@ fn downsampling_by_interface_impl(flow: InterceptorFlow) {
             rules: vec![DownsamplingRuleConf {
                 key_expr: ke_no_effect.clone().into(),
                 freq: 10.0,
+                mode: DownsamplingMode::Drop,
+                per_source: false,
             }],
         },
     ];
@@ -276,3 +294,1228 @@ fn downsampling_config_error_wrong_strategy() {
 
     zenoh::open(config).wait().unwrap();
 }
+
+fn downsampling_latest_impl(flow: InterceptorFlow) {
+    let ke_prefix = "test/downsamples_latest";
+    let locator = "tcp/127.0.0.1:31454";
+    let ke: KeyExpr = format!("{ke_prefix}/1hz/*").try_into().unwrap();
+
+    let ds_config = DownsamplingItemConf {
+        id: None,
+        flow,
+        interfaces: None,
+        rules: vec![DownsamplingRuleConf {
+            key_expr: ke.clone().into(),
+            freq: 1.0,
+            mode: DownsamplingMode::Latest,
+            per_source: false,
+        }],
+    };
+    let (mut pub_config, mut sub_config) = build_config(locator, vec![ds_config], flow);
+    // The held samples go through the interceptors following downsampling
+    let (chain_config, flow) = match flow {
+        InterceptorFlow::Egress => (&mut pub_config, "egress"),
+        InterceptorFlow::Ingress => (&mut sub_config, "ingress"),
+    };
+    chain_config
+        .insert_json5(
+            "qos/network",
+            &format!(
+                r#"[ {{ messages: ["put"], key_exprs: ["{ke_prefix}/**"], overwrite: {{ priority: "background" }}, flow: "{flow}" }} ]"#
+            ),
+        )
+        .unwrap();
+    chain_config
+        .insert_json5(
+            "access_control",
+            &format!(
+                r#"{{
+                    enabled: true,
+                    default_permission: "allow",
+                    rules: [
+                        {{ id: "deny c", permission: "deny", flows: ["{flow}"], messages: ["put"], key_exprs: ["{ke_prefix}/1hz/c"] }},
+                    ],
+                    subjects: [ {{ id: "all" }} ],
+                    policies: [ {{ rules: ["deny c"], subjects: ["all"] }} ],
+                }}"#
+            ),
+        )
+        .unwrap();
+
+    let sub_session = zenoh::open(sub_config).wait().unwrap();
+    let received = Arc::new(std::sync::Mutex::new(vec![]));
+    let _sub = sub_session
+        .declare_subscriber(format!("{ke_prefix}/**"))
+        .callback({
+            let received = received.clone();
+            move |sample| {
+                let value = format!(
+                    "{}={}",
+                    sample.key_expr().as_str().rsplit('/').next().unwrap(),
+                    sample.payload().try_to_string().unwrap()
+                );
+                assert_eq!(sample.priority(), Priority::Background);
+                received.lock().unwrap().push(value);
+            }
+        })
+        .wait()
+        .unwrap();
+
+    let pub_session = zenoh::open(pub_config).wait().unwrap();
+    let publishers = ["a", "b", "c"].map(|key| {
+        pub_session
+            .declare_publisher(format!("{ke_prefix}/1hz/{key}"))
+            .wait()
+            .unwrap()
+    });
+    std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+
+    // A burst shorter than the period: the first sample of each key passes, the last one is held,
+    // the held samples of the denied key being dropped by the access control like the first one
+    for i in 0..10 {
+        for publisher in &publishers {
+            publisher.put(i.to_string()).wait().unwrap();
+        }
+        std::thread::sleep(std::time::Duration::from_millis(MINIMAL_SLEEP_INTERVAL_MS));
+    }
+    std::thread::sleep(std::time::Duration::from_millis(1500));
+
+    let mut received = received.lock().unwrap().clone();
+    received.sort();
+    assert_eq!(received, vec!["a=0", "a=9", "b=0", "b=9"]);
+}
+
+#[test]
+fn downsampling_latest() {
+    zenoh::init_log_from_env_or("error");
+    downsampling_latest_impl(InterceptorFlow::Ingress);
+    downsampling_latest_impl(InterceptorFlow::Egress);
+}
+
+#[test]
+#[should_panic(expected = "the latest mode requires a positive frequency")]
+fn downsampling_config_error_latest_zero_freq() {
+    zenoh::init_log_from_env_or("error");
+
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "downsampling",
+            r#"
+              [
+                {
+                  flow: "egress",
+                  rules: [
+                    { key_expr: "test/downsamples_latest/zero", freq: 0, mode: "latest" },
+                  ],
+                },
+              ]
+            "#,
+        )
+        .unwrap();
+
+    zenoh::open(config).wait().unwrap();
+}
+
+fn metric_value(metrics: &str, prefix: &str) -> usize {
+    metrics
+        .lines()
+        .find(|line| line.starts_with(prefix))
+        .and_then(|line| line.rsplit(' ').next())
+        .and_then(|value| value.parse().ok())
+        .unwrap_or_else(|| panic!("{prefix} not found in metrics:\n{metrics}"))
+}
+
+fn quota_test(
+    locator: &str,
+    ke_prefix: &str,
+    quotas: Vec<QuotaItemConf>,
+    publishers: usize,
+    messages: usize,
+) -> (usize, usize, bool, String) {
+    let (pub_config, mut sub_config) = build_config(locator, vec![], InterceptorFlow::Ingress);
+    sub_config.set_quota(quotas).unwrap();
+    sub_config.adminspace.set_enabled(true).unwrap();
+
+    let sub_session = zenoh::open(sub_config).wait().unwrap();
+    let limited = Arc::new(AtomicUsize::new(0));
+    let free = Arc::new(AtomicUsize::new(0));
+    let last_limited = Arc::new(AtomicBool::new(false));
+    let _sub = sub_session
+        .declare_subscriber(format!("{ke_prefix}/*"))
+        .callback({
+            let limited = limited.clone();
+            let free = free.clone();
+            let last_limited = last_limited.clone();
+            let ke_limited = format!("{ke_prefix}/limited");
+            move |sample| {
+                let is_limited = sample.key_expr().as_str() == ke_limited;
+                if is_limited {
+                    limited.fetch_add(1, Ordering::SeqCst);
+                } else {
+                    free.fetch_add(1, Ordering::SeqCst);
+                }
+                last_limited.store(is_limited, Ordering::SeqCst);
+            }
+        })
+        .wait()
+        .unwrap();
+
+    // Each publisher needs its own config, and thus its own zid
+    let pub_sessions = iter::once(pub_config)
+        .chain((1..publishers).map(|_| build_config(locator, vec![], InterceptorFlow::Ingress).0))
+        .map(|config| zenoh::open(config).wait().unwrap())
+        .collect::<Vec<_>>();
+    std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+    for _ in 0..messages {
+        for pub_session in &pub_sessions {
+            for suffix in ["limited", "free"] {
+                pub_session
+                    .put(format!("{ke_prefix}/{suffix}"), "message")
+                    .wait()
+                    .unwrap();
+            }
+        }
+    }
+    std::thread::sleep(std::time::Duration::from_millis(3 * WARMUP_MS));
+
+    let metrics = sub_session
+        .get(format!("@/{}/peer/metrics", sub_session.zid()))
+        .wait()
+        .unwrap()
+        .recv()
+        .unwrap()
+        .result()
+        .unwrap()
+        .payload()
+        .try_to_string()
+        .unwrap()
+        .into_owned();
+
+    (
+        limited.load(Ordering::SeqCst),
+        free.load(Ordering::SeqCst),
+        last_limited.load(Ordering::SeqCst),
+        metrics,
+    )
+}
+
+#[test]
+fn quota_drop() {
+    zenoh::init_log_from_env_or("error");
+    let ke_prefix = "test/quota_drop";
+    let quota = QuotaItemConf {
+        id: Some("limited".to_string()),
+        // The quota subjects have the properties of the access control subjects
+        subjects: Some(vec![InterceptorSubjectConf {
+            link_protocols: Some(vec![LinkProtocol::Tcp]),
+            remote_networks: Some(vec!["127.0.0.0/8".parse().unwrap()]),
+            ..Default::default()
+        }]),
+        key_exprs: Some(vec![format!("{ke_prefix}/limited").try_into().unwrap()]),
+        bytes_per_sec: None,
+        messages_per_sec: Some(10.0),
+        burst: Some(1.0),
+        action: QuotaAction::Drop,
+        max_delay_ms: None,
+        flow: InterceptorFlow::Ingress,
+    };
+
+    let unused = QuotaItemConf {
+        id: Some("unused".to_string()),
+        key_exprs: Some(vec![format!("{ke_prefix}/unused").try_into().unwrap()]),
+        ..quota.clone()
+    };
+
+    let (limited, free, _, metrics) = quota_test(
+        "tcp/127.0.0.1:31449",
+        ke_prefix,
+        vec![quota, unused],
+        1,
+        100,
+    );
+    assert_eq!(free, 100);
+    assert!(limited > 0 && limited < 20, "received {limited} messages");
+    assert_eq!(
+        metric_value(&metrics, "zenoh_quota_dropped{quota=\"limited\""),
+        100 - limited
+    );
+    assert_eq!(
+        metric_value(&metrics, "zenoh_quota_dropped{quota=\"unused\""),
+        0
+    );
+    // The metric families of the quotas are described once
+    assert_eq!(metrics.matches("# TYPE zenoh_quota_dropped ").count(), 1);
+    assert_eq!(metrics.matches("# HELP zenoh_quota_dropped ").count(), 1);
+}
+
+#[test]
+fn quota_shared_by_subject() {
+    zenoh::init_log_from_env_or("error");
+    let ke_prefix = "test/quota_shared";
+    let quota = QuotaItemConf {
+        id: Some("shared".to_string()),
+        subjects: Some(vec![InterceptorSubjectConf {
+            link_protocols: Some(vec![LinkProtocol::Tcp]),
+            ..Default::default()
+        }]),
+        key_exprs: Some(vec![format!("{ke_prefix}/limited").try_into().unwrap()]),
+        bytes_per_sec: None,
+        messages_per_sec: Some(10.0),
+        burst: Some(1.0),
+        action: QuotaAction::Drop,
+        max_delay_ms: None,
+        flow: InterceptorFlow::Ingress,
+    };
+
+    // Both sessions match the subject of the quota, so they share its budget
+    let (limited, free, _, metrics) =
+        quota_test("tcp/127.0.0.1:31460", ke_prefix, vec![quota], 2, 100);
+    assert_eq!(free, 200);
+    assert!(limited > 0 && limited < 20, "received {limited} messages");
+    let dropped = metrics
+        .lines()
+        .filter(|line| line.starts_with("zenoh_quota_dropped{quota=\"shared\""))
+        .filter_map(|line| line.rsplit(' ').next()?.parse::<usize>().ok())
+        .sum::<usize>();
+    assert_eq!(dropped, 200 - limited);
+}
+
+#[test]
+fn quota_delay() {
+    zenoh::init_log_from_env_or("error");
+    let ke_prefix = "test/quota_delay";
+    let quota = QuotaItemConf {
+        id: Some("delayed".to_string()),
+        subjects: None,
+        key_exprs: Some(vec![format!("{ke_prefix}/limited").try_into().unwrap()]),
+        bytes_per_sec: Some(700.0),
+        messages_per_sec: None,
+        burst: Some(0.1),
+        action: QuotaAction::Delay,
+        max_delay_ms: Some(1000),
+        flow: InterceptorFlow::Ingress,
+    };
+
+    let (limited, free, last_limited, metrics) =
+        quota_test("tcp/127.0.0.1:31450", ke_prefix, vec![quota], 1, 20);
+    assert_eq!(free, 20);
+    assert_eq!(limited, 20);
+    // The other messages are queued behind the delayed ones, so the last sent is the last received
+    assert!(!last_limited);
+    assert!(metric_value(&metrics, "zenoh_quota_delayed{quota=\"delayed\"") > 0);
+    assert_eq!(
+        metric_value(&metrics, "zenoh_quota_dropped{quota=\"delayed\""),
+        0
+    );
+}
+
+#[test]
+#[should_panic(expected = "should define at least one of bytes_per_sec or messages_per_sec")]
+fn quota_config_error_no_rate() {
+    zenoh::init_log_from_env_or("error");
+
+    let mut config = Config::default();
+    config
+        .insert_json5("quota", r#"[ { flow: "ingress", action: "drop" } ]"#)
+        .unwrap();
+
+    zenoh::open(config).wait().unwrap();
+}
+
+#[test]
+fn rewrite_keyexpr() {
+    zenoh::init_log_from_env_or("error");
+    let ke_prefix = "test/rewrite";
+    let locator = "tcp/127.0.0.1:31451";
+
+    let rule = |from: &str, to: &str| RewriteRuleConf {
+        from: format!("{ke_prefix}/{from}").try_into().unwrap(),
+        to: format!("{ke_prefix}/{to}").try_into().unwrap(),
+    };
+    let (pub_config, mut sub_config) = build_config(locator, vec![], InterceptorFlow::Ingress);
+    sub_config
+        .set_rewrite(vec![
+            RewriteItemConf {
+                id: None,
+                subjects: None,
+                rules: vec![rule("site-a/**", "fleet/site-a/**")],
+                flow: InterceptorFlow::Ingress,
+            },
+            RewriteItemConf {
+                id: None,
+                subjects: None,
+                rules: vec![rule("fleet/site-a/**", "site-a/**")],
+                flow: InterceptorFlow::Egress,
+            },
+        ])
+        .unwrap();
+
+    let sub_session = zenoh::open(sub_config).wait().unwrap();
+    let received = Arc::new(std::sync::Mutex::new(vec![]));
+    let _sub = sub_session
+        .declare_subscriber(format!("{ke_prefix}/**"))
+        .callback({
+            let received = received.clone();
+            move |sample| received.lock().unwrap().push(sample.key_expr().to_string())
+        })
+        .wait()
+        .unwrap();
+    let _qbl = sub_session
+        .declare_queryable(format!("{ke_prefix}/fleet/site-a/*"))
+        .callback(|query| {
+            let key_expr = query.key_expr().clone();
+            query.reply(key_expr, "reply").wait().unwrap();
+        })
+        .wait()
+        .unwrap();
+
+    let pub_session = zenoh::open(pub_config).wait().unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+    for suffix in ["site-a/data", "site-b/data"] {
+        pub_session
+            .put(format!("{ke_prefix}/{suffix}"), "message")
+            .wait()
+            .unwrap();
+    }
+    let replies: Vec<String> = pub_session
+        .get(format!("{ke_prefix}/site-a/query"))
+        .wait()
+        .unwrap()
+        .iter()
+        .map(|reply| reply.result().unwrap().key_expr().to_string())
+        .collect();
+    std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+
+    assert_eq!(
+        *received.lock().unwrap(),
+        vec![
+            format!("{ke_prefix}/fleet/site-a/data"),
+            format!("{ke_prefix}/site-b/data")
+        ]
+    );
+    assert_eq!(replies, vec![format!("{ke_prefix}/site-a/query")]);
+}
+
+#[cfg(feature = "unstable")]
+#[test]
+fn rewrite_batch() {
+    zenoh::init_log_from_env_or("error");
+    let ke_prefix = "test/rewrite_batch";
+    let locator = "tcp/127.0.0.1:31457";
+
+    let (pub_config, mut sub_config) = build_config(locator, vec![], InterceptorFlow::Ingress);
+    sub_config
+        .set_rewrite(vec![RewriteItemConf {
+            id: None,
+            subjects: None,
+            rules: vec![RewriteRuleConf {
+                from: format!("{ke_prefix}/site-a/**").try_into().unwrap(),
+                to: format!("{ke_prefix}/fleet/site-a/**").try_into().unwrap(),
+            }],
+            flow: InterceptorFlow::Ingress,
+        }])
+        .unwrap();
+
+    let sub_session = zenoh::open(sub_config).wait().unwrap();
+    let received = Arc::new(std::sync::Mutex::new(vec![]));
+    let _sub = sub_session
+        .declare_subscriber(format!("{ke_prefix}/**"))
+        .batch_callback({
+            let received = received.clone();
+            move |batch| {
+                let keys: Vec<String> = batch
+                    .samples()
+                    .iter()
+                    .map(|sample| sample.key_expr().to_string())
+                    .collect();
+                received.lock().unwrap().push(keys)
+            }
+        })
+        .wait()
+        .unwrap();
+
+    let pub_session = zenoh::open(pub_config).wait().unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+    pub_session
+        .batch()
+        .put(format!("{ke_prefix}/site-a/data"), "message")
+        .put(format!("{ke_prefix}/site-b/data"), "message")
+        .wait()
+        .unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+
+    // The entries are rewritten one by one, the batch staying a unit
+    assert_eq!(
+        *received.lock().unwrap(),
+        vec![vec![
+            format!("{ke_prefix}/fleet/site-a/data"),
+            format!("{ke_prefix}/site-b/data")
+        ]]
+    );
+}
+
+#[test]
+#[should_panic(expected = "Invalid rewrite key-expression")]
+fn rewrite_config_error_wildcard() {
+    zenoh::init_log_from_env_or("error");
+
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "rewrite",
+            r#"[ { flow: "ingress", rules: [ { from: "a/*/b", to: "c/**" } ] } ]"#,
+        )
+        .unwrap();
+
+    zenoh::open(config).wait().unwrap();
+}
+
+#[test]
+fn qos_overwrite() {
+    zenoh::init_log_from_env_or("error");
+    let ke_prefix = "test/qos_overwrite";
+    let locator = "tcp/127.0.0.1:31455";
+
+    let (pub_config, mut sub_config) = build_config(locator, vec![], InterceptorFlow::Ingress);
+    sub_config
+        .insert_json5(
+            "qos/network",
+            &format!(
+                r#"[
+                    {{
+                        messages: ["put"],
+                        key_exprs: ["{ke_prefix}/bulk/**"],
+                        overwrite: {{ priority: "background", congestion_control: "drop", express: true }},
+                        flow: "ingress",
+                    }},
+                ]"#
+            ),
+        )
+        .unwrap();
+
+    let sub_session = zenoh::open(sub_config).wait().unwrap();
+    let received = Arc::new(std::sync::Mutex::new(vec![]));
+    let _sub = sub_session
+        .declare_subscriber(format!("{ke_prefix}/**"))
+        .callback({
+            let received = received.clone();
+            move |sample| {
+                received.lock().unwrap().push((
+                    sample.key_expr().to_string(),
+                    sample.priority(),
+                    sample.congestion_control(),
+                    sample.express(),
+                ))
+            }
+        })
+        .wait()
+        .unwrap();
+
+    let pub_session = zenoh::open(pub_config).wait().unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+    for suffix in ["bulk/data", "control/data"] {
+        pub_session
+            .put(format!("{ke_prefix}/{suffix}"), "message")
+            .priority(Priority::InteractiveHigh)
+            .congestion_control(CongestionControl::Block)
+            .wait()
+            .unwrap();
+    }
+    std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+
+    assert_eq!(
+        *received.lock().unwrap(),
+        vec![
+            (
+                format!("{ke_prefix}/bulk/data"),
+                Priority::Background,
+                CongestionControl::Drop,
+                true
+            ),
+            (
+                format!("{ke_prefix}/control/data"),
+                Priority::InteractiveHigh,
+                CongestionControl::Block,
+                false
+            ),
+        ]
+    );
+}
+
+#[test]
+#[should_panic(expected = "should define at least one of priority, congestion_control or express")]
+fn qos_overwrite_config_error_no_overwrite() {
+    zenoh::init_log_from_env_or("error");
+
+    let mut config = Config::default();
+    config
+        .insert_json5("qos/network", r#"[ { flow: "ingress", overwrite: {} } ]"#)
+        .unwrap();
+
+    zenoh::open(config).wait().unwrap();
+}
+
+#[cfg(feature = "unstable")]
+#[test]
+fn batch_entries_interceptors() {
+    zenoh::init_log_from_env_or("error");
+    let ke_prefix = "test/batch_entries";
+    let locator = "tcp/127.0.0.1:31461";
+
+    let ds_config = DownsamplingItemConf {
+        id: None,
+        flow: InterceptorFlow::Ingress,
+        interfaces: None,
+        rules: vec![DownsamplingRuleConf {
+            key_expr: format!("{ke_prefix}/sampled").try_into().unwrap(),
+            freq: 1.0,
+            mode: DownsamplingMode::Drop,
+            per_source: false,
+        }],
+    };
+    let (pub_config, mut sub_config) =
+        build_config(locator, vec![ds_config], InterceptorFlow::Ingress);
+    sub_config
+        .set_quota(vec![QuotaItemConf {
+            id: None,
+            subjects: None,
+            key_exprs: Some(vec![format!("{ke_prefix}/limited").try_into().unwrap()]),
+            bytes_per_sec: None,
+            messages_per_sec: Some(1.0),
+            burst: Some(1.0),
+            action: QuotaAction::Drop,
+            max_delay_ms: None,
+            flow: InterceptorFlow::Ingress,
+        }])
+        .unwrap();
+    sub_config
+        .insert_json5(
+            "qos/network",
+            &format!(
+                r#"[ {{ key_exprs: ["{ke_prefix}/bulk"], overwrite: {{ priority: "background" }}, flow: "ingress" }} ]"#
+            ),
+        )
+        .unwrap();
+
+    let sub_session = zenoh::open(sub_config).wait().unwrap();
+    let received = Arc::new(std::sync::Mutex::new(vec![]));
+    let _sub = sub_session
+        .declare_subscriber(format!("{ke_prefix}/**"))
+        .batch_callback({
+            let received = received.clone();
+            move |batch| {
+                let sample = &batch.samples()[1];
+                received
+                    .lock()
+                    .unwrap()
+                    .push((sample.key_expr().to_string(), sample.priority()))
+            }
+        })
+        .wait()
+        .unwrap();
+
+    let pub_session = zenoh::open(pub_config).wait().unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+    // The batches are routed on {ke_prefix}/**, intersecting the key expressions of all the
+    // interceptors, which only apply to the batches with a matching entry
+    for suffix in ["free", "sampled", "limited", "bulk"] {
+        for _ in 0..10 {
+            pub_session
+                .batch()
+                .put(format!("{ke_prefix}/first"), "message")
+                .put(format!("{ke_prefix}/{suffix}"), "message")
+                .wait()
+                .unwrap();
+        }
+    }
+    std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+
+    let received = received.lock().unwrap();
+    let count = |suffix: &str| {
+        received
+            .iter()
+            .filter(|(key, _)| *key == format!("{ke_prefix}/{suffix}"))
+            .count()
+    };
+    assert_eq!(count("free"), 10);
+    assert_eq!(count("sampled"), 1);
+    assert_eq!(count("limited"), 1);
+    assert_eq!(count("bulk"), 10);
+    for (key, priority) in received.iter() {
+        let expected = if *key == format!("{ke_prefix}/bulk") {
+            Priority::Background
+        } else {
+            Priority::default()
+        };
+        assert_eq!(*priority, expected, "{key}");
+    }
+}
+
+fn audit_test(locator: &str, ke_prefix: &str, audit: &str, puts: usize) {
+    let (pub_config, mut sub_config) = build_config(locator, vec![], InterceptorFlow::Ingress);
+    sub_config.insert_json5("audit", audit).unwrap();
+    sub_config
+        .insert_json5(
+            "access_control",
+            &format!(
+                r#"{{
+                  enabled: true,
+                  default_permission: "allow",
+                  rules: [
+                    {{
+                      id: "deny_put",
+                      messages: ["put"],
+                      flows: ["ingress"],
+                      permission: "deny",
+                      key_exprs: ["{ke_prefix}/denied"],
+                    }},
+                  ],
+                  subjects: [ {{ id: "all", interfaces: ["lo", "lo0"] }} ],
+                  policies: [ {{ rules: ["deny_put"], subjects: ["all"] }} ],
+                }}"#
+            ),
+        )
+        .unwrap();
+
+    let sub_session = zenoh::open(sub_config).wait().unwrap();
+    let _sub = sub_session
+        .declare_subscriber(format!("{ke_prefix}/**"))
+        .wait()
+        .unwrap();
+
+    let pub_session = zenoh::open(pub_config).wait().unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+    for _ in 0..puts {
+        for suffix in ["allowed", "denied", "unaudited/data"] {
+            pub_session
+                .put(format!("{ke_prefix}/{suffix}"), "message")
+                .wait()
+                .unwrap();
+        }
+    }
+    std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+}
+
+#[test]
+fn audit_log_json() {
+    zenoh::init_log_from_env_or("error");
+    let ke_prefix = "test/audit_json";
+    let path = std::env::temp_dir().join(format!("zenoh-audit-{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let audit = format!(
+        r#"{{ enabled: true, path: {:?}, flows: ["ingress"], key_exprs: ["{ke_prefix}/*"] }}"#,
+        path.display().to_string()
+    );
+    audit_test("tcp/127.0.0.1:31452", ke_prefix, &audit, 1);
+
+    let records: Vec<serde_json::Value> = std::fs::read_to_string(&path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let _ = std::fs::remove_file(&path);
+
+    let audited: Vec<(&str, &str, &str)> = records
+        .iter()
+        .map(|r| {
+            (
+                r["key_expr"].as_str().unwrap(),
+                r["message"].as_str().unwrap(),
+                r["decision"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        audited,
+        vec![
+            (format!("{ke_prefix}/allowed").as_str(), "put", "allow"),
+            (format!("{ke_prefix}/denied").as_str(), "put", "deny"),
+        ]
+    );
+    for record in &records {
+        assert_eq!(record["flow"], "ingress");
+        assert_eq!(record["size"], "message".len());
+        assert!(record["zid"].is_string());
+    }
+}
+
+#[cfg(feature = "unstable")]
+#[test]
+fn audit_log_batch() {
+    zenoh::init_log_from_env_or("error");
+    let ke_prefix = "test/audit_batch";
+    let locator = "tcp/127.0.0.1:31459";
+    let path = std::env::temp_dir().join(format!("zenoh-audit-batch-{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let (pub_config, mut sub_config) = build_config(locator, vec![], InterceptorFlow::Ingress);
+    sub_config
+        .insert_json5(
+            "audit",
+            &format!(
+                r#"{{ enabled: true, path: {:?}, flows: ["ingress"], key_exprs: ["{ke_prefix}/*"] }}"#,
+                path.display().to_string()
+            ),
+        )
+        .unwrap();
+
+    let sub_session = zenoh::open(sub_config).wait().unwrap();
+    let _sub = sub_session
+        .declare_subscriber(format!("{ke_prefix}/**"))
+        .wait()
+        .unwrap();
+
+    let pub_session = zenoh::open(pub_config).wait().unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+    pub_session
+        .batch()
+        .put(format!("{ke_prefix}/a"), "message")
+        .delete(format!("{ke_prefix}/b"))
+        .put(format!("{ke_prefix}/unaudited/data"), "message")
+        .wait()
+        .unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+
+    let records: Vec<serde_json::Value> = std::fs::read_to_string(&path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let _ = std::fs::remove_file(&path);
+
+    // One record per audited entry, with its own kind, key expression and size
+    let audited: Vec<(&str, &str, u64)> = records
+        .iter()
+        .map(|r| {
+            (
+                r["key_expr"].as_str().unwrap(),
+                r["message"].as_str().unwrap(),
+                r["size"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        audited,
+        vec![
+            (
+                format!("{ke_prefix}/a").as_str(),
+                "put",
+                "message".len() as u64
+            ),
+            (format!("{ke_prefix}/b").as_str(), "delete", 0),
+        ]
+    );
+}
+
+#[test]
+fn audit_log_binary_rotation() {
+    zenoh::init_log_from_env_or("error");
+    let ke_prefix = "test/audit_binary";
+    let path = std::env::temp_dir().join(format!("zenoh-audit-{}.bin", std::process::id()));
+    let rotated = |idx: usize| std::path::PathBuf::from(format!("{}.{idx}", path.display()));
+    let cleanup = || {
+        let _ = std::fs::remove_file(&path);
+        (1..=3).for_each(|idx| {
+            let _ = std::fs::remove_file(rotated(idx));
+        });
+    };
+    cleanup();
+
+    let audit = format!(
+        r#"{{ enabled: true, path: {:?}, format: "binary", flows: ["ingress"], key_exprs: ["{ke_prefix}/*"], max_file_size: 500, max_files: 2 }}"#,
+        path.display().to_string()
+    );
+    audit_test("tcp/127.0.0.1:31453", ke_prefix, &audit, 20);
+
+    let files = [path.clone(), rotated(1), rotated(2)];
+    let mut records = 0;
+    for file in &files {
+        let bytes = std::fs::read(file).unwrap();
+        assert!(bytes.len() <= 500);
+        let mut bytes = bytes.as_slice();
+        while !bytes.is_empty() {
+            let len = u32::from_le_bytes(bytes[..4].try_into().unwrap()) as usize;
+            bytes = &bytes[4 + len..];
+            records += 1;
+        }
+    }
+    assert!(!rotated(3).exists());
+    cleanup();
+    // The oldest records have been discarded by the rotation
+    assert!(records > 0 && records < 40, "{records} records");
+}
+
+#[test]
+fn namespaces() {
+    zenoh::init_log_from_env_or("error");
+    let locator = "tcp/127.0.0.1:31456";
+    let credentials =
+        std::env::temp_dir().join(format!("zenoh-namespaces-{}.txt", std::process::id()));
+    std::fs::write(&credentials, "alice:alicepwd\nbob:bobpwd\nadmin:adminpwd").unwrap();
+
+    let mut router_config = Config::default();
+    router_config.set_mode(Some(WhatAmI::Router)).unwrap();
+    router_config
+        .scouting
+        .multicast
+        .set_enabled(Some(false))
+        .unwrap();
+    router_config
+        .listen
+        .endpoints
+        .set(vec![locator.parse().unwrap()])
+        .unwrap();
+    router_config
+        .insert_json5(
+            "transport/auth/usrpwd",
+            &format!(
+                r#"{{ user: "router", password: "routerpwd", dictionary_file: {:?} }}"#,
+                credentials.display().to_string()
+            ),
+        )
+        .unwrap();
+    router_config
+        .insert_json5("adminspace", r#"{ enabled: true }"#)
+        .unwrap();
+    router_config
+        .insert_json5(
+            "namespaces",
+            r#"[
+                { id: "a", prefix: "tenants/a", subjects: [ { usernames: ["alice"] } ] },
+                { id: "b", prefix: "tenants/b", subjects: [ { usernames: ["bob"] } ] },
+            ]"#,
+        )
+        .unwrap();
+    let _router = zenoh::open(router_config).wait().unwrap();
+    std::fs::remove_file(&credentials).unwrap();
+
+    let client = |user: &str| {
+        let mut config = Config::default();
+        config.set_mode(Some(WhatAmI::Client)).unwrap();
+        config
+            .connect
+            .endpoints
+            .set(vec![locator.parse().unwrap()])
+            .unwrap();
+        config
+            .insert_json5(
+                "transport/auth/usrpwd",
+                &format!(r#"{{ user: "{user}", password: "{user}pwd" }}"#),
+            )
+            .unwrap();
+        zenoh::open(config).wait().unwrap()
+    };
+    let (alice, bob, admin) = (client("alice"), client("bob"), client("admin"));
+
+    let subscribe = |session: &zenoh::Session| {
+        let received = Arc::new(std::sync::Mutex::new(vec![]));
+        let sub = session
+            .declare_subscriber("**")
+            .callback({
+                let received = received.clone();
+                move |sample| received.lock().unwrap().push(sample.key_expr().to_string())
+            })
+            .wait()
+            .unwrap();
+        (sub, received)
+    };
+    let received = |received: &Arc<std::sync::Mutex<Vec<String>>>| {
+        let mut received = received.lock().unwrap().clone();
+        received.sort();
+        received
+    };
+    let (_alice_sub, alice_received) = subscribe(&alice);
+    let (_bob_sub, bob_received) = subscribe(&bob);
+    let (_admin_sub, admin_received) = subscribe(&admin);
+    let _alice_qbl = alice
+        .declare_queryable("q/*")
+        .callback(|query| {
+            let key_expr = query.key_expr().clone();
+            query.reply(key_expr, "reply").wait().unwrap();
+        })
+        .wait()
+        .unwrap();
+    let _alice_token = alice.liveliness().declare_token("alice").wait().unwrap();
+    let _bob_token = bob.liveliness().declare_token("bob").wait().unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+
+    admin.put("tenants/a/data", "message").wait().unwrap();
+    alice.put("data/1", "message").wait().unwrap();
+    bob.put("data/2", "message").wait().unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+    assert_eq!(received(&alice_received), ["data", "data/1"]);
+    assert_eq!(received(&bob_received), ["data/2"]);
+    assert_eq!(
+        received(&admin_received),
+        ["tenants/a/data", "tenants/a/data/1", "tenants/b/data/2"]
+    );
+
+    let keys = |replies: zenoh::handlers::FifoChannelHandler<zenoh::query::Reply>| {
+        let mut keys: Vec<String> = replies
+            .iter()
+            .map(|reply| reply.result().unwrap().key_expr().to_string())
+            .collect();
+        keys.sort();
+        keys
+    };
+    assert_eq!(
+        keys(admin.get("tenants/a/q/1").wait().unwrap()),
+        ["tenants/a/q/1"]
+    );
+    assert_eq!(keys(alice.get("q/2").wait().unwrap()), ["q/2"]);
+    assert!(keys(bob.get("**").wait().unwrap()).is_empty());
+
+    assert_eq!(
+        keys(admin.liveliness().get("tenants/**").wait().unwrap()),
+        ["tenants/a/alice", "tenants/b/bob"]
+    );
+    assert!(!keys(alice.liveliness().get("**").wait().unwrap())
+        .iter()
+        .any(|key| key.contains("bob")));
+
+    // The admin space is out of reach of the confined subjects
+    assert_eq!(admin.get("@/*/router").wait().unwrap().iter().count(), 1);
+    assert_eq!(
+        bob.get("@/*/router")
+            .timeout(std::time::Duration::from_secs(1))
+            .wait()
+            .unwrap()
+            .iter()
+            .count(),
+        0
+    );
+}
+
+#[cfg(feature = "unstable")]
+#[test]
+fn namespaces_batch() {
+    zenoh::init_log_from_env_or("error");
+    let locator = "tcp/127.0.0.1:31458";
+
+    let mut router_config = Config::default();
+    router_config.set_mode(Some(WhatAmI::Router)).unwrap();
+    router_config
+        .scouting
+        .multicast
+        .set_enabled(Some(false))
+        .unwrap();
+    router_config
+        .listen
+        .endpoints
+        .set(vec![locator.parse().unwrap()])
+        .unwrap();
+    router_config
+        .insert_json5(
+            "namespaces",
+            r#"[ { id: "a", prefix: "tenants/a", subjects: [ { zids: ["a1a1"] } ] } ]"#,
+        )
+        .unwrap();
+    let _router = zenoh::open(router_config).wait().unwrap();
+
+    let client = |zid: Option<&str>| {
+        let mut config = Config::default();
+        config.set_mode(Some(WhatAmI::Client)).unwrap();
+        if let Some(zid) = zid {
+            config.set_id(zid.parse().unwrap()).unwrap();
+        }
+        config
+            .connect
+            .endpoints
+            .set(vec![locator.parse().unwrap()])
+            .unwrap();
+        zenoh::open(config).wait().unwrap()
+    };
+    let (alice, admin) = (client(Some("a1a1")), client(None));
+
+    let subscribe = |session: &zenoh::Session| {
+        let received = Arc::new(std::sync::Mutex::new(vec![]));
+        let sub = session
+            .declare_subscriber("**")
+            .batch_callback({
+                let received = received.clone();
+                move |batch| {
+                    let keys: Vec<String> = batch
+                        .samples()
+                        .iter()
+                        .map(|sample| sample.key_expr().to_string())
+                        .collect();
+                    received.lock().unwrap().push(keys)
+                }
+            })
+            .wait()
+            .unwrap();
+        (sub, received)
+    };
+    let received = |received: &Arc<std::sync::Mutex<Vec<Vec<String>>>>| {
+        let mut received = std::mem::take(&mut *received.lock().unwrap());
+        received.sort();
+        received
+    };
+    let (_alice_sub, alice_received) = subscribe(&alice);
+    let (_admin_sub, admin_received) = subscribe(&admin);
+    std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+
+    // The entries outside the namespace are dropped, the others stripped
+    admin
+        .batch()
+        .put("tenants/a/data/1", "message")
+        .put("tenants/a/data/2", "message")
+        .put("tenants/b/data/3", "message")
+        .wait()
+        .unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+    assert_eq!(received(&alice_received), [["data/1", "data/2"]]);
+    assert_eq!(
+        received(&admin_received),
+        [["tenants/a/data/1", "tenants/a/data/2", "tenants/b/data/3"]]
+    );
+
+    // The stripped entries sharing no common prefix are still received as a single batch
+    admin
+        .batch()
+        .put("tenants/a/x/1", "message")
+        .put("tenants/a/y/2", "message")
+        .wait()
+        .unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+    assert_eq!(received(&alice_received), [["x/1", "y/2"]]);
+    received(&admin_received);
+
+    // The entries published in the namespace are prefixed
+    alice
+        .batch()
+        .put("data/1", "message")
+        .delete("data/2")
+        .wait()
+        .unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+    assert_eq!(
+        received(&admin_received),
+        [["tenants/a/data/1", "tenants/a/data/2"]]
+    );
+    assert_eq!(received(&alice_received), [["data/1", "data/2"]]);
+}
+
+#[test]
+#[should_panic(expected = "only wildcard-free prefixes are supported")]
+fn namespaces_config_error_wildcard() {
+    zenoh::init_log_from_env_or("error");
+
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "namespaces",
+            r#"[ { prefix: "tenants/*", subjects: [ { usernames: ["alice"] } ] } ]"#,
+        )
+        .unwrap();
+
+    zenoh::open(config).wait().unwrap();
+}
+
+#[cfg(feature = "unstable")]
+mod custom {
+    use std::sync::Mutex;
+
+    use zenoh::interceptor::{
+        protocol::network::NetworkBody, EgressInterceptor, IngressInterceptor,
+        InterceptorFactoryTrait, InterceptorTrait, NetworkMessage, RoutingContext,
+        TransportUnicast,
+    };
+
+    use super::*;
+
+    struct TestInterceptor {
+        drop_ke: String,
+        reroute_from: String,
+        reroute_to: String,
+    }
+
+    impl InterceptorTrait for TestInterceptor {
+        fn intercept(
+            &self,
+            mut ctx: RoutingContext<NetworkMessage>,
+            _cache: Option<&Box<dyn std::any::Any + Send + Sync>>,
+        ) -> Option<RoutingContext<NetworkMessage>> {
+            if !matches!(ctx.msg().body, NetworkBody::Push(_)) {
+                return Some(ctx);
+            }
+            assert_eq!(ctx.inface_whatami(), Some(WhatAmI::Peer));
+            assert!(ctx.inface_zid().is_some());
+            match ctx.full_expr() {
+                Some(ke) if ke == self.drop_ke => None,
+                Some(ke) if ke == self.reroute_from => {
+                    if let NetworkBody::Push(push) = &mut ctx.msg_mut().body {
+                        push.wire_expr = self.reroute_to.clone().into();
+                    }
+                    assert_eq!(ctx.full_expr(), Some(self.reroute_to.as_str()));
+                    Some(ctx)
+                }
+                _ => Some(ctx),
+            }
+        }
+    }
+
+    struct TestInterceptorFactory {
+        ke_prefix: String,
+    }
+
+    impl InterceptorFactoryTrait for TestInterceptorFactory {
+        fn new_transport_unicast(
+            &self,
+            transport: &TransportUnicast,
+        ) -> (Option<IngressInterceptor>, Option<EgressInterceptor>) {
+            assert!(transport.get_zid().is_ok());
+            (
+                Some(Box::new(TestInterceptor {
+                    drop_ke: format!("{}/drop", self.ke_prefix),
+                    reroute_from: format!("{}/reroute", self.ke_prefix),
+                    reroute_to: format!("{}/rerouted", self.ke_prefix),
+                })),
+                None,
+            )
+        }
+    }
+
+    #[test]
+    fn custom_interceptor() {
+        zenoh::init_log_from_env_or("error");
+        let ke_prefix = "test/custom_interceptor";
+        let locator = "tcp/127.0.0.1:31448";
+
+        let (pub_config, sub_config) = build_config(locator, vec![], InterceptorFlow::Ingress);
+
+        let sub_session = zenoh::open(sub_config)
+            .with_interceptor_factory(Box::new(TestInterceptorFactory {
+                ke_prefix: ke_prefix.to_string(),
+            }))
+            .wait()
+            .unwrap();
+        let received = Arc::new(Mutex::new(vec![]));
+        let _sub = sub_session
+            .declare_subscriber(format!("{ke_prefix}/*"))
+            .callback({
+                let received = received.clone();
+                move |sample| received.lock().unwrap().push(sample.key_expr().to_string())
+            })
+            .wait()
+            .unwrap();
+
+        let pub_session = zenoh::open(pub_config).wait().unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+        for suffix in ["pass", "drop", "reroute"] {
+            pub_session
+                .put(format!("{ke_prefix}/{suffix}"), "message")
+                .wait()
+                .unwrap();
+        }
+        std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+
+        let received = received.lock().unwrap().clone();
+        assert_eq!(
+            received,
+            vec![format!("{ke_prefix}/pass"), format!("{ke_prefix}/rerouted")]
+        );
+    }
+}
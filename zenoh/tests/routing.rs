@@ -17,14 +17,19 @@
 use std::{
     sync::{
         atomic::{AtomicUsize, Ordering},
-        Arc,
+        Arc, Mutex,
     },
     time::Duration,
 };
 
 use itertools::Itertools;
 use tokio_util::sync::CancellationToken;
-use zenoh::{config::WhatAmI, qos::CongestionControl, Config, Result, Session};
+use zenoh::{
+    config::WhatAmI,
+    qos::CongestionControl,
+    query::{ConsolidationMode, QueryTarget},
+    Config, Result, Session, Wait,
+};
 use zenoh_config::{ModeDependentValue, WhatAmIMatcher};
 use zenoh_core::ztimeout;
 use zenoh_result::bail;
@@ -1420,3 +1425,1541 @@ async fn router_linkstate() -> Result<()> {
     println!("Router linkstate test passed.");
     Ok(())
 }
+
+fn router_config(id: &str, listen: &str, connect: &[String]) -> Result<Config> {
+    let mut config = Config::default();
+    config.set_mode(Some(WhatAmI::Router)).unwrap();
+    config.set_id(id.parse()?).unwrap();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config.listen.endpoints.set(vec![listen.parse()?]).unwrap();
+    config
+        .connect
+        .endpoints
+        .set(connect.iter().map(|c| c.parse()).collect::<Result<_>>()?)
+        .unwrap();
+    Ok(config)
+}
+
+fn client_config(connect: &str) -> Result<Config> {
+    let mut config = Config::default();
+    config.set_mode(Some(WhatAmI::Client)).unwrap();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config
+        .connect
+        .endpoints
+        .set(vec![connect.parse()?])
+        .unwrap();
+    Ok(config)
+}
+
+async fn close_sessions(sessions: impl IntoIterator<Item = Session>) -> Result<()> {
+    for session in sessions {
+        ztimeout!(session.close())?;
+    }
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn router_linkstate_weights() -> Result<()> {
+    zenoh_util::try_init_log_from_env();
+    let ke = "router_linkstate_weights";
+    let base_port = 17700;
+
+    // Routers 1, 2 and 3 are connected to each other. Router 2 drops the puts it forwards,
+    // so the puts from router 1 only reach router 3 if they take the direct link.
+    // With round-trip time probes, the loopback links get a weight far below the default one.
+    for (idx, (weight, rtt_probes, expected)) in [
+        (None, false, 1),
+        (Some(1000), false, 0),
+        (Some(150), false, 1),
+        (Some(150), true, 0),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let locators: Vec<String> = (0..3)
+            .map(|i| format!("tcp/127.0.0.1:{}", base_port + idx * 3 + i))
+            .collect();
+        let linkstate = |transport_weights: &str| {
+            format!(
+                "{{ transport_weights: [{transport_weights}], rtt_probe_interval_ms: {} }}",
+                if rtt_probes { "100" } else { "null" }
+            )
+        };
+
+        let mut config1 = router_config("a1", &locators[0], &[])?;
+        config1
+            .insert_json5(
+                "routing/router/linkstate",
+                &linkstate(&weight.map_or(String::new(), |weight| {
+                    format!(r#"{{ dst_zid: "a3", weight: {weight} }}"#)
+                })),
+            )
+            .unwrap();
+        let mut config2 = router_config("a2", &locators[1], &locators[..1])?;
+        config2
+            .insert_json5(
+                "access_control",
+                &format!(
+                    r#"{{
+                        "enabled": true,
+                        "default_permission": "allow",
+                        "rules": [{{ "id": "r1", "permission": "deny", "flows": ["egress"], "messages": ["put"], "key_exprs": ["{ke}"] }}],
+                        "subjects": [{{ "id": "all" }}],
+                        "policies": [{{ "rules": ["r1"], "subjects": ["all"] }}]
+                    }}"#
+                ),
+            )
+            .unwrap();
+        config2
+            .insert_json5("routing/router/linkstate", &linkstate(""))
+            .unwrap();
+        let mut config3 = router_config("a3", &locators[2], &locators[..2])?;
+        config3
+            .insert_json5("routing/router/linkstate", &linkstate(""))
+            .unwrap();
+        let router1 = ztimeout!(zenoh::open(config1))?;
+        let router2 = ztimeout!(zenoh::open(config2))?;
+        let router3 = ztimeout!(zenoh::open(config3))?;
+
+        let sub_session = ztimeout!(zenoh::open(client_config(&locators[2])?))?;
+        let received = Arc::new(AtomicUsize::new(0));
+        let _sub = ztimeout!(sub_session.declare_subscriber(ke).callback({
+            let received = received.clone();
+            move |_| {
+                received.fetch_add(1, Ordering::Relaxed);
+            }
+        }))?;
+        let pub_session = ztimeout!(zenoh::open(client_config(&locators[0])?))?;
+        tokio::time::sleep(Duration::from_secs(2)).await;
+
+        ztimeout!(pub_session.put(ke, "value"))?;
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert_eq!(received.load(Ordering::Relaxed), expected);
+
+        close_sessions([pub_session, sub_session, router3, router2, router1]).await?;
+    }
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn router_query_load_balancing() -> Result<()> {
+    zenoh_util::try_init_log_from_env();
+    let ke = "router_query_load_balancing";
+    let base_port = 17712;
+    const QUERY_COUNT: usize = 10;
+
+    for (idx, (policy, expected)) in [
+        ("first", [QUERY_COUNT, 0]),
+        ("round_robin", [QUERY_COUNT / 2, QUERY_COUNT / 2]),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let locator = format!("tcp/127.0.0.1:{}", base_port + idx);
+        let mut config = router_config("a1", &locator, &[])?;
+        config
+            .insert_json5("routing/queries/load_balancing", &format!(r#""{policy}""#))
+            .unwrap();
+        config
+            .insert_json5("adminspace", r#"{ "enabled": true }"#)
+            .unwrap();
+        let router = ztimeout!(zenoh::open(config))?;
+
+        let mut qabl_sessions = vec![];
+        let mut qabls = vec![];
+        for i in 0..2 {
+            let session = ztimeout!(zenoh::open(client_config(&locator)?))?;
+            let qabl =
+                ztimeout!(session
+                    .declare_queryable(ke)
+                    .complete(true)
+                    .callback(move |query| {
+                        query.reply(ke, i.to_string()).wait().unwrap();
+                    }))?;
+            qabl_sessions.push(session);
+            qabls.push(qabl);
+        }
+        let get_session = ztimeout!(zenoh::open(client_config(&locator)?))?;
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let mut received = [0; 2];
+        for _ in 0..QUERY_COUNT {
+            let replies = ztimeout!(get_session.get(ke))?;
+            while let Ok(reply) = ztimeout!(replies.recv_async()) {
+                let idx: usize = reply.result().unwrap().payload().try_to_string()?.parse()?;
+                received[idx] += 1;
+            }
+        }
+        received.sort_unstable_by(|a, b| b.cmp(a));
+        assert_eq!(received, expected);
+
+        let replies =
+            ztimeout!(router.get(format!("@/{}/router/queries/load_balancing", router.zid())))?;
+        let reply = ztimeout!(replies.recv_async())?;
+        let stats: serde_json::Value =
+            serde_json::from_slice(&reply.result().unwrap().payload().to_bytes())?;
+        assert_eq!(stats["policy"], policy);
+        let mut routed = stats["routed"]
+            .as_object()
+            .unwrap()
+            .values()
+            .map(|count| count.as_u64().unwrap() as usize)
+            .collect::<Vec<_>>();
+        routed.sort_unstable_by(|a, b| b.cmp(a));
+        assert_eq!(
+            routed,
+            expected.into_iter().filter(|c| *c > 0).collect::<Vec<_>>()
+        );
+
+        for qabl in qabls {
+            ztimeout!(qabl.undeclare())?;
+        }
+        close_sessions(qabl_sessions.into_iter().chain([get_session, router])).await?;
+    }
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn router_query_least_outstanding() -> Result<()> {
+    zenoh_util::try_init_log_from_env();
+    let ke = "router_query_least_outstanding";
+    let locator = "tcp/127.0.0.1:17751";
+    const QUERY_COUNT: usize = 10;
+    const HELD_COUNT: usize = 4;
+
+    let mut config = router_config("a1", locator, &[])?;
+    config
+        .insert_json5("routing/queries/load_balancing", r#""least_outstanding""#)
+        .unwrap();
+    let router = ztimeout!(zenoh::open(config))?;
+
+    let qabl_sessions = [
+        ztimeout!(zenoh::open(client_config(locator)?))?,
+        ztimeout!(zenoh::open(client_config(locator)?))?,
+    ];
+    // The first session also holds queries on another queryable, they must not count
+    let held = Arc::new(Mutex::new(vec![]));
+    let c_held = held.clone();
+    let held_qabl = ztimeout!(qabl_sessions[0]
+        .declare_queryable(format!("{ke}/held"))
+        .complete(true)
+        .callback(move |query| c_held.lock().unwrap().push(query)))?;
+    let mut qabls = vec![];
+    for (i, session) in qabl_sessions.iter().enumerate() {
+        qabls.push(ztimeout!(session
+            .declare_queryable(ke)
+            .complete(true)
+            .callback(move |query| {
+                // Replies are delayed so that all the queries are outstanding at once
+                std::thread::spawn(move || {
+                    std::thread::sleep(Duration::from_millis(500));
+                    query.reply(ke, i.to_string()).wait().unwrap();
+                });
+            }))?);
+    }
+    let get_session = ztimeout!(zenoh::open(client_config(locator)?))?;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    let mut held_replies = vec![];
+    for _ in 0..HELD_COUNT {
+        held_replies.push(ztimeout!(get_session.get(format!("{ke}/held")))?);
+    }
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert_eq!(held.lock().unwrap().len(), HELD_COUNT);
+
+    let mut replies = vec![];
+    for _ in 0..QUERY_COUNT {
+        replies.push(ztimeout!(get_session.get(ke))?);
+    }
+    let mut received = [0; 2];
+    for replies in replies {
+        while let Ok(reply) = ztimeout!(replies.recv_async()) {
+            let idx: usize = reply.result().unwrap().payload().try_to_string()?.parse()?;
+            received[idx] += 1;
+        }
+    }
+    assert_eq!(received, [QUERY_COUNT / 2; 2]);
+
+    held.lock().unwrap().clear();
+    drop(held_replies);
+    ztimeout!(held_qabl.undeclare())?;
+    for qabl in qabls {
+        ztimeout!(qabl.undeclare())?;
+    }
+    close_sessions(qabl_sessions.into_iter().chain([get_session, router])).await
+}
+
+async fn explain_route(router: &Session, parameters: &str) -> Result<serde_json::Value> {
+    let replies = ztimeout!(router.get(format!("@/{}/router/route?{parameters}", router.zid())))?;
+    let reply = ztimeout!(replies.recv_async())?;
+    match reply.result() {
+        Ok(sample) => Ok(serde_json::from_slice(&sample.payload().to_bytes())?),
+        Err(err) => bail!("{}", err.payload().try_to_string()?),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn router_route_explanation() -> Result<()> {
+    zenoh_util::try_init_log_from_env();
+    let ke = "router_route_explanation";
+    let locator = "tcp/127.0.0.1:17714".to_string();
+
+    let mut config = router_config("a1", &locator, &[])?;
+    config
+        .insert_json5("adminspace", r#"{ "enabled": true }"#)
+        .unwrap();
+    config
+        .insert_json5(
+            "downsampling",
+            &format!(
+                r#"[{{ "flow": "egress", "rules": [{{ "key_expr": "{ke}", "freq": 10.0 }}] }}]"#
+            ),
+        )
+        .unwrap();
+    let router = ztimeout!(zenoh::open(config))?;
+
+    let sub_session = ztimeout!(zenoh::open(client_config(&locator)?))?;
+    let _sub = ztimeout!(sub_session.declare_subscriber(ke).callback(|_| ()))?;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    let route = explain_route(&router, &format!("ke={ke}")).await?;
+    assert_eq!(route["message"], "put");
+    assert_eq!(route["accepted"], true);
+    let hops = route["hops"].as_array().unwrap();
+    assert_eq!(hops.len(), 1);
+    assert_eq!(hops[0]["zid"], sub_session.zid().to_string());
+    assert_eq!(hops[0]["forwarded"], true);
+    assert_eq!(hops[0]["declarations"], serde_json::json!([ke]));
+    let interceptors = hops[0]["interceptors"].as_array().unwrap();
+    assert_eq!(interceptors.len(), 1);
+    assert!(interceptors[0]
+        .as_str()
+        .unwrap()
+        .starts_with("downsampling"));
+
+    // The hop limit of the message is reached
+    let route = explain_route(&router, &format!("ke={ke};hop_limit=0")).await?;
+    assert_eq!(route["hops"][0]["forwarded"], false);
+    assert!(explain_route(&router, &format!("ke={ke};hop_limit=256"))
+        .await
+        .is_err());
+
+    // Queries are not downsampled and there is no queryable
+    let route = explain_route(&router, &format!("ke={ke};kind=query")).await?;
+    assert_eq!(route["hops"], serde_json::json!([]));
+
+    assert!(explain_route(&router, &format!("ke={ke};kind=reply"))
+        .await
+        .is_err());
+    assert!(explain_route(&router, "kind=put").await.is_err());
+
+    ztimeout!(sub_session.close())?;
+    ztimeout!(router.close())?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn router_hop_limit() -> Result<()> {
+    zenoh_util::try_init_log_from_env();
+    let ke = "router_hop_limit";
+    let base_port = 17715;
+
+    // Routers 1, 2 and 3 form a chain. A message sent with a hop limit of 3 by a client of
+    // router 1 crosses the links to router 1, router 2 and a client of router 2, but not
+    // the link to router 3.
+    let locators: Vec<String> = (0..3)
+        .map(|i| format!("tcp/127.0.0.1:{}", base_port + i))
+        .collect();
+    let router1 = ztimeout!(zenoh::open(router_config("a1", &locators[0], &[])?))?;
+    let router2 = ztimeout!(zenoh::open(router_config(
+        "a2",
+        &locators[1],
+        &locators[..1]
+    )?))?;
+    let router3 = ztimeout!(zenoh::open(router_config(
+        "a3",
+        &locators[2],
+        &locators[1..2]
+    )?))?;
+
+    let client = |locator: &String, hop_limit: Option<u8>| {
+        let mut config = client_config(locator)?;
+        if let Some(hop_limit) = hop_limit {
+            config
+                .insert_json5("routing/hop_limit", &hop_limit.to_string())
+                .unwrap();
+        }
+        Ok::<_, zenoh::Error>(config)
+    };
+    let mut sessions = vec![];
+    let mut subs = vec![];
+    let mut qabls = vec![];
+    let mut received = vec![];
+    for locator in &locators[1..] {
+        let session = ztimeout!(zenoh::open(client(locator, None)?))?;
+        let counter = Arc::new(AtomicUsize::new(0));
+        subs.push(ztimeout!(session.declare_subscriber(ke).callback({
+            let counter = counter.clone();
+            move |_| {
+                counter.fetch_add(1, Ordering::Relaxed);
+            }
+        }))?);
+        qabls.push(ztimeout!(session
+            .declare_queryable(ke)
+            .callback(move |query| query.reply(ke, "reply").wait().unwrap()))?);
+        received.push(counter);
+        sessions.push(session);
+    }
+    let limited_session = ztimeout!(zenoh::open(client(&locators[0], Some(3))?))?;
+    let unlimited_session = ztimeout!(zenoh::open(client(&locators[0], None)?))?;
+    tokio::time::sleep(Duration::from_secs(2)).await;
+
+    ztimeout!(limited_session.put(ke, "value"))?;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    let counts = || {
+        received
+            .iter()
+            .map(|c| c.load(Ordering::Relaxed))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(counts(), [1, 0]);
+
+    ztimeout!(unlimited_session.put(ke, "value"))?;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(counts(), [2, 1]);
+
+    for (session, expected) in [(&limited_session, 1), (&unlimited_session, 2)] {
+        let replies = ztimeout!(session
+            .get(ke)
+            .target(QueryTarget::All)
+            .consolidation(ConsolidationMode::None))?;
+        let mut count = 0;
+        while ztimeout!(replies.recv_async()).is_ok() {
+            count += 1;
+        }
+        assert_eq!(count, expected);
+    }
+
+    drop((subs, qabls));
+    close_sessions(sessions.into_iter().chain([
+        limited_session,
+        unlimited_session,
+        router3,
+        router2,
+        router1,
+    ]))
+    .await
+}
+
+#[cfg(feature = "unstable")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn router_redundancy() -> Result<()> {
+    use zenoh::sample::SourceInfo;
+
+    zenoh_util::try_init_log_from_env();
+    let ke = "router_redundancy";
+    let base_port = 17718;
+    const PUT_COUNT: usize = 10;
+
+    // Routers 1, 2, 4 and 3 form a ring where the heavy link between routers 1 and 3 makes
+    // the tree of router 1 go through router 2, which drops the puts it forwards when broken.
+    // The redundant tree of router 1 reaches router 4 through router 3, duplicates being
+    // dropped by router 4.
+    for (idx, (redundant, broken, expected)) in [
+        (false, true, 0),
+        (true, true, PUT_COUNT),
+        (true, false, PUT_COUNT),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let locators: Vec<String> = (0..4)
+            .map(|i| format!("tcp/127.0.0.1:{}", base_port + idx * 4 + i))
+            .collect();
+        let redundancy = |config: &mut Config| {
+            if redundant {
+                config
+                    .insert_json5(
+                        "routing/router/redundancy",
+                        &format!(r#"{{ key_exprs: ["{ke}/**"] }}"#),
+                    )
+                    .unwrap();
+            }
+        };
+        let mut config1 = router_config("a1", &locators[0], &[])?;
+        config1
+            .insert_json5(
+                "routing/router/linkstate",
+                r#"{ transport_weights: [{ dst_zid: "a3", weight: 1000 }] }"#,
+            )
+            .unwrap();
+        redundancy(&mut config1);
+        let mut config2 = router_config("a2", &locators[1], &locators[..1])?;
+        if broken {
+            config2
+                .insert_json5(
+                    "access_control",
+                    &format!(
+                        r#"{{
+                            "enabled": true,
+                            "default_permission": "allow",
+                            "rules": [{{ "id": "r1", "permission": "deny", "flows": ["egress"], "messages": ["put"], "key_exprs": ["{ke}/**"] }}],
+                            "subjects": [{{ "id": "all" }}],
+                            "policies": [{{ "rules": ["r1"], "subjects": ["all"] }}]
+                        }}"#
+                    ),
+                )
+                .unwrap();
+        }
+        redundancy(&mut config2);
+        let mut config3 = router_config("a3", &locators[2], &locators[..1])?;
+        redundancy(&mut config3);
+        let mut config4 = router_config("a4", &locators[3], &locators[1..3])?;
+        redundancy(&mut config4);
+        let router1 = ztimeout!(zenoh::open(config1))?;
+        let router2 = ztimeout!(zenoh::open(config2))?;
+        let router3 = ztimeout!(zenoh::open(config3))?;
+        let router4 = ztimeout!(zenoh::open(config4))?;
+
+        let sub_session = ztimeout!(zenoh::open(client_config(&locators[3])?))?;
+        let received = Arc::new(Mutex::new(vec![]));
+        let _sub = ztimeout!(sub_session
+            .declare_subscriber(format!("{ke}/**"))
+            .callback({
+                let received = received.clone();
+                move |sample| {
+                    let source_id = sample.source_info().source_id().cloned();
+                    received.lock().unwrap().push(source_id);
+                }
+            }))?;
+        let pub_session = ztimeout!(zenoh::open(client_config(&locators[0])?))?;
+        let source_id = ztimeout!(pub_session.declare_publisher(format!("{ke}/pub")))?.id();
+        tokio::time::sleep(Duration::from_secs(2)).await;
+
+        // Half of the puts share the same source info without sequence number, none of them
+        // is a duplicate
+        for i in 0..PUT_COUNT {
+            let put = pub_session.put(format!("{ke}/{i}"), "value");
+            match i % 2 {
+                0 => ztimeout!(put)?,
+                _ => ztimeout!(put.source_info(SourceInfo::new(Some(source_id), None)))?,
+            }
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        let received = received.lock().unwrap().clone();
+        assert_eq!(received.len(), expected);
+        // The source info of the puts is left untouched
+        assert_eq!(
+            received.iter().filter(|id| id.is_none()).count(),
+            expected / 2
+        );
+        assert!(received.iter().flatten().all(|id| *id == source_id));
+
+        close_sessions([pub_session, sub_session, router4, router3, router2, router1]).await?;
+    }
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn router_topology_events() -> Result<()> {
+    use zenoh::session::TopologyEvent;
+
+    zenoh_util::try_init_log_from_env();
+    let base_port = 17730;
+
+    // Routers 1, 2 and 3 form a chain. Router 1 sees the transport to router 2 open and close,
+    // and routers 2 and 3 join and leave its link-state graph.
+    let locators: Vec<String> = (0..3)
+        .map(|i| format!("tcp/127.0.0.1:{}", base_port + i))
+        .collect();
+    let router1 = ztimeout!(zenoh::open(router_config("b1", &locators[0], &[])?))?;
+    let listener = ztimeout!(router1.info().topology_listener())?;
+
+    let router2 = ztimeout!(zenoh::open(router_config(
+        "b2",
+        &locators[1],
+        &locators[..1]
+    )?))?;
+    let router3 = ztimeout!(zenoh::open(router_config(
+        "b3",
+        &locators[2],
+        &locators[1..2]
+    )?))?;
+    let (zid1, zid2, zid3) = (router1.zid(), router2.zid(), router3.zid());
+    tokio::time::sleep(Duration::from_secs(2)).await;
+
+    let events = listener.drain().collect::<Vec<_>>();
+    assert!(events.iter().any(|event| matches!(
+        event,
+        TopologyEvent::TransportOpened { zid, whatami: WhatAmI::Router, locators }
+            if *zid == zid2 && !locators.is_empty()
+    )));
+    for added in [zid2, zid3] {
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, TopologyEvent::NodeAdded { zid, .. } if *zid == added)));
+    }
+    assert!(events.iter().any(|event| matches!(
+        event,
+        TopologyEvent::LinksChanged { zid, links } if *zid == zid1 && links == &[zid2]
+    )));
+    assert!(events.iter().any(|event| matches!(
+        event,
+        TopologyEvent::LinksChanged { zid, links } if *zid == zid2 && links.contains(&zid3)
+    )));
+
+    ztimeout!(router2.close())?;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    let events = listener.drain().collect::<Vec<_>>();
+    assert!(events.iter().any(|event| matches!(
+        event,
+        TopologyEvent::TransportClosed { zid, whatami: WhatAmI::Router } if *zid == zid2
+    )));
+    for removed in [zid2, zid3] {
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, TopologyEvent::NodeRemoved { zid } if *zid == removed)));
+    }
+    assert!(events.iter().any(|event| matches!(
+        event,
+        TopologyEvent::LinksChanged { zid, links } if *zid == zid1 && links.is_empty()
+    )));
+
+    ztimeout!(listener.undeclare())?;
+    close_sessions([router3, router1]).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn router_regions() -> Result<()> {
+    use zenoh::session::TopologyEvent;
+
+    zenoh_util::try_init_log_from_env();
+    let ke = "router_regions";
+    let base_port = 17733;
+
+    // Routers c1 and c2 form region "c", routers d1 and d2 region "d", c1 and d1 being the
+    // gateways of their regions. The routers of a region do not see the ones of the other
+    // region, but the clients of both regions communicate, the subscribers and queryables of
+    // a region being aggregated on `<ke>/**` by its gateway.
+    let locators: Vec<String> = (0..4)
+        .map(|i| format!("tcp/127.0.0.1:{}", base_port + i))
+        .collect();
+    let region_config = |id: &str, listen: &String, connect: &[String], region: &str, gateway| {
+        let mut config = router_config(id, listen, connect).unwrap();
+        config
+            .insert_json5("routing/router/region/name", &format!("\"{region}\""))
+            .unwrap();
+        if let Some((zid, region)) = gateway {
+            config
+                .insert_json5(
+                    "routing/router/region/gateways",
+                    &format!(
+                        "[{{ zid: \"{zid}\", region: \"{region}\", aggregation: [\"{ke}/**\"] }}]"
+                    ),
+                )
+                .unwrap();
+        }
+        config
+    };
+    let mut config = region_config("c2", &locators[1], &[], "c", None);
+    config
+        .insert_json5(
+            "adminspace",
+            r#"{ enabled: true, permissions: { read: true } }"#,
+        )
+        .unwrap();
+    let router_c = ztimeout!(zenoh::open(config))?;
+    let listener = ztimeout!(router_c.info().topology_listener())?;
+    let gateway_c = ztimeout!(zenoh::open(region_config(
+        "c1",
+        &locators[0],
+        &locators[1..2],
+        "c",
+        Some(("d1", "d"))
+    )))?;
+    let gateway_d = ztimeout!(zenoh::open(region_config(
+        "d1",
+        &locators[2],
+        &locators[..1],
+        "d",
+        Some(("c1", "c"))
+    )))?;
+    let mut config = region_config("d2", &locators[3], &locators[2..3], "d", None);
+    config
+        .insert_json5(
+            "adminspace",
+            r#"{ enabled: true, permissions: { read: true } }"#,
+        )
+        .unwrap();
+    let router_d = ztimeout!(zenoh::open(config))?;
+
+    let session_c = ztimeout!(zenoh::open(client_config(&locators[1])?))?;
+    let session_d = ztimeout!(zenoh::open(client_config(&locators[3])?))?;
+
+    let received = Arc::new(AtomicUsize::new(0));
+    let _sub = ztimeout!(session_d.declare_subscriber(ke).callback({
+        let received = received.clone();
+        move |_| {
+            received.fetch_add(1, Ordering::Relaxed);
+        }
+    }))?;
+    let qabl = ztimeout!(session_c
+        .declare_queryable(ke)
+        .callback(move |query| query.reply(ke, "reply").wait().unwrap()))?;
+    let _other_sub = ztimeout!(session_d.declare_subscriber(format!("{ke}/other")))?;
+    let _token = ztimeout!(session_d.liveliness().declare_token(ke))?;
+    tokio::time::sleep(Duration::from_secs(2)).await;
+
+    // Router c2 only knows the subscription aggregated by gateway d1
+    let prefix = format!("@/{}/router/subscriber/", router_c.zid());
+    let replies = ztimeout!(router_c.get(format!("{prefix}{ke}/**")))?;
+    let mut subscriptions = vec![];
+    while let Ok(reply) = ztimeout!(replies.recv_async()) {
+        let key_expr = reply.result().unwrap().key_expr().to_string();
+        subscriptions.push(key_expr.strip_prefix(&prefix).unwrap().to_string());
+    }
+    assert_eq!(subscriptions, vec![format!("{ke}/**")]);
+
+    for _ in 0..10 {
+        ztimeout!(session_c.put(ke, "put"))?;
+    }
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(received.load(Ordering::Relaxed), 10);
+
+    let replies = ztimeout!(session_d.get(ke))?;
+    let mut count = 0;
+    while ztimeout!(replies.recv_async()).is_ok() {
+        count += 1;
+    }
+    assert_eq!(count, 1);
+
+    let replies = ztimeout!(session_c.liveliness().get(ke))?;
+    let mut count = 0;
+    while ztimeout!(replies.recv_async()).is_ok() {
+        count += 1;
+    }
+    assert_eq!(count, 1);
+
+    // The link-state graph of c2 only contains the routers of region "c"
+    let nodes = listener
+        .drain()
+        .filter_map(|event| match event {
+            TopologyEvent::NodeAdded { zid, .. } => Some(zid),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(nodes, vec![gateway_c.zid()]);
+
+    // Router d2 only knows the queryable aggregated by gateway c1, until the last queryable it
+    // includes is undeclared
+    let other_qabl = ztimeout!(session_c
+        .declare_queryable(format!("{ke}/other"))
+        .callback(move |query| query.reply(format!("{ke}/other"), "reply").wait().unwrap()))?;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    let prefix = format!("@/{}/router/queryable/", router_d.zid());
+    let queryables = || async {
+        let replies = ztimeout!(router_d.get(format!("{prefix}{ke}/**"))).unwrap();
+        let mut queryables = vec![];
+        while let Ok(reply) = ztimeout!(replies.recv_async()) {
+            let key_expr = reply.result().unwrap().key_expr().to_string();
+            queryables.push(key_expr.strip_prefix(&prefix).unwrap().to_string());
+        }
+        queryables
+    };
+    assert_eq!(queryables().await, vec![format!("{ke}/**")]);
+    ztimeout!(qabl.undeclare())?;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(queryables().await, vec![format!("{ke}/**")]);
+    let replies = ztimeout!(session_d.get(format!("{ke}/**")))?;
+    let reply = ztimeout!(replies.recv_async())?;
+    assert_eq!(
+        reply.result().unwrap().key_expr().as_str(),
+        format!("{ke}/other")
+    );
+    assert!(ztimeout!(replies.recv_async()).is_err());
+    ztimeout!(other_qabl.undeclare())?;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert!(queryables().await.is_empty());
+    let replies = ztimeout!(session_d.get(format!("{ke}/**")))?;
+    assert!(ztimeout!(replies.recv_async()).is_err());
+
+    ztimeout!(listener.undeclare())?;
+    close_sessions([
+        session_c, session_d, router_d, gateway_d, gateway_c, router_c,
+    ])
+    .await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn router_regions_multiple_gateways() -> Result<()> {
+    zenoh_util::try_init_log_from_env();
+    let ke = "router_regions_multiple_gateways";
+    let base_port = 17747;
+
+    // Routers a1 and a2 form region "a", routers b1 and b2 region "b", the regions being
+    // connected by two gateway links: a1 to b1 and a2 to b2. A single link is elected, so the
+    // samples are not duplicated and the declarations do not loop between the regions, the
+    // other link taking over when the elected one closes.
+    let locators: Vec<String> = (0..4)
+        .map(|i| format!("tcp/127.0.0.1:{}", base_port + i))
+        .collect();
+    let region_config = |id: &str, listen: &String, connect: &[String], region: &str, gateway| {
+        let mut config = router_config(id, listen, connect).unwrap();
+        let (zid, gateway_region) = gateway;
+        config
+            .insert_json5("routing/router/region/name", &format!("\"{region}\""))
+            .unwrap();
+        config
+            .insert_json5(
+                "routing/router/region/gateways",
+                &format!("[{{ zid: \"{zid}\", region: \"{gateway_region}\" }}]"),
+            )
+            .unwrap();
+        config
+    };
+    let mut config = region_config("a2", &locators[1], &[], "a", ("b2", "b"));
+    config
+        .insert_json5(
+            "adminspace",
+            r#"{ enabled: true, permissions: { read: true } }"#,
+        )
+        .unwrap();
+    let router_a2 = ztimeout!(zenoh::open(config))?;
+    let router_a1 = ztimeout!(zenoh::open(region_config(
+        "a1",
+        &locators[0],
+        &locators[1..2],
+        "a",
+        ("b1", "b")
+    )))?;
+    let router_b1 = ztimeout!(zenoh::open(region_config(
+        "b1",
+        &locators[2],
+        &locators[..1],
+        "b",
+        ("a1", "a")
+    )))?;
+    let router_b2 = ztimeout!(zenoh::open(region_config(
+        "b2",
+        &locators[3],
+        &[locators[2].clone(), locators[1].clone()],
+        "b",
+        ("a2", "a")
+    )))?;
+
+    let session_a = ztimeout!(zenoh::open(client_config(&locators[1])?))?;
+    let session_b = ztimeout!(zenoh::open(client_config(&locators[3])?))?;
+
+    let received = Arc::new(AtomicUsize::new(0));
+    let sub = ztimeout!(session_b.declare_subscriber(ke).callback({
+        let received = received.clone();
+        move |_| {
+            received.fetch_add(1, Ordering::Relaxed);
+        }
+    }))?;
+    let _qabl = ztimeout!(session_a
+        .declare_queryable(ke)
+        .callback(move |query| query.reply(ke, "reply").wait().unwrap()))?;
+    tokio::time::sleep(Duration::from_secs(3)).await;
+
+    for _ in 0..10 {
+        ztimeout!(session_a.put(ke, "put"))?;
+    }
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(received.load(Ordering::Relaxed), 10);
+
+    let replies = ztimeout!(session_b.get(ke))?;
+    let mut count = 0;
+    while ztimeout!(replies.recv_async()).is_ok() {
+        count += 1;
+    }
+    assert_eq!(count, 1);
+
+    // The subscription of region "b" does not linger in region "a" once undeclared
+    let prefix = format!("@/{}/router/subscriber/", router_a2.zid());
+    let subscriptions = || async {
+        let replies = ztimeout!(router_a2.get(format!("{prefix}{ke}/**"))).unwrap();
+        let mut count = 0;
+        while ztimeout!(replies.recv_async()).is_ok() {
+            count += 1;
+        }
+        count
+    };
+    assert_eq!(subscriptions().await, 1);
+    ztimeout!(sub.undeclare())?;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(subscriptions().await, 0);
+
+    // The link between a2 and b2 takes over when the one between a1 and b1 closes
+    let _sub = ztimeout!(session_b.declare_subscriber(ke).callback({
+        let received = received.clone();
+        move |_| {
+            received.fetch_add(1, Ordering::Relaxed);
+        }
+    }))?;
+    ztimeout!(router_a1.close())?;
+    tokio::time::sleep(Duration::from_secs(3)).await;
+    for _ in 0..10 {
+        ztimeout!(session_a.put(ke, "put"))?;
+    }
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(received.load(Ordering::Relaxed), 20);
+
+    close_sessions([session_a, session_b, router_b2, router_b1, router_a2]).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn router_subscribers_aggregation() -> Result<()> {
+    zenoh_util::try_init_log_from_env();
+    let ke = "router_subscribers_aggregation";
+    let base_port = 17737;
+
+    // Router a1 advertises a single subscription on `<ke>/**` on behalf of its clients
+    let locators: Vec<String> = (0..2)
+        .map(|i| format!("tcp/127.0.0.1:{}", base_port + i))
+        .collect();
+    let mut config = router_config("a1", &locators[0], &[])?;
+    config
+        .insert_json5(
+            "routing/router/aggregation/subscribers",
+            &format!("[\"{ke}/**\"]"),
+        )
+        .unwrap();
+    let router1 = ztimeout!(zenoh::open(config))?;
+    let router2 = ztimeout!(zenoh::open(router_config(
+        "a2",
+        &locators[1],
+        &locators[..1]
+    )?))?;
+
+    let sub_session = ztimeout!(zenoh::open(client_config(&locators[0])?))?;
+    let local_session = ztimeout!(zenoh::open(client_config(&locators[0])?))?;
+    let pub_session = ztimeout!(zenoh::open(client_config(&locators[1])?))?;
+
+    let counters: Vec<Arc<AtomicUsize>> = (0..2).map(|_| Arc::new(AtomicUsize::new(0))).collect();
+    let mut subs = vec![];
+    for (i, counter) in counters.iter().enumerate() {
+        let counter = counter.clone();
+        subs.push(ztimeout!(sub_session
+            .declare_subscriber(format!("{ke}/{i}/**"))
+            .callback(move |_| {
+                counter.fetch_add(1, Ordering::Relaxed);
+            }))?);
+    }
+    let publisher = ztimeout!(pub_session.declare_publisher(format!("{ke}/0/a")))?;
+    let local_publisher = ztimeout!(local_session.declare_publisher(format!("{ke}/0/a")))?;
+    let unsubscribed_publisher = ztimeout!(local_session.declare_publisher(format!("{ke}/2/a")))?;
+    tokio::time::sleep(Duration::from_secs(2)).await;
+
+    // The clients of router a1 get the subscriptions themselves, not the aggregate
+    assert!(ztimeout!(local_publisher.matching_status())?.matching());
+    assert!(!ztimeout!(unsubscribed_publisher.matching_status())?.matching());
+    assert!(ztimeout!(publisher.matching_status())?.matching());
+
+    for i in 0..3 {
+        for _ in 0..5 {
+            ztimeout!(pub_session.put(format!("{ke}/{i}/a"), "put"))?;
+        }
+    }
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    // The data is filtered by router a1
+    for counter in &counters {
+        assert_eq!(counter.load(Ordering::Relaxed), 5);
+    }
+
+    // The aggregate is undeclared with the last subscription it includes
+    ztimeout!(subs.remove(0).undeclare())?;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert!(ztimeout!(publisher.matching_status())?.matching());
+    assert!(!ztimeout!(local_publisher.matching_status())?.matching());
+    ztimeout!(subs.remove(0).undeclare())?;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert!(!ztimeout!(publisher.matching_status())?.matching());
+
+    ztimeout!(publisher.undeclare())?;
+    ztimeout!(local_publisher.undeclare())?;
+    ztimeout!(unsubscribed_publisher.undeclare())?;
+    close_sessions([sub_session, local_session, pub_session, router2, router1]).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn queryable_admission_control() -> Result<()> {
+    use std::sync::Mutex;
+
+    use zenoh::query::{OverloadPolicy, Query};
+
+    zenoh_util::try_init_log_from_env();
+    let ke = "queryable_admission_control";
+    let locator = "tcp/127.0.0.1:17739".to_string();
+
+    let router = ztimeout!(zenoh::open(router_config("a1", &locator, &[])?))?;
+    let qabl_session = ztimeout!(zenoh::open(client_config(&locator)?))?;
+    let get_session = ztimeout!(zenoh::open(client_config(&locator)?))?;
+
+    // The queries handled by the overloadable queryables are kept until released
+    let held = Arc::new(Mutex::new(Vec::<Query>::new()));
+    let count_replies = |replies: zenoh::handlers::FifoChannelHandler<zenoh::query::Reply>| async move {
+        let (mut ok, mut overloaded) = (0, 0);
+        while let Ok(reply) = ztimeout!(replies.recv_async()) {
+            match reply.result() {
+                Ok(_) => ok += 1,
+                Err(err) if err.is_overloaded() => overloaded += 1,
+                Err(_) => panic!("Unexpected error reply"),
+            }
+        }
+        (ok, overloaded)
+    };
+
+    // Reject: the queries beyond the limit are rejected with an overloaded error
+    let qabl = ztimeout!(qabl_session
+        .declare_queryable(format!("{ke}/reject"))
+        .complete(true)
+        .max_concurrent_queries(1)
+        .callback({
+            let held = held.clone();
+            move |query| held.lock().unwrap().push(query)
+        }))?;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    let first = ztimeout!(get_session.get(format!("{ke}/reject")))?;
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    let second = ztimeout!(get_session.get(format!("{ke}/reject")))?;
+    assert_eq!(count_replies(second).await, (0, 1));
+    for query in held.lock().unwrap().drain(..) {
+        query.reply(query.key_expr().clone(), "ok").wait()?;
+    }
+    assert_eq!(count_replies(first).await, (1, 0));
+    ztimeout!(qabl.undeclare())?;
+
+    // Redirect: the overloaded queryable is declared incomplete, the queries being routed to
+    // another complete queryable
+    let qabl = ztimeout!(qabl_session
+        .declare_queryable(format!("{ke}/redirect"))
+        .complete(true)
+        .max_concurrent_queries(1)
+        .overload_policy(OverloadPolicy::Redirect)
+        .callback({
+            let held = held.clone();
+            move |query| held.lock().unwrap().push(query)
+        }))?;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    let first = ztimeout!(get_session.get(format!("{ke}/redirect")))?;
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    let other_session = ztimeout!(zenoh::open(client_config(&locator)?))?;
+    let other_qabl = ztimeout!(other_session
+        .declare_queryable(format!("{ke}/redirect"))
+        .complete(true)
+        .callback(|query| query.reply(query.key_expr().clone(), "ok").wait().unwrap()))?;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    for _ in 0..5 {
+        let replies = ztimeout!(get_session
+            .get(format!("{ke}/redirect"))
+            .target(QueryTarget::AllComplete))?;
+        assert_eq!(count_replies(replies).await, (1, 0));
+    }
+    assert_eq!(held.lock().unwrap().len(), 1);
+    held.lock().unwrap().clear();
+    assert_eq!(count_replies(first).await, (0, 0));
+
+    ztimeout!(other_qabl.undeclare())?;
+    ztimeout!(qabl.undeclare())?;
+    close_sessions([other_session, get_session, qabl_session, router]).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn query_cancellation() -> Result<()> {
+    use zenoh::query::CancellationToken;
+
+    zenoh_util::try_init_log_from_env();
+    let ke = "query_cancellation";
+    let locator = "tcp/127.0.0.1:17740".to_string();
+
+    let router = ztimeout!(zenoh::open(router_config("a1", &locator, &[])?))?;
+    let qabl_session = ztimeout!(zenoh::open(client_config(&locator)?))?;
+    let get_session = ztimeout!(zenoh::open(client_config(&locator)?))?;
+
+    // The queryables, one remote and one local to the querier, only reply once the query is cancelled
+    let cancelled = Arc::new(AtomicUsize::new(0));
+    let handle_query = {
+        let cancelled = cancelled.clone();
+        move |query: zenoh::query::Query| {
+            let cancelled = cancelled.clone();
+            tokio::spawn(async move {
+                ztimeout!(query.cancelled());
+                assert!(query.is_cancelled());
+                cancelled.fetch_add(1, Ordering::Relaxed);
+                query.reply(ke, "late").await.unwrap();
+            });
+        }
+    };
+    let remote_qabl = ztimeout!(qabl_session
+        .declare_queryable(ke)
+        .callback(handle_query.clone()))?;
+    let local_qabl = ztimeout!(get_session.declare_queryable(ke).callback(handle_query))?;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    let token = CancellationToken::default();
+    let replies = ztimeout!(get_session
+        .get(ke)
+        .target(QueryTarget::All)
+        .timeout(Duration::from_secs(60))
+        .cancellation_token(token.clone()))?;
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert_eq!(cancelled.load(Ordering::Relaxed), 0);
+
+    // Cancelling closes the reply handler without waiting for the timeout
+    token.cancel();
+    assert!(token.is_cancelled());
+    assert!(ztimeout!(replies.recv_async()).is_err());
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert_eq!(cancelled.load(Ordering::Relaxed), 2);
+
+    // The queries issued with a cancelled token are cancelled right away
+    let replies = ztimeout!(get_session.get(ke).cancellation_token(token))?;
+    assert!(ztimeout!(replies.recv_async()).is_err());
+
+    ztimeout!(local_qabl.undeclare())?;
+    ztimeout!(remote_qabl.undeclare())?;
+    close_sessions([get_session, qabl_session, router]).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn query_reply_credits() -> Result<()> {
+    use zenoh::handlers::FifoChannel;
+
+    zenoh_util::try_init_log_from_env();
+    let ke = "query_reply_credits";
+    let locator = "tcp/127.0.0.1:17741".to_string();
+    const REPLIES: usize = 20;
+    const CREDITS: u32 = 4;
+
+    let router = ztimeout!(zenoh::open(router_config("a1", &locator, &[])?))?;
+    let qabl_session = ztimeout!(zenoh::open(client_config(&locator)?))?;
+    let get_session = ztimeout!(zenoh::open(client_config(&locator)?))?;
+
+    // The queryable streams many replies, counting the ones it could send
+    let sent = Arc::new(AtomicUsize::new(0));
+    let qabl = ztimeout!(qabl_session.declare_queryable(ke).callback({
+        let sent = sent.clone();
+        move |query| {
+            let sent = sent.clone();
+            tokio::spawn(async move {
+                for i in 0..REPLIES {
+                    query.reply(ke, i.to_string()).await.unwrap();
+                    sent.fetch_add(1, Ordering::Relaxed);
+                }
+            });
+        }
+    }))?;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    // The queryable waits for credits while the querier does not consume the replies
+    let replies = ztimeout!(get_session
+        .get(ke)
+        .consolidation(ConsolidationMode::None)
+        .reply_credits(CREDITS)
+        .with(FifoChannel::new(1)))?;
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    // The initial credit, then the window granted back with its first reply
+    assert_eq!(sent.load(Ordering::Relaxed), CREDITS as usize + 1);
+
+    // Consuming the replies grants credits back until all of them are received
+    for i in 0..REPLIES {
+        let reply = ztimeout!(replies.recv_async())?;
+        let sample = reply.result().unwrap();
+        assert_eq!(sample.payload().try_to_string()?, i.to_string());
+    }
+    assert!(ztimeout!(replies.recv_async()).is_err());
+    assert_eq!(sent.load(Ordering::Relaxed), REPLIES);
+
+    ztimeout!(qabl.undeclare())?;
+    close_sessions([get_session, qabl_session, router]).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn query_reply_credits_release() -> Result<()> {
+    zenoh_util::try_init_log_from_env();
+    let ke = "query_reply_credits_release";
+    let locator = "tcp/127.0.0.1:17752".to_string();
+    const REPLIES: usize = 20;
+    const CREDITS: u32 = 4;
+
+    // The router drops the replies, so that the querier never grants credits back
+    let mut config = router_config("a1", &locator, &[])?;
+    config
+        .insert_json5(
+            "access_control",
+            &format!(
+                r#"{{
+                    "enabled": true,
+                    "default_permission": "allow",
+                    "rules": [{{
+                        "id": "r1",
+                        "permission": "deny",
+                        "messages": ["reply"],
+                        "flows": ["egress"],
+                        "key_exprs": ["{ke}"],
+                    }}],
+                    "subjects": [{{ "id": "s1", "interfaces": ["lo", "lo0"] }}],
+                    "policies": [{{ "rules": ["r1"], "subjects": ["s1"] }}],
+                }}"#
+            ),
+        )
+        .unwrap();
+    let router = ztimeout!(zenoh::open(config))?;
+    let qabl_session = ztimeout!(zenoh::open(client_config(&locator)?))?;
+    let get_session = ztimeout!(zenoh::open(client_config(&locator)?))?;
+    let closed_session = ztimeout!(zenoh::open(client_config(&locator)?))?;
+
+    // The queryable streams many replies, counting the queries it is done with
+    let done = Arc::new(AtomicUsize::new(0));
+    let qabl = ztimeout!(qabl_session.declare_queryable(ke).callback({
+        let done = done.clone();
+        move |query| {
+            let done = done.clone();
+            tokio::spawn(async move {
+                for i in 0..REPLIES {
+                    if query.reply(ke, i.to_string()).await.is_err() {
+                        break;
+                    }
+                }
+                done.fetch_add(1, Ordering::Relaxed);
+            });
+        }
+    }))?;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    // A query timing out releases the queryable waiting for credits
+    ztimeout!(get_session
+        .get(ke)
+        .consolidation(ConsolidationMode::None)
+        .reply_credits(CREDITS)
+        .timeout(Duration::from_secs(1))
+        .callback(|_| {}))?;
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert_eq!(done.load(Ordering::Relaxed), 0);
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(done.load(Ordering::Relaxed), 1);
+
+    // So does the querier going away
+    ztimeout!(closed_session
+        .get(ke)
+        .consolidation(ConsolidationMode::None)
+        .reply_credits(CREDITS)
+        .callback(|_| {}))?;
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert_eq!(done.load(Ordering::Relaxed), 1);
+    ztimeout!(closed_session.close())?;
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert_eq!(done.load(Ordering::Relaxed), 2);
+
+    ztimeout!(qabl.undeclare())?;
+    close_sessions([get_session, qabl_session, router]).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn query_reply_credits_sync() -> Result<()> {
+    zenoh_util::try_init_log_from_env();
+    let ke = "query_reply_credits_sync";
+    let locator = "tcp/127.0.0.1:17745".to_string();
+    const REPLIES: usize = 10;
+    const CREDITS: u32 = 4;
+
+    let router = ztimeout!(zenoh::open(router_config("a1", &locator, &[])?))?;
+    let qabl_session = ztimeout!(zenoh::open(client_config(&locator)?))?;
+    let get_session = ztimeout!(zenoh::open(client_config(&locator)?))?;
+
+    // The queryable replies synchronously past the window, counting the replies it could send
+    let sent = Arc::new(AtomicUsize::new(0));
+    let qabl = ztimeout!(qabl_session.declare_queryable(ke).callback({
+        let sent = sent.clone();
+        move |query| {
+            for i in 0..REPLIES {
+                if query.reply(ke, i.to_string()).wait().is_ok() {
+                    sent.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+    }))?;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    // The replies without credit fail instead of blocking the callback
+    let replies = ztimeout!(get_session
+        .get(ke)
+        .consolidation(ConsolidationMode::None)
+        .reply_credits(CREDITS))?;
+    let mut received = 0;
+    while ztimeout!(replies.recv_async()).is_ok() {
+        received += 1;
+    }
+    let sent = sent.load(Ordering::Relaxed);
+    assert!(sent < REPLIES);
+    assert_eq!(received, sent);
+
+    ztimeout!(qabl.undeclare())?;
+    close_sessions([get_session, qabl_session, router]).await
+}
+
+#[cfg(feature = "unstable")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn batch_publication() -> Result<()> {
+    use zenoh::{qos::Reliability, sample::SampleKind};
+
+    zenoh_util::try_init_log_from_env();
+    let locator = "tcp/127.0.0.1:17742".to_string();
+
+    let router = ztimeout!(zenoh::open(router_config("a1", &locator, &[])?))?;
+    let pub_session = ztimeout!(zenoh::open(client_config(&locator)?))?;
+    let sub_session = ztimeout!(zenoh::open(client_config(&locator)?))?;
+
+    // A subscriber matching the whole batch receives it as a unit
+    let (batch_tx, batch_rx) = flume::unbounded();
+    let all_sub = ztimeout!(sub_session
+        .declare_subscriber("robot/**")
+        .batch_callback(move |batch| batch_tx.send(batch).unwrap()))?;
+    // A subscriber matching a part of the batch receives the matching samples only
+    let (batch_tx, partial_batch_rx) = flume::unbounded();
+    let pose_sub = ztimeout!(sub_session
+        .declare_subscriber("robot/pose")
+        .batch_callback(move |batch| batch_tx.send(batch).unwrap()))?;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    ztimeout!(pub_session
+        .batch()
+        .put("robot/pose", "1.0,2.0")
+        .put("robot/velocity", "0.5")
+        .delete("robot/covariance"))?;
+
+    let batch = ztimeout!(batch_rx.recv_async())?;
+    assert!(batch.timestamp().is_some());
+    let samples = batch.samples();
+    assert_eq!(
+        samples
+            .iter()
+            .map(|s| (s.key_expr().as_str(), s.kind()))
+            .collect::<Vec<_>>(),
+        [
+            ("robot/pose", SampleKind::Put),
+            ("robot/velocity", SampleKind::Put),
+            ("robot/covariance", SampleKind::Delete),
+        ]
+    );
+    assert!(samples.iter().all(|s| s.timestamp() == batch.timestamp()));
+    assert_eq!(samples[1].payload().try_to_string()?, "0.5");
+
+    let sample = ztimeout!(pose_sub.recv_async())?;
+    assert_eq!(sample.key_expr().as_str(), "robot/pose");
+    assert_eq!(sample.payload().try_to_string()?, "1.0,2.0");
+    assert_eq!(sample.timestamp(), batch.timestamp());
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert!(pose_sub.is_empty());
+    assert!(all_sub.is_empty());
+    assert!(partial_batch_rx.is_empty());
+    assert!(batch_rx.is_empty());
+
+    // A batch whose samples share no common prefix is still received as a unit, with the
+    // reliability and lifespan of the publication
+    let (batch_tx, any_batch_rx) = flume::unbounded();
+    let _any_sub = ztimeout!(sub_session
+        .declare_subscriber("**")
+        .batch_callback(move |batch| batch_tx.send(batch).unwrap()))?;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    ztimeout!(pub_session
+        .batch()
+        .put("robot/pose", "3.0,4.0")
+        .put("drone/pose", "5.0,6.0")
+        .reliability(Reliability::BestEffort)
+        .lifespan(Duration::from_secs(60)))?;
+
+    let batch = ztimeout!(any_batch_rx.recv_async())?;
+    assert_eq!(
+        batch
+            .samples()
+            .iter()
+            .map(|s| s.key_expr().as_str())
+            .collect::<Vec<_>>(),
+        ["robot/pose", "drone/pose"]
+    );
+    assert!(batch.samples().iter().all(|s| {
+        s.reliability() == Reliability::BestEffort && s.lifespan() == Some(Duration::from_secs(60))
+    }));
+    let sample = ztimeout!(pose_sub.recv_async())?;
+    assert_eq!(sample.payload().try_to_string()?, "3.0,4.0");
+    let sample = ztimeout!(all_sub.recv_async())?;
+    assert_eq!(sample.payload().try_to_string()?, "3.0,4.0");
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert!(pose_sub.is_empty());
+    assert!(all_sub.is_empty());
+    assert!(any_batch_rx.is_empty());
+    assert!(batch_rx.is_empty());
+
+    close_sessions([sub_session, pub_session, router]).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn publication_lifespan() -> Result<()> {
+    use zenoh::time::{Timestamp, NTP64};
+
+    zenoh_util::try_init_log_from_env();
+    let ke = "publication_lifespan";
+    let locator = "tcp/127.0.0.1:17743".to_string();
+    const LIFESPAN: Duration = Duration::from_secs(1);
+
+    let router = ztimeout!(zenoh::open(router_config("a1", &locator, &[])?))?;
+    let pub_session = ztimeout!(zenoh::open(client_config(&locator)?))?;
+    let sub_session = ztimeout!(zenoh::open(client_config(&locator)?))?;
+
+    let sub = ztimeout!(sub_session.declare_subscriber(ke))?;
+    let local_sub = ztimeout!(pub_session.declare_subscriber(ke))?;
+    let publisher = ztimeout!(pub_session.declare_publisher(ke).lifespan(LIFESPAN))?;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    // Fresh data is timestamped and delivered with its lifespan
+    ztimeout!(publisher.put("fresh"))?;
+    for sub in [&sub, &local_sub] {
+        let sample = ztimeout!(sub.recv_async())?;
+        assert_eq!(sample.payload().try_to_string()?, "fresh");
+        assert!(sample.timestamp().is_some());
+        assert_eq!(sample.lifespan(), Some(LIFESPAN));
+        assert!(!sample.is_expired());
+    }
+
+    // Data older than its lifespan is discarded instead of being delivered
+    let time = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)? - 5 * LIFESPAN;
+    let stale = Timestamp::new(NTP64::from(time), pub_session.zid().into());
+    ztimeout!(publisher.put("stale").timestamp(stale))?;
+    ztimeout!(pub_session
+        .put(ke, "stale")
+        .lifespan(LIFESPAN)
+        .timestamp(stale))?;
+    ztimeout!(pub_session.put(ke, "unlimited").timestamp(stale))?;
+    for sub in [&sub, &local_sub] {
+        let sample = ztimeout!(sub.recv_async())?;
+        assert_eq!(sample.payload().try_to_string()?, "unlimited");
+        assert_eq!(sample.lifespan(), None);
+    }
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert!(sub.is_empty());
+    assert!(local_sub.is_empty());
+
+    ztimeout!(publisher.undeclare())?;
+    close_sessions([sub_session, pub_session, router]).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn deadline_monitoring() -> Result<()> {
+    use zenoh::sample::SourceInfo;
+
+    zenoh_util::try_init_log_from_env();
+    let ke = "deadline_monitoring";
+    let locator = "tcp/127.0.0.1:17744".to_string();
+    const DEADLINE: Duration = Duration::from_millis(500);
+    const PERIOD: Duration = Duration::from_millis(100);
+
+    let router = ztimeout!(zenoh::open(router_config("a1", &locator, &[])?))?;
+    let pub_session1 = ztimeout!(zenoh::open(client_config(&locator)?))?;
+    let pub_session2 = ztimeout!(zenoh::open(client_config(&locator)?))?;
+    let sub_session = ztimeout!(zenoh::open(client_config(&locator)?))?;
+
+    let (missed_tx, missed_rx) = flume::unbounded();
+    let sub = ztimeout!(sub_session
+        .declare_subscriber(ke)
+        .deadline(DEADLINE, move |missed| missed_tx.send(missed).unwrap()))?;
+    let pub1 = ztimeout!(pub_session1.declare_publisher(ke).deadline(DEADLINE))?;
+    let pub2 = ztimeout!(pub_session2.declare_publisher(ke).deadline(DEADLINE))?;
+    assert_eq!(pub1.deadline(), Some(DEADLINE));
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    // The samples of the publishers with a deadline carry their id and sequence number
+    for sn in 0..2 {
+        ztimeout!(pub1.put("data"))?;
+        let sample = ztimeout!(sub.recv_async())?;
+        assert_eq!(sample.source_info().source_id(), Some(&pub1.id()));
+        assert_eq!(sample.source_info().source_sn(), Some(sn));
+    }
+
+    // No deadline is missed while both publishers publish in time
+    for _ in 0..10 {
+        ztimeout!(pub1.put("data"))?;
+        ztimeout!(pub2.put("data"))?;
+        tokio::time::sleep(PERIOD).await;
+    }
+    assert!(missed_rx.is_empty());
+
+    // Only the publisher which stopped publishing misses its deadline, once per deadline period,
+    // the publishers without deadline are not monitored even if their samples carry their id
+    let pub3 = ztimeout!(pub_session2.declare_publisher(ke))?;
+    ztimeout!(pub3
+        .put("data")
+        .source_info(SourceInfo::new(Some(pub3.id()), Some(0))))?;
+    for _ in 0..15 {
+        ztimeout!(pub2.put("data"))?;
+        tokio::time::sleep(PERIOD).await;
+    }
+    let missed = missed_rx.drain().collect::<Vec<_>>();
+    assert!(missed.len() >= 2);
+    for (i, missed) in missed.iter().enumerate() {
+        assert_eq!(missed.publisher(), pub1.id());
+        assert_eq!(missed.key_expr().as_str(), ke);
+        assert_eq!(missed.missed_count(), i as u64 + 1);
+        assert!(missed.elapsed() >= DEADLINE * (i as u32 + 1));
+    }
+
+    // The publisher is monitored again from its next sample
+    for _ in 0..3 {
+        ztimeout!(pub1.put("data"))?;
+        ztimeout!(pub2.put("data"))?;
+        tokio::time::sleep(PERIOD).await;
+    }
+    missed_rx.drain().for_each(drop);
+    for _ in 0..3 {
+        ztimeout!(pub1.put("data"))?;
+        ztimeout!(pub2.put("data"))?;
+        tokio::time::sleep(PERIOD).await;
+    }
+    assert!(missed_rx.is_empty());
+
+    ztimeout!(sub.undeclare())?;
+    close_sessions([sub_session, pub_session1, pub_session2, router]).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn deadline_undeclare() -> Result<()> {
+    zenoh_util::try_init_log_from_env();
+    let ke = "deadline_undeclare";
+    let locator = "tcp/127.0.0.1:17746".to_string();
+    const DEADLINE: Duration = Duration::from_millis(300);
+
+    let router = ztimeout!(zenoh::open(router_config("a1", &locator, &[])?))?;
+    let pub_session = ztimeout!(zenoh::open(client_config(&locator)?))?;
+    let sub_session = ztimeout!(zenoh::open(client_config(&locator)?))?;
+
+    // A zero deadline is rejected
+    assert!(ztimeout!(pub_session.declare_publisher(ke).deadline(Duration::ZERO)).is_err());
+    assert!(ztimeout!(sub_session
+        .declare_subscriber(ke)
+        .deadline(Duration::ZERO, |_| {}))
+    .is_err());
+
+    let (missed_tx, missed_rx) = flume::unbounded();
+    let sub = ztimeout!(sub_session
+        .declare_subscriber(ke)
+        .deadline(DEADLINE, move |missed| missed_tx.send(missed).unwrap()))?;
+    let publisher = ztimeout!(pub_session.declare_publisher(ke).deadline(DEADLINE))?;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    ztimeout!(publisher.put("data"))?;
+    ztimeout!(sub.recv_async())?;
+
+    // The undeclared publisher is not monitored anymore
+    ztimeout!(publisher.undeclare())?;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    missed_rx.drain().for_each(drop);
+    tokio::time::sleep(DEADLINE * 5).await;
+    assert!(missed_rx.is_empty());
+
+    ztimeout!(sub.undeclare())?;
+    close_sessions([sub_session, pub_session, router]).await
+}